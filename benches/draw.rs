@@ -0,0 +1,59 @@
+//! Times `ui::draw` rendering into an offscreen buffer at a fixed size, for catching
+//! regressions in the redraw path. Run with `cargo bench`.
+
+use std::{hint::black_box, time::Instant};
+
+use chrono::{Local, TimeZone};
+use criterion::{Criterion, criterion_group, criterion_main};
+use ratatui::{Terminal, backend::TestBackend};
+use tui_time::{
+    app::{App, Mode},
+    stopwatch::Stopwatch,
+    ui,
+};
+
+const SIZE: (u16, u16) = (120, 40);
+
+fn draw(c: &mut Criterion) {
+    let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 34, 56).unwrap();
+    let cases = [
+        ("clock", App::default()),
+        (
+            "seconds",
+            App {
+                seconds: true,
+                ..App::default()
+            },
+        ),
+        (
+            "analog",
+            App {
+                analog: true,
+                sweep: true,
+                ..App::default()
+            },
+        ),
+        (
+            "stopwatch",
+            App {
+                mode: Mode::Stopwatch(Stopwatch::start(Instant::now())),
+                ..App::default()
+            },
+        ),
+    ];
+    let mut group = c.benchmark_group("draw");
+    for (name, app) in &cases {
+        let mut terminal = Terminal::new(TestBackend::new(SIZE.0, SIZE.1)).unwrap();
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                terminal
+                    .draw(|frame| ui::draw(frame, black_box(app), local, Instant::now()))
+                    .unwrap();
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, draw);
+criterion_main!(benches);
@@ -0,0 +1,332 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeDelta, TimeZone, Weekday};
+use serde::Deserialize;
+
+use crate::duration::parse_time_of_day;
+
+/// How long `snooze` silences an alarm unless `--snooze` says otherwise.
+pub const DEFAULT_SNOOZE: Duration = Duration::from_secs(9 * 60);
+
+/// A set of days of the week, written like `mon-fri`, `sat,sun`, or `mon,wed-fri`. Ranges may
+/// wrap around the weekend, so `fri-mon` is Friday through Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    pub const ALL: Self = Self(0b111_1111);
+
+    pub fn contains(self, day: Weekday) -> bool {
+        self.0 & (1 << day.num_days_from_monday()) != 0
+    }
+
+    fn insert(&mut self, day: Weekday) {
+        self.0 |= 1 << day.num_days_from_monday();
+    }
+}
+
+impl FromStr for Weekdays {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let day = |name: &str| {
+            name.trim()
+                .parse::<Weekday>()
+                .ok()
+                .with_context(|| format!("unknown day {name:?} in {s:?}"))
+        };
+        let mut days = Self(0);
+        for item in s.split(',') {
+            match item.split_once('-') {
+                Some((first, last)) => {
+                    let (mut at, last) = (day(first)?, day(last)?);
+                    days.insert(at);
+                    while at != last {
+                        at = at.succ();
+                        days.insert(at);
+                    }
+                },
+                None => days.insert(day(item)?),
+            }
+        }
+        Ok(days)
+    }
+}
+
+/// The first time after `now` that the wall clock reads `time` on one of `days`. Days where
+/// `time` falls in a DST gap are skipped.
+pub fn next_occurrence<Tz: TimeZone>(
+    time: NaiveTime,
+    days: Weekdays,
+    now: &DateTime<Tz>,
+) -> DateTime<Tz> {
+    let tz = now.timezone();
+    now.date_naive()
+        .iter_days()
+        .filter(|date| days.contains(date.weekday()))
+        .filter_map(|date| tz.from_local_datetime(&date.and_time(time)).earliest())
+        .find(|at| at > now)
+        .expect("weekday sets are never empty")
+}
+
+/// One entry of the config's `[[alarms]]` list.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmConfig {
+    /// `HH:MM`, 24-hour.
+    pub time: String,
+    /// Which days it rings on. Every day when unset.
+    #[serde(default)]
+    pub days: Option<String>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl AlarmConfig {
+    /// The alarm this entry describes, or `None` if it is disabled.
+    pub fn alarm(&self, snooze: Duration, now: DateTime<Local>) -> anyhow::Result<Option<Alarm>> {
+        let time = parse_time_of_day(&self.time)?;
+        let days = match &self.days {
+            Some(days) => days.parse()?,
+            None => Weekdays::ALL,
+        };
+        Ok(self
+            .enabled
+            .then(|| Alarm::new(time, days, self.label.clone(), snooze, now)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmState {
+    /// Waiting for the next time the clock reads the alarm time.
+    Armed {
+        next: DateTime<Local>,
+    },
+    Ringing,
+    /// Silenced until `until`, when it rings again.
+    Snoozed {
+        until: DateTime<Local>,
+    },
+}
+
+/// A recurring alarm. Its deadlines are wall-clock times checked on every update, so when the
+/// clock is stepped (which wakes the main loop through the timerfd's ECANCELED path) they stay
+/// pinned to what the clock reads rather than drifting with the monotonic clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alarm {
+    time: NaiveTime,
+    days: Weekdays,
+    label: Option<String>,
+    snooze: Duration,
+    state: AlarmState,
+}
+
+impl Alarm {
+    pub fn new(
+        time: NaiveTime,
+        days: Weekdays,
+        label: Option<String>,
+        snooze: Duration,
+        now: DateTime<Local>,
+    ) -> Self {
+        Self {
+            time,
+            days,
+            label,
+            snooze,
+            state: AlarmState::Armed {
+                next: next_occurrence(time, days, &now),
+            },
+        }
+    }
+
+    /// Whether `other` rings at the same times with the same label, whatever state either is in.
+    pub fn same_schedule(&self, other: &Self) -> bool {
+        (self.time, self.days, &self.label, self.snooze)
+            == (other.time, other.days, &other.label, other.snooze)
+    }
+
+    pub fn state(&self) -> AlarmState {
+        self.state
+    }
+
+    /// Whether the alarm is ringing or snoozed, i.e. there is something to dismiss.
+    pub fn is_active(&self) -> bool {
+        !matches!(self.state, AlarmState::Armed { .. })
+    }
+
+    /// Whether the alarm or snooze time has passed, so the next update will ring.
+    pub fn is_due(&self, now: DateTime<Local>) -> bool {
+        self.deadline().is_some_and(|at| now >= at)
+    }
+
+    /// Starts ringing once the alarm or snooze time has passed, returning whether it just did.
+    pub fn update(&mut self, now: DateTime<Local>) -> bool {
+        let due = self.is_due(now);
+        if due {
+            self.state = AlarmState::Ringing;
+        }
+        due
+    }
+
+    /// Silences a ringing alarm for the snooze interval, counted from `now` so repeated snoozes
+    /// each last the full interval from the press.
+    pub fn snooze(&mut self, now: DateTime<Local>) {
+        if self.state != AlarmState::Ringing {
+            return;
+        }
+        match TimeDelta::from_std(self.snooze)
+            .ok()
+            .and_then(|snooze| now.checked_add_signed(snooze))
+        {
+            Some(until) => self.state = AlarmState::Snoozed { until },
+            None => self.dismiss(now),
+        }
+    }
+
+    /// Stops a ringing or snoozed alarm until its next occurrence.
+    pub fn dismiss(&mut self, now: DateTime<Local>) {
+        self.state = AlarmState::Armed {
+            next: next_occurrence(self.time, self.days, &now),
+        };
+    }
+
+    /// When the alarm next starts ringing, or `None` while it already is.
+    pub fn deadline(&self) -> Option<DateTime<Local>> {
+        match self.state {
+            AlarmState::Armed { next } => Some(next),
+            AlarmState::Snoozed { until } => Some(until),
+            AlarmState::Ringing => None,
+        }
+    }
+}
+
+impl fmt::Display for Alarm {
+    /// The alarm time and label, e.g. `07:30 Standup`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.time.format("%H:%M"))?;
+        if let Some(label) = &self.label {
+            write!(f, " {label}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap()
+    }
+
+    fn time(s: &str) -> NaiveTime {
+        parse_time_of_day(s).unwrap()
+    }
+
+    #[test]
+    fn parses_weekday_sets() {
+        let weekdays: Weekdays = "mon-fri".parse().unwrap();
+        assert!(weekdays.contains(Weekday::Mon) && weekdays.contains(Weekday::Fri));
+        assert!(!weekdays.contains(Weekday::Sat));
+
+        let wrapped: Weekdays = "fri-mon".parse().unwrap();
+        assert_eq!(wrapped, "sat,sun,fri,mon".parse().unwrap());
+        assert_eq!("Monday-Sunday".parse::<Weekdays>().unwrap(), Weekdays::ALL);
+        assert!("mon-funday".parse::<Weekdays>().is_err());
+        assert!("".parse::<Weekdays>().is_err());
+    }
+
+    #[test]
+    fn next_occurrence_follows_the_weekday_filter() {
+        let weekdays: Weekdays = "mon-fri".parse().unwrap();
+        // 2024-03-01 is a Friday
+        let friday = |h, m| Utc.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+
+        assert_eq!(
+            next_occurrence(time("07:30"), weekdays, &friday(7, 0)),
+            friday(7, 30)
+        );
+        assert_eq!(
+            next_occurrence(time("07:30"), weekdays, &friday(7, 30)),
+            Utc.with_ymd_and_hms(2024, 3, 4, 7, 30, 0).unwrap()
+        );
+        assert_eq!(
+            next_occurrence(time("07:30"), Weekdays::ALL, &friday(18, 0)),
+            Utc.with_ymd_and_hms(2024, 3, 2, 7, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn next_occurrence_wraps_past_midnight() {
+        // Thursday night, for an alarm just after midnight on Fridays only
+        let thursday = Utc.with_ymd_and_hms(2024, 2, 29, 23, 50, 0).unwrap();
+        let friday: Weekdays = "fri".parse().unwrap();
+        assert_eq!(
+            next_occurrence(time("00:15"), friday, &thursday),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 15, 0).unwrap()
+        );
+        assert_eq!(
+            next_occurrence(time("23:50"), friday, &thursday),
+            Utc.with_ymd_and_hms(2024, 3, 1, 23, 50, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn snoozes_stack_from_each_press() {
+        let mut alarm = Alarm::new(time("07:30"), Weekdays::ALL, None, DEFAULT_SNOOZE, at(6, 0));
+        assert!(!alarm.update(at(7, 29)));
+        assert!(alarm.update(at(7, 30)));
+
+        // Pressed two minutes into ringing, so the snooze runs from 07:32
+        alarm.snooze(at(7, 32));
+        assert_eq!(alarm.state(), AlarmState::Snoozed { until: at(7, 41) });
+        assert!(!alarm.update(at(7, 40)));
+        assert!(alarm.update(at(7, 41)));
+
+        alarm.snooze(at(7, 45));
+        assert_eq!(alarm.deadline(), Some(at(7, 54)));
+
+        // Snoozing again while already snoozed doesn't push it further out
+        alarm.snooze(at(7, 50));
+        assert_eq!(alarm.deadline(), Some(at(7, 54)));
+    }
+
+    #[test]
+    fn dismissing_reschedules_the_next_occurrence() {
+        let config = AlarmConfig {
+            time: "07:30".to_owned(),
+            days: Some("mon-fri".to_owned()),
+            label: Some("Standup".to_owned()),
+            enabled: true,
+        };
+        let mut alarm = config.alarm(DEFAULT_SNOOZE, at(6, 0)).unwrap().unwrap();
+        assert_eq!(alarm.to_string(), "07:30 Standup");
+        alarm.update(at(7, 30));
+        alarm.snooze(at(7, 30));
+        assert!(alarm.is_active());
+
+        // Friday morning's alarm is next due on Monday
+        alarm.dismiss(at(7, 31));
+        assert!(!alarm.is_active());
+        assert_eq!(
+            alarm.deadline(),
+            Some(Local.with_ymd_and_hms(2024, 3, 4, 7, 30, 0).unwrap())
+        );
+
+        let disabled = AlarmConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(disabled.alarm(DEFAULT_SNOOZE, at(6, 0)).unwrap(), None);
+    }
+}
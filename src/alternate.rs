@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeDelta};
+
+/// The date as `--alternate` shows it in place of the time: short enough to fit where the time
+/// does, like `Mar 1`.
+pub const DATE_FORMAT: &str = "%b %-d";
+
+/// The `--alternate` cycle between the time and the date, for screens too small to show both.
+/// The phase is counted on the wall clock from an anchor, so it doesn't depend on when ticks
+/// happen to land; the anchor starts at the epoch and moves to each key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alternation {
+    /// How long each of the time and the date is shown for.
+    period: Duration,
+    /// The wall-clock millisecond a time phase last started on.
+    anchor: i64,
+}
+
+impl Alternation {
+    pub fn new(period: Duration) -> Self {
+        Self { period, anchor: 0 }
+    }
+
+    /// Whether the date is showing at `local` rather than the time.
+    pub fn showing_date(&self, local: DateTime<Local>) -> bool {
+        self.phase(local) % 2 == 1
+    }
+
+    /// When the display next switches over after `local`.
+    pub fn next_switch(&self, local: DateTime<Local>) -> DateTime<Local> {
+        let next = self.anchor + (self.phase(local) + 1) * self.period_millis();
+        local + TimeDelta::milliseconds(next - local.timestamp_millis())
+    }
+
+    /// Starts a time phase at `local`, for a key press to bring the time straight back.
+    pub fn restart(&mut self, local: DateTime<Local>) {
+        self.anchor = local.timestamp_millis();
+    }
+
+    /// How many whole periods have passed since the anchor.
+    fn phase(&self, local: DateTime<Local>) -> i64 {
+        (local.timestamp_millis() - self.anchor).div_euclid(self.period_millis())
+    }
+
+    fn period_millis(&self) -> i64 {
+        (self.period.as_millis() as i64).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn alternates_on_the_wall_clock_until_restarted() {
+        let mut alternation = Alternation::new(Duration::from_secs(5));
+        let at = |secs, millis| {
+            Local.with_ymd_and_hms(2024, 3, 1, 12, 0, secs).unwrap()
+                + TimeDelta::milliseconds(millis)
+        };
+        assert!(!alternation.showing_date(at(0, 0)));
+        assert!(!alternation.showing_date(at(4, 999)));
+        assert!(alternation.showing_date(at(5, 0)));
+        assert!(!alternation.showing_date(at(10, 0)));
+        assert_eq!(alternation.next_switch(at(7, 250)), at(10, 0));
+
+        // A key press in the date phase brings the time back for a whole period
+        alternation.restart(at(7, 250));
+        assert!(!alternation.showing_date(at(7, 250)));
+        assert!(!alternation.showing_date(at(12, 249)));
+        assert!(alternation.showing_date(at(12, 250)));
+        assert_eq!(alternation.next_switch(at(8, 0)), at(12, 250));
+    }
+}
@@ -0,0 +1,144 @@
+use std::{f64::consts::TAU, time::Duration};
+
+use chrono::{DateTime, Local, Timelike};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Size},
+    style::Color,
+    symbols::Marker,
+    widgets::{
+        Widget,
+        canvas::{Canvas, Circle, Line},
+    },
+};
+
+use crate::ring::{ProgressRing, RingSpan};
+
+/// The fastest a sweeping second hand redraws, which keeps a large face at about 20fps.
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Lengths of the hands as a fraction of the face's radius.
+const HOUR_HAND: f64 = 0.5;
+const MINUTE_HAND: f64 = 0.75;
+const SECOND_HAND: f64 = 0.9;
+
+/// The cells a round face takes up within `available`. Cells are about twice as tall as they
+/// are wide, so a circle is twice as many columns across as it is rows.
+pub fn face_size(available: Size) -> (u16, u16) {
+    let rows = available.height.min(available.width / 2);
+    (rows * 2, rows)
+}
+
+/// How often a sweeping second hand on a face `rows` tall needs redrawing: about as long as its
+/// tip takes to move one braille dot (a quarter of a row), so redraws that wouldn't move it are
+/// skipped.
+pub fn sweep_interval(rows: u16) -> Duration {
+    let tip_radius = f64::from(rows) * 2.0 * SECOND_HAND;
+    let dots_per_minute = TAU * tip_radius;
+    if dots_per_minute < 60.0 {
+        return Duration::from_secs(1);
+    }
+    Duration::from_secs_f64(60.0 / dots_per_minute).max(MIN_SWEEP_INTERVAL)
+}
+
+/// A clock face with hour, minute, and second hands. The hour and minute hands step once a
+/// minute; the second hand either ticks once a second or, with `sweep`, moves continuously.
+#[derive(Debug, Clone, Copy)]
+pub struct AnalogClock {
+    pub time: DateTime<Local>,
+    pub sweep: bool,
+    pub color: Color,
+    /// A progress ring drawn over the rim, and its color.
+    pub rim: Option<(RingSpan, Color)>,
+}
+
+impl AnalogClock {
+    /// The angle of each hand in turns clockwise from twelve: hour, minute, second.
+    fn angles(&self) -> (f64, f64, f64) {
+        let minute = f64::from(self.time.minute());
+        let mut second = f64::from(self.time.second());
+        if self.sweep {
+            second += f64::from(self.time.nanosecond().min(999_999_999)) / 1e9;
+        }
+        let hour = f64::from(self.time.hour() % 12) + minute / 60.0;
+        (hour / 12.0, minute / 60.0, second / 60.0)
+    }
+}
+
+impl Widget for AnalogClock {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (hour, minute, second) = self.angles();
+        let hand = |turns: f64, length: f64| {
+            let angle = turns * TAU;
+            Line::new(
+                0.0,
+                0.0,
+                length * angle.sin(),
+                length * angle.cos(),
+                self.color,
+            )
+        };
+        Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([-1.0, 1.0])
+            .y_bounds([-1.0, 1.0])
+            .paint(|ctx| {
+                ctx.draw(&Circle {
+                    x: 0.0,
+                    y: 0.0,
+                    radius: 1.0,
+                    color: self.color,
+                });
+                ctx.draw(&hand(hour, HOUR_HAND));
+                ctx.draw(&hand(minute, MINUTE_HAND));
+                ctx.draw(&hand(second, SECOND_HAND));
+                if let Some((span, color)) = self.rim {
+                    let ring = ProgressRing {
+                        time: self.time,
+                        span,
+                        color,
+                    };
+                    ctx.draw(&ring.arc());
+                }
+            })
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn only_the_sweeping_second_hand_moves_between_seconds() {
+        let time = Local.with_ymd_and_hms(2024, 3, 1, 15, 30, 15).unwrap()
+            + chrono::TimeDelta::milliseconds(500);
+        let ticking = AnalogClock {
+            time,
+            sweep: false,
+            color: Color::Reset,
+            rim: None,
+        };
+        assert_eq!(ticking.angles(), (3.5 / 12.0, 0.5, 0.25));
+
+        let sweeping = AnalogClock {
+            sweep: true,
+            ..ticking
+        };
+        let (hour, minute, second) = sweeping.angles();
+        assert_eq!((hour, minute), (3.5 / 12.0, 0.5));
+        assert!((second - 15.5 / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sweep_interval_shrinks_with_the_face_but_stays_bounded() {
+        assert_eq!(sweep_interval(0), Duration::from_secs(1));
+        assert_eq!(sweep_interval(2), Duration::from_secs(1));
+        assert!(sweep_interval(20) < Duration::from_secs(2) / 3);
+        assert_eq!(sweep_interval(200), MIN_SWEEP_INTERVAL);
+        assert_eq!(face_size(Size::new(80, 24)), (48, 24));
+        assert_eq!(face_size(Size::new(30, 24)), (30, 15));
+    }
+}
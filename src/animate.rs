@@ -0,0 +1,233 @@
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::{Color, Modifier},
+};
+
+/// How long a change of digits takes to animate.
+pub const DURATION: Duration = Duration::from_millis(300);
+
+/// The frame rate while a change animates, which makes for ten frames. Between changes nothing
+/// is redrawn any faster than usual.
+pub const FPS: u32 = 30;
+
+/// How changed digits are animated, as `--animate` spells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Animation {
+    /// Snap straight to the new digits.
+    #[default]
+    None,
+    /// The old digit slides up out of its place as the new one rises in from below.
+    Slide,
+    /// The old digit fades out and the new one fades in: blended into the background when both
+    /// are truecolor, and dimmed otherwise.
+    Fade,
+}
+
+/// A change of the clock's text being animated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    /// The text before the change.
+    pub from: String,
+    pub animation: Animation,
+    pub started: Instant,
+}
+
+impl Transition {
+    /// How far through the animation it is at `now`, from 0 to 1.
+    pub fn progress(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.started);
+        (elapsed.as_secs_f64() / DURATION.as_secs_f64()).min(1.0)
+    }
+
+    pub fn is_done(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started) >= DURATION
+    }
+}
+
+/// Whether going from `from` to `to` can be animated digit by digit: both one line with as many
+/// characters, so each stays in its place.
+pub fn animatable(from: &str, to: &str) -> bool {
+    from != to
+        && !from.contains('\n')
+        && !to.contains('\n')
+        && from.chars().count() == to.chars().count()
+}
+
+/// One frame of the change from `from` to `to`, both one line of the same length drawn over
+/// `area` in glyphs `glyph_width` columns wide, into `buf`. Characters that didn't change are
+/// copied from `to` as they are; only the changed ones move, `progress` of the way through.
+pub fn composite(
+    (from_text, from): (&str, &Buffer),
+    (to_text, to): (&str, &Buffer),
+    glyph_width: u16,
+    animation: Animation,
+    progress: f64,
+    buf: &mut Buffer,
+) {
+    let area = to.area;
+    let changed = from_text.chars().zip(to_text.chars()).map(|(a, b)| a != b);
+    for (i, changed) in changed.enumerate() {
+        let left = area
+            .x
+            .saturating_add((i as u16).saturating_mul(glyph_width));
+        let columns = Rect {
+            x: left,
+            width: glyph_width.min(area.right().saturating_sub(left)),
+            ..area
+        };
+        for Position { x, y } in columns.positions() {
+            let row = y - area.y;
+            let cell = match animation {
+                _ if !changed => to[(x, y)].clone(),
+                Animation::None => to[(x, y)].clone(),
+                // The old glyph's rows move up by `offset` with the new one's following below
+                Animation::Slide => {
+                    let offset = (progress * f64::from(area.height)).round() as u16;
+                    match row + offset {
+                        shifted if shifted < area.height => from[(x, area.y + shifted)].clone(),
+                        shifted => to[(x, area.y + shifted - area.height)].clone(),
+                    }
+                },
+                // Out over the first half and back in over the second
+                Animation::Fade => {
+                    let source = if progress < 0.5 { from } else { to };
+                    let mut cell = source[(x, y)].clone();
+                    let strength = (2.0 * progress - 1.0).abs();
+                    match blend(cell.fg, cell.bg, strength) {
+                        Some(fg) => cell.fg = fg,
+                        None => cell.modifier.insert(Modifier::DIM),
+                    }
+                    cell
+                },
+            };
+            buf[(x, y)] = cell;
+        }
+    }
+}
+
+/// `fg` mixed into `bg`, all of `fg` at a `strength` of 1 and none of it at 0. Only 24-bit colors
+/// can be mixed.
+fn blend(fg: Color, bg: Color, strength: f64) -> Option<Color> {
+    let (Color::Rgb(fr, fg, fb), Color::Rgb(br, bg, bb)) = (fg, bg) else {
+        return None;
+    };
+    let mix =
+        |f: u8, b: u8| (f64::from(b) + (f64::from(f) - f64::from(b)) * strength).round() as u8;
+    Some(Color::Rgb(mix(fr, br), mix(fg, bg), mix(fb, bb)))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Style;
+
+    use super::*;
+
+    /// A buffer of one-column glyphs, each its character repeated down `height` rows.
+    fn glyphs(text: &str, height: u16) -> Buffer {
+        let area = Rect::new(0, 0, text.chars().count() as u16, height);
+        let mut buf = Buffer::empty(area);
+        for (x, c) in text.chars().enumerate() {
+            for y in 0..height {
+                buf[(x as u16, y)].set_symbol(&format!("{c}"));
+            }
+        }
+        buf
+    }
+
+    fn frame(animation: Animation, progress: f64) -> Vec<String> {
+        let (from, to) = (glyphs("12", 4), glyphs("13", 4));
+        let mut buf = Buffer::empty(to.area);
+        composite(("12", &from), ("13", &to), 1, animation, progress, &mut buf);
+        crate::widget::tests::rows(&buf)
+    }
+
+    #[test]
+    fn only_changed_digits_slide() {
+        assert_eq!(frame(Animation::Slide, 0.0), ["12", "12", "12", "12"]);
+        assert_eq!(frame(Animation::Slide, 0.5), ["12", "12", "13", "13"]);
+        assert_eq!(frame(Animation::Slide, 0.75), ["12", "13", "13", "13"]);
+        assert_eq!(frame(Animation::Slide, 1.0), ["13", "13", "13", "13"]);
+    }
+
+    #[test]
+    fn fading_dims_the_changed_digit_through_the_swap() {
+        let (from, to) = (glyphs("12", 1), glyphs("13", 1));
+        let mut buf = Buffer::empty(to.area);
+        composite(
+            ("12", &from),
+            ("13", &to),
+            1,
+            Animation::Fade,
+            0.25,
+            &mut buf,
+        );
+        assert_eq!(crate::widget::tests::rows(&buf), ["12"]);
+        assert!(buf[(1, 0)].modifier.contains(Modifier::DIM));
+        assert!(!buf[(0, 0)].modifier.contains(Modifier::DIM));
+        composite(
+            ("12", &from),
+            ("13", &to),
+            1,
+            Animation::Fade,
+            0.75,
+            &mut buf,
+        );
+        assert_eq!(crate::widget::tests::rows(&buf), ["13"]);
+    }
+
+    #[test]
+    fn fading_blends_truecolor_digits_into_the_background() {
+        let (mut from, mut to) = (glyphs("12", 1), glyphs("13", 1));
+        for buf in [&mut from, &mut to] {
+            buf.set_style(
+                buf.area,
+                Style::new()
+                    .fg(Color::Rgb(255, 200, 0))
+                    .bg(Color::Rgb(0, 0, 0)),
+            );
+        }
+        let fade = |progress| {
+            let mut buf = Buffer::empty(to.area);
+            composite(
+                ("12", &from),
+                ("13", &to),
+                1,
+                Animation::Fade,
+                progress,
+                &mut buf,
+            );
+            buf
+        };
+        let buf = fade(0.25);
+        assert_eq!(buf[(1, 0)].fg, Color::Rgb(128, 100, 0));
+        assert!(!buf[(1, 0)].modifier.contains(Modifier::DIM));
+        assert_eq!(buf[(0, 0)].fg, Color::Rgb(255, 200, 0));
+        assert_eq!(fade(0.5)[(1, 0)].fg, Color::Rgb(0, 0, 0));
+        let buf = fade(1.0);
+        assert_eq!(crate::widget::tests::rows(&buf), ["13"]);
+        assert_eq!(buf[(1, 0)].fg, Color::Rgb(255, 200, 0));
+    }
+
+    #[test]
+    fn only_same_length_single_lines_animate() {
+        assert!(animatable("12:59", "13:00"));
+        assert!(!animatable("12:59", "12:59"));
+        assert!(!animatable("9:59", "10:00"));
+        assert!(!animatable("Focus\n12:59", "Focus\n13:00"));
+
+        let started = Instant::now();
+        let transition = Transition {
+            from: "12:59".to_owned(),
+            animation: Animation::Slide,
+            started,
+        };
+        assert_eq!(transition.progress(started + DURATION / 2), 0.5);
+        assert!(!transition.is_done(started + DURATION / 2));
+        assert_eq!(transition.progress(started + DURATION * 2), 1.0);
+        assert!(transition.is_done(started + DURATION));
+    }
+}
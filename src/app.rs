@@ -0,0 +1,2455 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveTime, Offset, TimeDelta, TimeZone, Utc,
+    format::{Item, StrftimeItems},
+};
+use clap::ValueEnum;
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Position, Rect, Size},
+    style::{Color, Style},
+};
+use tui_big_text::PixelSize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    alarm::{Alarm, AlarmState},
+    alternate::{self, Alternation},
+    analog::{self, AnalogClock},
+    animate::{self, Animation, Transition},
+    battery::BatteryWatch,
+    bounce::Bounce,
+    calendar,
+    chess::{ChessClock, GameState, Side},
+    chime::Chime,
+    clipboard, config,
+    content::{ClockLine, ContentMode},
+    countdown::{Preset, Timer, TimerState},
+    decimal,
+    dots::SecondsDots,
+    dst::DstWatch,
+    duration::{
+        ceil_secs, format_compact, format_days, format_hms, format_hms_millis, format_until,
+    },
+    failure::ErrorLine,
+    flash::{FlashState, FlashStyle},
+    fuzzy::{self, Granularity},
+    grid::{Cell, CellKind, Grid},
+    holiday::Holidays,
+    ics::Agenda,
+    keys::Keymap,
+    layout::{Align, Margins, Region},
+    matrix::{self, Matrix},
+    moon::Moon,
+    notes::Notes,
+    notify::Notifier,
+    palette::ColorDepth,
+    picker::{self, ZonePicker},
+    rainbow::Rainbow,
+    record::{Event, Record, Recorder},
+    reminder::Reminders,
+    ring::{ProgressRing, Ring},
+    segment_font::SegmentFont,
+    stopwatch::{Stopwatch, write_laps_csv},
+    sun::{self, Coordinates},
+    sync::SyncWatch,
+    theme::Theme,
+    tick, uptime, widget,
+    zone::{self, Comparison, Zone, format_offset_difference},
+};
+
+/// How close the next alarm has to be for its corner reminder to stand out.
+pub const ALARM_SOON: Duration = Duration::from_secs(10 * 60);
+
+/// How long each on/off phase of the ringing alarm's banner lasts.
+pub const ALARM_FLASH_NANOS: u32 = 500_000_000;
+
+/// What `--header` shows unless the config sets its own template.
+pub const DEFAULT_HEADER: &str = "%A · %Y-%m-%d · :%Ss";
+
+/// How long the screen flashes as an alarm starts ringing. The banner keeps blinking after.
+const ALARM_SCREEN_FLASH: Duration = Duration::from_secs(1);
+
+/// Everything that can change the running clock. Keyboard input and the control socket both
+/// produce these, so any runtime toggle is reachable from either.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ToggleSeconds,
+    /// Switch between the 12- and 24-hour clock.
+    ToggleFormat,
+    /// Cycle the clock between the time, the date, and the time over the date.
+    CycleContent,
+    Pause,
+    /// Pause or resume both sides of a chess clock, where `pause` ends the turn instead.
+    /// Elsewhere, freeze the display at the moment it's pressed, say for a photo, until it's
+    /// pressed again. Timers and alarms carry on underneath, and one going off unfreezes it.
+    Hold,
+    Help,
+    ToggleDayNight,
+    /// Record a stopwatch lap.
+    Lap,
+    /// Write the stopwatch laps to the lap file now rather than on quit.
+    ExportLaps,
+    CycleTheme,
+    PreviousPreset,
+    NextPreset,
+    /// Start the selected timer preset. While an alarm is ringing or snoozed this dismisses it
+    /// instead, so Enter does both.
+    StartPreset,
+    /// Start the last timer again with its original duration.
+    RestartTimer,
+    AddMinute,
+    /// Silence a ringing alarm for the snooze interval.
+    Snooze,
+    /// Stop a ringing or snoozed alarm until its next occurrence.
+    Dismiss,
+    /// Silence every alarm until toggled off again. Alarms that come due meanwhile are skipped.
+    ToggleDoNotDisturb,
+    /// Copy what the clock is showing to the clipboard.
+    Copy,
+    ToggleCalendar,
+    /// Page the calendar back a month. It stays on the month paged to until `current-month`.
+    PreviousMonth,
+    NextMonth,
+    /// Page the calendar back to the current month, and follow it from then on.
+    CurrentMonth,
+    /// Switch between the clock and the machine's uptime.
+    ToggleUptime,
+    /// Open the picker for switching the clock to another time zone.
+    PickZone,
+    /// Scroll the `--agenda` notes panel up a line.
+    ScrollNotesUp,
+    ScrollNotesDown,
+    /// Stop an expired timer flashing. Keys with no binding send this, and every other action
+    /// acknowledges the timer as well, so any key stops the flash.
+    Acknowledge,
+    /// Stop the process as Ctrl+Z does in a shell, which raw mode keeps from happening by
+    /// itself, putting the terminal back until it's continued with `fg`.
+    Suspend,
+    SetFormat(String),
+    /// Show this text instead of the time.
+    SetText(String),
+    StartTimer(Duration),
+}
+
+/// The actions that take no argument, so can be bound to keys or sent by name alone.
+pub const NAMED_ACTIONS: &[Action] = &[
+    Action::Quit,
+    Action::ToggleSeconds,
+    Action::ToggleFormat,
+    Action::CycleContent,
+    Action::Pause,
+    Action::Hold,
+    Action::Help,
+    Action::ToggleDayNight,
+    Action::Lap,
+    Action::ExportLaps,
+    Action::CycleTheme,
+    Action::PreviousPreset,
+    Action::NextPreset,
+    Action::StartPreset,
+    Action::RestartTimer,
+    Action::AddMinute,
+    Action::Snooze,
+    Action::Dismiss,
+    Action::ToggleDoNotDisturb,
+    Action::Copy,
+    Action::ToggleCalendar,
+    Action::PreviousMonth,
+    Action::NextMonth,
+    Action::CurrentMonth,
+    Action::ToggleUptime,
+    Action::PickZone,
+    Action::ScrollNotesUp,
+    Action::ScrollNotesDown,
+    Action::Acknowledge,
+    Action::Suspend,
+];
+
+impl Action {
+    /// The name used for this action in `[keys]` and on the control socket.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::ToggleSeconds => "toggle-seconds",
+            Self::ToggleFormat => "toggle-format",
+            Self::CycleContent => "cycle-content",
+            Self::Pause => "pause",
+            Self::Hold => "hold",
+            Self::Help => "help",
+            Self::ToggleDayNight => "toggle-day-night",
+            Self::Lap => "lap",
+            Self::ExportLaps => "export-laps",
+            Self::CycleTheme => "cycle-theme",
+            Self::PreviousPreset => "previous-preset",
+            Self::NextPreset => "next-preset",
+            Self::StartPreset => "start-preset",
+            Self::RestartTimer => "restart-timer",
+            Self::AddMinute => "add-minute",
+            Self::Snooze => "snooze",
+            Self::Dismiss => "dismiss",
+            Self::ToggleDoNotDisturb => "toggle-do-not-disturb",
+            Self::Copy => "copy",
+            Self::ToggleCalendar => "toggle-calendar",
+            Self::PreviousMonth => "previous-month",
+            Self::NextMonth => "next-month",
+            Self::CurrentMonth => "current-month",
+            Self::ToggleUptime => "toggle-uptime",
+            Self::PickZone => "pick-zone",
+            Self::ScrollNotesUp => "scroll-notes-up",
+            Self::ScrollNotesDown => "scroll-notes-down",
+            Self::Acknowledge => "acknowledge",
+            Self::Suspend => "suspend",
+            Self::SetFormat(_) => "set-format",
+            Self::SetText(_) => "set-text",
+            Self::StartTimer(_) => "start-timer",
+        }
+    }
+}
+
+/// What the big text shows. The clock, uptime, and time since read their clocks as they're
+/// drawn, while timers and stopwatches measure on the monotonic clock, so setting the time or an
+/// NTP step moves the clock without touching anything being timed.
+#[derive(Debug, Default)]
+pub enum Mode {
+    #[default]
+    Clock,
+    Timer(Timer),
+    Stopwatch(Stopwatch),
+    /// How long the machine has been up.
+    Uptime,
+    /// How long it has been since a moment in the past, on the wall clock.
+    Since(DateTime<Local>),
+    /// Two players' clocks, one counting down while the other waits.
+    Chess(ChessClock),
+}
+
+impl Mode {
+    /// What the status bar calls this mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Clock => "clock",
+            Self::Timer(_) => "timer",
+            Self::Stopwatch(_) => "stopwatch",
+            Self::Uptime => "uptime",
+            Self::Since(_) => "since",
+            Self::Chess(_) => "chess",
+        }
+    }
+}
+
+/// How the 12-hour clock writes AM and PM, as `--ampm` spells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum AmPm {
+    #[default]
+    Upper,
+    Lower,
+    /// Leave the suffix off.
+    None,
+}
+
+/// Where the 12-hour clock's AM or PM goes, as `--ampm-position` places it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum AmPmPosition {
+    /// In the big digits, after the time.
+    #[default]
+    Inline,
+    /// In normal-sized text to the right of the digits' top row.
+    Beside,
+    /// In normal-sized text on a line of its own under the digits.
+    Below,
+}
+
+/// A short message shown under the clock until `until`.
+#[derive(Debug)]
+pub struct Notice {
+    pub text: String,
+    pub until: Instant,
+}
+
+/// Runtime state read by `draw`.
+#[derive(Debug, Default)]
+pub struct App {
+    /// A format set at runtime, used instead of the built-in 12/24-hour formats until a format
+    /// toggle is pressed.
+    pub custom_format: Option<String>,
+    /// Text set at runtime, shown in place of the time until the format is changed.
+    pub text: Option<String>,
+    pub hour24: bool,
+    /// Other zones whose time is shown under the clock.
+    pub comparisons: Vec<Comparison>,
+    /// The shape to lay out `cells` in, in place of the clock.
+    pub grid: Option<Grid>,
+    /// The clocks in the grid, from the config's `[[cell]]` entries.
+    pub cells: Vec<Cell>,
+    /// Label the clock with its zone's abbreviation.
+    pub zone_label: bool,
+    /// The zone the clock shows, for its abbreviation. Without one, the label is the offset.
+    pub local_zone: Option<Zone>,
+    /// The time zone picker, while it's open. It takes every key until it's closed.
+    pub zone_picker: Option<ZonePicker>,
+    /// The zones last picked, most recent first, listed at the top of the picker.
+    pub recent_zones: Vec<String>,
+    /// Where `recent_zones` is saved each time one is picked.
+    pub recents_path: Option<PathBuf>,
+    /// The zone picked while running, whose time the clock shows in place of the local time.
+    pub picked_zone: Option<Zone>,
+    /// The moment `hold` froze the display at, which is drawn instead of the current one.
+    pub frozen: Option<(DateTime<Local>, Instant)>,
+    /// An error the loop carried on after, shown until dismissed.
+    pub error: Option<ErrorLine>,
+    /// Whether the terminal has reported losing focus, as in a tmux pane out of view. The clock
+    /// still ticks, but animations drawn many times a second are put off until it's back.
+    pub unfocused: bool,
+    /// Drop the 12-hour clock's leading zero, so `07:30` is `7:30` and the narrower clock is
+    /// centered again.
+    pub no_leading_zero: bool,
+    /// What the built-in formats separate the hours, minutes, and seconds with in place of `:`,
+    /// possibly nothing.
+    pub separator: Option<String>,
+    pub ampm: AmPm,
+    pub ampm_position: AmPmPosition,
+    pub seconds: bool,
+    /// Whether the clock shows the time, the date, or both.
+    pub content: ContentMode,
+    /// The config's `[[lines]]`, which `datetime` draws in place of the time over the date.
+    pub lines: Vec<ClockLine>,
+    /// Spell out the weekday on a second line under the big date, when there's room.
+    pub date_weekday: bool,
+    pub show_help: bool,
+    /// Ask before quitting from the keyboard while something is being timed.
+    pub confirm_quit: bool,
+    /// The question asked before quitting, while it's on screen.
+    pub quit_prompt: Option<String>,
+    /// What `--persist` saved last time, offered back at startup until the next key answers.
+    pub resuming: Option<Mode>,
+    /// Show the sun/moon indicator above the clock.
+    pub day_night: bool,
+    /// Show a calendar of the month under the clock.
+    pub calendar: bool,
+    /// The month the calendar has been paged to, or `None` to follow the current one.
+    pub calendar_month: Option<NaiveDate>,
+    /// The `--ics` calendar whose next event is shown under the clock.
+    pub agenda: Option<Agenda>,
+    /// Cycle between the time and the date, for screens too small for both.
+    pub alternation: Option<Alternation>,
+    /// The `--agenda` notes shown in a panel beside the clock.
+    pub notes: Option<Notes>,
+    /// How many columns the notes panel takes, at most half the screen.
+    pub notes_width: u16,
+    /// The `--holidays` named above the clock.
+    pub holidays: Holidays,
+    /// How many days ahead to name a holiday coming up, if at all.
+    pub holidays_ahead: Option<u32>,
+    pub location: Option<Coordinates>,
+    /// Show today's sunrise and sunset at `location` under the clock.
+    pub sun_times: bool,
+    /// Show the moon's phase under the clock.
+    pub moon: bool,
+    /// Draw the digits and symbols like the moon in plain ASCII, rather than block characters and
+    /// emoji.
+    pub ascii: bool,
+    pub keymap: Keymap,
+    pub mode: Mode,
+    pub alarms: Vec<Alarm>,
+    /// The config's `[reminders]`, and the banner showing their messages.
+    pub reminders: Reminders,
+    pub do_not_disturb: bool,
+    /// Flash the whole screen when a timer expires or the hour chimes, rather than only
+    /// inverting a banner.
+    pub flash: bool,
+    /// How alarms and finished timers get attention beyond the screen, per `--notify`.
+    pub notifier: Notifier,
+    /// The hourly flash and bell, if turned on in the config.
+    pub chime: Option<Chime>,
+    /// The whole-screen flash under way, if any.
+    pub screen_flash: FlashState,
+    /// The next daylight saving change, warned about the day before.
+    pub dst: DstWatch,
+    /// Timer presets, carried into any timer started from the clock.
+    pub presets: Vec<Preset>,
+    /// Where stopwatch laps are exported to, on `export-laps` and on quit.
+    pub lap_file: Option<PathBuf>,
+    /// Show the stopwatch to the millisecond, redrawing it this many times a second while it
+    /// runs.
+    pub stopwatch_millis: Option<u32>,
+    /// Keep the stopwatch counting through a suspend, rather than only while the machine's awake.
+    pub stopwatch_counts_suspend: bool,
+    /// Where laps and finished timers are logged, with `--record`.
+    pub recorder: Option<Recorder>,
+    pub notice: Option<Notice>,
+    pub theme: Theme,
+    /// Cycle the digits through the hues instead of using the theme's color.
+    pub rainbow: Option<Rainbow>,
+    /// The colors the terminal can show, which everything drawn is brought down to.
+    pub color_depth: ColorDepth,
+    /// Every theme, in the order `cycle-theme` steps through them.
+    pub themes: Vec<Theme>,
+    /// The config file, where the chosen theme is saved.
+    pub config_path: Option<PathBuf>,
+    /// The config's profile in use, whose section the theme is saved in.
+    pub profile: Option<String>,
+    pub align: Align,
+    /// Cells kept clear along the sides of the frame before aligning the clock.
+    pub margin: Margins,
+    pub status_bar: bool,
+    /// The format of the line above the clock, if it's shown.
+    pub header: Option<String>,
+    /// Ticks come from a plain interval because timerfds aren't available, so clock changes
+    /// aren't noticed until the next tick. Flagged in the status bar, and in a notice at startup.
+    pub tick_fallback: bool,
+    /// How many ticks the timerfd fired without the loop waking for them, as when the process was
+    /// stopped. Counted in the status bar, for diagnosing a clock that lags.
+    pub missed_ticks: u64,
+    /// Ticks are counted from startup rather than wall-clock boundaries (`--no-align`). Flagged
+    /// in the status bar too.
+    pub relative_ticks: bool,
+    /// How big each pixel of the digits is.
+    pub pixel_size: PixelSize,
+    /// A bitmap font to draw the digits in, instead of tui-big-text's.
+    pub font: Option<SegmentFont>,
+    /// The color of the font's unlit segments, to show them faintly like an LCD's.
+    pub segment_off_color: Option<Color>,
+    /// Drift round the screen instead of staying put, until a key is pressed.
+    pub bounce: Option<Bounce>,
+    /// Rain green glyphs down the screen behind the clock.
+    pub matrix: Option<Matrix>,
+    /// Show the clock as a face with hands instead of digits.
+    pub analog: bool,
+    /// Sweep the analog second hand smoothly rather than ticking it once a second.
+    pub sweep: bool,
+    /// A ring showing progress through the minute or hour.
+    pub ring: Option<Ring>,
+    /// A row of dots under the clock filling up through the minute.
+    pub seconds_dots: Option<SecondsDots>,
+    /// Whether the system clock is synchronized, shown in the top-left corner.
+    pub sync: Option<SyncWatch>,
+    /// The battery's charge, shown in the bottom-right corner.
+    pub battery: Option<BatteryWatch>,
+    /// How the clock's digits change, when they do.
+    pub animate: Animation,
+    /// The change of digits being animated, if one is under way.
+    pub transition: Option<Transition>,
+    /// The clock's text as of the last update, to notice it changing.
+    pub shown_text: Option<String>,
+    /// The terminal's size, which decides how often a sweeping second hand needs redrawing.
+    pub frame_size: Size,
+    /// The fixed part of the frame to draw in instead of all of it, for use as an overlay.
+    pub region: Option<Region>,
+    /// Show the built-in clock in decimal time, 10 hours of 100 minutes of 100 seconds a day.
+    pub decimal: bool,
+    /// Round the clock to the nearest few minutes before it's formatted.
+    pub fuzzy: Option<Granularity>,
+    /// Spell the built-in clock out in words, like "quarter past ten".
+    pub fuzzy_words: bool,
+    /// Set by the suspend action for the loop, which has the terminal to put back first.
+    pub suspending: bool,
+}
+
+impl App {
+    /// Handles a key press bound to `action`. While something is being timed, quitting asks
+    /// first, and the next key answers: `y`, Enter, or quit again to quit, anything else to carry
+    /// on. Only keys ask; `quit` from the control socket or `--stdin` quits straight away. Asked
+    /// whether to resume what `--persist` saved, `y` or Enter resumes it, quit leaves it for
+    /// next time, and anything else drops it.
+    pub fn press(
+        &mut self,
+        key: &KeyEvent,
+        action: Action,
+        local: DateTime<Local>,
+        now: Instant,
+    ) -> ControlFlow<()> {
+        if self.zone_picker.is_some() {
+            self.pick_zone(key);
+            return ControlFlow::Continue(());
+        }
+        // Quitting leaves the saved state for next time, rather than answering no
+        if self.resuming.is_some() && action == Action::Quit {
+            return ControlFlow::Break(());
+        }
+        if let Some(mode) = self.resuming.take() {
+            if matches!(key.code, KeyCode::Char('y' | 'Y') | KeyCode::Enter) {
+                self.mode = mode;
+            }
+            return ControlFlow::Continue(());
+        }
+        if self.quit_prompt.take().is_some() {
+            let yes = matches!(key.code, KeyCode::Char('y' | 'Y') | KeyCode::Enter)
+                || action == Action::Quit;
+            return if yes {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            };
+        }
+        if action == Action::Quit
+            && self.confirm_quit
+            && let Some(timing) = self.timing()
+        {
+            self.quit_prompt = Some(format!("{timing} — quit? y/N"));
+            return ControlFlow::Continue(());
+        }
+        if let Some(alternation) = &mut self.alternation {
+            alternation.restart(local);
+        }
+        // Like a screensaver, any key but quit puts the clock back where it belongs
+        if action != Action::Quit {
+            self.bounce = None;
+        }
+        self.apply(action, local, now)
+    }
+
+    /// Types into the open zone picker or moves its selection. Enter picks the selected zone and
+    /// Esc closes the picker without one.
+    fn pick_zone(&mut self, key: &KeyEvent) {
+        let Some(picker) = &mut self.zone_picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.zone_picker = None,
+            KeyCode::Enter => {
+                if let Some(name) = picker.selected().map(str::to_owned) {
+                    picker::remember(&mut self.recent_zones, &name);
+                    if let Some(path) = &self.recents_path
+                        && let Err(err) = picker::save_recents(path, &self.recent_zones)
+                    {
+                        self.report_error(&err.context("saving the recent zones failed"));
+                    }
+                    if let Ok(zone) = Zone::load(&name) {
+                        self.picked_zone = Some(zone);
+                        self.local_zone = Some(zone);
+                        self.dst.invalidate();
+                    }
+                }
+                self.zone_picker = None;
+            },
+            KeyCode::Up => picker.move_selection(-1),
+            KeyCode::Down => picker.move_selection(1),
+            KeyCode::PageUp => picker.move_selection(-10),
+            KeyCode::PageDown => picker.move_selection(10),
+            KeyCode::Backspace => picker.pop(),
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                picker.push(c);
+            },
+            _ => {},
+        }
+    }
+
+    /// The question asked about what `--persist` saved, with the reading it would resume at.
+    pub fn resume_prompt(&self, now: Instant) -> Option<String> {
+        let resuming = self.resuming.as_ref()?;
+        let reading = match resuming {
+            Mode::Stopwatch(stopwatch) => format_hms(stopwatch.elapsed(now)),
+            Mode::Timer(timer) => match timer.overtime(now) {
+                Some(overtime) => format!("+{}", format_hms(overtime)),
+                None => format_hms(ceil_secs(timer.remaining(now))),
+            },
+            _ => return None,
+        };
+        Some(format!("resume {} at {reading}? y/N", resuming.name()))
+    }
+
+    /// What's being timed that quitting would lose, if anything.
+    fn timing(&self) -> Option<&'static str> {
+        match &self.mode {
+            Mode::Stopwatch(_) => Some("Stopwatch running"),
+            Mode::Chess(chess) if chess.state() == GameState::Playing => Some("Game in progress"),
+            Mode::Timer(timer) if matches!(timer.state(), TimerState::Running(_)) => {
+                Some("Timer running")
+            },
+            _ if self.alarm_active() => Some("Alarm pending"),
+            _ => None,
+        }
+    }
+
+    pub fn apply(
+        &mut self,
+        action: Action,
+        local: DateTime<Local>,
+        now: Instant,
+    ) -> ControlFlow<()> {
+        self.skip_transition();
+        match &mut self.mode {
+            Mode::Timer(timer) => timer.acknowledge(),
+            Mode::Chess(chess) => chess.acknowledge(),
+            _ => {},
+        }
+        match action {
+            Action::Quit => return ControlFlow::Break(()),
+            Action::Suspend => self.suspending = true,
+            Action::ToggleSeconds => {
+                self.seconds = !self.seconds;
+                self.custom_format = None;
+                self.text = None;
+            },
+            Action::ToggleFormat => {
+                self.hour24 = !self.hour24;
+                self.custom_format = None;
+                self.text = None;
+            },
+            Action::CycleContent => self.content = self.content.next(),
+            Action::Help => self.show_help = !self.show_help,
+            Action::ToggleDayNight => self.day_night = !self.day_night,
+            Action::SetFormat(format) => {
+                self.custom_format = Some(format);
+                self.text = None;
+            },
+            Action::SetText(text) => self.text = Some(text),
+            Action::StartTimer(duration) => {
+                self.timer().start(duration, now);
+            },
+            Action::Pause => match &mut self.mode {
+                Mode::Clock | Mode::Uptime | Mode::Since(_) => {},
+                Mode::Timer(timer) => timer.toggle_pause(now),
+                Mode::Stopwatch(stopwatch) => stopwatch.toggle_pause(now),
+                Mode::Chess(chess) => chess.switch(now),
+            },
+            Action::Hold => match &mut self.mode {
+                Mode::Chess(chess) => chess.toggle_pause(now),
+                _ if self.frozen.is_some() => self.frozen = None,
+                _ => self.frozen = Some((local, now)),
+            },
+            Action::Lap => {
+                if let Mode::Stopwatch(stopwatch) = &mut self.mode {
+                    stopwatch.lap(now);
+                    if let (Some(recorder), Some(lap)) = (&self.recorder, stopwatch.laps().last()) {
+                        recorder.record(Record {
+                            event: Event::Lap,
+                            wallclock: local,
+                            elapsed: lap.total,
+                        });
+                    }
+                }
+            },
+            Action::ExportLaps => {
+                let text = match self.export_laps() {
+                    Ok(Some(path)) => format!("laps written to {}", path.display()),
+                    Ok(None) => "no --lap-file to export to".to_owned(),
+                    Err(err) => format!("{err:#}"),
+                };
+                self.notify(text, now);
+            },
+            Action::CycleTheme => self.cycle_theme(now),
+            Action::PreviousPreset => self.timer().select(-1),
+            Action::NextPreset => self.timer().select(1),
+            Action::StartPreset | Action::Dismiss if self.alarm_active() => {
+                self.dismiss_alarms(local);
+            },
+            Action::Dismiss | Action::Acknowledge => self.error = None,
+            Action::StartPreset => self.timer().start_selected(now),
+            Action::Snooze => {
+                for alarm in &mut self.alarms {
+                    alarm.snooze(local);
+                }
+            },
+            Action::ToggleDoNotDisturb => {
+                self.do_not_disturb = !self.do_not_disturb;
+                let text = if self.do_not_disturb {
+                    for alarm in self.alarms.iter_mut().filter(|alarm| alarm.is_active()) {
+                        alarm.dismiss(local);
+                    }
+                    "do not disturb: alarms off"
+                } else {
+                    "do not disturb off"
+                };
+                self.notify(text.to_owned(), now);
+            },
+            Action::Copy => {
+                let shown = self.display_text(local, now);
+                let text = match clipboard::copy(shown.trim()) {
+                    Ok(()) => format!("copied {}", shown.trim()),
+                    Err(err) => format!("not copied: {err:#}"),
+                };
+                self.notify(text, now);
+            },
+            Action::ToggleCalendar => self.calendar = !self.calendar,
+            Action::PreviousMonth | Action::NextMonth if self.calendar => {
+                let step = if action == Action::NextMonth { 1 } else { -1 };
+                self.calendar_month = Some(calendar::add_months(
+                    self.calendar_month(local.date_naive()),
+                    step,
+                ));
+            },
+            Action::PreviousMonth | Action::NextMonth => {},
+            Action::CurrentMonth => self.calendar_month = None,
+            // A running timer or stopwatch isn't thrown away for it
+            Action::ToggleUptime => match self.mode {
+                Mode::Clock => self.mode = Mode::Uptime,
+                Mode::Uptime => self.mode = Mode::Clock,
+                Mode::Timer(_) | Mode::Stopwatch(_) | Mode::Since(_) | Mode::Chess(_) => {},
+            },
+            Action::PickZone => {
+                self.zone_picker = Some(ZonePicker::new(zone::zone_names(), &self.recent_zones));
+            },
+            Action::ScrollNotesUp | Action::ScrollNotesDown => {
+                let lines = if action == Action::ScrollNotesDown {
+                    1
+                } else {
+                    -1
+                };
+                if let Some(notes) = &mut self.notes {
+                    notes.scroll_by(lines);
+                }
+            },
+            Action::RestartTimer => {
+                if let Mode::Timer(timer) = &mut self.mode {
+                    timer.restart(now);
+                }
+            },
+            Action::AddMinute => {
+                if let Mode::Timer(timer) = &mut self.mode {
+                    timer.extend(Duration::from_secs(60));
+                }
+            },
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Advances time-driven state, e.g. a timer reaching zero. Called before every draw.
+    pub fn update(&mut self, local: DateTime<Local>, now: Instant) {
+        let mut rang = false;
+        for alarm in &mut self.alarms {
+            if self.do_not_disturb && alarm.is_due(local) {
+                alarm.dismiss(local);
+            } else if alarm.update(local) {
+                self.notifier.notify(&format!("Alarm: {alarm}"));
+                rang = true;
+            }
+        }
+        if rang {
+            self.frozen = None;
+            self.start_flash(Some(ALARM_SCREEN_FLASH), FlashStyle::Inverted, now);
+        }
+        let chime_flash = match &mut self.chime {
+            Some(chime) => chime.update(local, now).then(|| chime.flash()).flatten(),
+            None => None,
+        };
+        if let Some((duration, style)) = chime_flash {
+            self.start_flash(Some(duration), style, now);
+        }
+        self.reminders.update(local, now);
+        if let Some(bounce) = &mut self.bounce {
+            bounce.update(now);
+        }
+        // Held still while the frames that would show it moving are put off
+        if let Some(matrix) = &mut self.matrix
+            && self.frozen.is_none()
+            && !self.unfocused
+        {
+            matrix.update(self.frame_size.width, self.frame_size.height, now);
+        }
+        let picked_zone = self.picked_zone;
+        self.dst.update(local.to_utc(), |time| match picked_zone {
+            Some(zone) => zone.offset_at(time),
+            None => local_offset(time),
+        });
+        if let Some(sync) = &mut self.sync {
+            sync.update(now);
+        }
+        if let Some(battery) = &mut self.battery {
+            battery.update(now);
+        }
+        if let Mode::Timer(timer) = &mut self.mode
+            && timer.update(now)
+        {
+            let length = timer.last_started();
+            let event = match length {
+                Some(length) => format!("Timer done: {}", format_compact(length)),
+                None => "Timer done".to_owned(),
+            };
+            self.notifier.notify(&event);
+            self.frozen = None;
+            if let (Some(recorder), Some(length)) = (&self.recorder, length) {
+                recorder.record(Record {
+                    event: Event::TimerDone,
+                    wallclock: local,
+                    elapsed: length,
+                });
+            }
+        }
+        if let Mode::Chess(chess) = &mut self.mode {
+            chess.update(now);
+        }
+        // An expired timer blinks until acknowledged, however the acknowledging happened
+        match self.expiry_flash_start() {
+            Some(at) if !self.screen_flash.is_blinking() => {
+                self.start_flash(None, FlashStyle::Inverted, at);
+            },
+            None if self.screen_flash.is_blinking() => self.screen_flash.stop(),
+            _ => {},
+        }
+        self.screen_flash.update(now);
+        self.follow_text(local, now);
+    }
+
+    /// Flashes the whole screen, for `duration` or blinking until stopped, unless `--no-flash`
+    /// turned flashing off. Replaces any flash already under way.
+    pub fn start_flash(&mut self, duration: Option<Duration>, style: FlashStyle, now: Instant) {
+        if self.flash {
+            self.screen_flash.start(duration, style, now);
+        }
+    }
+
+    /// Starts animating the clock's digits if they changed since the last update. Only the
+    /// clock animates, and only when it changes once a second or slower, so a transition is
+    /// always over well before the next one.
+    fn follow_text(&mut self, local: DateTime<Local>, now: Instant) {
+        if self.transition.as_ref().is_some_and(|t| t.is_done(now)) {
+            self.transition = None;
+        }
+        let showing_clock = matches!(self.mode, Mode::Clock) && self.text.is_none();
+        if self.animate == Animation::None
+            || !showing_clock
+            || self.analog
+            || self.tick_interval() < tick::SECOND
+        {
+            self.shown_text = None;
+            return;
+        }
+        let text = self.display_text(local, now);
+        if let Some(from) = self.shown_text.replace(text.clone())
+            && animate::animatable(&from, &text)
+        {
+            self.transition = Some(Transition {
+                from,
+                animation: self.animate,
+                started: now,
+            });
+        }
+    }
+
+    /// Catches up after the machine was suspended for `slept`, which the monotonic clock timers
+    /// and stopwatches run on doesn't count. A timer counts down to a moment, so it counts the
+    /// time asleep; a stopwatch only does with `--count-suspend`, and a chess game doesn't.
+    pub fn resumed(&mut self, slept: Duration) {
+        match &mut self.mode {
+            Mode::Timer(timer) => timer.slept(slept),
+            Mode::Stopwatch(stopwatch) if self.stopwatch_counts_suspend => {
+                stopwatch.slept(slept);
+            },
+            _ => {},
+        }
+        for cell in &mut self.cells {
+            cell.slept(slept, self.stopwatch_counts_suspend);
+        }
+        self.dst.invalidate();
+        self.skip_transition();
+    }
+
+    /// Drops any transition under way, jumping straight to the current digits. Keys and resizes
+    /// change what's drawn, so finishing the animation over the top would only look broken.
+    pub fn skip_transition(&mut self) {
+        self.transition = None;
+        self.shown_text = None;
+    }
+
+    /// The banner shown under an expired timer until a key is pressed.
+    pub fn expiry_banner(&self) -> Option<String> {
+        let Mode::Timer(timer) = &self.mode else {
+            return None;
+        };
+        if !timer.is_unacknowledged() {
+            return None;
+        }
+        Some(match self.keymap.key_for(&Action::RestartTimer) {
+            Some(key) => format!("time's up ({key} to restart)"),
+            None => "time's up".to_owned(),
+        })
+    }
+
+    /// How the frame should be drawn right now if it's flashed, like during the on half of an
+    /// expired timer's blink or while the hour chimes.
+    pub fn flash_style(&self, now: Instant) -> Option<FlashStyle> {
+        self.screen_flash.active(now)
+    }
+
+    /// When the flashing timer expired, if there is one.
+    fn expiry_flash_start(&self) -> Option<Instant> {
+        match &self.mode {
+            Mode::Timer(timer) => match timer.state() {
+                TimerState::Expired {
+                    at,
+                    acknowledged: false,
+                } => Some(at),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn alarm_active(&self) -> bool {
+        self.alarms.iter().any(Alarm::is_active)
+    }
+
+    /// Dismisses the ringing alarms, or the snoozed ones if none are ringing.
+    fn dismiss_alarms(&mut self, local: DateTime<Local>) {
+        let ringing = self
+            .alarms
+            .iter()
+            .any(|alarm| alarm.state() == AlarmState::Ringing);
+        for alarm in &mut self.alarms {
+            if alarm.state() == AlarmState::Ringing || (!ringing && alarm.is_active()) {
+                alarm.dismiss(local);
+            }
+        }
+    }
+
+    /// The line shown under the clock while an alarm is ringing or snoozed, and whether it is
+    /// ringing. A ringing alarm wins over snoozed ones.
+    pub fn alarm_status(&self) -> Option<(String, bool)> {
+        let alarm = self
+            .alarms
+            .iter()
+            .filter(|alarm| alarm.is_active())
+            .min_by_key(|alarm| alarm.deadline())?;
+        match alarm.state() {
+            AlarmState::Armed { .. } => None,
+            AlarmState::Ringing => {
+                let mut text = format!("alarm {alarm}");
+                let dismiss = self
+                    .keymap
+                    .key_for(&Action::Dismiss)
+                    .or_else(|| self.keymap.key_for(&Action::StartPreset));
+                for (key, what) in [
+                    (self.keymap.key_for(&Action::Snooze), "snooze"),
+                    (dismiss, "dismiss"),
+                ] {
+                    if let Some(key) = key {
+                        text += &format!(" · {key} to {what}");
+                    }
+                }
+                Some((text, true))
+            },
+            AlarmState::Snoozed { until } => {
+                Some((format!("snoozed until {}", until.format("%H:%M")), false))
+            },
+        }
+    }
+
+    /// The label under the clock naming its zone, like `CET`. Looked up for each draw, since
+    /// the abbreviation changes with DST.
+    pub fn zone_abbreviation(&self, local: DateTime<Local>) -> Option<String> {
+        let showing_clock = matches!(self.mode, Mode::Clock) && self.text.is_none();
+        if !self.zone_label || !showing_clock {
+            return None;
+        }
+        Some(match &self.local_zone {
+            Some(zone) => zone.abbreviation_at(local.to_utc()),
+            None => local.format("UTC%:z").to_string(),
+        })
+    }
+
+    /// The line under the clock with today's sunrise and sunset, e.g. `☀ 06:42 ↑ · 19:55 ↓`,
+    /// and the moon's phase, each when turned on. Both are for the day rather than the moment,
+    /// so they move on at midnight.
+    pub fn info_line(&self, local: DateTime<Local>) -> Option<String> {
+        let showing_clock = matches!(self.mode, Mode::Clock) && self.text.is_none();
+        if !showing_clock {
+            return None;
+        }
+        let sun = self
+            .location
+            .filter(|_| self.sun_times)
+            .map(|location| sun::sun_times(local.date_naive(), location).line(&Local));
+        // The moon as it is at midday, so the phase doesn't change partway through the day
+        let noon =
+            local.with_time(NaiveTime::MIN).earliest().unwrap_or(local) + TimeDelta::hours(12);
+        let moon = self.moon.then(|| Moon::at(noon.to_utc()).line(self.ascii));
+        let parts: Vec<String> = [sun, moon].into_iter().flatten().collect();
+        (!parts.is_empty()).then(|| parts.join("   "))
+    }
+
+    /// A line per compared zone, e.g. `Kolkata: 21:11 (+4h30m)`, with the difference from the
+    /// local zone taken from both zones' current offsets so it follows either one's DST.
+    pub fn comparison_lines(&self, local: DateTime<Local>) -> Vec<String> {
+        let format = if self.hour24 { "%H:%M" } else { "%-I:%M %p" };
+        self.comparisons
+            .iter()
+            .map(|comparison| {
+                let offset = comparison.zone.offset_at(local.to_utc());
+                let difference = offset.local_minus_utc() - local.offset().local_minus_utc();
+                format!(
+                    "{}: {} ({})",
+                    comparison.label,
+                    local.with_timezone(&offset).format(format),
+                    format_offset_difference(difference)
+                )
+            })
+            .collect()
+    }
+
+    /// The part of `frame` the clock is drawn in: the `--x`/`--y` region, or all of it.
+    pub fn screen_area(&self, frame: Rect) -> Rect {
+        self.region.map_or(frame, |region| region.within(frame))
+    }
+
+    /// How many of `width` columns the notes panel takes: its set width, but no more than half,
+    /// so the clock keeps the larger part. Nothing without `--agenda`.
+    pub fn notes_panel_width(&self, width: u16) -> u16 {
+        match self.notes {
+            Some(_) => self.notes_width.min(width / 2),
+            None => 0,
+        }
+    }
+
+    /// The first of the month the calendar shows on `today`.
+    pub fn calendar_month(&self, today: NaiveDate) -> NaiveDate {
+        self.calendar_month
+            .unwrap_or_else(|| calendar::month_of(today))
+    }
+
+    /// The corner reminder of the next alarm to ring or come back from snoozing, e.g.
+    /// `⏰ 07:30 Standup in 6h 12m`, and whether it's soon enough to stand out.
+    pub fn next_alarm_text(&self, local: DateTime<Local>) -> Option<(String, bool)> {
+        if self.do_not_disturb {
+            return (!self.alarms.is_empty()).then(|| ("⏰ do not disturb".to_owned(), false));
+        }
+        let (alarm, next) = self
+            .alarms
+            .iter()
+            .filter_map(|alarm| Some((alarm, alarm.deadline()?)))
+            .min_by_key(|(_, next)| *next)?;
+        let until = (next - local).to_std().unwrap_or_default();
+        Some((
+            format!("⏰ {alarm} in {}", format_until(until)),
+            until <= ALARM_SOON,
+        ))
+    }
+
+    /// The status bar's text, fitted to `width` columns: the mode, UTC offset, and next alarm on
+    /// the left and key hints on the right. Hints are dropped first when space runs out, then the
+    /// rest is cut short.
+    pub fn status_bar_text(&self, local: DateTime<Local>, width: usize) -> String {
+        let mut info = vec![
+            self.mode.name().to_owned(),
+            local.format("UTC%:z").to_string(),
+        ];
+        info.extend(self.next_alarm_text(local).map(|(text, _)| text));
+        if self.tick_fallback {
+            info.push("no timerfd".to_owned());
+        }
+        if self.relative_ticks {
+            info.push("unaligned ticks".to_owned());
+        }
+        if self.missed_ticks > 0 {
+            info.push(format!("{} missed", self.missed_ticks));
+        }
+        let info = info.join(" · ");
+
+        let second = match self.mode {
+            Mode::Clock | Mode::Since(_) => (Action::ToggleSeconds, "seconds"),
+            Mode::Timer(_) | Mode::Stopwatch(_) => (Action::Pause, "pause"),
+            Mode::Uptime => (Action::ToggleUptime, "clock"),
+            Mode::Chess(_) => (Action::Pause, "move"),
+        };
+        let mut hints: Vec<String> = [(Action::Quit, "quit"), second, (Action::Help, "help")]
+            .into_iter()
+            .filter_map(|(action, what)| Some(format!("{} {what}", self.keymap.key_for(&action)?)))
+            .collect();
+
+        loop {
+            let right = hints.join(" · ");
+            let gap = width.saturating_sub(info.width() + right.width());
+            if hints.is_empty() {
+                return truncate(&info, width);
+            }
+            if gap >= 2 {
+                return format!("{info}{}{right}", " ".repeat(gap));
+            }
+            hints.pop();
+        }
+    }
+
+    /// The timer, switching to timer mode first if the clock isn't already in it.
+    fn timer(&mut self) -> &mut Timer {
+        if !matches!(self.mode, Mode::Timer(_)) {
+            self.mode = Mode::Timer(Timer::new(self.presets.clone()));
+        }
+        let Mode::Timer(timer) = &mut self.mode else {
+            unreachable!()
+        };
+        timer
+    }
+
+    fn cycle_theme(&mut self, now: Instant) {
+        let current = self
+            .themes
+            .iter()
+            .position(|theme| theme.name == self.theme.name);
+        let next = current.map_or(0, |i| (i + 1) % self.themes.len());
+        let Some(theme) = self.themes.get(next) else {
+            return;
+        };
+        self.theme = theme.clone();
+
+        let text = match &self.config_path {
+            Some(path) => match config::save_theme(path, self.profile.as_deref(), &self.theme.name)
+            {
+                Ok(()) => format!("theme: {}", self.theme.name),
+                Err(err) => format!("theme: {} (not saved: {err:#})", self.theme.name),
+            },
+            None => format!("theme: {}", self.theme.name),
+        };
+        self.notify(text, now);
+    }
+
+    /// Shows `text` under the clock for a few seconds.
+    pub fn notify(&mut self, text: String, now: Instant) {
+        const NOTICE_DURATION: Duration = Duration::from_secs(4);
+        self.notice = Some(Notice {
+            text,
+            until: now + NOTICE_DURATION,
+        });
+    }
+
+    /// Shows an error the loop carried on after, until it's dismissed.
+    pub fn report_error(&mut self, err: &anyhow::Error) {
+        // Only the first line, as with config errors quoting their source after it
+        let message = format!("{err:#}");
+        let message = message.lines().next().unwrap_or_default().to_owned();
+        ErrorLine::report(&mut self.error, message);
+    }
+
+    pub fn visible_notice(&self, now: Instant) -> Option<&Notice> {
+        self.notice.as_ref().filter(|notice| notice.until > now)
+    }
+
+    /// Writes the stopwatch's laps to the lap file, returning the path if there was one to write.
+    pub fn export_laps(&self) -> anyhow::Result<Option<&Path>> {
+        let (Mode::Stopwatch(stopwatch), Some(path)) = (&self.mode, &self.lap_file) else {
+            return Ok(None);
+        };
+        write_laps_csv(stopwatch.laps(), path)?;
+        Ok(Some(path))
+    }
+
+    /// The string shown in big text: the countdown or stopwatch reading, or else the time. Only
+    /// the time comes from `local`; readings come from the monotonic `now`.
+    pub fn display_text(&self, local: DateTime<Local>, now: Instant) -> String {
+        match &self.mode {
+            Mode::Clock => match &self.text {
+                Some(text) => text.clone(),
+                None if !self.clock_lines().is_empty() => {
+                    ClockLine::text(&self.lines, self.fuzzed(local))
+                },
+                None => self.content.text(self.time_text(local), local),
+            },
+            Mode::Timer(timer) => match timer.overtime(now) {
+                Some(overtime) => format!("+{}", format_hms(overtime)),
+                None => format_hms(ceil_secs(timer.remaining(now))),
+            },
+            Mode::Stopwatch(stopwatch) if self.stopwatch_millis.is_some() => {
+                format_hms_millis(stopwatch.elapsed(now))
+            },
+            Mode::Stopwatch(stopwatch) => format_hms(stopwatch.elapsed(now)),
+            Mode::Uptime => uptime::read().map_or_else(
+                |_| "--:--:--".to_owned(),
+                |uptime| format_days(uptime, true),
+            ),
+            // A moment still to come reads zero rather than counting down
+            Mode::Since(since) => {
+                format_days((local - *since).to_std().unwrap_or_default(), self.seconds)
+            },
+            Mode::Chess(chess) => {
+                let [white, black] = Side::BOTH.map(|side| chess.reading(side, now));
+                format!("{white} {black}")
+            },
+        }
+    }
+
+    /// The cells to lay out in the grid instead of the clock, with `--grid`, while the clock
+    /// is showing.
+    pub fn grid_cells(&self) -> &[Cell] {
+        match self.showing_clock() && self.grid.is_some() {
+            true => &self.cells,
+            false => &[],
+        }
+    }
+
+    /// What a grid cell reads: the time in its zone, formatted like the clock, or its timer's
+    /// or stopwatch's reading.
+    pub fn cell_text(&self, cell: &Cell, local: DateTime<Local>, now: Instant) -> String {
+        match &cell.kind {
+            CellKind::Clock(zone) => {
+                let time = match zone {
+                    Some(zone) => local.with_timezone(&zone.offset_at(local.to_utc())),
+                    None => local.fixed_offset(),
+                };
+                match &self.custom_format {
+                    Some(format) => time.format(format).to_string(),
+                    None => self.builtin_text(time, &self.format()),
+                }
+            },
+            CellKind::Timer(countdown) => format_hms(ceil_secs(countdown.remaining(now))),
+            CellKind::Stopwatch(stopwatch) => format_hms(stopwatch.elapsed(now)),
+        }
+    }
+
+    /// The lines from the config to draw, when there are any and both the time and date are
+    /// showing.
+    pub fn clock_lines(&self) -> &[ClockLine] {
+        match self.showing_clock() && self.content == ContentMode::DateTime {
+            true => &self.lines,
+            false => &[],
+        }
+    }
+
+    /// The time at `local` as the clock shows it, in whichever format is chosen.
+    fn time_text(&self, local: DateTime<Local>) -> String {
+        let local = self.fuzzed(local);
+        match &self.custom_format {
+            Some(format) => local.format(format).to_string(),
+            None if self.decimal => self.decimal_text(local, self.seconds),
+            None if self.fuzzy_words => fuzzy::words(local),
+            None => self.builtin_text(local, &self.builtin_format(self.shows_seconds())),
+        }
+    }
+
+    /// `local` rounded for `--fuzzy`, or as it is without.
+    fn fuzzed(&self, local: DateTime<Local>) -> DateTime<Local> {
+        match self.fuzzy {
+            Some(granularity) => fuzzy::round(local, granularity),
+            None => local,
+        }
+    }
+
+    /// Whether the built-in formats have seconds, which a fuzzy clock would only ever show as
+    /// zero.
+    fn shows_seconds(&self) -> bool {
+        self.seconds && self.fuzzy.is_none()
+    }
+
+    /// The date to take turns with the time, when alternating and the clock is showing.
+    pub fn date_text(&self, local: DateTime<Local>) -> Option<String> {
+        // Showing the date already, there's no need to take turns
+        let showing_time_only = self.showing_clock() && self.content == ContentMode::Time;
+        self.alternation
+            .filter(|_| showing_time_only)
+            .map(|_| local.format(alternate::DATE_FORMAT).to_string())
+    }
+
+    /// The big date with the weekday under it, to show instead of the date alone when both fit.
+    pub fn date_with_weekday(&self, local: DateTime<Local>) -> Option<String> {
+        let showing_date = self.showing_clock() && self.content == ContentMode::Date;
+        (showing_date && self.date_weekday).then(|| {
+            let date = self.content.text(String::new(), local);
+            format!("{date}\n{}", local.format("%a").to_string().to_uppercase())
+        })
+    }
+
+    /// The time on its small line under the big date.
+    pub fn time_under_date(&self, local: DateTime<Local>) -> Option<String> {
+        let showing_date = self.showing_clock() && self.content == ContentMode::Date;
+        showing_date.then(|| self.time_text(local))
+    }
+
+    /// The line above the clock: the header, with the holiday after it. Without a header, the
+    /// holiday has the line to itself.
+    pub fn header_text(&self, local: DateTime<Local>) -> Option<String> {
+        let holiday = self.holiday_text(local);
+        match (&self.header, holiday) {
+            (Some(header), Some(holiday)) => Some(format!("{} · {holiday}", local.format(header))),
+            (Some(header), None) => Some(local.format(header).to_string()),
+            (None, holiday) => holiday,
+        }
+    }
+
+    /// The holiday to name on the header line: today's, or failing that the next within
+    /// `holidays_ahead` days, like "Bank Holiday in 3d".
+    pub fn holiday_text(&self, local: DateTime<Local>) -> Option<String> {
+        let today = local.date_naive();
+        self.holidays.on(today).or_else(|| {
+            let (name, days) = self.holidays.upcoming(today, self.holidays_ahead?)?;
+            Some(format!("{name} in {days}d"))
+        })
+    }
+
+    /// The format the clock is drawn with. A custom one wins over every built-in option.
+    pub fn format(&self) -> Cow<'_, str> {
+        match &self.custom_format {
+            Some(format) => Cow::Borrowed(format),
+            None => Cow::Owned(self.builtin_format(self.shows_seconds())),
+        }
+    }
+
+    fn builtin_format(&self, seconds: bool) -> String {
+        let seconds = if seconds { ":%S" } else { "" };
+        let suffix = match self.ampm {
+            _ if self.hour24 => "",
+            AmPm::Upper => " %p",
+            AmPm::Lower => " %P",
+            AmPm::None => "",
+        };
+        format!("{}:%M{seconds}{suffix}", self.hour_format())
+    }
+
+    fn hour_format(&self) -> &'static str {
+        if self.hour24 { "%H" } else { "%I" }
+    }
+
+    /// `local` in `format`, one of the built-in formats, without the 12-hour clock's leading
+    /// zero if `no_leading_zero` is on. The zero is stripped once formatted: chrono's `%-I`
+    /// would drop it too, but `%_I`'s space and `%l` are no good, as the space would be drawn.
+    fn builtin_text<Tz: TimeZone>(&self, local: DateTime<Tz>, format: &str) -> String
+    where
+        Tz::Offset: fmt::Display,
+    {
+        let mut text = local.format(format).to_string();
+        if let Some(separator) = &self.separator {
+            text = text.replace(':', separator);
+        }
+        match text.strip_prefix('0') {
+            Some(stripped) if !self.hour24 && self.no_leading_zero => stripped.to_owned(),
+            _ => text,
+        }
+    }
+
+    /// Separates the built-in formats' hours, minutes, and seconds with `separator` instead of
+    /// `:`, as long as the big digits' font can draw it. If not, the colon stays and a notice
+    /// says why.
+    pub fn set_separator(&mut self, separator: &str, now: Instant) {
+        let missing = separator.chars().find(|&c| match &self.font {
+            Some(font) => !font.has_glyph(c),
+            None => !widget::has_big_glyph(c),
+        });
+        match missing {
+            Some(c) => self.notify(
+                format!("the font has no {c:?}, so the clock keeps its colons"),
+                now,
+            ),
+            None => self.separator = Some(separator.to_owned()),
+        }
+    }
+
+    /// The clock without its seconds, for when the full time doesn't fit. Only the built-in
+    /// formats with seconds turned on have any to drop. The time in words falls back to digits
+    /// here, which are far narrower.
+    pub fn without_seconds_text(&self, local: DateTime<Local>) -> Option<String> {
+        let showing_time = self.showing_clock() && self.content.shows_time();
+        if !showing_time
+            || self.custom_format.is_some()
+            || !self.clock_lines().is_empty()
+            || !(self.seconds || self.fuzzy_words)
+        {
+            return None;
+        }
+        let local = self.fuzzed(local);
+        let time = match self.decimal {
+            true => self.decimal_text(local, false),
+            false => self.builtin_text(local, &self.builtin_format(false)),
+        };
+        Some(self.content.text(time, local))
+    }
+
+    /// Just the hours and minutes, the last thing given up when the terminal is tiny.
+    pub fn short_text(&self, local: DateTime<Local>) -> Option<String> {
+        let showing_time = self.showing_clock() && self.content.shows_time();
+        let format = format!("{}:%M", self.hour_format());
+        let local = self.fuzzed(local);
+        showing_time.then(|| {
+            let time = match self.decimal && self.custom_format.is_none() {
+                true => self.decimal_text(local, false),
+                false => self.builtin_text(local, &format),
+            };
+            self.content.text(time, local)
+        })
+    }
+
+    /// The AM or PM to draw in normal-sized text beside or below the big digits, which
+    /// [`without_ampm`] takes out of them. Only the built-in 12-hour formats have one to move.
+    pub fn ampm_apart(&self, local: DateTime<Local>) -> Option<String> {
+        let apart = self.ampm_position != AmPmPosition::Inline
+            && self.showing_clock()
+            && self.content.shows_time()
+            && self.custom_format.is_none()
+            && self.clock_lines().is_empty()
+            && !self.decimal
+            && !self.fuzzy_words
+            && !self.hour24;
+        let format = match self.ampm {
+            AmPm::Upper => "%p",
+            AmPm::Lower => "%P",
+            AmPm::None => return None,
+        };
+        apart.then(|| local.format(format).to_string())
+    }
+
+    /// Whether the clock is showing, rather than a countdown or text set at runtime.
+    fn showing_clock(&self) -> bool {
+        matches!(self.mode, Mode::Clock) && self.text.is_none()
+    }
+
+    /// The decimal time at `local`, with the `--separator` in place of the colons.
+    fn decimal_text(&self, local: DateTime<Local>, seconds: bool) -> String {
+        let text = decimal::format(local.time(), seconds);
+        match &self.separator {
+            Some(separator) => text.replace(':', separator),
+            None => text,
+        }
+    }
+
+    /// Whether the built-in clock is showing in decimal time.
+    fn showing_decimal(&self) -> bool {
+        self.showing_clock()
+            && self.custom_format.is_none()
+            && self.clock_lines().is_empty()
+            && self.decimal
+            && !self.analog
+    }
+
+    /// How far after the tick interval's multiples its boundaries fall. Decimal time counts from
+    /// local midnight, which lines up with them only in some zones; everything else is on them.
+    pub fn tick_phase(&self, local: DateTime<Local>) -> Duration {
+        if matches!(self.mode, Mode::Uptime) {
+            return uptime::suspended();
+        }
+        if !self.showing_decimal() {
+            return Duration::ZERO;
+        }
+        decimal::phase(local.offset().local_minus_utc(), self.tick_interval())
+    }
+
+    /// How often the wall-clock timer needs to fire for what's on screen. Countdowns and
+    /// stopwatches tick on their own monotonic deadlines instead (see [`App::next_tick`]), so
+    /// they only need the minute tick for the extras around them.
+    pub fn tick_interval(&self) -> Duration {
+        let clock = match &self.mode {
+            // Only the clocks in a grid tick on the wall clock
+            Mode::Clock if !self.grid_cells().is_empty() => {
+                let mut cells = self.grid_cells().iter();
+                match cells.any(|cell| matches!(cell.kind, CellKind::Clock(_))) {
+                    true => tick::interval_for_format(&self.format()),
+                    false => tick::MINUTE,
+                }
+            },
+            Mode::Clock if self.text.is_none() && self.analog && self.sweep && !self.unfocused => {
+                let area = self
+                    .margin
+                    .inset(self.screen_area(Rect::from((Position::ORIGIN, self.frame_size))));
+                let (_, rows) = analog::face_size(Size::new(
+                    area.width - self.notes_panel_width(area.width),
+                    area.height,
+                ));
+                analog::sweep_interval(rows)
+            },
+            Mode::Clock
+                if self.text.is_none()
+                    && (self.analog || self.ring.is_some() || self.seconds_dots.is_some()) =>
+            {
+                tick::SECOND
+            },
+            // The rounded time can only change on the half minute, whatever the format
+            Mode::Clock if self.text.is_none() && self.fuzzy.is_some() => fuzzy::INTERVAL,
+            // Each line has a format of its own, and the one changing most often sets the pace
+            Mode::Clock if !self.clock_lines().is_empty() => self
+                .clock_lines()
+                .iter()
+                .map(|line| tick::interval_for_format(&line.format))
+                .min()
+                .unwrap_or(tick::MINUTE),
+            // Decimal boundaries don't line up with the header's, so with a header they come
+            // every decimal second, which keeps it less than one behind
+            Mode::Clock if self.showing_decimal() => {
+                return match self.seconds || self.header.is_some() {
+                    true => decimal::SECOND,
+                    false => decimal::MINUTE,
+                };
+            },
+            Mode::Clock if self.text.is_none() => tick::interval_for_format(&self.format()),
+            Mode::Uptime => tick::SECOND,
+            Mode::Since(_) if self.seconds => tick::SECOND,
+            _ => tick::MINUTE,
+        };
+        let header = self
+            .header
+            .as_deref()
+            .map_or(tick::MINUTE, tick::interval_for_format);
+        clock.min(header)
+    }
+
+    /// The clock the ticks are measured on. Uptime and ticks counted from startup have nothing
+    /// to do with the wall clock, so they're measured on the monotonic clock, where no step can
+    /// move or cancel them. Uptime's seconds are counted on the boot clock, which runs on while
+    /// the machine sleeps, so [`App::tick_phase`] moves its boundaries along by the time slept.
+    pub fn tick_clock(&self) -> libc::clockid_t {
+        match self.mode {
+            Mode::Uptime => libc::CLOCK_MONOTONIC,
+            _ if self.relative_ticks => libc::CLOCK_MONOTONIC,
+            _ => libc::CLOCK_REALTIME,
+        }
+    }
+
+    /// The analog face to draw in place of the digits, when it's turned on and the clock (rather
+    /// than text or another mode) is showing.
+    pub fn analog_face(&self, local: DateTime<Local>) -> Option<AnalogClock> {
+        let showing_clock = matches!(self.mode, Mode::Clock) && self.text.is_none();
+        (self.analog && showing_clock).then(|| AnalogClock {
+            time: local,
+            sweep: self.sweep,
+            color: self.digit_color(local),
+            rim: self
+                .ring
+                .map(|ring| (ring.span, ring.color.unwrap_or(self.theme.fg))),
+        })
+    }
+
+    /// The progress ring to draw above the digits. An analog face has its own on the rim.
+    pub fn progress_ring(&self, local: DateTime<Local>) -> Option<(Ring, ProgressRing)> {
+        let showing_clock = matches!(self.mode, Mode::Clock) && self.text.is_none();
+        let ring = self.ring.filter(|_| !self.analog && showing_clock)?;
+        Some((
+            ring,
+            ProgressRing {
+                time: local,
+                span: ring.span,
+                color: ring.color.unwrap_or(self.theme.fg),
+            },
+        ))
+    }
+
+    /// The color of the digits or analog face at `local`: the theme's, or with `--rainbow` a hue
+    /// that steps round on each of the clock's ticks, but no more than once a second.
+    pub fn digit_color(&self, local: DateTime<Local>) -> Color {
+        match self.rainbow {
+            Some(rainbow) => {
+                let step = tick::interval_for_format(&self.format()).max(tick::SECOND);
+                rainbow.color(local.timestamp(), step)
+            },
+            None => self.theme.fg,
+        }
+    }
+
+    /// The style of the font's unlit segments, with `--segment-off-color`. Without color they'd
+    /// look as lit as the rest, so they're left blank.
+    pub fn segment_off_style(&self) -> Option<Style> {
+        self.segment_off_color
+            .filter(|_| self.color_depth != ColorDepth::Mono)
+            .map(|color| Style::new().fg(color))
+    }
+
+    /// The seconds dots to draw under the clock, when it's showing rather than text or another
+    /// mode.
+    pub fn visible_seconds_dots(&self) -> Option<SecondsDots> {
+        let showing_clock = matches!(self.mode, Mode::Clock) && self.text.is_none();
+        self.seconds_dots.filter(|_| showing_clock)
+    }
+
+    /// How often to draw frames while something moves faster than any tick, like a change of
+    /// digits animating, a millisecond stopwatch running, or `--matrix` rain. `None` the rest of
+    /// the time, so nothing is redrawn while a stopwatch is paused or the display is frozen.
+    pub fn frame_period(&self) -> Option<Duration> {
+        if self.frozen.is_some() || self.unfocused {
+            return None;
+        }
+        let animation = self
+            .transition
+            .is_some()
+            .then(|| Duration::from_secs(1) / animate::FPS);
+        let stopwatch = match (&self.mode, self.stopwatch_millis) {
+            (Mode::Stopwatch(stopwatch), Some(fps)) if stopwatch.is_running() => {
+                Some(Duration::from_secs(1) / fps.max(1))
+            },
+            _ => None,
+        };
+        let rain = self.matrix.is_some().then_some(matrix::STEP);
+        animation.into_iter().chain(stopwatch).chain(rain).min()
+    }
+
+    /// The time now, as the clock shows it: in the picked zone, if one was picked.
+    pub fn local_now(&self) -> DateTime<Local> {
+        self.zoned(Local::now())
+    }
+
+    /// `local` as the picked zone's clocks read it, if one was picked. It's the same moment, only
+    /// carrying the zone's offset, so the time and date are formatted in the zone.
+    pub fn zoned(&self, local: DateTime<Local>) -> DateTime<Local> {
+        match self.picked_zone {
+            Some(zone) => DateTime::from_naive_utc_and_offset(
+                local.naive_utc(),
+                zone.offset_at(local.to_utc()),
+            ),
+            None => local,
+        }
+    }
+
+    /// The moment to draw: `local` and `now`, unless the display is frozen at another.
+    pub fn shown_moment(&self, local: DateTime<Local>, now: Instant) -> (DateTime<Local>, Instant) {
+        self.frozen.unwrap_or((local, now))
+    }
+
+    /// When a redraw is needed between wall-clock ticks, if ever.
+    pub fn next_tick(&self, local: DateTime<Local>, now: Instant) -> Option<Instant> {
+        let mode_tick = match &self.mode {
+            Mode::Timer(timer) => timer.next_tick(now),
+            // A millisecond stopwatch is drawn in frames instead, unless they're put off
+            Mode::Stopwatch(_) if self.stopwatch_millis.is_some() && !self.unfocused => None,
+            Mode::Stopwatch(stopwatch) => stopwatch.next_tick(now),
+            Mode::Chess(chess) => chess.next_tick(now),
+            Mode::Clock | Mode::Uptime | Mode::Since(_) => None,
+        };
+        // The grid's timers and stopwatches keep to their own seconds, like the modes'
+        let cell_tick = self
+            .grid_cells()
+            .iter()
+            .filter_map(|cell| match &cell.kind {
+                CellKind::Clock(_) => None,
+                CellKind::Timer(countdown) => countdown.next_tick(now),
+                CellKind::Stopwatch(stopwatch) => stopwatch.next_tick(now),
+            })
+            .min();
+        let notice_expiry = self.visible_notice(now).map(|notice| notice.until);
+        let alarm_tick = self
+            .alarms
+            .iter()
+            .map(|alarm| match alarm.deadline() {
+                Some(at) => now + (at - local).to_std().unwrap_or(Duration::ZERO),
+                // The banner flashes on wall-clock half seconds
+                None => {
+                    let into_half = local.timestamp_subsec_nanos() % ALARM_FLASH_NANOS;
+                    now + Duration::from_nanos((ALARM_FLASH_NANOS - into_half).into())
+                },
+            })
+            .min();
+        let chime_tick = self.chime.as_ref().and_then(Chime::next_tick);
+        let reminder_tick = self.reminders.next_tick(local, now);
+        let flash_tick = self.screen_flash.next_frame(now);
+        let bounce_tick = self.bounce.as_ref().map(Bounce::next_step);
+        let alternate_tick = self
+            .alternation
+            .filter(|_| self.date_text(local).is_some())
+            .map(|alternation| {
+                let at = alternation.next_switch(local);
+                now + (at - local).to_std().unwrap_or(Duration::ZERO)
+            });
+        [
+            mode_tick,
+            cell_tick,
+            notice_expiry,
+            alarm_tick,
+            chime_tick,
+            reminder_tick,
+            flash_tick,
+            alternate_tick,
+            bounce_tick,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+}
+
+/// The local zone's UTC offset at `time`.
+fn local_offset(time: DateTime<Utc>) -> FixedOffset {
+    Local.offset_from_utc_datetime(&time.naive_utc()).fix()
+}
+
+/// Cuts `text` to at most `width` columns, ending in `…` if anything was lost.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_owned();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if width > 0 {
+        out.push('…');
+    }
+    out
+}
+
+/// `text` with the AM or PM that ends its first line taken off, for digits drawn with it apart.
+pub fn without_ampm(text: &str) -> String {
+    let (first, rest) = match text.split_once('\n') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (text, None),
+    };
+    let first = [" AM", " PM", " am", " pm"]
+        .into_iter()
+        .find_map(|suffix| first.strip_suffix(suffix))
+        .unwrap_or(first);
+    match rest {
+        Some(rest) => format!("{first}\n{rest}"),
+        None => first.to_owned(),
+    }
+}
+
+/// Rejects format strings chrono cannot render, since formatting one would panic in `draw`.
+pub fn validate_format(format: &str) -> anyhow::Result<()> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        bail!("invalid format string {format:?}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{alarm::Weekdays, widget::LineStyle};
+
+    #[test]
+    fn status_bar_drops_hints_before_truncating() {
+        let app = App::default();
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let offset = local.format("UTC%:z").to_string();
+
+        let wide = app.status_bar_text(local, 80);
+        assert!(wide.starts_with(&format!("clock · {offset}")));
+        assert!(wide.ends_with("q quit · s seconds · ? help"));
+        assert_eq!(wide.width(), 80);
+
+        let narrow = app.status_bar_text(local, 30);
+        assert!(narrow.starts_with(&format!("clock · {offset}")));
+        assert!(narrow.ends_with(" q quit"));
+        assert_eq!(narrow.width(), 30);
+
+        assert_eq!(app.status_bar_text(local, 10), "clock · U…");
+        assert_eq!(app.status_bar_text(local, 0), "");
+
+        // Ticks the loop slept through are counted, for diagnosing a lagging clock
+        let app = App {
+            missed_ticks: 2,
+            ..app
+        };
+        let wide = app.status_bar_text(local, 80);
+        assert!(wide.starts_with(&format!("clock · {offset} · 2 missed")));
+    }
+
+    #[test]
+    fn header_seconds_tick_without_seconds_in_the_clock() {
+        let mut app = App {
+            hour24: true,
+            ..App::default()
+        };
+        assert_eq!(app.tick_interval(), tick::MINUTE);
+        app.header = Some(DEFAULT_HEADER.to_owned());
+        assert_eq!(app.tick_interval(), tick::SECOND);
+        assert_eq!(app.format(), "%H:%M");
+    }
+
+    #[test]
+    fn datetime_draws_the_configured_lines() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 9, 41, 7).unwrap();
+        let line = |format: &str| ClockLine {
+            format: format.to_owned(),
+            style: LineStyle::default(),
+        };
+        let mut app = App {
+            lines: vec![line("%A"), line("%H:%M")],
+            seconds: true,
+            ampm_position: AmPmPosition::Beside,
+            ..App::default()
+        };
+        // Only in place of the time over the date
+        assert_eq!(app.display_text(local, Instant::now()), "09:41:07 AM");
+        app.content = ContentMode::DateTime;
+        assert_eq!(app.display_text(local, Instant::now()), "Friday\n09:41");
+        assert_eq!(app.without_seconds_text(local), None);
+        assert_eq!(app.ampm_apart(local), None);
+        // Whichever line changes most often sets the tick
+        assert_eq!(app.tick_interval(), tick::MINUTE);
+        app.lines.push(line("%S"));
+        assert_eq!(app.tick_interval(), tick::SECOND);
+    }
+
+    #[test]
+    fn twelve_hour_padding_and_suffix() {
+        let before_ten = Local.with_ymd_and_hms(2024, 3, 1, 9, 59, 0).unwrap();
+        let ten = Local.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        let mut app = App {
+            no_leading_zero: true,
+            ..App::default()
+        };
+        assert_eq!(app.display_text(before_ten, Instant::now()), "9:59 AM");
+        assert_eq!(app.display_text(ten, Instant::now()), "10:00 AM");
+        assert_eq!(app.short_text(before_ten).as_deref(), Some("9:59"));
+        app.seconds = true;
+        assert_eq!(
+            app.without_seconds_text(before_ten).as_deref(),
+            Some("9:59 AM")
+        );
+        app.seconds = false;
+
+        app.no_leading_zero = false;
+        app.ampm = AmPm::Lower;
+        assert_eq!(app.display_text(before_ten, Instant::now()), "09:59 am");
+        app.ampm = AmPm::None;
+        app.seconds = true;
+        assert_eq!(app.display_text(before_ten, Instant::now()), "09:59:00");
+        assert_eq!(
+            app.without_seconds_text(before_ten).as_deref(),
+            Some("09:59")
+        );
+
+        app.hour24 = true;
+        assert_eq!(app.display_text(before_ten, Instant::now()), "09:59:00");
+        app.custom_format = Some("%I%M".to_owned());
+        assert_eq!(app.display_text(before_ten, Instant::now()), "0959");
+    }
+
+    #[test]
+    fn ampm_can_be_drawn_apart_from_the_digits() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 21, 41, 0).unwrap();
+        let mut app = App::default();
+        // Inline by default, as part of the digits
+        assert_eq!(app.ampm_apart(local), None);
+        app.ampm_position = AmPmPosition::Below;
+        assert_eq!(app.ampm_apart(local).as_deref(), Some("PM"));
+        app.ampm = AmPm::Lower;
+        assert_eq!(app.ampm_apart(local).as_deref(), Some("pm"));
+        // The text shown elsewhere, like the title, keeps it
+        assert_eq!(app.display_text(local, Instant::now()), "09:41 pm");
+        assert_eq!(without_ampm("9:41 pm"), "9:41");
+        assert_eq!(
+            without_ampm("9:41 PM\nFri Mar 01 2024"),
+            "9:41\nFri Mar 01 2024"
+        );
+
+        // Nothing to move without a built-in 12-hour clock's suffix
+        app.ampm = AmPm::None;
+        assert_eq!(app.ampm_apart(local), None);
+        app.ampm = AmPm::Upper;
+        app.hour24 = true;
+        assert_eq!(app.ampm_apart(local), None);
+        app.hour24 = false;
+        app.custom_format = Some("%I:%M %p".to_owned());
+        assert_eq!(app.ampm_apart(local), None);
+    }
+
+    #[test]
+    fn content_modes_cycle_between_the_time_and_date() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 9, 5, 30).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            hour24: true,
+            seconds: true,
+            alternation: Some(Alternation::new(Duration::from_secs(5))),
+            ..App::default()
+        };
+        assert!(app.date_text(local).is_some());
+
+        let _ = app.apply(Action::CycleContent, local, now);
+        assert_eq!(app.display_text(local, now), "MAR 01");
+        assert_eq!(app.short_text(local), None);
+        assert_eq!(app.date_with_weekday(local), None);
+        app.date_weekday = true;
+        assert_eq!(app.date_with_weekday(local).as_deref(), Some("MAR 01\nFRI"));
+        // The time under it still ticks, and the date isn't alternated with itself
+        assert_eq!(app.time_under_date(local).as_deref(), Some("09:05:30"));
+        assert_eq!(app.tick_interval(), tick::SECOND);
+        assert_eq!(app.date_text(local), None);
+
+        let _ = app.apply(Action::CycleContent, local, now);
+        assert_eq!(app.display_text(local, now), "09:05:30\nMar 1");
+        assert_eq!(
+            app.without_seconds_text(local).as_deref(),
+            Some("09:05\nMar 1")
+        );
+        assert_eq!(app.tick_interval(), tick::SECOND);
+
+        let _ = app.apply(Action::CycleContent, local, now);
+        assert_eq!(app.content, ContentMode::Time);
+        assert_eq!(app.display_text(local, now), "09:05:30");
+        assert_eq!(app.time_under_date(local), None);
+    }
+
+    #[test]
+    fn separators_replace_the_built_in_colons() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 9, 5, 30).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            hour24: true,
+            seconds: true,
+            ..App::default()
+        };
+        app.set_separator(".", now);
+        assert_eq!(app.display_text(local, now), "09.05.30");
+        assert_eq!(app.without_seconds_text(local).as_deref(), Some("09.05"));
+        app.set_separator("", now);
+        assert_eq!(app.display_text(local, now), "090530");
+        assert_eq!(app.short_text(local).as_deref(), Some("0905"));
+
+        // A space is a blank glyph, so the clock is just as wide
+        app.set_separator(" ", now);
+        assert_eq!(app.display_text(local, now), "09 05 30");
+        assert_eq!(
+            crate::layout::text_size("09 05 30", Some(PixelSize::Full), None),
+            crate::layout::text_size("09:05:30", Some(PixelSize::Full), None)
+        );
+
+        // Fonts without the character keep the last separator they could draw
+        app.set_separator("·", now);
+        assert_eq!(app.display_text(local, now), "09 05 30");
+        assert!(app.visible_notice(now).is_some());
+        app.font = Some(SegmentFont::seven_segment(1));
+        app.set_separator("/", now);
+        assert_eq!(app.display_text(local, now), "09 05 30");
+
+        // Custom formats are left as written
+        app.custom_format = Some("%H:%M".to_owned());
+        assert_eq!(app.display_text(local, now), "09:05");
+    }
+
+    #[test]
+    fn next_alarm_counts_down_and_stands_out_when_close() {
+        let at = |h, m| Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+        let alarm = |time: &str, label: &str| {
+            Alarm::new(
+                time.parse().unwrap(),
+                Weekdays::ALL,
+                Some(label.to_owned()),
+                Duration::from_secs(5 * 60),
+                at(1, 18),
+            )
+        };
+        let mut app = App::default();
+        assert_eq!(app.next_alarm_text(at(1, 18)), None);
+
+        app.alarms = vec![alarm("07:30:00", "Standup"), alarm("12:00:00", "Lunch")];
+        assert_eq!(
+            app.next_alarm_text(at(1, 18)),
+            Some(("⏰ 07:30 Standup in 6h 12m".to_owned(), false))
+        );
+        assert_eq!(
+            app.next_alarm_text(at(7, 20)),
+            Some(("⏰ 07:30 Standup in 10m".to_owned(), true))
+        );
+
+        // Once it rings the next one is shown, until a snooze brings it back
+        app.update(at(7, 30), Instant::now());
+        assert_eq!(
+            app.next_alarm_text(at(7, 30)),
+            Some(("⏰ 12:00 Lunch in 4h 30m".to_owned(), false))
+        );
+        let _ = app.apply(Action::Snooze, at(7, 30), Instant::now());
+        assert_eq!(
+            app.next_alarm_text(at(7, 30)),
+            Some(("⏰ 07:30 Standup in 5m".to_owned(), true))
+        );
+    }
+
+    #[test]
+    fn since_counts_up_in_days() {
+        let since = Local.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let local = since + TimeDelta::days(1234) + TimeDelta::seconds(5 * 3600 + 6 * 60 + 7);
+        let now = Instant::now();
+        let mut app = App {
+            mode: Mode::Since(since),
+            ..App::default()
+        };
+        assert_eq!(app.display_text(local, now), "1234d 05:06");
+        assert_eq!(app.tick_interval(), tick::MINUTE);
+        let _ = app.apply(Action::ToggleSeconds, local, now);
+        assert_eq!(app.display_text(local, now), "1234d 05:06:07");
+        assert_eq!(app.tick_interval(), tick::SECOND);
+        // Set before the moment, the clock reads zero until it comes
+        assert_eq!(
+            app.display_text(since - TimeDelta::hours(1), now),
+            "00:00:00"
+        );
+    }
+
+    #[test]
+    fn comparison_lines_show_the_offset_from_local_time() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let app = App {
+            hour24: true,
+            comparisons: vec!["UTC".parse().unwrap(), "UTC=Greenwich".parse().unwrap()],
+            ..App::default()
+        };
+        let time = local.to_utc().format("%H:%M");
+        let difference = format_offset_difference(-local.offset().local_minus_utc());
+        assert_eq!(
+            app.comparison_lines(local),
+            [
+                format!("UTC: {time} ({difference})"),
+                format!("Greenwich: {time} ({difference})"),
+            ]
+        );
+    }
+
+    #[test]
+    fn uptime_flips_with_the_clock_and_ticks_on_the_monotonic_clock() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut app = App::default();
+        let _ = app.apply(Action::ToggleUptime, local, Instant::now());
+        assert!(matches!(app.mode, Mode::Uptime));
+        assert_eq!(app.tick_interval(), tick::SECOND);
+        assert_eq!(app.tick_clock(), libc::CLOCK_MONOTONIC);
+        assert!(app.display_text(local, Instant::now()).contains(':'));
+
+        let _ = app.apply(Action::ToggleUptime, local, Instant::now());
+        assert!(matches!(app.mode, Mode::Clock));
+        assert_eq!(app.tick_clock(), libc::CLOCK_REALTIME);
+        app.relative_ticks = true;
+        assert_eq!(app.tick_clock(), libc::CLOCK_MONOTONIC);
+        app.relative_ticks = false;
+
+        // A running stopwatch stays put
+        app.mode = Mode::Stopwatch(Stopwatch::start(Instant::now()));
+        let _ = app.apply(Action::ToggleUptime, local, Instant::now());
+        assert!(matches!(app.mode, Mode::Stopwatch(_)));
+    }
+
+    #[test]
+    fn stepping_the_wall_clock_leaves_readings_alone() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let start = Instant::now();
+        let mut timer = Timer::new(Vec::new());
+        timer.start(Duration::from_secs(600), start);
+        let mut app = App::default();
+
+        // Five seconds pass while the clock is set back an hour, then forward a day
+        let later = start + Duration::from_secs(5);
+        for stepped in [local - TimeDelta::hours(1), local + TimeDelta::days(1)] {
+            app.mode = Mode::Stopwatch(Stopwatch::start(start));
+            app.update(stepped, later);
+            assert_eq!(app.display_text(stepped, later), "00:05");
+
+            app.mode = Mode::Timer(timer.clone());
+            app.update(stepped, later);
+            assert_eq!(app.display_text(stepped, later), "09:55");
+        }
+    }
+
+    #[test]
+    fn timers_count_time_suspended_and_stopwatches_only_if_asked() {
+        let now = Instant::now();
+        let secs = Duration::from_secs;
+        let mut timer = Timer::new(Vec::new());
+        timer.start(secs(300), now);
+        let mut app = App {
+            mode: Mode::Timer(timer),
+            ..App::default()
+        };
+        app.resumed(secs(60));
+        assert_eq!(app.display_text(Local::now(), now), "04:00");
+        // Asleep past the end, it's expired with the overtime since
+        app.resumed(secs(250));
+        let Mode::Timer(timer) = &mut app.mode else {
+            panic!("not a timer");
+        };
+        assert!(timer.update(now));
+        assert_eq!(timer.overtime(now), Some(secs(10)));
+
+        app.mode = Mode::Stopwatch(Stopwatch::start(now));
+        app.resumed(secs(60));
+        assert_eq!(app.display_text(Local::now(), now), "00:00");
+        app.stopwatch_counts_suspend = true;
+        app.resumed(secs(60));
+        assert_eq!(app.display_text(Local::now(), now), "01:00");
+    }
+
+    #[test]
+    fn quitting_asks_first_only_while_timing() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        let mut app = App {
+            confirm_quit: true,
+            ..App::default()
+        };
+        assert!(app.press(&key('q'), Action::Quit, local, now).is_break());
+
+        app.mode = Mode::Stopwatch(Stopwatch::start(now));
+        assert!(app.press(&key('q'), Action::Quit, local, now).is_continue());
+        assert_eq!(
+            app.quit_prompt.as_deref(),
+            Some("Stopwatch running — quit? y/N")
+        );
+        // Anything but yes carries on, without doing what the key is bound to
+        assert!(app.press(&key('l'), Action::Lap, local, now).is_continue());
+        assert_eq!(app.quit_prompt, None);
+        let Mode::Stopwatch(stopwatch) = &app.mode else {
+            panic!("not a stopwatch");
+        };
+        assert!(stopwatch.laps().is_empty());
+
+        let _ = app.press(&key('q'), Action::Quit, local, now);
+        assert!(
+            app.press(&key('y'), Action::Acknowledge, local, now)
+                .is_break()
+        );
+        let _ = app.press(&key('q'), Action::Quit, local, now);
+        assert!(app.press(&key('q'), Action::Quit, local, now).is_break());
+        let enter = KeyEvent::from(KeyCode::Enter);
+        let _ = app.press(&key('q'), Action::Quit, local, now);
+        assert!(
+            app.press(&enter, Action::StartPreset, local, now)
+                .is_break()
+        );
+
+        app.confirm_quit = false;
+        assert!(app.press(&key('q'), Action::Quit, local, now).is_break());
+    }
+
+    #[test]
+    fn the_saved_state_is_resumed_only_if_asked() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        let saved = || {
+            Some(Mode::Stopwatch(Stopwatch::resumed(
+                Duration::from_secs(872),
+                true,
+                Vec::new(),
+                now,
+            )))
+        };
+        let mut app = App {
+            resuming: saved(),
+            ..App::default()
+        };
+        assert_eq!(
+            app.resume_prompt(now).as_deref(),
+            Some("resume stopwatch at 14:32? y/N")
+        );
+        assert!(
+            app.press(&key('y'), Action::Acknowledge, local, now)
+                .is_continue()
+        );
+        assert_eq!(app.resume_prompt(now), None);
+        assert_eq!(app.display_text(local, now), "14:32");
+
+        // Anything else drops it, without doing what the key is bound to
+        let mut app = App {
+            resuming: saved(),
+            ..App::default()
+        };
+        assert!(
+            app.press(&key('s'), Action::ToggleSeconds, local, now)
+                .is_continue()
+        );
+        assert!(matches!(app.mode, Mode::Clock));
+        assert!(app.resuming.is_none() && !app.seconds);
+
+        // Quitting keeps it for next time
+        let mut app = App {
+            resuming: saved(),
+            ..App::default()
+        };
+        assert!(app.press(&key('q'), Action::Quit, local, now).is_break());
+        assert!(app.resuming.is_some());
+    }
+
+    #[test]
+    fn zone_picker_takes_keys_until_closed() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        let zones = ["Asia/Tokyo", "Europe/Berlin", "Europe/Paris"].map(str::to_owned);
+        let mut app = App {
+            zone_picker: Some(ZonePicker::new(zones.to_vec(), &[])),
+            ..App::default()
+        };
+        // Letters bound to actions are typed into the filter instead
+        for c in "eq".chars() {
+            assert!(app.press(&key(c), Action::Quit, local, now).is_continue());
+        }
+        let picker = app.zone_picker.as_ref().unwrap();
+        assert_eq!(picker.query(), "eq");
+        assert_eq!(picker.selected(), None);
+
+        let press = |app: &mut App, code| {
+            let _ = app.press(&KeyEvent::from(code), Action::Acknowledge, local, now);
+        };
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.zone_picker.is_none());
+        let paris = Zone::load("Europe/Paris").unwrap();
+        assert_eq!(app.picked_zone, Some(paris));
+        assert_eq!(app.recent_zones, ["Europe/Paris"]);
+        // Shown in Paris from now on, an hour ahead in March
+        assert_eq!(app.display_text(app.zoned(local), now), "01:00 PM");
+
+        // The zone picked last comes first next time, and Esc picks nothing new
+        app.picked_zone = None;
+        app.zone_picker = Some(ZonePicker::new(zones.to_vec(), &app.recent_zones));
+        assert_eq!(
+            app.zone_picker.as_ref().unwrap().selected(),
+            Some("Europe/Paris")
+        );
+        press(&mut app, KeyCode::Esc);
+        assert!(app.zone_picker.is_none());
+        assert_eq!(app.picked_zone, None);
+    }
+
+    #[test]
+    fn laps_and_finished_timers_are_recorded() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let start = Instant::now();
+        let (recorder, mut records) = Recorder::new();
+        let mut app = App {
+            mode: Mode::Stopwatch(Stopwatch::start(start)),
+            recorder: Some(recorder),
+            ..App::default()
+        };
+        let _ = app.apply(Action::Lap, local, start + Duration::from_secs(5));
+        assert_eq!(
+            records.try_recv(),
+            Ok(Record {
+                event: Event::Lap,
+                wallclock: local,
+                elapsed: Duration::from_secs(5),
+            })
+        );
+
+        let mut timer = Timer::new(Vec::new());
+        timer.start(Duration::from_secs(90), start);
+        app.mode = Mode::Timer(timer);
+        app.update(local, start + Duration::from_secs(89));
+        assert!(records.try_recv().is_err());
+        app.update(local, start + Duration::from_secs(90));
+        app.update(local, start + Duration::from_secs(91));
+        assert_eq!(
+            records
+                .try_recv()
+                .map(|record| (record.event, record.elapsed)),
+            Ok((Event::TimerDone, Duration::from_secs(90)))
+        );
+        assert!(records.try_recv().is_err());
+    }
+
+    #[test]
+    fn millisecond_stopwatches_draw_frames_only_while_running() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let start = Instant::now();
+        let mut app = App {
+            mode: Mode::Stopwatch(Stopwatch::start(start)),
+            stopwatch_millis: Some(20),
+            ..App::default()
+        };
+        let later = start + Duration::from_micros(62_345_678);
+        assert_eq!(app.display_text(local, later), "01:02.345");
+        assert_eq!(app.frame_period(), Some(Duration::from_millis(50)));
+        assert_eq!(app.next_tick(local, later), None);
+
+        // Out of view, it's drawn on each second, as it would be without milliseconds
+        app.unfocused = true;
+        assert_eq!(app.frame_period(), None);
+        assert_eq!(
+            app.next_tick(local, later),
+            Some(start + Duration::from_secs(63))
+        );
+        app.unfocused = false;
+
+        let _ = app.apply(Action::Pause, local, later);
+        assert_eq!(app.frame_period(), None);
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!(app.display_text(local, much_later), "01:02.345");
+    }
+
+    #[test]
+    fn hold_freezes_the_display_until_a_timer_goes_off() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let start = Instant::now();
+        let mut timer = Timer::new(Vec::new());
+        timer.start(Duration::from_secs(90), start);
+        let mut app = App {
+            mode: Mode::Timer(timer),
+            ..App::default()
+        };
+        let _ = app.apply(Action::Hold, local, start);
+        let later = local + chrono::Duration::seconds(30);
+        let thirty = start + Duration::from_secs(30);
+        assert_eq!(app.shown_moment(later, thirty), (local, start));
+        assert_eq!(app.display_text(local, start), "01:30");
+
+        // Pressing it again snaps back to the present, where the timer kept going
+        let _ = app.apply(Action::Hold, later, thirty);
+        assert_eq!(app.shown_moment(later, thirty), (later, thirty));
+        let (local, now) = app.shown_moment(later, thirty);
+        assert_eq!(app.display_text(local, now), "01:00");
+
+        // And the timer running out unfreezes it
+        let _ = app.apply(Action::Hold, later, thirty);
+        let expiry = start + Duration::from_secs(90);
+        app.update(later, expiry);
+        assert_eq!(app.frozen, None);
+    }
+
+    #[test]
+    fn expired_timers_blink_until_acknowledged() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let start = Instant::now();
+        let mut timer = Timer::new(Vec::new());
+        timer.start(Duration::from_secs(1), start);
+        let mut app = App {
+            mode: Mode::Timer(timer),
+            flash: true,
+            ..App::default()
+        };
+        app.update(local, start);
+        assert_eq!(app.flash_style(start), None);
+        assert!(!app.notifier.take_bell());
+
+        let expiry = start + Duration::from_secs(1);
+        app.update(local, expiry);
+        assert_eq!(app.flash_style(expiry), Some(FlashStyle::Inverted));
+        assert!(app.notifier.take_bell());
+        assert_eq!(
+            app.next_tick(local, expiry),
+            Some(expiry + crate::flash::BLINK_PHASE)
+        );
+        assert_eq!(app.flash_style(expiry + crate::flash::BLINK_PHASE), None);
+
+        let _ = app.apply(Action::Acknowledge, local, expiry);
+        app.update(local, expiry);
+        assert_eq!(app.flash_style(expiry), None);
+
+        // Nothing flashes with flashing turned off
+        app.flash = false;
+        app.start_flash(Some(Duration::from_secs(1)), FlashStyle::Inverted, expiry);
+        assert_eq!(app.flash_style(expiry), None);
+    }
+
+    #[test]
+    fn changed_digits_animate_until_a_key_is_pressed() {
+        let before = Local.with_ymd_and_hms(2024, 3, 1, 12, 59, 0).unwrap();
+        let after = Local.with_ymd_and_hms(2024, 3, 1, 13, 0, 0).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            animate: Animation::Slide,
+            ..App::default()
+        };
+        app.update(before, now);
+        assert_eq!(app.transition, None);
+        app.update(after, now);
+        assert_eq!(
+            app.transition,
+            Some(Transition {
+                from: "12:59 PM".to_owned(),
+                animation: Animation::Slide,
+                started: now,
+            })
+        );
+        app.update(after, now + animate::DURATION);
+        assert_eq!(app.transition, None);
+
+        app.update(before, now);
+        let _ = app.apply(Action::ToggleFormat, before, now);
+        assert_eq!(app.transition, None);
+        // Nor does the next update animate what the key changed
+        app.update(before, now);
+        assert_eq!(app.transition, None);
+    }
+
+    #[test]
+    fn calendar_stays_on_the_paged_month_until_home() {
+        let march = Local.with_ymd_and_hms(2024, 3, 31, 23, 59, 0).unwrap();
+        let april = Local.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        let month = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+        let mut app = App::default();
+        let now = Instant::now();
+
+        // Paging does nothing while the calendar is hidden
+        let _ = app.apply(Action::PreviousMonth, march, now);
+        assert_eq!(app.calendar_month(march.date_naive()), month(2024, 3));
+        assert_eq!(app.calendar_month(april.date_naive()), month(2024, 4));
+
+        let _ = app.apply(Action::ToggleCalendar, march, now);
+        for _ in 0..3 {
+            let _ = app.apply(Action::PreviousMonth, march, now);
+        }
+        let _ = app.apply(Action::NextMonth, march, now);
+        app.update(april, now);
+        assert_eq!(app.calendar_month(april.date_naive()), month(2024, 1));
+
+        let _ = app.apply(Action::CurrentMonth, april, now);
+        assert_eq!(app.calendar_month(april.date_naive()), month(2024, 4));
+    }
+
+    #[test]
+    fn zone_label_names_the_zone_or_its_offset() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut app = App {
+            zone_label: true,
+            ..App::default()
+        };
+        assert_eq!(
+            app.zone_abbreviation(local),
+            Some(local.format("UTC%:z").to_string())
+        );
+        app.local_zone = Some(Zone::load("UTC").unwrap());
+        assert_eq!(app.zone_abbreviation(local).as_deref(), Some("UTC"));
+
+        app.mode = Mode::Stopwatch(Stopwatch::start(Instant::now()));
+        assert_eq!(app.zone_abbreviation(local), None);
+    }
+
+    #[test]
+    fn decimal_time_ticks_from_local_midnight() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            decimal: true,
+            seconds: true,
+            ..App::default()
+        };
+        assert_eq!(app.display_text(local, now), "5:00:00");
+        assert_eq!(app.without_seconds_text(local).as_deref(), Some("5:00"));
+        assert_eq!(app.tick_interval(), decimal::SECOND);
+        assert_eq!(
+            app.tick_phase(local),
+            decimal::phase(local.offset().local_minus_utc(), decimal::SECOND)
+        );
+
+        app.seconds = false;
+        app.separator = Some(".".to_owned());
+        assert_eq!(app.display_text(local, now), "5.00");
+        assert_eq!(app.tick_interval(), decimal::MINUTE);
+        // Its boundaries miss the header's, so a header brings them down to every second
+        app.header = Some("%a %e %b".to_owned());
+        assert_eq!(app.tick_interval(), decimal::SECOND);
+
+        app.mode = Mode::Stopwatch(Stopwatch::start(now));
+        assert_eq!(app.tick_phase(local), Duration::ZERO);
+    }
+
+    #[test]
+    fn a_fuzzy_clock_rounds_before_formatting() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 14, 42, 40).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            fuzzy: Some(Granularity::Five),
+            seconds: true,
+            ..App::default()
+        };
+        // Seconds would only ever be zero, so they're left off
+        assert_eq!(app.display_text(local, now), "02:45 PM");
+        assert_eq!(app.tick_interval(), fuzzy::INTERVAL);
+        app.hour24 = true;
+        app.fuzzy = Some(Granularity::Fifteen);
+        assert_eq!(app.display_text(local, now), "14:45");
+        assert_eq!(app.short_text(local).as_deref(), Some("14:45"));
+
+        app.fuzzy_words = true;
+        assert_eq!(app.display_text(local, now), "quarter to three");
+        // Too wide for the terminal, the words give way to digits
+        assert_eq!(app.without_seconds_text(local).as_deref(), Some("14:45"));
+        app.hour24 = false;
+        assert_eq!(app.ampm_apart(local), None);
+    }
+}
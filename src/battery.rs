@@ -0,0 +1,310 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Where the kernel lists batteries and chargers.
+pub const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// How often the batteries are read again. Charge moves a percent every few minutes at most.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The default for `--battery-low`.
+pub const DEFAULT_LOW: u8 = 20;
+
+/// The charge of all the machine's batteries together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Battery {
+    pub percent: u8,
+    /// Whether any of them is charging.
+    pub charging: bool,
+}
+
+impl Battery {
+    /// The batteries under `dir`, a `power_supply` class directory, or `None` when there are
+    /// none. Chargers and the batteries of devices like wireless mice are skipped. With several,
+    /// the charge is their combined energy over their combined capacity, so a small battery
+    /// running flat doesn't drag the figure down as much as an average would; batteries that
+    /// only report a percentage are averaged instead.
+    pub fn read(dir: &Path) -> io::Result<Option<Self>> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut paths = entries
+            .map(|entry| Ok(entry?.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.sort();
+
+        let mut energy = Some((0, 0));
+        let mut capacities = Vec::new();
+        let mut charging = false;
+        for path in paths {
+            if read_attribute(&path, "type")?.as_deref() != Some("Battery")
+                || read_attribute(&path, "scope")?.as_deref() == Some("Device")
+            {
+                continue;
+            }
+            let capacity = read_number(&path, "capacity")?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} has no capacity", path.display()),
+                )
+            })?;
+            capacities.push(capacity);
+            energy = match (
+                energy,
+                read_number(&path, "energy_now")?,
+                read_number(&path, "energy_full")?,
+            ) {
+                (Some((now, full)), Some(more_now), Some(more_full)) => {
+                    Some((now + more_now, full + more_full))
+                },
+                _ => None,
+            };
+            charging |= read_attribute(&path, "status")?.as_deref() == Some("Charging");
+        }
+
+        if capacities.is_empty() {
+            return Ok(None);
+        }
+        let percent = match energy {
+            Some((now, full)) if full > 0 => (now * 100 + full / 2) / full,
+            _ => capacities.iter().sum::<u64>() / capacities.len() as u64,
+        };
+        Ok(Some(Self {
+            percent: percent.min(100) as u8,
+            charging,
+        }))
+    }
+}
+
+/// The trimmed contents of the file `name` under `path`, or `None` if there's no such file.
+fn read_attribute(path: &Path, name: &str) -> io::Result<Option<String>> {
+    match fs::read_to_string(path.join(name)) {
+        Ok(text) => Ok(Some(text.trim().to_owned())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn read_number(path: &Path, name: &str) -> io::Result<Option<u64>> {
+    let Some(text) = read_attribute(path, name)? else {
+        return Ok(None);
+    };
+    text.parse().map(Some).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} has {name} {text:?}", path.display()),
+        )
+    })
+}
+
+/// The `--battery` corner's reading, refreshed every [`POLL_INTERVAL`].
+#[derive(Debug)]
+pub struct BatteryWatch {
+    dir: PathBuf,
+    /// The charge, in percent, below which it's shown in red.
+    low: u8,
+    reading: Option<io::Result<Option<Battery>>>,
+    checked_at: Option<Instant>,
+}
+
+impl BatteryWatch {
+    pub fn new(dir: impl Into<PathBuf>, low: u8) -> Self {
+        Self {
+            dir: dir.into(),
+            low,
+            reading: None,
+            checked_at: None,
+        }
+    }
+
+    /// Reads the batteries again if the last reading is a minute old, or there isn't one yet.
+    pub fn update(&mut self, now: Instant) {
+        if self
+            .checked_at
+            .is_some_and(|at| now.saturating_duration_since(at) < POLL_INTERVAL)
+        {
+            return;
+        }
+        self.reading = Some(Battery::read(&self.dir));
+        self.checked_at = Some(now);
+    }
+
+    /// The corner's text, like `🔋 83%`, `⚡ 83%` while charging, or `🔋 --` when the batteries
+    /// can't be read, and whether the charge is low. `None` without a battery.
+    pub fn tag(&self, ascii: bool) -> Option<(String, bool)> {
+        let battery = match self.reading.as_ref()? {
+            Ok(battery) => (*battery)?,
+            Err(_) => {
+                let icon = if ascii { "BAT" } else { "🔋" };
+                return Some((format!("{icon} --"), false));
+            },
+        };
+        let icon = match (battery.charging, ascii) {
+            (false, false) => "🔋",
+            (true, false) => "⚡",
+            (false, true) => "BAT",
+            (true, true) => "AC",
+        };
+        Some((
+            format!("{icon} {}%", battery.percent),
+            battery.percent < self.low,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// A fake `power_supply` directory, removed when dropped.
+    struct FakeSysfs(PathBuf);
+
+    impl FakeSysfs {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!(
+                "tui-time-power-supply-{name}-{}",
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn supply(&self, name: &str, attributes: &[(&str, &str)]) -> &Self {
+            let dir = self.0.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            for (attribute, value) in attributes {
+                fs::write(dir.join(attribute), format!("{value}\n")).unwrap();
+            }
+            self
+        }
+
+        fn read(&self) -> io::Result<Option<Battery>> {
+            Battery::read(&self.0)
+        }
+    }
+
+    impl Drop for FakeSysfs {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn reads_one_battery_and_skips_chargers_and_peripherals() {
+        let sysfs = FakeSysfs::new("one");
+        sysfs
+            .supply("AC", &[("type", "Mains"), ("online", "1")])
+            .supply(
+                "BAT0",
+                &[
+                    ("type", "Battery"),
+                    ("capacity", "83"),
+                    ("status", "Charging"),
+                ],
+            )
+            .supply(
+                "hidpp_battery_0",
+                &[
+                    ("type", "Battery"),
+                    ("scope", "Device"),
+                    ("capacity", "5"),
+                    ("status", "Discharging"),
+                ],
+            );
+        assert_eq!(
+            sysfs.read().unwrap(),
+            Some(Battery {
+                percent: 83,
+                charging: true,
+            })
+        );
+    }
+
+    #[test]
+    fn combines_several_batteries_by_energy() {
+        let sysfs = FakeSysfs::new("two");
+        sysfs
+            .supply(
+                "BAT0",
+                &[
+                    ("type", "Battery"),
+                    ("capacity", "90"),
+                    ("energy_now", "18000000"),
+                    ("energy_full", "20000000"),
+                    ("status", "Discharging"),
+                ],
+            )
+            .supply(
+                "BAT1",
+                &[
+                    ("type", "Battery"),
+                    ("capacity", "10"),
+                    ("energy_now", "6000000"),
+                    ("energy_full", "60000000"),
+                    ("status", "Not charging"),
+                ],
+            );
+        // 24 of 80 Wh, where averaging the percentages would say 50
+        assert_eq!(
+            sysfs.read().unwrap(),
+            Some(Battery {
+                percent: 30,
+                charging: false,
+            })
+        );
+
+        // Without energy figures for both, the percentages are averaged
+        fs::remove_file(sysfs.0.join("BAT1/energy_now")).unwrap();
+        assert_eq!(sysfs.read().unwrap().unwrap().percent, 50);
+    }
+
+    #[test]
+    fn no_battery_hides_and_bad_files_show_dashes() {
+        let desktop = FakeSysfs::new("desktop");
+        desktop.supply("AC", &[("type", "Mains")]);
+        assert_eq!(desktop.read().unwrap(), None);
+        assert_eq!(Battery::read(&desktop.0.join("missing")).unwrap(), None);
+
+        let broken = FakeSysfs::new("broken");
+        broken.supply("BAT0", &[("type", "Battery"), ("capacity", "lots")]);
+        assert!(broken.read().is_err());
+
+        let mut watch = BatteryWatch::new(&broken.0, DEFAULT_LOW);
+        watch.update(Instant::now());
+        assert_eq!(watch.tag(false), Some(("🔋 --".to_owned(), false)));
+        let mut watch = BatteryWatch::new(&desktop.0, DEFAULT_LOW);
+        watch.update(Instant::now());
+        assert_eq!(watch.tag(false), None);
+    }
+
+    #[test]
+    fn flags_a_low_charge_and_polls_once_a_minute() {
+        let sysfs = FakeSysfs::new("low");
+        sysfs.supply(
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("capacity", "15"),
+                ("status", "Discharging"),
+            ],
+        );
+        let start = Instant::now();
+        let mut watch = BatteryWatch::new(&sysfs.0, DEFAULT_LOW);
+        watch.update(start);
+        assert_eq!(watch.tag(false), Some(("🔋 15%".to_owned(), true)));
+
+        sysfs.supply("BAT0", &[("capacity", "25"), ("status", "Charging")]);
+        watch.update(start + Duration::from_secs(59));
+        assert_eq!(watch.tag(true), Some(("BAT 15%".to_owned(), true)));
+        watch.update(start + POLL_INTERVAL);
+        assert_eq!(watch.tag(true), Some(("AC 25%".to_owned(), false)));
+    }
+}
@@ -0,0 +1,153 @@
+//! `--bounce`: the clock drifting round the screen like a screensaver, a cell at a time, turning
+//! back off the edges.
+
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+use ratatui::layout::{Position, Rect, Size};
+
+/// Where the bouncing clock is and which way it's heading. The room it has to move in depends on
+/// how big everything drawn with it is, which only drawing finds out, so the position and room are
+/// kept from one draw to the next; steps between draws move within the room last drawn.
+#[derive(Debug)]
+pub struct Bounce {
+    period: Duration,
+    next_step: Instant,
+    /// The top-left corner's offset within the area drawn in, or `None` until the first draw
+    /// puts it in the middle.
+    position: Cell<Option<Position>>,
+    /// How far right and down the corner can go and still keep everything inside.
+    room: Cell<Size>,
+    /// Heading right and down, or left and up.
+    right: bool,
+    down: bool,
+}
+
+impl Bounce {
+    /// A bounce moving a cell every `period`, starting from the middle of the screen.
+    pub fn new(period: Duration, now: Instant) -> Self {
+        Self {
+            period,
+            next_step: now + period,
+            position: Cell::new(None),
+            room: Cell::new(Size::ZERO),
+            right: true,
+            down: true,
+        }
+    }
+
+    /// When the clock next moves.
+    pub fn next_step(&self) -> Instant {
+        self.next_step
+    }
+
+    /// Moves a cell diagonally if it's time to, turning back off any edge it's reached. Steps
+    /// missed while the loop was busy are skipped rather than made up.
+    pub fn update(&mut self, now: Instant) {
+        if now < self.next_step {
+            return;
+        }
+        self.next_step += self.period;
+        if self.next_step <= now {
+            self.next_step = now + self.period;
+        }
+        let Some(position) = self.position.get() else {
+            return;
+        };
+        let room = self.room.get();
+        let (x, right) = step(position.x, room.width, self.right);
+        let (y, down) = step(position.y, room.height, self.down);
+        (self.right, self.down) = (right, down);
+        self.position.set(Some(Position::new(x, y)));
+    }
+
+    /// Where something `size` goes in `area`, which records the room it has there. A position
+    /// left outside by the area shrinking is clamped back inside it.
+    pub fn place(&self, area: Rect, size: (u16, u16)) -> Rect {
+        let (width, height) = (size.0.min(area.width), size.1.min(area.height));
+        let room = Size::new(area.width - width, area.height - height);
+        let position = match self.position.get() {
+            Some(position) => {
+                Position::new(position.x.min(room.width), position.y.min(room.height))
+            },
+            None => Position::new(room.width / 2, room.height / 2),
+        };
+        self.room.set(room);
+        self.position.set(Some(position));
+        Rect::new(area.x + position.x, area.y + position.y, width, height)
+    }
+}
+
+/// Moves `at` a cell forward or back within `0..=room`, returning where it ends up and which way
+/// it's headed after. Reaching an edge turns it round, and with no room at all it stays put.
+fn step(at: u16, room: u16, forward: bool) -> (u16, bool) {
+    let forward = match forward {
+        true => at < room,
+        false => at == 0,
+    };
+    match (room, forward) {
+        (0, _) => (0, forward),
+        (_, true) => (at + 1, forward),
+        (_, false) => (at - 1, forward),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflects_off_the_edges() {
+        let now = Instant::now();
+        let mut bounce = Bounce::new(Duration::from_secs(1), now);
+        let area = Rect::new(10, 5, 8, 5);
+        // A 4x2 clock starts in the middle of its 4x3 room
+        assert_eq!(bounce.place(area, (4, 2)), Rect::new(12, 6, 4, 2));
+
+        let mut corners = Vec::new();
+        for secs in 1..=6 {
+            bounce.update(now + Duration::from_secs(secs));
+            corners.push(bounce.place(area, (4, 2)).as_position());
+        }
+        // Into the bottom-right corner and back out, then down again off the top
+        let expected = [(13, 7), (14, 8), (13, 7), (12, 6), (11, 5), (10, 6)];
+        assert_eq!(corners, expected.map(Position::from));
+    }
+
+    #[test]
+    fn moves_once_per_period() {
+        let now = Instant::now();
+        let mut bounce = Bounce::new(Duration::from_secs(2), now);
+        let area = Rect::new(0, 0, 20, 20);
+        let start = bounce.place(area, (2, 2));
+        bounce.update(now + Duration::from_secs(1));
+        assert_eq!(bounce.place(area, (2, 2)), start);
+        // Late, it moves just the once, and goes on a period from then
+        bounce.update(now + Duration::from_secs(7));
+        assert_eq!(bounce.place(area, (2, 2)).x, start.x + 1);
+        assert_eq!(bounce.next_step(), now + Duration::from_secs(9));
+        bounce.update(now + Duration::from_secs(8));
+        assert_eq!(bounce.place(area, (2, 2)).x, start.x + 1);
+    }
+
+    #[test]
+    fn resizing_clamps_it_back_inside() {
+        let now = Instant::now();
+        let bounce = Bounce::new(Duration::from_secs(1), now);
+        assert_eq!(
+            bounce.place(Rect::new(0, 0, 40, 20), (10, 4)),
+            Rect::new(15, 8, 10, 4)
+        );
+        assert_eq!(
+            bounce.place(Rect::new(0, 0, 20, 10), (10, 4)),
+            Rect::new(10, 6, 10, 4)
+        );
+        // With no room, it's pinned to the corner and clipped
+        assert_eq!(
+            bounce.place(Rect::new(0, 0, 6, 3), (10, 4)),
+            Rect::new(0, 0, 6, 3)
+        );
+    }
+}
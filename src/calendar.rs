@@ -0,0 +1,148 @@
+use chrono::{Datelike, Months, NaiveDate};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+/// The size in cells of every month: a title, the weekdays, and room for six weeks, so the
+/// layout doesn't shift between months.
+pub const SIZE: (u16, u16) = (20, 8);
+
+/// The first day of the month `date` is in.
+pub fn month_of(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+/// The month `months` after `month` (before it when negative), or `month` itself at the ends
+/// of the calendar.
+pub fn add_months(month: NaiveDate, months: i32) -> NaiveDate {
+    let shifted = match months {
+        0.. => month.checked_add_months(Months::new(months.unsigned_abs())),
+        _ => month.checked_sub_months(Months::new(months.unsigned_abs())),
+    };
+    shifted.unwrap_or(month)
+}
+
+/// A month as a grid of days, weeks starting on Monday, with today highlighted if it's in the
+/// month. Clipped to the area it's rendered to.
+#[derive(Debug, Clone, Copy)]
+pub struct MonthCalendar {
+    /// Any day in the month to show.
+    pub month: NaiveDate,
+    pub today: NaiveDate,
+    pub style: Style,
+    /// For the weekday names.
+    pub header_style: Style,
+    pub today_style: Style,
+}
+
+impl Widget for MonthCalendar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let first = month_of(self.month);
+        let mut lines = vec![
+            Line::styled(first.format("%B %Y").to_string(), self.style).centered(),
+            Line::styled("Mo Tu We Th Fr Sa Su", self.header_style),
+        ];
+
+        let mut week = Vec::new();
+        let lead = first.weekday().num_days_from_monday() as usize;
+        // Each day after Monday is preceded by a space, so the blanks before the first day need
+        // one fewer
+        week.push(Span::styled(
+            " ".repeat((3 * lead).saturating_sub(1)),
+            self.style,
+        ));
+        for day in first
+            .iter_days()
+            .take_while(|day| day.month() == first.month())
+        {
+            let style = if day == self.today {
+                self.today_style
+            } else {
+                self.style
+            };
+            if day.weekday().num_days_from_monday() > 0 {
+                week.push(Span::styled(" ", self.style));
+            }
+            week.push(Span::styled(format!("{:>2}", day.day()), style));
+            if day.weekday().num_days_from_monday() == 6 {
+                lines.push(Line::from(std::mem::take(&mut week)));
+            }
+        }
+        if !week.is_empty() {
+            lines.push(Line::from(week));
+        }
+
+        for (line, y) in lines.into_iter().zip(area.top()..area.bottom()) {
+            line.render(
+                Rect {
+                    y,
+                    height: 1,
+                    ..area
+                },
+                buf,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend, style::Modifier};
+
+    use super::*;
+    use crate::widget::tests::rows;
+
+    #[test]
+    fn lays_out_a_month_from_monday() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let calendar = MonthCalendar {
+            month: today,
+            today,
+            style: Style::new(),
+            header_style: Style::new(),
+            today_style: Style::new().reversed(),
+        };
+        let mut terminal = Terminal::new(TestBackend::new(SIZE.0, SIZE.1)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(calendar, frame.area()))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        assert_eq!(
+            rows(buffer),
+            [
+                "     March 2024     ",
+                "Mo Tu We Th Fr Sa Su",
+                "             1  2  3",
+                " 4  5  6  7  8  9 10",
+                "11 12 13 14 15 16 17",
+                "18 19 20 21 22 23 24",
+                "25 26 27 28 29 30 31",
+                "                    ",
+            ]
+        );
+        assert!(buffer[(6, 4)].modifier.contains(Modifier::REVERSED));
+        assert!(!buffer[(9, 4)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn pages_across_years() {
+        let january = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert_eq!(
+            add_months(january, -1),
+            NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()
+        );
+        assert_eq!(
+            add_months(january, 13),
+            NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()
+        );
+        assert_eq!(
+            month_of(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()
+        );
+    }
+}
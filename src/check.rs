@@ -0,0 +1,165 @@
+//! `--check`: a self-test of what the clock depends on, for when it isn't updating and a bug
+//! report needs to say why.
+
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, bail};
+
+use crate::{
+    palette::ColorDepth,
+    timer::{Arming, TimerFd},
+    widget,
+};
+
+/// How long a timerfd armed for a second may take to fire before the check fails.
+pub const TIMERFD_LIMIT: Duration = Duration::from_millis(1100);
+
+/// What a check that passed found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Found {
+    Fine(String),
+    /// Something the clock works around, but that the report should point out.
+    Warning(String),
+}
+
+impl From<String> for Found {
+    fn from(found: String) -> Self {
+        Self::Fine(found)
+    }
+}
+
+/// The results of each check, in the order they ran.
+#[derive(Debug, Default)]
+pub struct Report {
+    checks: Vec<(&'static str, anyhow::Result<Found>)>,
+}
+
+impl Report {
+    /// Records the result of the check called `name`: what it found, or why it failed.
+    pub fn add(&mut self, name: &'static str, result: anyhow::Result<impl Into<Found>>) {
+        self.checks.push((name, result.map(Into::into)));
+    }
+
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// Writes a line per check, like `ok    timerfd: fired after 1.000s`. Warnings don't fail the
+    /// report.
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        for (name, result) in &self.checks {
+            match result {
+                Ok(Found::Fine(found)) => writeln!(out, "ok    {name}: {found}")?,
+                Ok(Found::Warning(found)) => writeln!(out, "warn  {name}: {found}")?,
+                Err(err) => writeln!(out, "FAIL  {name}: {err:#}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Arms a wall-clock timerfd for a second and waits for it, as the clock's ticks do.
+pub async fn timerfd() -> anyhow::Result<String> {
+    let start = Instant::now();
+    let tfd = TimerFd::new(
+        libc::CLOCK_REALTIME,
+        Duration::from_secs(1),
+        Arming::Relative,
+        false,
+    )
+    .context("timerfd_create failed")?;
+    let fired = tokio::time::timeout(2 * TIMERFD_LIMIT, tfd.wait()).await;
+    let elapsed = start.elapsed();
+    match fired {
+        Err(_) => bail!("armed for 1s but didn't fire within {elapsed:.1?}"),
+        Ok(Err(err)) => Err(err).context("waiting on the timerfd failed"),
+        Ok(Ok(_)) if elapsed > TIMERFD_LIMIT => {
+            bail!(
+                "armed for 1s but took {:.3}s to fire",
+                elapsed.as_secs_f64()
+            )
+        },
+        Ok(Ok(_)) => Ok(format!("fired after {:.3}s", elapsed.as_secs_f64())),
+    }
+}
+
+/// Arms a timerfd on the next minute the way the clock does, to be cancelled if the wall clock is
+/// stepped, which some kernels and sandboxes don't allow.
+pub fn clock_change_wakeups() -> anyhow::Result<String> {
+    TimerFd::new(
+        libc::CLOCK_REALTIME,
+        Duration::from_secs(60),
+        Arming::ALIGNED,
+        true,
+    )
+    .context("arming on the minute boundary failed")?;
+    Ok("armed on the minute boundary, cancelled if the clock is stepped".to_owned())
+}
+
+/// Whether the clock can be drawn and driven here: keys need a terminal on stdin, and `$TERM`
+/// has to name one. One without block characters, like a dumb terminal, gets a warning, as the
+/// digits are drawn in ASCII there.
+pub fn terminal(
+    stdin_is_terminal: bool,
+    size: io::Result<(u16, u16)>,
+    term: &str,
+    colorterm: &str,
+) -> anyhow::Result<Found> {
+    if !stdin_is_terminal {
+        bail!("stdin isn't a terminal, so keys won't reach the clock");
+    }
+    if term.is_empty() {
+        bail!("$TERM isn't set, so there's no telling what can be drawn");
+    }
+    let (width, height) = size.context("reading the terminal size failed")?;
+    let depth = ColorDepth::Auto.resolve(colorterm, term);
+    let colors = match depth {
+        ColorDepth::Truecolor => "truecolor",
+        ColorDepth::Ansi256 => "256 colors",
+        ColorDepth::Ansi16 => "16 colors",
+        ColorDepth::Auto | ColorDepth::Mono => "no colors",
+    };
+    let found = format!("{term}, {width}x{height}, {colors}");
+    Ok(match widget::term_lacks_blocks(term) {
+        true => Found::Warning(format!(
+            "{found}, without block characters, so the digits are drawn in ASCII"
+        )),
+        false => Found::Fine(found),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_check_and_fails_on_any() {
+        let mut report = Report::default();
+        report.add("config", Ok("/tmp/config.toml".to_owned()));
+        assert!(report.passed());
+        report.add(
+            "terminal",
+            terminal(true, Ok((80, 24)), "xterm-256color", ""),
+        );
+        report.add("dumb", terminal(true, Ok((80, 24)), "dumb", ""));
+        assert!(report.passed());
+        report.add("stdin", terminal(false, Ok((80, 24)), "xterm", ""));
+        report.add("term", terminal(true, Ok((80, 24)), "", ""));
+        assert!(!report.passed());
+
+        let mut out = Vec::new();
+        report.write(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "ok    config: /tmp/config.toml\n\
+             ok    terminal: xterm-256color, 80x24, 256 colors\n\
+             warn  dumb: dumb, 80x24, no colors, without block characters, so the digits are drawn \
+             in ASCII\n\
+             FAIL  stdin: stdin isn't a terminal, so keys won't reach the clock\n\
+             FAIL  term: $TERM isn't set, so there's no telling what can be drawn\n"
+        );
+    }
+}
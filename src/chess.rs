@@ -0,0 +1,269 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    countdown::Countdown,
+    duration::{ceil_secs, format_hms},
+    flash::{self, BLINK_PHASE},
+};
+
+/// A player of a chess clock. White moves first, and is shown on the left or on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    White,
+    Black,
+}
+
+impl Side {
+    pub const BOTH: [Self; 2] = [Self::White, Self::Black];
+
+    pub fn other(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::White => "White",
+            Self::Black => "Black",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::White => 0,
+            Self::Black => 1,
+        }
+    }
+}
+
+/// Where a game stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    /// Waiting for the first press to start White's clock.
+    Ready,
+    /// A clock is running, or held by a pause.
+    Playing,
+    /// The side to move ran out of time at `at`. Both clocks stay frozen from then on.
+    Flagged { at: Instant, acknowledged: bool },
+}
+
+/// Two countdowns of which only the side to move's runs, measured on the monotonic clock. Ending
+/// a turn adds the Fischer increment to the side that just moved and starts the other's clock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChessClock {
+    clocks: [Countdown; 2],
+    increment: Duration,
+    turn: Side,
+    state: GameState,
+}
+
+impl ChessClock {
+    /// A game of `time` a side with `increment` added after each move, not yet started.
+    pub fn new(time: Duration, increment: Duration) -> Self {
+        let clock = Countdown::Paused { remaining: time };
+        Self {
+            clocks: [clock; 2],
+            increment,
+            turn: Side::White,
+            state: GameState::Ready,
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// The side to move, whose clock is running unless paused.
+    pub fn turn(&self) -> Side {
+        self.turn
+    }
+
+    pub fn remaining(&self, side: Side, now: Instant) -> Duration {
+        self.clocks[side.index()].remaining(now)
+    }
+
+    /// A side's time as shown, rounded up so it only reads `00:00` once it has run out.
+    pub fn reading(&self, side: Side, now: Instant) -> String {
+        format_hms(ceil_secs(self.remaining(side, now)))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state == GameState::Playing
+            && matches!(self.clocks[self.turn.index()], Countdown::Paused { .. })
+    }
+
+    /// Ends the turn of the side to move, or starts White's clock if the game hasn't begun.
+    /// Does nothing while paused or once a side has run out of time.
+    pub fn switch(&mut self, now: Instant) {
+        if self.state == GameState::Ready {
+            self.state = GameState::Playing;
+            self.clocks[self.turn.index()].toggle_pause(now);
+            return;
+        }
+        if self.is_paused() || self.update(now) || self.state != GameState::Playing {
+            return;
+        }
+        let moved = &mut self.clocks[self.turn.index()];
+        moved.toggle_pause(now);
+        moved.extend(self.increment);
+        self.turn = self.turn.other();
+        self.clocks[self.turn.index()].toggle_pause(now);
+    }
+
+    /// Pauses or resumes the running clock, once the game has begun and until a flag falls.
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if self.state == GameState::Playing && !self.update(now) {
+            self.clocks[self.turn.index()].toggle_pause(now);
+        }
+    }
+
+    /// Flags the side to move once its clock reaches zero, dated from the moment it did. Returns
+    /// whether it just ran out.
+    pub fn update(&mut self, now: Instant) -> bool {
+        let clock = &mut self.clocks[self.turn.index()];
+        if let Countdown::Running { deadline } = *clock
+            && now >= deadline
+        {
+            *clock = Countdown::Paused {
+                remaining: Duration::ZERO,
+            };
+            self.state = GameState::Flagged {
+                at: deadline,
+                acknowledged: false,
+            };
+            return true;
+        }
+        false
+    }
+
+    /// The side that ran out of time, if one has.
+    pub fn flagged(&self) -> Option<Side> {
+        matches!(self.state, GameState::Flagged { .. }).then_some(self.turn)
+    }
+
+    /// Stops the flagged side blinking. It stays marked.
+    pub fn acknowledge(&mut self) {
+        if let GameState::Flagged { acknowledged, .. } = &mut self.state {
+            *acknowledged = true;
+        }
+    }
+
+    /// Whether the flagged side is in the lit half of its blink at `now`.
+    pub fn blink_on(&self, now: Instant) -> bool {
+        match self.state {
+            GameState::Flagged {
+                at,
+                acknowledged: false,
+            } => flash::phases(at, now).is_multiple_of(2),
+            _ => false,
+        }
+    }
+
+    /// When the display next changes: the running clock's next second, or the flagged side's
+    /// next blink.
+    pub fn next_tick(&self, now: Instant) -> Option<Instant> {
+        match self.state {
+            GameState::Playing => self.clocks[self.turn.index()].next_tick(now),
+            GameState::Flagged {
+                at,
+                acknowledged: false,
+            } => Some(at + BLINK_PHASE * (flash::phases(at, now) + 1) as u32),
+            GameState::Ready | GameState::Flagged { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn only_the_side_to_move_counts_down() {
+        let start = Instant::now();
+        let mut game = ChessClock::new(secs(300), secs(3));
+        assert_eq!(game.remaining(Side::White, start + secs(10)), secs(300));
+
+        // The first press starts White's clock, with no increment for it
+        game.switch(start);
+        assert_eq!(game.turn(), Side::White);
+        assert_eq!(game.remaining(Side::White, start + secs(10)), secs(290));
+        assert_eq!(game.remaining(Side::Black, start + secs(10)), secs(300));
+
+        // White moves after 10s and gets 3s back; Black's clock starts
+        game.switch(start + secs(10));
+        assert_eq!(game.turn(), Side::Black);
+        assert_eq!(game.remaining(Side::White, start + secs(20)), secs(293));
+        assert_eq!(game.remaining(Side::Black, start + secs(20)), secs(290));
+
+        game.switch(start + secs(30));
+        assert_eq!(game.remaining(Side::Black, start + secs(40)), secs(283));
+        assert_eq!(game.remaining(Side::White, start + secs(40)), secs(283));
+    }
+
+    #[test]
+    fn pausing_holds_both_clocks() {
+        let start = Instant::now();
+        let mut game = ChessClock::new(secs(60), Duration::ZERO);
+        // Nothing to pause before the game starts
+        game.toggle_pause(start);
+        assert_eq!(game.state(), GameState::Ready);
+
+        game.switch(start);
+        game.toggle_pause(start + secs(5));
+        assert!(game.is_paused());
+        // Moving while paused would hand over time nobody used
+        game.switch(start + secs(20));
+        assert_eq!(game.turn(), Side::White);
+        assert_eq!(game.remaining(Side::White, start + secs(40)), secs(55));
+        assert_eq!(game.next_tick(start + secs(40)), None);
+
+        game.toggle_pause(start + secs(40));
+        assert_eq!(game.remaining(Side::White, start + secs(45)), secs(50));
+    }
+
+    #[test]
+    fn running_out_flags_the_side_and_freezes_the_game() {
+        let start = Instant::now();
+        let mut game = ChessClock::new(secs(10), secs(5));
+        game.switch(start);
+        game.switch(start + secs(4));
+        assert!(!game.update(start + secs(13)));
+        assert!(game.update(start + secs(15)));
+        assert_eq!(game.flagged(), Some(Side::Black));
+        assert_eq!(
+            game.state(),
+            GameState::Flagged {
+                at: start + secs(14),
+                acknowledged: false,
+            }
+        );
+        assert_eq!(
+            game.remaining(Side::Black, start + secs(20)),
+            Duration::ZERO
+        );
+        assert_eq!(game.remaining(Side::White, start + secs(20)), secs(11));
+
+        // A late press can't rescue a side whose time already ran out
+        let mut late = ChessClock::new(secs(10), secs(5));
+        late.switch(start);
+        late.switch(start + secs(12));
+        assert_eq!(late.flagged(), Some(Side::White));
+        assert_eq!(late.turn(), Side::White);
+
+        assert!(game.blink_on(start + secs(14)));
+        assert!(!game.blink_on(start + secs(14) + BLINK_PHASE));
+        assert_eq!(
+            game.next_tick(start + secs(14)),
+            Some(start + secs(14) + BLINK_PHASE)
+        );
+        game.acknowledge();
+        assert!(!game.blink_on(start + secs(14)));
+        assert_eq!(game.next_tick(start + secs(14)), None);
+    }
+}
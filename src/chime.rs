@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chrono::{DateTime, Local, Timelike};
+use serde::Deserialize;
+
+use crate::{duration::parse_duration, flash::FlashStyle, theme::parse_color};
+
+/// Time between bells, so the terminal rings each one separately instead of merging them.
+const BELL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The config's `[chime]` table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ChimeConfig {
+    /// Chime at the top of every hour.
+    pub enabled: bool,
+    /// Ring the terminal bell once per hour on the 12-hour clock.
+    pub bell: bool,
+    /// How long the screen stays inverted, e.g. `1s`. `0s` turns the flash off.
+    pub flash: String,
+    /// Fill the screen with this color for the flash, rather than inverting it.
+    pub flash_color: Option<String>,
+}
+
+impl Default for ChimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bell: true,
+            flash: "1s".to_owned(),
+            flash_color: None,
+        }
+    }
+}
+
+impl ChimeConfig {
+    /// The chime described by this table, or `None` when it is turned off.
+    pub fn chime(&self) -> anyhow::Result<Option<Chime>> {
+        let flash = parse_duration(&self.flash).context("invalid [chime] flash")?;
+        let style = match &self.flash_color {
+            Some(color) => {
+                FlashStyle::Solid(parse_color(color).context("invalid [chime] flash-color")?)
+            },
+            None => FlashStyle::Inverted,
+        };
+        Ok(self.enabled.then(|| Chime::new(self.bell, flash, style)))
+    }
+}
+
+/// A grandfather-clock chime: flashes the screen and rings the bell when the hour changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chime {
+    bell: bool,
+    flash: Duration,
+    style: FlashStyle,
+    /// The hour the clock read at the last update, to notice it rolling over.
+    last_hour: Option<u32>,
+    bells_left: u32,
+    next_bell: Option<Instant>,
+}
+
+impl Chime {
+    pub fn new(bell: bool, flash: Duration, style: FlashStyle) -> Self {
+        Self {
+            bell,
+            flash,
+            style,
+            last_hour: None,
+            bells_left: 0,
+            next_bell: None,
+        }
+    }
+
+    /// Starts chiming if the hour is different from the last update's, returning whether it
+    /// just did. The first update only records the hour, so starting the clock doesn't chime.
+    pub fn update(&mut self, local: DateTime<Local>, now: Instant) -> bool {
+        let hour = local.hour();
+        let chimed = self.last_hour.is_some_and(|last| last != hour);
+        if chimed && self.bell {
+            self.bells_left = local.hour12().1;
+            self.next_bell = Some(now);
+        }
+        self.last_hour = Some(hour);
+        chimed
+    }
+
+    /// How long and in what style the screen flashes as the hour chimes, unless it doesn't.
+    pub fn flash(&self) -> Option<(Duration, FlashStyle)> {
+        (!self.flash.is_zero()).then_some((self.flash, self.style))
+    }
+
+    /// Whether a bell is due at `now`. Each bell is only handed out once and the next is
+    /// scheduled a second later, however often this is called.
+    pub fn take_bell(&mut self, now: Instant) -> bool {
+        match self.next_bell {
+            Some(at) if now >= at && self.bells_left > 0 => {
+                self.bells_left -= 1;
+                self.next_bell = (self.bells_left > 0).then(|| now + BELL_INTERVAL);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// When the next bell is due, if one is pending.
+    pub fn next_tick(&self) -> Option<Instant> {
+        self.next_bell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn chimes_on_rollover_not_at_startup() {
+        let start = Instant::now();
+        let mut chime = Chime::new(true, Duration::from_secs(1), FlashStyle::Inverted);
+        assert!(!chime.update(at(14, 59), start));
+        assert!(!chime.take_bell(start));
+
+        let hour = start + Duration::from_secs(60);
+        assert!(chime.update(at(15, 0), hour));
+        assert!(chime.take_bell(hour));
+        assert!(!chime.update(at(15, 1), hour));
+    }
+
+    #[test]
+    fn bells_are_throttled_to_one_a_second() {
+        let start = Instant::now();
+        let mut chime = Chime::new(true, Duration::ZERO, FlashStyle::Inverted);
+        chime.update(at(14, 59), start);
+        chime.update(at(15, 0), start);
+        assert_eq!(chime.flash(), None);
+
+        // Three o'clock: three bells, however often we ask
+        let mut rung = 0;
+        for tenth in 0..100 {
+            if chime.take_bell(start + Duration::from_millis(tenth * 100)) {
+                rung += 1;
+            }
+        }
+        assert_eq!(rung, 3);
+        assert_eq!(chime.next_tick(), None);
+    }
+}
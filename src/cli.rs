@@ -0,0 +1,696 @@
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use anyhow::bail;
+use chrono::{DateTime, Local, NaiveTime};
+use clap::{Parser, Subcommand};
+use ratatui::style::Color;
+use tracing::level_filters::LevelFilter;
+
+use crate::{
+    animate::Animation,
+    app::{AmPm, AmPmPosition, validate_format},
+    battery,
+    duration::{parse_datetime, parse_duration, parse_time_of_day},
+    feed::FeedKind,
+    fuzzy::Granularity,
+    grid::Grid,
+    layout::{Align, Margins},
+    notes,
+    notify::{self, NotifyMethod},
+    palette::ColorDepth,
+    ring::RingSpan,
+    stopwatch::{self, Precision},
+    theme::{parse_background, parse_color},
+    tick,
+    widget::Scale,
+    zone::{Comparison, Zone},
+};
+
+/// A big terminal clock.
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about,
+    after_help = "Settings are taken from, each overriding the last: built-in defaults, the config \
+                  file, its --profile section, the TUI_TIME_FORMAT, TUI_TIME_24H, \
+                  TUI_TIME_LEADING_ZERO, TUI_TIME_COLOR and TUI_TIME_TZ environment variables, and \
+                  flags."
+)]
+pub struct Cli {
+    /// Config file to read instead of $XDG_CONFIG_HOME/tui-time/config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Lay the config's [profile.NAME] section over the rest of it. Overrides TUI_TIME_PROFILE
+    #[arg(short, long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Where to place the clock: center, top, bottom, left, right, or a combination like top-left
+    #[arg(long, global = true, default_value = "center")]
+    pub align: Align,
+
+    /// Blank cells to keep along the terminal's edges: one number for every side, or
+    /// VERTICAL,HORIZONTAL, or TOP,RIGHT,BOTTOM,LEFT
+    #[arg(long, global = true, default_value = "0")]
+    pub margin: Margins,
+
+    /// Blank columns to keep at the left and right edges, overriding --margin's
+    #[arg(long, global = true, value_name = "N")]
+    pub margin_x: Option<u16>,
+
+    /// Blank rows to keep at the top and bottom edges, overriding --margin's
+    #[arg(long, global = true, value_name = "N")]
+    pub margin_y: Option<u16>,
+
+    /// Draw the clock only in the part of the terminal starting this many columns from the left,
+    /// leaving the rest blank, as an overlay. --align places it within that part
+    #[arg(long, global = true, value_name = "COLUMN")]
+    pub x: Option<u16>,
+
+    /// Start the part of the terminal the clock is drawn in this many rows from the top
+    #[arg(long, global = true, value_name = "ROW")]
+    pub y: Option<u16>,
+
+    /// How many columns the part of the terminal the clock is drawn in takes, up to the right
+    /// edge if not given
+    #[arg(
+        long,
+        global = true,
+        value_name = "COLUMNS",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub width: Option<u16>,
+
+    /// How many rows the part of the terminal the clock is drawn in takes, down to the bottom if
+    /// not given
+    #[arg(
+        long,
+        global = true,
+        value_name = "ROWS",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub height: Option<u16>,
+
+    /// Draw in ROWS rows under the prompt, 8 unless given, instead of taking over the screen, so
+    /// the clock is left in the scrollback on quitting
+    #[arg(
+        long,
+        global = true,
+        value_name = "ROWS",
+        num_args = 0..=1,
+        default_missing_value = "8",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub inline: Option<u16>,
+
+    /// Drift round the screen like a screensaver, a cell every DURATION (1s unless given), turning
+    /// back off the edges. Any key but quit puts the clock back in its place
+    #[arg(
+        long,
+        global = true,
+        value_name = "DURATION",
+        num_args = 0..=1,
+        default_missing_value = "1s",
+        value_parser = parse_bounce_period
+    )]
+    pub bounce: Option<Duration>,
+
+    /// Rain falling green glyphs down the screen behind the clock, which is drawn on a cleared
+    /// patch of its own. It stops while the terminal is out of focus
+    #[arg(long, global = true)]
+    pub matrix: bool,
+
+    /// Raise a vertically centered clock by PERCENT of the rows around it, so it looks centered
+    /// rather than low on the tall cells of most terminals
+    #[arg(
+        long,
+        global = true,
+        value_name = "PERCENT",
+        num_args = 0..=1,
+        default_missing_value = "8",
+        value_parser = clap::value_parser!(u8).range(0..=50)
+    )]
+    pub optical_center: Option<u8>,
+
+    /// Show the time in this strftime format instead of the built-in 12/24-hour ones. %n starts
+    /// a new line, e.g. "Focus%n%H:%M" for a label above the time. Overrides TUI_TIME_FORMAT and
+    /// the config
+    #[arg(long, global = true, value_parser = parse_format)]
+    pub format: Option<String>,
+
+    /// Show the time in decimal time, with 10 hours a day, 100 minutes an hour, and 100 seconds a
+    /// minute, counted from midnight
+    #[arg(long, global = true, conflicts_with_all = ["format", "analog"])]
+    pub decimal: bool,
+
+    /// Round the time to the nearest MINUTES, 5 unless given, before it's shown in whichever
+    /// format: 1, 5, or 15
+    #[arg(
+        long,
+        global = true,
+        value_name = "MINUTES",
+        num_args = 0..=1,
+        default_missing_value = "5",
+        conflicts_with = "decimal"
+    )]
+    pub fuzzy: Option<Granularity>,
+
+    /// Spell the time out like "quarter past ten", rounded to the nearest five minutes unless
+    /// --fuzzy says otherwise
+    #[arg(long, global = true, conflicts_with_all = ["decimal", "format", "analog"])]
+    pub fuzzy_words: bool,
+
+    /// Start on the 24-hour format rather than the 12-hour one. Without this or --12-hour,
+    /// TUI_TIME_24H and the config's 24-hour decide, and if they're unset or "auto" it's the
+    /// locale's (LC_ALL, LC_TIME or LANG) format, or 12-hour when the locale is unset or unknown
+    #[arg(long = "24-hour", global = true)]
+    pub hour24: bool,
+
+    /// Start on the 12-hour format, whatever the locale or config say
+    #[arg(long = "12-hour", global = true, conflicts_with = "hour24")]
+    pub hour12: bool,
+
+    /// Show the date big, like MAR 08, with the time on a small line under it. Overrides the
+    /// config's content; the cycle-content key (D) still cycles on from here
+    #[arg(long, global = true)]
+    pub big_date: bool,
+
+    /// Spell out the weekday on a second big line under the big date, when there's room
+    #[arg(long, global = true)]
+    pub weekday: bool,
+
+    /// Drop the 12-hour clock's leading zero, e.g. 9:41 AM rather than 09:41 AM. Overrides the
+    /// config's leading-zero and TUI_TIME_LEADING_ZERO
+    #[arg(long, global = true)]
+    pub no_leading_zero: bool,
+
+    /// Separate the hours, minutes, and seconds with CHAR instead of a colon, e.g. "." or " ", or
+    /// with nothing if it's empty. Only the built-in formats are changed, not --format's
+    #[arg(long, global = true, value_name = "CHAR", value_parser = parse_separator)]
+    pub separator: Option<String>,
+
+    /// How the 12-hour clock shows AM and PM: upper, lower, or none
+    #[arg(long, global = true, default_value = "upper")]
+    pub ampm: AmPm,
+
+    /// Where the 12-hour clock shows AM and PM: inline in the big digits, or in normal-sized text
+    /// beside or below them, leaving the digits room to grow
+    #[arg(long, global = true, value_name = "WHERE", default_value = "inline")]
+    pub ampm_position: AmPmPosition,
+
+    /// Show the time in this zone, e.g. Europe/Paris or UTC, rather than the system's. Overrides
+    /// TUI_TIME_TZ and the config
+    #[arg(long, global = true, value_name = "ZONE", value_parser = parse_timezone)]
+    pub timezone: Option<String>,
+
+    /// Label the clock with its time zone's abbreviation, like CET or JST
+    #[arg(long, global = true)]
+    pub zone_label: bool,
+
+    /// Latitude of the clock, in degrees north, for sunrise and sunset. Overrides the config's
+    /// location
+    #[arg(
+        long,
+        global = true,
+        requires = "lon",
+        allow_negative_numbers = true,
+        value_parser = parse_latitude
+    )]
+    pub lat: Option<f64>,
+
+    /// Longitude of the clock, in degrees east
+    #[arg(
+        long,
+        global = true,
+        requires = "lat",
+        allow_negative_numbers = true,
+        value_parser = parse_longitude
+    )]
+    pub lon: Option<f64>,
+
+    /// Show today's sunrise and sunset under the clock, which needs a location from --lat and
+    /// --lon or the config
+    #[arg(long, global = true)]
+    pub sun_times: bool,
+
+    /// Show the moon's phase under the clock, with how much of it is lit
+    #[arg(long, global = true)]
+    pub moon: bool,
+
+    /// Draw the digits, the moon, and other symbols in plain ASCII, for terminals without block
+    /// characters or that give emoji the wrong width. The default when TERM is dumb or a VT model
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// Show whether the system clock is synchronized, and how far off it may be, in the top-left
+    /// corner
+    #[arg(long, global = true)]
+    pub sync_status: bool,
+
+    /// Show the battery's charge, and whether it's charging, in the bottom-right corner
+    #[arg(long, global = true)]
+    pub battery: bool,
+
+    /// Show the battery's charge in red below this percentage
+    #[arg(
+        long,
+        global = true,
+        requires = "battery",
+        value_name = "PERCENT",
+        default_value_t = battery::DEFAULT_LOW,
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    pub battery_low: u8,
+
+    /// Also show the time in this zone on a line under the clock, labelled with its city or with
+    /// LABEL. Repeat for more zones
+    #[arg(long, global = true, value_name = "ZONE[=LABEL]")]
+    pub compare: Vec<Comparison>,
+
+    /// Lay out the config's [[cell]] clocks in a grid of COLUMNSxROWS, like 2x2, each labeled and
+    /// as big as its cell allows
+    #[arg(
+        long,
+        global = true,
+        value_name = "COLUMNSxROWS",
+        conflicts_with_all = ["analog", "decimal", "fuzzy_words"]
+    )]
+    pub grid: Option<Grid>,
+
+    /// Color of the digits, overriding the theme's: a name, an index, or #rrggbb. Overrides
+    /// TUI_TIME_COLOR and the config
+    #[arg(long, global = true, value_parser = parse_color)]
+    pub color: Option<Color>,
+
+    /// Cycle the digits' color slowly through the rainbow, a step on every tick. Overrides
+    /// --color and the theme's digit color
+    #[arg(long, global = true)]
+    pub rainbow: bool,
+
+    /// Fill the background with this color, overriding the theme's and the config's: a color, or
+    /// none for the terminal's own
+    #[arg(long, global = true, value_name = "COLOR", value_parser = parse_background)]
+    pub bg: Option<Color>,
+
+    /// How many cells each pixel of the digits takes; smaller pixels fit smaller terminals
+    #[arg(long, global = true, value_name = "SIZE", default_value = "full")]
+    pub pixel_size: Scale,
+
+    /// Font for the digits: big, seven-segment, or the path of a font file
+    #[arg(long, global = true, default_value = "big")]
+    pub font: String,
+
+    /// How many cells thick the seven-segment font's segments are, from 1 to 3
+    #[arg(
+        long,
+        global = true,
+        value_name = "CELLS",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u16).range(1..=3)
+    )]
+    pub segment_thickness: u16,
+
+    /// Draw the font's unlit segments faintly in this color, like an LCD: a name, an index, or
+    /// #rrggbb
+    #[arg(long, global = true, value_name = "COLOR", value_parser = parse_color)]
+    pub segment_off_color: Option<Color>,
+
+    /// Show how long the machine has been up instead of the time, suspended time included
+    #[arg(long, global = true)]
+    pub uptime: bool,
+
+    /// Count up from a moment in the past instead of showing the time, like 1234d 05:06, e.g.
+    /// 2020-01-01 or "2020-01-01 18:05" (local time)
+    #[arg(
+        long,
+        global = true,
+        value_name = "DATETIME",
+        value_parser = parse_since,
+        conflicts_with = "uptime"
+    )]
+    pub since: Option<DateTime<Local>>,
+
+    /// Check the timer, terminal, and config the clock depends on, print a report, and exit,
+    /// failing if any check does
+    #[arg(long, global = true)]
+    pub check: bool,
+
+    /// Print the tz database's zone names, one per line, and exit. Only those containing FILTER,
+    /// ignoring case, if one is given
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILTER",
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
+    pub list_timezones: Option<String>,
+
+    /// Append a line to this CSV file for each stopwatch lap and each timer that finishes,
+    /// with the event, the wall-clock time, and the elapsed seconds
+    #[arg(long, global = true, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Follow each listed zone with its current UTC offset
+    #[arg(long, global = true, requires = "list_timezones")]
+    pub with_offset: bool,
+
+    /// Show a clock face with hands instead of digits
+    #[arg(long, global = true)]
+    pub analog: bool,
+
+    /// Sweep the analog second hand smoothly instead of ticking once a second
+    #[arg(long, global = true, requires = "analog")]
+    pub sweep: bool,
+
+    /// Show a ring filling up through the current minute or hour, above the digits or around
+    /// the analog face
+    #[arg(long, global = true, value_name = "SPAN")]
+    pub ring: Option<RingSpan>,
+
+    /// Color of the ring, instead of the digits': a name, an index, or #rrggbb
+    #[arg(long, global = true, requires = "ring", value_parser = parse_color)]
+    pub ring_color: Option<Color>,
+
+    /// Radius of the ring above the digits, in rows
+    #[arg(
+        long,
+        global = true,
+        requires = "ring",
+        value_name = "ROWS",
+        default_value_t = 3,
+        value_parser = clap::value_parser!(u16).range(1..=50)
+    )]
+    pub ring_radius: u16,
+
+    /// Show a row of dots under the clock, one per second of the minute, filled as they pass.
+    /// Narrow terminals get fewer dots, each a few seconds
+    #[arg(long, global = true)]
+    pub seconds_dots: bool,
+
+    /// Color of the passed seconds' dots, instead of the digits'
+    #[arg(long, global = true, requires = "seconds_dots", value_parser = parse_color)]
+    pub dots_color: Option<Color>,
+
+    /// Color of the dots still to come, instead of the theme's secondary color
+    #[arg(long, global = true, requires = "seconds_dots", value_parser = parse_color)]
+    pub dots_empty_color: Option<Color>,
+
+    /// On screens too small for both, show the time for SECS seconds, then the date for as long,
+    /// and so on. Any key brings the time straight back
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub alternate: Option<u64>,
+
+    /// How many colors the terminal shows, to draw the rest as the nearest it has. Worked out
+    /// from $COLORTERM and $TERM unless given; mono drops color altogether
+    #[arg(long, global = true, value_name = "DEPTH", default_value = "auto")]
+    pub color_depth: ColorDepth,
+
+    /// Animate the clock's digits as they change, only the ones that do
+    #[arg(long, global = true, value_name = "STYLE", default_value = "none")]
+    pub animate: Animation,
+
+    /// Quit after this long, e.g. 25m or 1h30m
+    #[arg(
+        long,
+        global = true,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        conflicts_with = "exit_at"
+    )]
+    pub exit_after: Option<Duration>,
+
+    /// Quit the next time the clock reads HH:MM (24-hour, local time)
+    #[arg(long, global = true, value_name = "HH:MM", value_parser = parse_time_of_day)]
+    pub exit_at: Option<NaiveTime>,
+
+    /// Ring an alarm the next time the clock reads HH:MM, and every day after
+    #[arg(long, global = true, value_name = "HH:MM", value_parser = parse_time_of_day)]
+    pub alarm: Option<NaiveTime>,
+
+    /// How long snooze silences an alarm for [default: 9m]
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration)]
+    pub snooze: Option<Duration>,
+
+    /// How to get attention when a timer finishes or an alarm rings. desktop needs a build with
+    /// the desktop-notifications feature and a notification daemon, and rings the bell without
+    #[arg(long, global = true, value_name = "METHOD", default_value = "bell")]
+    pub notify: NotifyMethod,
+
+    /// What desktop notifications say, with {event} standing for what happened, like
+    /// `Timer done: 5m`
+    #[arg(
+        long,
+        global = true,
+        value_name = "TEXT",
+        default_value = notify::DEFAULT_MESSAGE
+    )]
+    pub notify_message: String,
+
+    /// Never flash the screen; an expired timer shows a steady inverted banner instead
+    #[arg(long, global = true)]
+    pub no_flash: bool,
+
+    /// Quit straight away on the quit key, even with a stopwatch, timer, or alarm going. Without
+    /// it, quitting then asks first, and pressing quit twice skips the question
+    #[arg(long, global = true)]
+    pub no_confirm: bool,
+
+    /// Save a running stopwatch or timer to $XDG_STATE_HOME/tui-time/state.json whenever it
+    /// changes and on exit, and offer to resume it the next time the clock starts with this
+    #[arg(long, global = true)]
+    pub persist: bool,
+
+    /// Read lines from stdin and show each one, as a format or as literal text. Quits at EOF
+    #[arg(long, global = true, value_name = "KIND")]
+    pub stdin: Option<FeedKind>,
+
+    /// Show a status line along the bottom with the mode, time zone, next alarm, and key hints
+    #[arg(long, global = true)]
+    pub status_bar: bool,
+
+    /// Show a line above the clock with the weekday, date, and live seconds. The config's
+    /// `header` changes what it shows
+    #[arg(long, global = true)]
+    pub header: bool,
+
+    /// Listen on $XDG_RUNTIME_DIR/tui-time.sock for `tui-time ctl` commands. Only one clock can
+    /// have the socket; another started with this runs without it
+    #[arg(long, global = true)]
+    pub control: bool,
+
+    /// Tick at fixed intervals from startup instead of on the wall clock's minute (or second)
+    /// boundaries, so the display can lag the clock by up to one interval
+    #[arg(long, global = true)]
+    pub no_align: bool,
+
+    /// The most times a second the clock redraws on its own, which keeps fast displays like a
+    /// sweeping second hand or a stopwatch's hundredths from using more CPU than they need.
+    /// Keys and resizes still redraw straight away
+    #[arg(
+        long,
+        global = true,
+        value_name = "FPS",
+        default_value_t = tick::DEFAULT_MAX_FPS,
+        value_parser = clap::value_parser!(u32).range(1..=240)
+    )]
+    pub max_fps: u32,
+
+    /// Show a calendar of the month under the clock, with today highlighted
+    #[arg(long, global = true)]
+    pub calendar: bool,
+
+    /// Show the next event from this iCalendar file under the clock, like "Standup in 23m".
+    /// The file is read again whenever it changes
+    #[arg(long, global = true, value_name = "PATH")]
+    pub ics: Option<PathBuf>,
+
+    /// Show the notes in this text file in a panel beside the clock, scrolled with up and down.
+    /// The file is read again whenever it changes, and the panel is empty while it's missing
+    #[arg(long, global = true, value_name = "PATH")]
+    pub agenda: Option<PathBuf>,
+
+    /// How many columns the --agenda panel takes, up to half the screen
+    #[arg(
+        long,
+        global = true,
+        value_name = "COLUMNS",
+        requires = "agenda",
+        default_value_t = notes::DEFAULT_WIDTH,
+        value_parser = clap::value_parser!(u16).range(8..)
+    )]
+    pub agenda_width: u16,
+
+    /// Name today's holiday above the clock, after the --header if it's shown, from a TOML file of
+    /// `date = "name"` entries. Dates are like `12-25`, `2024-06-03`, `last-monday-of-may`,
+    /// `monday-on-or-after-may-24`, or `easter-2`
+    #[arg(long, global = true, value_name = "PATH")]
+    pub holidays: Option<PathBuf>,
+
+    /// Also name a holiday this many days away, like "Bank Holiday in 3d"
+    #[arg(
+        long,
+        global = true,
+        value_name = "DAYS",
+        requires = "holidays",
+        value_parser = clap::value_parser!(u32).range(1..=366)
+    )]
+    pub holidays_ahead: Option<u32>,
+
+    /// Don't reload the config file when it changes
+    #[arg(long, global = true)]
+    pub no_watch: bool,
+
+    /// Keep the terminal's (or tmux pane's) title set to the time, restoring the old title on quit
+    #[arg(long, global = true, visible_alias = "set-title")]
+    pub title_clock: bool,
+
+    /// Write the time shown to this file on every tick, replacing it whole so readers like status
+    /// bars never see a partial one
+    #[arg(long, global = true, value_name = "PATH")]
+    pub output_file: Option<PathBuf>,
+
+    /// Write the time shown to this named pipe as a line on every tick, making the pipe if need
+    /// be. Lines are dropped while no reader has it open, so the clock never waits on one
+    #[arg(long, global = true, value_name = "PATH")]
+    pub output_fifo: Option<PathBuf>,
+
+    /// Also draw the clock on the terminal at TTY, like /dev/pts/3, laid out for its size. Repeat
+    /// for more. One that goes away is dropped, and the clock carries on with the rest
+    #[arg(long, global = true, value_name = "TTY")]
+    pub output: Vec<PathBuf>,
+
+    /// Answer HTTP GETs on this address, like 127.0.0.1:8080, with the time shown as JSON, for
+    /// dashboards to poll. Needs a build with the serve feature
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub serve: Option<SocketAddr>,
+
+    /// Log what the ticker and the main loop are doing to this file, appending to it. Nothing is
+    /// logged without it
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// How much to log to --log-file: error, warn, info (the default), debug, or trace. Without
+    /// it, RUST_LOG is read
+    #[arg(long, global = true, value_name = "LEVEL", requires = "log_file")]
+    pub log_level: Option<LevelFilter>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Show the time (the default)
+    Clock,
+    /// Run a countdown timer, or pick one of the config's presets with [ and ]
+    Timer {
+        /// How long to count down, e.g. 90s, 10m, or 1h30m
+        #[arg(value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// Run a stopwatch instead of showing the time
+    Stopwatch {
+        /// Write laps here as CSV on quit, or when export-laps is pressed
+        #[arg(long, value_name = "PATH")]
+        lap_file: Option<PathBuf>,
+
+        /// Show whole seconds, or milliseconds
+        #[arg(long, value_name = "UNIT", default_value = "s")]
+        precision: Precision,
+
+        /// How many times a second to redraw with --precision ms, at most --max-fps
+        #[arg(
+            long,
+            value_name = "FPS",
+            default_value_t = stopwatch::DEFAULT_FPS,
+            value_parser = clap::value_parser!(u32).range(1..=240)
+        )]
+        fps: u32,
+
+        /// Keep counting while the machine is suspended, rather than only while it's awake
+        #[arg(long)]
+        count_suspend: bool,
+    },
+    /// Run a chess clock: pause ends the turn, and hold (p) pauses the game
+    Chess {
+        /// Each side's time, e.g. 5m or 1h30m
+        #[arg(long, value_name = "DURATION", default_value = "5m", value_parser = parse_game_time)]
+        time: Duration,
+
+        /// Added to a side's time after each of its moves
+        #[arg(long, value_name = "DURATION", default_value = "0s", value_parser = parse_duration)]
+        increment: Duration,
+    },
+    /// Send a command to a clock started with --control, e.g. `ctl set-format %H:%M` or
+    /// `ctl start-timer 10m`
+    Ctl {
+        /// A key binding action name (quit, pause, ...), set-format <FORMAT>, or start-timer <DURATION>
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+}
+
+fn parse_format(s: &str) -> anyhow::Result<String> {
+    validate_format(s)?;
+    Ok(s.to_owned())
+}
+
+fn parse_since(s: &str) -> anyhow::Result<DateTime<Local>> {
+    let since = parse_datetime(s)?;
+    if since > Local::now() {
+        bail!("{s} is in the future");
+    }
+    Ok(since)
+}
+
+fn parse_game_time(s: &str) -> anyhow::Result<Duration> {
+    let time = parse_duration(s)?;
+    if time.is_zero() {
+        bail!("a game needs some time on the clock");
+    }
+    Ok(time)
+}
+
+fn parse_bounce_period(s: &str) -> anyhow::Result<Duration> {
+    let period = parse_duration(s)?;
+    if period.is_zero() {
+        bail!("the clock needs some time between moves");
+    }
+    Ok(period)
+}
+
+fn parse_separator(s: &str) -> anyhow::Result<String> {
+    if s.chars().nth(1).is_some() {
+        bail!("expected a single character or nothing, found {s:?}");
+    }
+    Ok(s.to_owned())
+}
+
+/// Checks `zone` names a tz database entry, since an unknown `TZ` silently falls back to UTC.
+pub fn parse_timezone(zone: &str) -> anyhow::Result<String> {
+    Zone::load(zone)?;
+    Ok(zone.to_owned())
+}
+
+fn parse_latitude(s: &str) -> anyhow::Result<f64> {
+    parse_degrees(s, 90.0)
+}
+
+fn parse_longitude(s: &str) -> anyhow::Result<f64> {
+    parse_degrees(s, 180.0)
+}
+
+/// Degrees no further than `limit` either side of zero.
+fn parse_degrees(s: &str, limit: f64) -> anyhow::Result<f64> {
+    let degrees: f64 = s.parse()?;
+    if !(-limit..=limit).contains(&degrees) {
+        bail!("must be between -{limit} and {limit}");
+    }
+    Ok(degrees)
+}
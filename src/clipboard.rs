@@ -0,0 +1,26 @@
+use anyhow::bail;
+
+/// Copies `text` to the system clipboard with an OSC 52 sequence, which the terminal carries out
+/// itself, so it works over SSH with no clipboard tools installed. Terminals that don't support
+/// it ignore the sequence, which can't be detected.
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> anyhow::Result<()> {
+    use std::io::{self, IsTerminal};
+
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use ratatui::crossterm::{execute, style::Print};
+
+    if !io::stdout().is_terminal() {
+        bail!("no terminal to copy through");
+    }
+    execute!(
+        io::stdout(),
+        Print(format!("\x1b]52;c;{}\x07", STANDARD.encode(text)))
+    )?;
+    Ok(())
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> anyhow::Result<()> {
+    bail!("built without clipboard support")
+}
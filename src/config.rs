@@ -0,0 +1,656 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Local};
+use indexmap::IndexMap;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::{
+    alarm::{Alarm, AlarmConfig},
+    app::validate_format,
+    chime::ChimeConfig,
+    cli::{Cli, parse_timezone},
+    content::{ClockLine, ContentMode, LineConfig},
+    countdown::Preset,
+    duration::parse_duration,
+    grid::{Cell, CellConfig},
+    locale::HourFormat,
+    reminder::{self, Reminder, Reminders},
+    sun::Coordinates,
+    theme::{ThemeConfig, parse_color},
+};
+
+/// The contents of `config.toml`. Everything is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// A strftime format to show the time in, instead of the built-in 12/24-hour ones. This,
+    /// `24-hour`, and `timezone` are only read at startup.
+    pub format: Option<String>,
+    /// Start on the 24-hour format, or on whichever the locale uses with `"auto"`, as when it's
+    /// unset.
+    #[serde(rename = "24-hour")]
+    pub hour24: Option<HourFormat>,
+    /// What the clock shows to begin with: `time`, `date` for the big date with the time small
+    /// under it, or `datetime` for the time over the date.
+    pub content: ContentMode,
+    /// Lines of big text, each with a strftime format and optionally a color and pixel size,
+    /// that `datetime` shows in place of the time over the date.
+    pub lines: Vec<LineConfig>,
+    /// The clocks `--grid` lays out, each a `clock` (optionally in a `zone`), a `timer` with a
+    /// `duration`, or a `stopwatch`, with an optional `label`. Read at startup.
+    #[serde(rename = "cell")]
+    pub cells: Vec<CellConfig>,
+    /// Color of the digits, overriding the theme's.
+    pub color: Option<String>,
+    /// Zero-pad the 12-hour clock's hour, as in `09:41`, as when it's unset.
+    pub leading_zero: Option<bool>,
+    /// The zone to show the time in, rather than the system's.
+    pub timezone: Option<String>,
+    /// Show the sun/moon indicator above the clock at startup.
+    pub day_night: bool,
+    /// Label the clock with its time zone's abbreviation, like `CET`.
+    pub zone_label: bool,
+    /// Where the clock is, for anything that depends on the sun.
+    pub location: Option<Coordinates>,
+    /// Show today's sunrise and sunset at `location` under the clock.
+    pub sun_times: bool,
+    /// The theme to start with. Updated whenever a theme is picked at runtime.
+    pub theme: Option<String>,
+    /// A background color for every theme, or `none` for the terminal's own.
+    pub bg: Option<String>,
+    /// Extra themes by name, cycled through after the built-in ones.
+    pub themes: BTreeMap<String, ThemeConfig>,
+    /// Named timer durations like `tea = "3m"`, cycled through in the order written.
+    pub presets: IndexMap<String, String>,
+    /// Action name to the key or keys that trigger it, replacing that action's default keys.
+    pub keys: BTreeMap<String, KeyList>,
+    pub chime: ChimeConfig,
+    /// A strftime template for a line above the clock, like `%A · %Y-%m-%d · :%Ss`. Setting one
+    /// turns the line on.
+    pub header: Option<String>,
+    /// Recurring alarms, each with a time and optionally days, a label, and an enabled flag.
+    pub alarms: Vec<AlarmConfig>,
+    /// Cron schedules to the messages shown across the bottom of the screen when they come
+    /// round, like `"*/30 9-17 * * mon-fri" = "stretch"`.
+    pub reminders: IndexMap<String, String>,
+    /// How long each reminder's message stays up, like `30s`. 10 seconds unless set.
+    pub reminder_duration: Option<String>,
+    /// Named sets of settings, like `[profile.desk]`, laid over the rest of the file when picked
+    /// with `--profile`. Tables within them are merged with the file's key by key.
+    #[serde(rename = "profile")]
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+impl Config {
+    pub fn presets(&self) -> anyhow::Result<Vec<Preset>> {
+        self.presets
+            .iter()
+            .map(|(name, duration)| {
+                Ok(Preset {
+                    name: name.clone(),
+                    duration: parse_duration(duration)
+                        .with_context(|| format!("in preset {name}"))?,
+                })
+            })
+            .collect()
+    }
+
+    /// The enabled alarms, armed for their next occurrence after `now`.
+    pub fn alarms(&self, snooze: Duration, now: DateTime<Local>) -> anyhow::Result<Vec<Alarm>> {
+        let mut alarms = Vec::new();
+        for config in &self.alarms {
+            let alarm = config
+                .alarm(snooze, now)
+                .with_context(|| format!("in alarm {}", config.time))?;
+            alarms.extend(alarm);
+        }
+        Ok(alarms)
+    }
+
+    /// The `[[lines]]`, each with its format and color checked.
+    pub fn lines(&self) -> anyhow::Result<Vec<ClockLine>> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| line.line().with_context(|| format!("in line {}", i + 1)))
+            .collect()
+    }
+
+    /// The `[[cell]]` entries, each checked, with any timers and stopwatches started at `now`.
+    pub fn cells(&self, now: Instant) -> anyhow::Result<Vec<Cell>> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| cell.cell(now).with_context(|| format!("in cell {}", i + 1)))
+            .collect()
+    }
+
+    /// The reminders, each due next after `now`.
+    pub fn reminders(&self, now: DateTime<Local>) -> anyhow::Result<Reminders> {
+        let duration = match &self.reminder_duration {
+            Some(duration) => parse_duration(duration).context("in reminder-duration")?,
+            None => reminder::DEFAULT_DURATION,
+        };
+        let reminders = self
+            .reminders
+            .iter()
+            .map(|(schedule, message)| {
+                let schedule = schedule
+                    .parse()
+                    .with_context(|| format!("in reminder {message:?}"))?;
+                Ok(Reminder::new(schedule, message.clone(), now))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Reminders::new(reminders, duration))
+    }
+}
+
+/// The settings that can come from any of the config file, the environment, and flags. Each
+/// source is read into a layer of its own, and [`resolve`] stacks them, an unset setting falling
+/// through to the layer below: defaults < config < environment < flags.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Layer {
+    pub format: Option<String>,
+    pub hour24: Option<HourFormat>,
+    pub leading_zero: Option<bool>,
+    pub color: Option<Color>,
+    pub timezone: Option<String>,
+}
+
+impl Layer {
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        if let Some(format) = &config.format {
+            validate_format(format).context("invalid format in config")?;
+        }
+        Ok(Self {
+            format: config.format.clone(),
+            hour24: config.hour24,
+            leading_zero: config.leading_zero,
+            color: config
+                .color
+                .as_deref()
+                .map(parse_color)
+                .transpose()
+                .context("invalid color in config")?,
+            timezone: config
+                .timezone
+                .as_deref()
+                .map(parse_timezone)
+                .transpose()
+                .context("invalid timezone in config")?,
+        })
+    }
+
+    /// The `TUI_TIME_*` variables, looked up with `var`. Empty ones count as unset.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+        let format = var("TUI_TIME_FORMAT");
+        if let Some(format) = &format {
+            validate_format(format).context("invalid TUI_TIME_FORMAT")?;
+        }
+        Ok(Self {
+            format,
+            hour24: var("TUI_TIME_24H")
+                .map(|value| parse_hour_format(&value))
+                .transpose()
+                .context("invalid TUI_TIME_24H")?,
+            leading_zero: var("TUI_TIME_LEADING_ZERO")
+                .map(|value| parse_bool(&value))
+                .transpose()
+                .context("invalid TUI_TIME_LEADING_ZERO")?,
+            color: var("TUI_TIME_COLOR")
+                .map(|value| parse_color(&value))
+                .transpose()
+                .context("invalid TUI_TIME_COLOR")?,
+            timezone: var("TUI_TIME_TZ")
+                .map(|value| parse_timezone(&value))
+                .transpose()
+                .context("invalid TUI_TIME_TZ")?,
+        })
+    }
+
+    /// The flags, which clap has already checked. Leaving off both `--24-hour` and `--12-hour`
+    /// defers to the layers below, as does leaving off `--no-leading-zero`.
+    pub fn from_cli(cli: &Cli) -> Self {
+        let hour24 = match (cli.hour24, cli.hour12) {
+            (true, _) => Some(HourFormat::TwentyFour),
+            (_, true) => Some(HourFormat::Twelve),
+            _ => None,
+        };
+        Self {
+            format: cli.format.clone(),
+            hour24,
+            leading_zero: cli.no_leading_zero.then_some(false),
+            color: cli.color,
+            timezone: cli.timezone.clone(),
+        }
+    }
+
+    /// This layer with whatever it leaves unset taken from `below`.
+    pub fn over(self, below: Self) -> Self {
+        Self {
+            format: self.format.or(below.format),
+            hour24: self.hour24.or(below.hour24),
+            leading_zero: self.leading_zero.or(below.leading_zero),
+            color: self.color.or(below.color),
+            timezone: self.timezone.or(below.timezone),
+        }
+    }
+}
+
+/// The settings `cli`, the environment's layer `env`, and `config` make together, the flags taking
+/// precedence. Anything still unset is left to the defaults.
+pub fn resolve(config: &Config, env: &Layer, cli: &Cli) -> anyhow::Result<Layer> {
+    Ok(Layer::from_cli(cli)
+        .over(env.clone())
+        .over(Layer::from_config(config)?))
+}
+
+fn parse_hour_format(s: &str) -> anyhow::Result<HourFormat> {
+    if s.eq_ignore_ascii_case("auto") {
+        return Ok(HourFormat::Auto);
+    }
+    parse_bool(s).map(HourFormat::from)
+}
+
+fn parse_bool(s: &str) -> anyhow::Result<bool> {
+    match s.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => bail!("expected true or false, not {s:?}"),
+    }
+}
+
+/// One key spec or a list of them, so `quit = "q"` and `quit = ["q", "ctrl-c"]` both work. An
+/// empty list leaves the action unbound.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn specs(&self) -> &[String] {
+        match self {
+            Self::One(spec) => std::slice::from_ref(spec),
+            Self::Many(specs) => specs,
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/tui-time/config.toml`, falling back to `~/.config` when unset.
+pub fn default_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => Path::new(&env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("tui-time").join("config.toml"))
+}
+
+/// The profile to load: `--profile`, or failing that `TUI_TIME_PROFILE`, looked up with `var`.
+pub fn profile_name(cli: &Cli, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    cli.profile
+        .clone()
+        .or_else(|| var("TUI_TIME_PROFILE").filter(|name| !name.is_empty()))
+}
+
+/// Loads the config at `path`, with `profile` laid over it if one is named. A missing file is the
+/// same as an empty one.
+pub fn load(path: &Path, profile: Option<&str>) -> anyhow::Result<Config> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+    };
+    parse(&text, profile).with_context(|| format!("in {}", path.display()))
+}
+
+/// Parses the config in `text`, with `profile` laid over it if one is named.
+pub fn parse(text: &str, profile: Option<&str>) -> anyhow::Result<Config> {
+    let config: Config = toml::from_str(text)?;
+    let Some(name) = profile else {
+        return Ok(config);
+    };
+    let Some(overrides) = config.profiles.get(name) else {
+        let names: Vec<_> = config.profiles.keys().map(String::as_str).collect();
+        match names[..] {
+            [] => bail!("there's no profile {name:?}, as no profiles are set up"),
+            _ => bail!(
+                "there's no profile {name:?}; the profiles are {}",
+                names.join(", ")
+            ),
+        }
+    };
+    // Parsed again as plain TOML for the profile's values to be merged in before they're checked
+    let mut table: toml::Table = toml::from_str(text)?;
+    merge(&mut table, overrides.clone());
+    toml::Value::Table(table)
+        .try_into()
+        .with_context(|| format!("in profile {name}"))
+}
+
+/// Lays `overrides` over `table`: tables are merged key by key, and anything else is replaced.
+fn merge(table: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(below)), toml::Value::Table(above)) => merge(below, above),
+            (_, value) => {
+                table.insert(key, value);
+            },
+        }
+    }
+}
+
+/// Records `name` as the starting theme in the config at `path`, or in `profile`'s section of it
+/// if one is in use, creating the file if needed and leaving the rest of it (comments included)
+/// untouched.
+pub fn save_theme(path: &Path, profile: Option<&str>, name: &str) -> anyhow::Result<()> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+    };
+    let mut doc: toml_edit::DocumentMut = text
+        .parse()
+        .with_context(|| format!("parsing {}", path.display()))?;
+    match profile {
+        Some(profile) => doc["profile"][profile]["theme"] = toml_edit::value(name),
+        None => doc["theme"] = toml_edit::value(name),
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    fs::write(path, doc.to_string()).with_context(|| format!("writing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::widget::{LineStyle, Scale};
+
+    fn resolve_with(config: &str, env: &[(&str, &str)], args: &[&str]) -> Layer {
+        let var = |name: &str| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_owned())
+        };
+        let cli = Cli::try_parse_from([&["tui-time"], args].concat()).unwrap();
+        let config = parse(config, profile_name(&cli, var).as_deref()).unwrap();
+        let env = Layer::from_env(var).unwrap();
+        resolve(&config, &env, &cli).unwrap()
+    }
+
+    #[test]
+    fn each_layer_overrides_the_one_below() {
+        assert_eq!(resolve_with("", &[], &[]), Layer::default());
+
+        let config = "format = \"%H.%M\"\n24-hour = true\ncolor = \"red\"\ntimezone = \"UTC\"\n\
+                      leading-zero = true\n";
+        let from_config = resolve_with(config, &[], &[]);
+        assert_eq!(
+            from_config,
+            Layer {
+                format: Some("%H.%M".to_owned()),
+                hour24: Some(HourFormat::TwentyFour),
+                leading_zero: Some(true),
+                color: Some(Color::Red),
+                timezone: Some("UTC".to_owned()),
+            }
+        );
+
+        let env = [
+            ("TUI_TIME_FORMAT", "%H:%M"),
+            ("TUI_TIME_24H", "false"),
+            ("TUI_TIME_COLOR", "blue"),
+            ("TUI_TIME_TZ", "Europe/Paris"),
+            ("TUI_TIME_LEADING_ZERO", "yes"),
+        ];
+        let from_env = resolve_with(config, &env, &[]);
+        assert_eq!(
+            from_env,
+            Layer {
+                format: Some("%H:%M".to_owned()),
+                hour24: Some(HourFormat::Twelve),
+                leading_zero: Some(true),
+                color: Some(Color::Blue),
+                timezone: Some("Europe/Paris".to_owned()),
+            }
+        );
+
+        let args = [
+            "--format",
+            "%T",
+            "--24-hour",
+            "--color",
+            "green",
+            "--timezone",
+            "Asia/Tokyo",
+            "--no-leading-zero",
+        ];
+        let from_flags = resolve_with(config, &env, &args);
+        assert_eq!(
+            from_flags,
+            Layer {
+                format: Some("%T".to_owned()),
+                hour24: Some(HourFormat::TwentyFour),
+                leading_zero: Some(false),
+                color: Some(Color::Green),
+                timezone: Some("Asia/Tokyo".to_owned()),
+            }
+        );
+
+        // Unset settings fall through every layer that leaves them alone
+        let mixed = resolve_with(
+            "color = \"red\"",
+            &[("TUI_TIME_24H", "1")],
+            &["--format", "%T"],
+        );
+        assert_eq!(
+            mixed,
+            Layer {
+                format: Some("%T".to_owned()),
+                hour24: Some(HourFormat::TwentyFour),
+                leading_zero: None,
+                color: Some(Color::Red),
+                timezone: None,
+            }
+        );
+    }
+
+    #[test]
+    fn profiles_sit_between_the_config_and_the_flags() {
+        let config = "color = \"red\"\n24-hour = true\n\
+                      [profile.desk]\ncolor = \"blue\"\nformat = \"%T\"\n\
+                      [profile.work]\ntimezone = \"UTC\"\n";
+        let top_level = Layer {
+            hour24: Some(HourFormat::TwentyFour),
+            color: Some(Color::Red),
+            ..Layer::default()
+        };
+        assert_eq!(resolve_with(config, &[], &[]), top_level);
+
+        // What the profile leaves alone comes from the top level
+        let desk = Layer {
+            format: Some("%T".to_owned()),
+            color: Some(Color::Blue),
+            ..top_level.clone()
+        };
+        assert_eq!(resolve_with(config, &[], &["--profile", "desk"]), desk);
+        assert_eq!(
+            resolve_with(config, &[("TUI_TIME_PROFILE", "desk")], &[]),
+            desk
+        );
+        // The flag picks over the environment, and other flags override the profile
+        assert_eq!(
+            resolve_with(
+                config,
+                &[("TUI_TIME_PROFILE", "work")],
+                &["-p", "desk", "--color", "green"],
+            ),
+            Layer {
+                color: Some(Color::Green),
+                ..desk
+            }
+        );
+    }
+
+    #[test]
+    fn profile_tables_merge_with_the_top_level() {
+        let config = "[keys]\nquit = \"x\"\nhelp = \"h\"\n\
+                      [profile.desk]\nday-night = true\n\
+                      [profile.desk.keys]\nquit = \"q\"\n";
+        let plain = parse(config, None).unwrap();
+        assert!(!plain.day_night);
+        let desk = parse(config, Some("desk")).unwrap();
+        assert!(desk.day_night);
+        let keys: BTreeMap<_, _> = desk
+            .keys
+            .iter()
+            .map(|(action, keys)| (action.as_str(), keys.specs().to_vec()))
+            .collect();
+        assert_eq!(
+            keys,
+            BTreeMap::from([
+                ("help", vec!["h".to_owned()]),
+                ("quit", vec!["q".to_owned()])
+            ])
+        );
+
+        // A profile's settings are checked like the rest
+        let err = parse("[profile.desk]\ncolour = \"red\"\n", Some("desk")).unwrap_err();
+        assert!(format!("{err:#}").starts_with("in profile desk: unknown field `colour`"));
+    }
+
+    #[test]
+    fn unknown_profiles_list_the_known_ones() {
+        let config = "[profile.work]\n[profile.desk]\n";
+        let err = parse(config, Some("dsek")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "there's no profile \"dsek\"; the profiles are desk, work"
+        );
+        let err = parse("", Some("desk")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "there's no profile \"desk\", as no profiles are set up"
+        );
+    }
+
+    #[test]
+    fn the_hour_format_can_be_left_to_the_locale() {
+        let hour24 = |config, env, args| resolve_with(config, env, args).hour24;
+        assert_eq!(
+            hour24("24-hour = \"auto\"", &[], &[]),
+            Some(HourFormat::Auto)
+        );
+        assert_eq!(
+            hour24("24-hour = true", &[("TUI_TIME_24H", "Auto")], &[]),
+            Some(HourFormat::Auto)
+        );
+        assert_eq!(
+            hour24("", &[("TUI_TIME_24H", "auto")], &["--12-hour"]),
+            Some(HourFormat::Twelve)
+        );
+        let err = parse("24-hour = \"sometimes\"", None).unwrap_err();
+        assert!(format!("{err:#}").contains("expected true, false or \"auto\""));
+    }
+
+    #[test]
+    fn bad_environment_values_name_the_variable() {
+        for (name, value) in [
+            ("TUI_TIME_FORMAT", "%Q"),
+            ("TUI_TIME_24H", "maybe"),
+            ("TUI_TIME_LEADING_ZERO", "sometimes"),
+            ("TUI_TIME_COLOR", "not-a-color"),
+            ("TUI_TIME_TZ", "Mars/Olympus_Mons"),
+        ] {
+            let err = Layer::from_env(|var| (var == name).then(|| value.to_owned())).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid {name}"));
+        }
+        // Empty is the same as unset, as with most tools
+        assert_eq!(
+            Layer::from_env(|_| Some(String::new())).unwrap(),
+            Layer::default()
+        );
+    }
+
+    #[test]
+    fn lines_are_checked_when_read() {
+        let config = parse(
+            "[[lines]]\nformat = \"%A\"\ncolor = \"blue\"\npixel-size = \"quadrant\"\n\
+             [[lines]]\nformat = \"%H:%M\"\n",
+            None,
+        )
+        .unwrap();
+        let lines = config.lines().unwrap();
+        assert_eq!(lines[0].format, "%A");
+        assert_eq!(lines[0].style.color, Some(Color::Blue));
+        assert_eq!(lines[0].style.pixel_size, Some(Scale::Quadrant.into()));
+        assert_eq!(lines[1].style, LineStyle::default());
+
+        let config = parse(
+            "[[lines]]\nformat = \"%A\"\n[[lines]]\nformat = \"%Q\"\n",
+            None,
+        )
+        .unwrap();
+        let err = config.lines().unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "in line 2: invalid format string \"%Q\""
+        );
+    }
+
+    #[test]
+    fn cells_are_checked_when_read() {
+        let config = parse(
+            "[[cell]]\nzone = \"UTC\"\n[[cell]]\nmode = \"stopwatch\"\nlabel = \"Run\"\n",
+            None,
+        )
+        .unwrap();
+        let cells = config.cells(Instant::now()).unwrap();
+        assert_eq!(cells[0].label, "UTC");
+        assert_eq!(cells[1].label, "Run");
+
+        let config = parse("[[cell]]\n[[cell]]\nmode = \"timer\"\n", None).unwrap();
+        let err = config.cells(Instant::now()).unwrap_err();
+        assert_eq!(format!("{err:#}"), "in cell 2: a timer needs a duration");
+        assert!(parse("[[cell]]\nmode = \"alarm\"\n", None).is_err());
+    }
+
+    #[test]
+    fn saving_the_theme_keeps_the_rest_of_the_file() {
+        let path = env::temp_dir().join(format!("tui-time-config-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "# my clock\nday-night = true # sun\ntheme = \"blue\"\n",
+        )
+        .unwrap();
+
+        save_theme(&path, None, "amber").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# my clock\nday-night = true # sun\ntheme = \"amber\"\n"
+        );
+        assert_eq!(load(&path, None).unwrap().theme.as_deref(), Some("amber"));
+
+        // Picked under a profile, it's saved in the profile's section
+        fs::write(&path, "theme = \"blue\"\n[profile.desk]\ntheme = \"red\"\n").unwrap();
+        save_theme(&path, Some("desk"), "amber").unwrap();
+        assert_eq!(load(&path, None).unwrap().theme.as_deref(), Some("blue"));
+        let desk = load(&path, Some("desk")).unwrap();
+        assert_eq!(desk.theme.as_deref(), Some("amber"));
+        fs::remove_file(path).unwrap();
+    }
+}
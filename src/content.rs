@@ -0,0 +1,122 @@
+//! What the clock shows: the time, the date, or both, cycled with `cycle-content`.
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use tui_big_text::PixelSize;
+
+use crate::{
+    alternate::DATE_FORMAT,
+    app::validate_format,
+    theme::parse_color,
+    widget::{LineStyle, Scale},
+};
+
+/// The big date, like `MAR 08`: the same width all month, and in capitals, which the big font
+/// draws more evenly than lowercase.
+pub const BIG_DATE_FORMAT: &str = "%b %d";
+
+/// What the clock's big text is made of, as the config's `content` spells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContentMode {
+    #[default]
+    Time,
+    /// The date drawn as big as the time would be, with the time on a small line under it.
+    Date,
+    /// The time with the date on a line under it.
+    #[serde(rename = "datetime")]
+    DateTime,
+}
+
+impl ContentMode {
+    /// The mode `cycle-content` goes on to.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Time => Self::Date,
+            Self::Date => Self::DateTime,
+            Self::DateTime => Self::Time,
+        }
+    }
+
+    /// What to show at `local`, given the time as it would be shown on its own.
+    pub fn text(self, time: String, local: DateTime<Local>) -> String {
+        match self {
+            Self::Time => time,
+            Self::Date => local.format(BIG_DATE_FORMAT).to_string().to_uppercase(),
+            Self::DateTime => format!("{time}\n{}", local.format(DATE_FORMAT)),
+        }
+    }
+
+    /// Whether the time is part of the big text, and so whether its seconds can be dropped to
+    /// make it fit.
+    pub fn shows_time(self) -> bool {
+        !matches!(self, Self::Date)
+    }
+}
+
+/// One entry of the config's `[[lines]]` list: a line of big text that the `datetime` content is
+/// drawn as, in place of the time over the date.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LineConfig {
+    /// A strftime format, like `%A` or `%H:%M`.
+    pub format: String,
+    /// The line's color, rather than the digits'.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// The line's pixel size, rather than `--pixel-size`'s.
+    #[serde(default)]
+    pub pixel_size: Option<Scale>,
+}
+
+impl LineConfig {
+    /// The line this entry describes, its format checked so drawing it can't fail.
+    pub fn line(&self) -> anyhow::Result<ClockLine> {
+        validate_format(&self.format)?;
+        Ok(ClockLine {
+            format: self.format.clone(),
+            style: LineStyle {
+                color: self.color.as_deref().map(parse_color).transpose()?,
+                pixel_size: self.pixel_size.map(PixelSize::from),
+            },
+        })
+    }
+}
+
+/// A line of the clock's big text with a format and style of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockLine {
+    pub format: String,
+    pub style: LineStyle,
+}
+
+impl ClockLine {
+    /// Each of `lines` at `local`, one under the other.
+    pub fn text(lines: &[Self], local: DateTime<Local>) -> String {
+        let lines: Vec<_> = lines
+            .iter()
+            .map(|line| local.format(&line.format).to_string())
+            .collect();
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn cycles_through_each_mode() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 9, 41, 0).unwrap();
+        let mut mode = ContentMode::default();
+        let mut shown = Vec::new();
+        for _ in 0..3 {
+            shown.push(mode.text("9:41".to_owned(), local));
+            mode = mode.next();
+        }
+        assert_eq!(shown, ["9:41", "MAR 01", "9:41\nMar 1"]);
+        assert_eq!(mode, ContentMode::Time);
+    }
+}
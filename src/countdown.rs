@@ -0,0 +1,372 @@
+use std::time::{Duration, Instant};
+
+/// A countdown measured against the monotonic clock, so wall-clock steps never change how much
+/// time is left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Countdown {
+    Running { deadline: Instant },
+    Paused { remaining: Duration },
+}
+
+impl Countdown {
+    pub fn start(duration: Duration, now: Instant) -> Self {
+        Self::Running {
+            deadline: now + duration,
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match *self {
+            Self::Running { deadline } => deadline.saturating_duration_since(now),
+            Self::Paused { remaining } => remaining,
+        }
+    }
+
+    /// Counts `slept` against the deadline, time the machine spent suspended that the monotonic
+    /// clock missed. A paused countdown wasn't counting anyway.
+    pub fn slept(&mut self, slept: Duration) {
+        if let Self::Running { deadline } = self {
+            *deadline = deadline.checked_sub(slept).unwrap_or(*deadline);
+        }
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        *self = match *self {
+            Self::Running { .. } => Self::Paused {
+                remaining: self.remaining(now),
+            },
+            Self::Paused { remaining } => Self::start(remaining, now),
+        };
+    }
+
+    pub fn extend(&mut self, by: Duration) {
+        match self {
+            Self::Running { deadline } => *deadline += by,
+            Self::Paused { remaining } => *remaining += by,
+        }
+    }
+
+    /// When the displayed whole-second remainder next changes, or `None` if it never will (paused
+    /// or already expired).
+    pub fn next_tick(&self, now: Instant) -> Option<Instant> {
+        let Self::Running { deadline } = *self else {
+            return None;
+        };
+        let remaining = deadline.checked_duration_since(now)?;
+        if remaining.is_zero() {
+            return None;
+        }
+
+        // The display rounds up, so it changes when the remainder drops to the next lower whole
+        // second.
+        let shown_secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+        Some(deadline - Duration::from_secs(shown_secs - 1))
+    }
+}
+
+/// A named countdown duration from the config's `[presets]` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerState {
+    /// Nothing started yet; the selected preset is shown.
+    Idle,
+    /// Counting down, possibly paused.
+    Running(Countdown),
+    /// Reached zero at `at`, and flashes until `acknowledged`.
+    Expired { at: Instant, acknowledged: bool },
+}
+
+/// The countdown mode: a timer that can be started from a preset, topped up while it runs, and
+/// restarted with the same duration once it expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timer {
+    state: TimerState,
+    /// The duration most recently started, for [`Timer::restart`].
+    last_started: Option<Duration>,
+    presets: Vec<Preset>,
+    selected: usize,
+}
+
+impl Timer {
+    pub fn new(presets: Vec<Preset>) -> Self {
+        Self {
+            state: TimerState::Idle,
+            last_started: None,
+            presets,
+            selected: 0,
+        }
+    }
+
+    /// A timer picked up in `state`, as an earlier run left it.
+    pub fn resumed(
+        presets: Vec<Preset>,
+        state: TimerState,
+        last_started: Option<Duration>,
+    ) -> Self {
+        Self {
+            state,
+            last_started,
+            ..Self::new(presets)
+        }
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.state
+    }
+
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    pub fn selected(&self) -> Option<&Preset> {
+        self.presets.get(self.selected)
+    }
+
+    pub fn start(&mut self, duration: Duration, now: Instant) {
+        self.state = TimerState::Running(Countdown::start(duration, now));
+        self.last_started = Some(duration);
+    }
+
+    pub fn start_selected(&mut self, now: Instant) {
+        if let Some(preset) = self.selected() {
+            self.start(preset.duration, now);
+        }
+    }
+
+    /// Replaces the presets, keeping the selection where it was if there are still enough.
+    pub fn set_presets(&mut self, presets: Vec<Preset>) {
+        self.presets = presets;
+        if self.selected >= self.presets.len() {
+            self.selected = 0;
+        }
+    }
+
+    /// Moves the preset selection by `offset`, wrapping at either end.
+    pub fn select(&mut self, offset: isize) {
+        if !self.presets.is_empty() {
+            let len = self.presets.len() as isize;
+            self.selected = (self.selected as isize + offset).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Starts the last-started duration again from the top.
+    pub fn restart(&mut self, now: Instant) {
+        if let Some(duration) = self.last_started {
+            self.start(duration, now);
+        }
+    }
+
+    /// Adds time to a running (or paused) countdown; does nothing otherwise.
+    pub fn extend(&mut self, by: Duration) {
+        if let TimerState::Running(countdown) = &mut self.state {
+            countdown.extend(by);
+        }
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if let TimerState::Running(countdown) = &mut self.state {
+            countdown.toggle_pause(now);
+        }
+    }
+
+    /// Catches up with `slept` spent suspended, as the timer counts down to a moment on the wall
+    /// clock: a countdown comes closer to its end, or past it, and an expired one's overtime
+    /// grows.
+    pub fn slept(&mut self, slept: Duration) {
+        match &mut self.state {
+            TimerState::Running(countdown) => countdown.slept(slept),
+            TimerState::Expired { at, .. } => *at = at.checked_sub(slept).unwrap_or(*at),
+            TimerState::Idle => {},
+        }
+    }
+
+    /// Moves a countdown that has reached zero to [`TimerState::Expired`], dated from its
+    /// deadline rather than `now` so the overtime is exact however late this is called. Returns
+    /// whether it just expired.
+    pub fn update(&mut self, now: Instant) -> bool {
+        if let TimerState::Running(Countdown::Running { deadline }) = self.state
+            && now >= deadline
+        {
+            self.state = TimerState::Expired {
+                at: deadline,
+                acknowledged: false,
+            };
+            return true;
+        }
+        false
+    }
+
+    /// The duration the timer was last started with, not counting time added since.
+    pub fn last_started(&self) -> Option<Duration> {
+        self.last_started
+    }
+
+    /// Stops the expiry flash. The overtime keeps counting.
+    pub fn acknowledge(&mut self) {
+        if let TimerState::Expired { acknowledged, .. } = &mut self.state {
+            *acknowledged = true;
+        }
+    }
+
+    /// Whether the timer has expired and nobody has acknowledged it yet.
+    pub fn is_unacknowledged(&self) -> bool {
+        matches!(
+            self.state,
+            TimerState::Expired {
+                acknowledged: false,
+                ..
+            }
+        )
+    }
+
+    /// How long ago the timer expired, if it has.
+    pub fn overtime(&self, now: Instant) -> Option<Duration> {
+        match self.state {
+            TimerState::Expired { at, .. } => Some(now.saturating_duration_since(at)),
+            TimerState::Idle | TimerState::Running(_) => None,
+        }
+    }
+
+    /// The time to show: what's left, or the selected preset's length before anything starts.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.state {
+            TimerState::Idle => self
+                .selected()
+                .map_or(Duration::ZERO, |preset| preset.duration),
+            TimerState::Running(countdown) => countdown.remaining(now),
+            TimerState::Expired { .. } => Duration::ZERO,
+        }
+    }
+
+    /// When the display next changes: the countdown's next second, or the overtime's.
+    pub fn next_tick(&self, now: Instant) -> Option<Instant> {
+        match self.state {
+            TimerState::Running(countdown) => countdown.next_tick(now),
+            TimerState::Expired { at, .. } => {
+                let overtime = now.saturating_duration_since(at);
+                Some(at + Duration::from_secs(overtime.as_secs() + 1))
+            },
+            TimerState::Idle => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pausing_freezes_the_remainder() {
+        let start = Instant::now();
+        let mut countdown = Countdown::start(Duration::from_secs(60), start);
+
+        countdown.toggle_pause(start + Duration::from_secs(10));
+        assert_eq!(
+            countdown.remaining(start + Duration::from_secs(40)),
+            Duration::from_secs(50)
+        );
+        assert_eq!(countdown.next_tick(start), None);
+
+        countdown.toggle_pause(start + Duration::from_secs(40));
+        assert_eq!(
+            countdown.remaining(start + Duration::from_secs(45)),
+            Duration::from_secs(45)
+        );
+    }
+
+    #[test]
+    fn next_tick_lands_on_whole_seconds_before_the_deadline() {
+        let start = Instant::now();
+        let countdown = Countdown::start(Duration::from_secs(10), start);
+
+        assert_eq!(
+            countdown.next_tick(start),
+            Some(start + Duration::from_secs(1))
+        );
+        assert_eq!(
+            countdown.next_tick(start + Duration::from_millis(1500)),
+            Some(start + Duration::from_secs(2))
+        );
+        assert_eq!(countdown.next_tick(start + Duration::from_secs(10)), None);
+    }
+
+    fn preset(name: &str, secs: u64) -> Preset {
+        Preset {
+            name: name.to_owned(),
+            duration: Duration::from_secs(secs),
+        }
+    }
+
+    #[test]
+    fn timer_runs_from_idle_to_expired() {
+        let start = Instant::now();
+        let mut timer = Timer::new(vec![preset("tea", 180), preset("egg", 420)]);
+        assert_eq!(timer.state(), TimerState::Idle);
+        assert_eq!(timer.remaining(start), Duration::from_secs(180));
+
+        timer.select(1);
+        timer.start_selected(start);
+        timer.update(start + Duration::from_secs(419));
+        assert!(matches!(timer.state(), TimerState::Running(_)));
+
+        // Noticed late, but the overtime still counts from the deadline
+        let late = start + Duration::from_millis(421_500);
+        timer.update(late);
+        assert!(timer.is_unacknowledged());
+        assert_eq!(timer.overtime(late), Some(Duration::from_millis(1500)));
+        assert_eq!(
+            timer.next_tick(late),
+            Some(start + Duration::from_secs(422))
+        );
+
+        timer.acknowledge();
+        assert!(!timer.is_unacknowledged());
+        assert_eq!(
+            timer.overtime(start + Duration::from_secs(430)),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn preset_selection_wraps_both_ways() {
+        let mut timer = Timer::new(vec![preset("a", 1), preset("b", 2), preset("c", 3)]);
+        timer.select(-1);
+        assert_eq!(timer.selected().unwrap().name, "c");
+        timer.select(1);
+        assert_eq!(timer.selected().unwrap().name, "a");
+
+        let mut empty = Timer::new(Vec::new());
+        empty.select(1);
+        empty.start_selected(Instant::now());
+        assert_eq!(empty.state(), TimerState::Idle);
+    }
+
+    #[test]
+    fn restart_and_extend() {
+        let start = Instant::now();
+        let mut timer = Timer::new(Vec::new());
+        timer.start(Duration::from_secs(60), start);
+        timer.extend(Duration::from_secs(60));
+        timer.update(start + Duration::from_secs(90));
+        assert_eq!(
+            timer.remaining(start + Duration::from_secs(90)),
+            Duration::from_secs(30)
+        );
+
+        let later = start + Duration::from_secs(200);
+        timer.update(later);
+        let expired = timer.state();
+        assert!(matches!(expired, TimerState::Expired { .. }));
+        timer.extend(Duration::from_secs(60));
+        assert_eq!(timer.state(), expired);
+
+        // Restart uses the originally started length, not the extended one
+        timer.restart(later);
+        assert_eq!(timer.remaining(later), Duration::from_secs(60));
+    }
+}
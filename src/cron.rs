@@ -0,0 +1,302 @@
+//! Cron schedules, for the config's `[reminders]`.
+
+use std::str::FromStr;
+
+use anyhow::{Context, bail, ensure};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone};
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// The most days each month can have, leap years included.
+const MONTH_LENGTHS: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// How many days ahead to look for a match. Every month and day that exists at all comes round
+/// within eight years, even the 29th of February across a century that isn't a leap year.
+const SEARCH_DAYS: usize = 8 * 366;
+
+/// A cron schedule like `*/30 9-17 * * mon-fri`: the minute, hour, day of the month, month, and
+/// day of the week it matches. Each field is `*`, a number, or a range `a-b`, optionally
+/// stepped with `/n`, or a list of those separated by commas; months and days of the week can
+/// be named by their first three letters, and Sunday is 0 or 7. As in cron, when both the day of
+/// the month and of the week are restricted, a day matching either matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    /// Bit `n` set for each minute `n` that matches, and likewise below.
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    /// Sunday is bit 0.
+    weekdays: u64,
+    /// Whether the day fields were `*`, which decides whether they both have to match.
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// Whether the schedule matches some time on `date`.
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day = self.days & 1 << date.day() != 0;
+        let weekday = self.weekdays & 1 << date.weekday().num_days_from_sunday() != 0;
+        let days = if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        };
+        self.months & 1 << date.month() != 0 && days
+    }
+
+    /// The first minute after `now` the schedule matches, as the wall clock reads it where `now`
+    /// is. Minutes skipped by a DST gap are skipped, and repeated ones match the first time.
+    pub fn next_after<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = now.timezone();
+        let dates = now.date_naive().iter_days().take(SEARCH_DAYS);
+        for date in dates.filter(|&date| self.matches_date(date)) {
+            for hour in (0..24).filter(|hour| self.hours & 1 << hour != 0) {
+                for minute in (0..60).filter(|minute| self.minutes & 1 << minute != 0) {
+                    let Some(time) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    match tz.from_local_datetime(&time).earliest() {
+                        Some(at) if at > *now => return Some(at),
+                        _ => {},
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            bail!(
+                "expected 5 fields (minute, hour, day, month, weekday) in {s:?}, not {}",
+                fields.len()
+            );
+        };
+        let weekday_mask = field(weekdays, 0, 7, &WEEKDAYS, 0).context("in the weekday")?;
+        let schedule = Self {
+            minutes: field(minutes, 0, 59, &[], 0).context("in the minute")?,
+            hours: field(hours, 0, 23, &[], 0).context("in the hour")?,
+            days: field(days, 1, 31, &[], 0).context("in the day")?,
+            months: field(months, 1, 12, &MONTHS, 1).context("in the month")?,
+            // 7 is Sunday as well as 0
+            weekdays: (weekday_mask | weekday_mask >> 7) & 0b111_1111,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        };
+        let possible = (1..=12)
+            .filter(|&month| schedule.months & 1 << month != 0)
+            .any(|month| {
+                // Bits 1 through the month's length
+                let days_in_month = (2_u64 << MONTH_LENGTHS[month - 1]) - 2;
+                schedule.days & days_in_month != 0
+            });
+        ensure!(
+            possible || !schedule.any_weekday,
+            "the days in {s:?} don't occur in its months"
+        );
+        Ok(schedule)
+    }
+}
+
+/// The bits for the values `text` lists, from `min` to `max`. `names` are accepted for the
+/// values from `first_name` on.
+fn field(text: &str, min: u32, max: u32, names: &[&str], first_name: u32) -> anyhow::Result<u64> {
+    let value = |text: &str| -> anyhow::Result<u32> {
+        let lower = text.to_ascii_lowercase();
+        let value = match names.iter().position(|name| *name == lower) {
+            Some(i) => i as u32 + first_name,
+            None => text
+                .parse()
+                .with_context(|| format!("{text:?} isn't a number or name"))?,
+        };
+        ensure!(
+            (min..=max).contains(&value),
+            "{value} is outside {min}-{max}"
+        );
+        Ok(value)
+    };
+    let mut mask = 0;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|&step| step > 0)
+                    .with_context(|| format!("step {step:?} isn't a positive number"))?;
+                (range, step)
+            },
+            None => (item, 1),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (value(first)?, value(last)?),
+            // A stepped single value runs to the end, as `5/15` is 5, 20, 35, and 50
+            None if step > 1 => (value(range)?, max),
+            None => {
+                let value = value(range)?;
+                (value, value)
+            },
+        };
+        ensure!(first <= last, "{range:?} runs backwards");
+        for value in (first..=last).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Utc};
+
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    fn next(schedule: &str, now: DateTime<Utc>) -> DateTime<Utc> {
+        schedule
+            .parse::<Schedule>()
+            .unwrap()
+            .next_after(&now)
+            .unwrap()
+    }
+
+    fn bits(values: &[u32]) -> u64 {
+        values.iter().fold(0, |mask, value| mask | 1 << value)
+    }
+
+    #[test]
+    fn parses_each_kind_of_field() {
+        assert_eq!(field("*", 0, 5, &[], 0).unwrap(), bits(&[0, 1, 2, 3, 4, 5]));
+        assert_eq!(field("3", 0, 59, &[], 0).unwrap(), bits(&[3]));
+        assert_eq!(
+            field("9-12", 0, 23, &[], 0).unwrap(),
+            bits(&[9, 10, 11, 12])
+        );
+        assert_eq!(
+            field("*/15", 0, 59, &[], 0).unwrap(),
+            bits(&[0, 15, 30, 45])
+        );
+        assert_eq!(
+            field("10-20/5", 0, 59, &[], 0).unwrap(),
+            bits(&[10, 15, 20])
+        );
+        assert_eq!(field("5/20", 0, 59, &[], 0).unwrap(), bits(&[5, 25, 45]));
+        assert_eq!(field("1,3,5-6", 0, 6, &[], 0).unwrap(), bits(&[1, 3, 5, 6]));
+        assert_eq!(
+            field("*/2", 1, 12, &MONTHS, 1).unwrap(),
+            bits(&[1, 3, 5, 7, 9, 11])
+        );
+    }
+
+    #[test]
+    fn names_months_and_weekdays() {
+        assert_eq!(field("jan,Dec", 1, 12, &MONTHS, 1).unwrap(), bits(&[1, 12]));
+        assert_eq!(
+            field("jun-aug", 1, 12, &MONTHS, 1).unwrap(),
+            bits(&[6, 7, 8])
+        );
+        assert_eq!(
+            field("mon-fri", 0, 7, &WEEKDAYS, 0).unwrap(),
+            bits(&[1, 2, 3, 4, 5])
+        );
+        // Sunday is 0 and 7 alike
+        let weekend: Schedule = "0 0 * * sat-7".parse().unwrap();
+        let sunday: Schedule = "0 0 * * 0,6".parse().unwrap();
+        assert_eq!(weekend, sunday);
+    }
+
+    #[test]
+    fn rejects_malformed_schedules() {
+        for schedule in [
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "* * * smarch *",
+            "*/0 * * * *",
+            "*/x * * * *",
+            "30-10 * * * *",
+            "1,,2 * * * *",
+            "- * * * *",
+            // There's never a February 30th
+            "0 0 30 feb *",
+        ] {
+            assert!(schedule.parse::<Schedule>().is_err(), "{schedule}");
+        }
+        let err = "0 25 * * *".parse::<Schedule>().unwrap_err();
+        assert_eq!(format!("{err:#}"), "in the hour: 25 is outside 0-23");
+    }
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        // Friday, 1 March 2024
+        let now = at(2024, 3, 1, 9, 10);
+        assert_eq!(next("* * * * *", now), at(2024, 3, 1, 9, 11));
+        assert_eq!(next("*/30 9-17 * * 1-5", now), at(2024, 3, 1, 9, 30));
+        assert_eq!(
+            next("*/30 9-17 * * 1-5", at(2024, 3, 1, 17, 30)),
+            at(2024, 3, 4, 9, 0)
+        );
+        assert_eq!(next("0 9 * * *", now), at(2024, 3, 2, 9, 0));
+        // Exactly on a match waits for the next one
+        assert_eq!(next("10 9 * * *", now), at(2024, 3, 2, 9, 10));
+        assert_eq!(next("0 0 1 jan *", now), at(2025, 1, 1, 0, 0));
+        assert_eq!(next("0 12 31 * *", now), at(2024, 3, 31, 12, 0));
+        assert_eq!(
+            next("0 12 31 * *", at(2024, 4, 1, 0, 0)),
+            at(2024, 5, 31, 12, 0)
+        );
+        assert_eq!(next("0 0 29 feb *", now), at(2028, 2, 29, 0, 0));
+    }
+
+    #[test]
+    fn either_day_field_matches_when_both_are_set() {
+        // The 15th, or any Monday: Monday 4 March comes first
+        let now = at(2024, 3, 1, 12, 0);
+        assert_eq!(next("0 8 15 * mon", now), at(2024, 3, 4, 8, 0));
+        assert_eq!(
+            next("0 8 15 * mon", at(2024, 3, 12, 0, 0)),
+            at(2024, 3, 15, 8, 0)
+        );
+        // With either one a star, the other has to match as well
+        assert_eq!(next("0 8 * * mon", now), at(2024, 3, 4, 8, 0));
+        assert_eq!(next("0 8 */14 * *", now), at(2024, 3, 15, 8, 0));
+        assert_eq!(
+            next("0 8 13 * fri", at(2024, 9, 1, 0, 0)),
+            at(2024, 9, 6, 8, 0)
+        );
+    }
+
+    #[test]
+    fn reads_the_wall_clock_where_now_is() {
+        let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+        let now = at(2024, 3, 1, 0, 0).with_timezone(&tokyo);
+        let next = "0 9 * * *"
+            .parse::<Schedule>()
+            .unwrap()
+            .next_after(&now)
+            .unwrap();
+        assert_eq!(next.to_utc(), at(2024, 3, 2, 0, 0));
+    }
+}
@@ -0,0 +1,77 @@
+//! `--decimal`: French Revolutionary time, which divides the day into 10 hours of 100 minutes of
+//! 100 seconds, counted from local midnight.
+
+use std::time::Duration;
+
+use chrono::{NaiveTime, Timelike};
+
+/// One decimal second, a hundred-thousandth of a day.
+pub const SECOND: Duration = Duration::from_millis(864);
+/// One decimal minute, a thousandth of a day.
+pub const MINUTE: Duration = Duration::from_millis(86_400);
+
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+/// The decimal hours, minutes, and seconds at the civil time of day `time`, rounded down.
+pub fn from_time_of_day(time: NaiveTime) -> (u32, u32, u32) {
+    // A leap second reads as the last moment of the one before it
+    let nanos = u64::from(time.num_seconds_from_midnight()) * 1_000_000_000
+        + u64::from(time.nanosecond().min(999_999_999));
+    let seconds = (nanos * 100_000 / NANOS_PER_DAY) as u32;
+    (seconds / 10_000, seconds / 100 % 100, seconds % 100)
+}
+
+/// The decimal time at `time`, like `4:37:21`, or `4:37` without seconds.
+pub fn format(time: NaiveTime, seconds: bool) -> String {
+    let (h, m, s) = from_time_of_day(time);
+    if seconds {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{h}:{m:02}")
+    }
+}
+
+/// How far after the epoch's multiples of `interval` the decimal boundaries fall, for a zone
+/// `offset_secs` ahead of UTC. They're counted from local midnight, which only lines up with
+/// them when the offset is a whole number of `interval`s.
+pub fn phase(offset_secs: i32, interval: Duration) -> Duration {
+    let step = interval.as_nanos() as i64;
+    let phase = (-i64::from(offset_secs) * 1_000_000_000).rem_euclid(step);
+    Duration::from_nanos(phase as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32, s: u32, nanos: u32) -> NaiveTime {
+        NaiveTime::from_hms_nano_opt(h, m, s, nanos).unwrap()
+    }
+
+    #[test]
+    fn converts_the_civil_time_of_day() {
+        assert_eq!(format(at(0, 0, 0, 0), true), "0:00:00");
+        assert_eq!(format(at(12, 0, 0, 0), true), "5:00:00");
+        assert_eq!(format(at(6, 0, 0, 0), false), "2:50");
+        assert_eq!(format(at(23, 59, 59, 0), true), "9:99:98");
+        assert_eq!(format(at(23, 59, 59, 999_999_999), true), "9:99:99");
+        assert_eq!(format(at(23, 59, 59, 1_500_000_000), true), "9:99:99");
+
+        // A decimal second is 0.864 SI seconds, so the first flips just before the second one
+        assert_eq!(from_time_of_day(at(0, 0, 0, 863_999_999)), (0, 0, 0));
+        assert_eq!(from_time_of_day(at(0, 0, 0, 864_000_000)), (0, 0, 1));
+        assert_eq!(from_time_of_day(at(11, 5, 30, 0)), (4, 62, 15));
+    }
+
+    #[test]
+    fn boundaries_follow_local_midnight() {
+        assert_eq!(phase(0, SECOND), Duration::ZERO);
+        // An hour is 4166 decimal seconds and 0.576s over, so an hour ahead of UTC they start
+        // 0.288s later than they would in UTC
+        assert_eq!(phase(3600, SECOND), Duration::from_millis(288));
+        assert_eq!(phase(-3600, SECOND), Duration::from_millis(576));
+        // Three hours is a whole number of them
+        assert_eq!(phase(3 * 3600, SECOND), Duration::ZERO);
+        assert_eq!(phase(3600, MINUTE), Duration::from_millis(28_800));
+    }
+}
@@ -0,0 +1,70 @@
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+const SECONDS: u16 = 60;
+
+/// The `--seconds-dots` colors, each falling back to the theme's when unset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SecondsDots {
+    pub filled: Option<Color>,
+    pub empty: Option<Color>,
+}
+
+/// A row of dots through the minute, filled up to and including `second`, fitted to `width`
+/// columns. With room for it the 60 dots are spaced out; narrower than 60 columns, each dot
+/// stands for a few seconds and fills once the first of them arrives, so the row is full for
+/// the minute's last second either way.
+pub fn line(second: u32, width: u16, ascii: bool, filled: Style, empty: Style) -> Line<'static> {
+    let (on, off) = if ascii { ("*", ".") } else { ("●", "·") };
+    let cells = width.min(SECONDS);
+    let separator = if width >= SECONDS * 2 - 1 { " " } else { "" };
+    let lit = (0..cells)
+        .filter(|&cell| u32::from(cell) * u32::from(SECONDS) / u32::from(cells) <= second)
+        .count();
+    let dots = |glyph: &str, count: usize| vec![glyph; count].join(separator);
+
+    let mut spans = vec![Span::styled(dots(on, lit), filled)];
+    if lit > 0 && lit < usize::from(cells) {
+        spans.push(Span::raw(separator));
+    }
+    spans.push(Span::styled(dots(off, usize::from(cells) - lit), empty));
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(second: u32, width: u16) -> String {
+        line(second, width, true, Style::new(), Style::new()).to_string()
+    }
+
+    #[test]
+    fn fills_one_dot_per_second() {
+        let dots = text(14, 80);
+        assert_eq!(dots.len(), 60);
+        assert_eq!(dots, format!("{}{}", "*".repeat(15), ".".repeat(45)));
+        assert_eq!(text(0, 60), format!("*{}", ".".repeat(59)));
+        assert_eq!(text(59, 60), "*".repeat(60));
+    }
+
+    #[test]
+    fn spaces_the_dots_out_when_wide() {
+        let dots = text(1, 200);
+        assert_eq!(dots.len(), 119);
+        assert!(dots.starts_with("* * . . "));
+        assert_eq!(text(59, 119), ["*"; 60].join(" "));
+    }
+
+    #[test]
+    fn compresses_into_narrow_terminals() {
+        // Each dot is four seconds
+        assert_eq!(text(0, 15), format!("*{}", ".".repeat(14)));
+        assert_eq!(text(3, 15), format!("*{}", ".".repeat(14)));
+        assert_eq!(text(4, 15), format!("**{}", ".".repeat(13)));
+        assert_eq!(text(59, 15), "*".repeat(15));
+        assert_eq!(text(30, 0), "");
+    }
+}
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta, Timelike, Utc};
+
+use crate::duration::format_compact;
+
+/// How far ahead to look for a transition. The search runs again each midnight, so two days
+/// always covers the 24 hours the notice is shown for.
+const SEARCH_AHEAD: TimeDelta = TimeDelta::days(2);
+
+/// How long before a transition the notice appears.
+const NOTICE_AHEAD: TimeDelta = TimeDelta::days(1);
+
+/// A change in the zone's UTC offset, such as the start or end of daylight saving time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// The first instant on the new offset.
+    pub at: DateTime<Utc>,
+    pub from: FixedOffset,
+    pub to: FixedOffset,
+}
+
+impl Transition {
+    /// The line shown before the change, e.g. `Clocks go forward 1h at 02:00 tonight`. The time
+    /// is what the clock reads just before it changes.
+    pub fn notice(&self, now: DateTime<Utc>) -> String {
+        let shift = self.to.local_minus_utc() - self.from.local_minus_utc();
+        let direction = if shift > 0 { "forward" } else { "back" };
+        let amount = format_compact(Duration::from_secs(shift.unsigned_abs().into()));
+
+        let at = self.at.with_timezone(&self.from);
+        let today = now.with_timezone(&self.from).date_naive();
+        let when = if at.date_naive() == today {
+            "today"
+        } else if at.hour() < 6 {
+            "tonight"
+        } else {
+            "tomorrow"
+        };
+        format!(
+            "Clocks go {direction} {amount} at {} {when}",
+            at.format("%H:%M")
+        )
+    }
+}
+
+/// The first change in `offset_at` after `from` and no later than `until`. Offsets are sampled
+/// hourly, then the change is narrowed down to the second, so transitions less than an hour
+/// apart could be missed; no zone has those.
+pub fn next_transition(
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+    offset_at: impl Fn(DateTime<Utc>) -> FixedOffset,
+) -> Option<Transition> {
+    let start = offset_at(from);
+    let mut before = from;
+    while before < until {
+        let after = (before + TimeDelta::hours(1)).min(until);
+        if offset_at(after) != start {
+            // The offset is `start` at `before` and something else at `after`
+            let (mut low, mut high) = (before, after);
+            while high - low > TimeDelta::seconds(1) {
+                let mid = low + (high - low) / 2;
+                if offset_at(mid) == start {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            return Some(Transition {
+                at: high.with_nanosecond(0).unwrap_or(high),
+                from: start,
+                to: offset_at(high),
+            });
+        }
+        before = after;
+    }
+    None
+}
+
+/// Watches for the next transition so a notice can be shown in the 24 hours before it. The
+/// search result is cached until the day changes, the transition passes, or the clock is
+/// stepped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DstWatch {
+    /// The next transition, if any, and the date (in the zone) it was searched for on.
+    cached: Option<(NaiveDate, Option<Transition>)>,
+}
+
+impl DstWatch {
+    /// Searches again if the cache is stale, reading the zone's offsets from `offset_at`.
+    pub fn update(&mut self, now: DateTime<Utc>, offset_at: impl Fn(DateTime<Utc>) -> FixedOffset) {
+        let today = now.with_timezone(&offset_at(now)).date_naive();
+        let fresh = self.cached.is_some_and(|(date, next)| {
+            date == today && next.is_none_or(|transition| now < transition.at)
+        });
+        if !fresh {
+            let next = next_transition(now, now + SEARCH_AHEAD, offset_at);
+            self.cached = Some((today, next));
+        }
+    }
+
+    /// Forgets the cached transition, for when the clock has been stepped.
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    /// The notice for an upcoming transition, once it's less than a day away.
+    pub fn notice(&self, now: DateTime<Utc>) -> Option<String> {
+        let (_, next) = self.cached?;
+        next.filter(|transition| now < transition.at && transition.at - now <= NOTICE_AHEAD)
+            .map(|transition| transition.notice(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    /// A zone `standard` hours east of UTC that moves an hour ahead for summer time between the
+    /// two instants.
+    fn zone(
+        standard: i32,
+        summer: (DateTime<Utc>, DateTime<Utc>),
+    ) -> impl Fn(DateTime<Utc>) -> FixedOffset {
+        move |t| {
+            let hours = if (summer.0..summer.1).contains(&t) {
+                standard + 1
+            } else {
+                standard
+            };
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    fn us_eastern_2024() -> impl Fn(DateTime<Utc>) -> FixedOffset {
+        zone(-5, (utc(2024, 3, 10, 7, 0), utc(2024, 11, 3, 6, 0)))
+    }
+
+    fn eu_central_2025() -> impl Fn(DateTime<Utc>) -> FixedOffset {
+        zone(1, (utc(2025, 3, 30, 1, 0), utc(2025, 10, 26, 1, 0)))
+    }
+
+    #[test]
+    fn finds_us_transitions() {
+        let from = utc(2024, 3, 9, 12, 0);
+        let spring = next_transition(from, from + SEARCH_AHEAD, us_eastern_2024()).unwrap();
+        assert_eq!(spring.at, utc(2024, 3, 10, 7, 0));
+        assert_eq!(spring.notice(from), "Clocks go forward 1h at 02:00 tonight");
+
+        let from = utc(2024, 11, 2, 20, 30);
+        let fall = next_transition(from, from + SEARCH_AHEAD, us_eastern_2024()).unwrap();
+        assert_eq!(fall.at, utc(2024, 11, 3, 6, 0));
+        assert_eq!(fall.notice(from), "Clocks go back 1h at 02:00 tonight");
+        assert_eq!(
+            fall.notice(utc(2024, 11, 3, 5, 0)),
+            "Clocks go back 1h at 02:00 today"
+        );
+    }
+
+    #[test]
+    fn finds_eu_transitions() {
+        let from = utc(2025, 3, 29, 9, 0);
+        let spring = next_transition(from, from + SEARCH_AHEAD, eu_central_2025()).unwrap();
+        assert_eq!(spring.at, utc(2025, 3, 30, 1, 0));
+        assert_eq!(spring.notice(from), "Clocks go forward 1h at 02:00 tonight");
+
+        let from = utc(2025, 10, 25, 9, 0);
+        let fall = next_transition(from, from + SEARCH_AHEAD, eu_central_2025()).unwrap();
+        assert_eq!(fall.at, utc(2025, 10, 26, 1, 0));
+        assert_eq!(fall.notice(from), "Clocks go back 1h at 03:00 tonight");
+    }
+
+    #[test]
+    fn zones_without_dst_have_no_transitions() {
+        let from = utc(2025, 3, 29, 9, 0);
+        for offset in [0, 9 * 3600, -(3 * 3600 + 30 * 60)] {
+            let fixed = FixedOffset::east_opt(offset).unwrap();
+            assert_eq!(
+                next_transition(from, from + TimeDelta::days(366), |_| fixed),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn notice_shows_within_a_day_and_clears_after() {
+        let mut watch = DstWatch::default();
+        let two_days_before = utc(2024, 3, 8, 7, 0);
+        watch.update(two_days_before, us_eastern_2024());
+        assert_eq!(watch.notice(two_days_before), None);
+
+        // The cached result is reused until the day changes
+        let next_day = utc(2024, 3, 9, 12, 0);
+        watch.update(next_day, us_eastern_2024());
+        assert_eq!(
+            watch.notice(next_day).as_deref(),
+            Some("Clocks go forward 1h at 02:00 tonight")
+        );
+
+        let after = utc(2024, 3, 10, 7, 0);
+        watch.update(after, us_eastern_2024());
+        assert_eq!(watch.notice(after), None);
+
+        // A stepped clock searches again from wherever it lands
+        watch.invalidate();
+        let stepped_back = utc(2024, 3, 10, 6, 0);
+        watch.update(stepped_back, us_eastern_2024());
+        assert_eq!(
+            watch.notice(stepped_back).as_deref(),
+            Some("Clocks go forward 1h at 02:00 today")
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// Parses a human duration such as `90s`, `10m`, or `1h30m`. A bare number is taken as seconds.
+pub fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        bail!("empty duration");
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0_u64;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            bail!("expected a number in duration {s:?}");
+        }
+        let value: u64 = rest[..digits]
+            .parse()
+            .with_context(|| format!("invalid number in duration {s:?}"))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let multiplier = match &rest[..unit_len] {
+            "h" => 3600,
+            "m" => 60,
+            "s" => 1,
+            "" => bail!("missing unit after {value} in duration {s:?}"),
+            unit => bail!("unknown unit {unit:?} in duration {s:?} (expected h, m, or s)"),
+        };
+        rest = &rest[unit_len..];
+
+        total = value
+            .checked_mul(multiplier)
+            .and_then(|secs| total.checked_add(secs))
+            .with_context(|| format!("duration {s:?} is too large"))?;
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Formats a duration the way [`parse_duration`] reads it, e.g. `1h30m` or `45s`.
+pub fn format_compact(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let mut out = String::new();
+    for (value, unit) in [(h, 'h'), (m, 'm'), (s, 's')] {
+        if value > 0 {
+            out += &format!("{value}{unit}");
+        }
+    }
+    if out.is_empty() { "0s".to_owned() } else { out }
+}
+
+/// How long until something happens, for reminders like `in 6h 12m`: rounded up to the minute,
+/// so it never says `0m` before the time comes, and in its two largest units, from minutes up to
+/// weeks.
+pub fn format_until(duration: Duration) -> String {
+    let minutes = ceil_secs(duration).as_secs().div_ceil(60);
+    let units = [
+        (minutes / (7 * 24 * 60), 'w'),
+        (minutes / (24 * 60) % 7, 'd'),
+        (minutes / 60 % 24, 'h'),
+        (minutes % 60, 'm'),
+    ];
+    let Some(largest) = units.iter().position(|&(value, _)| value > 0) else {
+        return "0m".to_owned();
+    };
+    units[largest..]
+        .iter()
+        .take(2)
+        .filter(|&&(value, _)| value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A long span as days and the time of day, like `14d 03:27:15`, or `03:27:15` within the first
+/// day, for uptime and `--since`. Without `seconds` it stops at the minutes, like `14d 03:27`.
+pub fn format_days(duration: Duration, seconds: bool) -> String {
+    let secs = duration.as_secs();
+    let (days, h, m, s) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    let time = if seconds {
+        format!("{h:02}:{m:02}:{s:02}")
+    } else {
+        format!("{h:02}:{m:02}")
+    };
+    match days {
+        0 => time,
+        days => format!("{days}d {time}"),
+    }
+}
+
+/// Rounds up to a whole second, so a countdown only shows `00:00` once it has actually finished.
+pub fn ceil_secs(duration: Duration) -> Duration {
+    Duration::from_secs(duration.as_secs() + u64::from(duration.subsec_nanos() > 0))
+}
+
+/// Formats a duration as `MM:SS`, or `H:MM:SS` once it reaches an hour, dropping any fraction of
+/// a second.
+pub fn format_hms(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m:02}:{s:02}")
+    }
+}
+
+/// Like [`format_hms`] with hundredths of a second, e.g. `01:02.34`.
+pub fn format_hms_hundredths(duration: Duration) -> String {
+    format!(
+        "{}.{:02}",
+        format_hms(duration),
+        duration.subsec_millis() / 10
+    )
+}
+
+/// Like [`format_hms`] with milliseconds, e.g. `01:02.345`.
+pub fn format_hms_millis(duration: Duration) -> String {
+    format!("{}.{:03}", format_hms(duration), duration.subsec_millis())
+}
+
+/// Formats a duration as seconds to the millisecond, e.g. `62.345`, for CSV files.
+pub fn format_seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// Parses a date such as `2020-01-01`, taken as its midnight, or a date and time to the minute
+/// such as `2020-01-01 18:05` or `2020-01-01T18:05`, in local time.
+pub fn parse_datetime(s: &str) -> anyhow::Result<DateTime<Local>> {
+    let s = s.trim();
+    let naive = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_time(NaiveTime::MIN))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M"))
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M"))
+        .with_context(|| {
+            format!("invalid date {s:?} (expected YYYY-MM-DD, optionally followed by HH:MM)")
+        })?;
+    // A repeated time is taken the first time round; a skipped one doesn't exist
+    naive
+        .and_local_timezone(Local)
+        .earliest()
+        .with_context(|| format!("{s} doesn't exist in the local time zone"))
+}
+
+/// Parses a 24-hour time of day such as `07:30` or `18:05`.
+pub fn parse_time_of_day(s: &str) -> anyhow::Result<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .with_context(|| format!("invalid time {s:?} (expected HH:MM)"))
+}
+
+/// Serde for durations as whole milliseconds, for fields like `elapsed_ms`.
+pub mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+
+    /// The same for an optional duration, left out while `None`.
+    pub mod option {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match duration {
+                Some(duration) => super::serialize(duration, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn parses_single_and_compound_units() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_duration(" 45 ").unwrap(), Duration::from_secs(45));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for s in ["", "m", "10x", "1h30", "h10"] {
+            assert!(parse_duration(s).is_err(), "{s:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn compact_format_round_trips() {
+        for s in ["0s", "45s", "3m", "47m", "1h30m", "2h5s"] {
+            assert_eq!(format_compact(parse_duration(s).unwrap()), s);
+        }
+    }
+
+    #[test]
+    fn time_until_rounds_up_to_its_two_largest_units() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        assert_eq!(format_until(Duration::ZERO), "0m");
+        assert_eq!(format_until(Duration::from_secs(30)), "1m");
+        assert_eq!(format_until(Duration::from_millis(60_001)), "2m");
+        assert_eq!(format_until(minutes(59)), "59m");
+        assert_eq!(format_until(Duration::from_secs(59 * 60 + 30)), "1h");
+        assert_eq!(format_until(minutes(6 * 60 + 12)), "6h 12m");
+        assert_eq!(format_until(minutes(24 * 60 - 1)), "23h 59m");
+        // Minutes stop counting once it's days away
+        assert_eq!(format_until(minutes(24 * 60 + 5)), "1d");
+        assert_eq!(format_until(minutes(3 * 24 * 60 + 4 * 60 + 30)), "3d 4h");
+        assert_eq!(format_until(minutes(7 * 24 * 60)), "1w");
+        assert_eq!(format_until(minutes(17 * 24 * 60 + 60)), "2w 3d");
+        assert_eq!(format_until(minutes(21 * 24 * 60)), "3w");
+    }
+
+    #[test]
+    fn parses_dates_with_optional_times() {
+        let at = |y, mo, d, h, mi| Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap();
+        assert_eq!(parse_datetime("2020-01-01").unwrap(), at(2020, 1, 1, 0, 0));
+        assert_eq!(
+            parse_datetime("2020-01-01 18:05").unwrap(),
+            at(2020, 1, 1, 18, 5)
+        );
+        assert_eq!(
+            parse_datetime(" 2020-01-01T18:05 ").unwrap(),
+            at(2020, 1, 1, 18, 5)
+        );
+        for s in [
+            "",
+            "2020-13-01",
+            "2020-01-01 25:00",
+            "01/01/2020",
+            "2020-01-01 18:05:30",
+        ] {
+            assert!(parse_datetime(s).is_err(), "{s:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn formats_days_then_the_time_of_day() {
+        let hms = 3 * 3600 + 27 * 60 + 15;
+        assert_eq!(format_days(Duration::from_secs(hms), true), "03:27:15");
+        assert_eq!(
+            format_days(
+                Duration::from_millis((14 * 86_400 + hms) * 1000 + 999),
+                true
+            ),
+            "14d 03:27:15"
+        );
+        assert_eq!(
+            format_days(Duration::from_secs(86_400), true),
+            "1d 00:00:00"
+        );
+        assert_eq!(
+            format_days(Duration::from_secs(1234 * 86_400 + hms), false),
+            "1234d 03:27"
+        );
+        assert_eq!(format_days(Duration::from_secs(59), false), "00:00");
+    }
+
+    #[test]
+    fn formats_minutes_and_hours() {
+        assert_eq!(format_hms(Duration::from_secs(0)), "00:00");
+        assert_eq!(format_hms(Duration::from_millis(59_999)), "00:59");
+        assert_eq!(
+            format_hms(ceil_secs(Duration::from_millis(59_001))),
+            "01:00"
+        );
+        assert_eq!(format_hms(Duration::from_secs(3599)), "59:59");
+        assert_eq!(format_hms(Duration::from_secs(3600)), "1:00:00");
+        assert_eq!(
+            format_hms_hundredths(Duration::from_millis(62_345)),
+            "01:02.34"
+        );
+        assert_eq!(
+            format_hms_millis(Duration::from_micros(62_345_999)),
+            "01:02.345"
+        );
+    }
+
+    #[test]
+    fn parses_times_of_day() {
+        assert_eq!(
+            parse_time_of_day("07:30").unwrap(),
+            NaiveTime::from_hms_opt(7, 30, 0).unwrap()
+        );
+        assert!(parse_time_of_day("25:00").is_err());
+        assert!(parse_time_of_day("7pm").is_err());
+    }
+}
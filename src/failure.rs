@@ -0,0 +1,89 @@
+//! Errors the clock carries on after: telling them from the ones it can't, and the line that
+//! shows them until dismissed.
+
+use std::{fmt, io};
+
+/// Whether `err` is likely to pass, like the `EIO` a redraw gets while the terminal is being
+/// reattached, rather than a sign something is broken for good. Only I/O errors are judged;
+/// anything else is taken to be fatal.
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<io::Error>())
+        .any(|err| {
+            matches!(
+                err.kind(),
+                io::ErrorKind::Interrupted
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::BrokenPipe
+            ) || err.raw_os_error() == Some(libc::EIO)
+        })
+}
+
+/// The last error ridden out, shown under the clock until it's dismissed, with how many times
+/// in a row it's happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLine {
+    pub message: String,
+    pub count: u32,
+}
+
+impl ErrorLine {
+    /// Counts `message` again if it's the one showing, or replaces it otherwise.
+    pub fn report(line: &mut Option<Self>, message: String) {
+        match line {
+            Some(line) if line.message == message => line.count += 1,
+            _ => *line = Some(Self { message, count: 1 }),
+        }
+    }
+}
+
+impl fmt::Display for ErrorLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {}", self.message)?;
+        if self.count > 1 {
+            write!(f, " ({} times)", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn io_errors_that_pass_are_transient() {
+        let eio = anyhow::Error::from(io::Error::from_raw_os_error(libc::EIO));
+        assert!(is_transient(&eio));
+        assert!(is_transient(&eio.context("drawing failed")));
+        let interrupted: anyhow::Result<()> =
+            Err(io::Error::from(io::ErrorKind::Interrupted)).context("reading the timer failed");
+        assert!(is_transient(&interrupted.unwrap_err()));
+
+        let badf = anyhow::Error::from(io::Error::from_raw_os_error(libc::EBADF));
+        assert!(!is_transient(&badf));
+        assert!(!is_transient(&anyhow::anyhow!("no such theme")));
+    }
+
+    #[test]
+    fn counts_repeats_of_the_same_error() {
+        let mut line = None;
+        ErrorLine::report(&mut line, "input/output error".to_owned());
+        assert_eq!(
+            line.as_ref().unwrap().to_string(),
+            "error: input/output error"
+        );
+        ErrorLine::report(&mut line, "input/output error".to_owned());
+        ErrorLine::report(&mut line, "input/output error".to_owned());
+        assert_eq!(
+            line.as_ref().unwrap().to_string(),
+            "error: input/output error (3 times)"
+        );
+        // A different one starts over
+        ErrorLine::report(&mut line, "config not reloaded".to_owned());
+        assert_eq!(line.unwrap().count, 1);
+    }
+}
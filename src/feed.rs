@@ -0,0 +1,97 @@
+use std::io;
+
+use clap::ValueEnum;
+use tokio::io::{AsyncBufReadExt, BufReader, Stdin};
+
+use crate::app::{Action, validate_format};
+
+/// What each line piped into `--stdin` sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FeedKind {
+    /// A strftime format for the clock, like `%H:%M`
+    Format,
+    /// Literal text to show instead of the time
+    Text,
+}
+
+impl FeedKind {
+    /// The action for one line of input, without its line ending.
+    pub fn action(self, line: String) -> anyhow::Result<Action> {
+        match self {
+            Self::Format => {
+                validate_format(&line)?;
+                Ok(Action::SetFormat(line))
+            },
+            Self::Text => Ok(Action::SetText(line)),
+        }
+    }
+}
+
+/// Lines read from stdin by another process driving the display.
+pub struct StdinFeed {
+    kind: FeedKind,
+    reader: BufReader<Stdin>,
+    /// Bytes of a line still being read. Kept across calls so a read cancelled by `select!` loses
+    /// nothing.
+    buf: Vec<u8>,
+}
+
+impl StdinFeed {
+    pub fn new(kind: FeedKind) -> Self {
+        Self {
+            kind,
+            reader: BufReader::new(tokio::io::stdin()),
+            buf: Vec::new(),
+        }
+    }
+
+    /// The next line, with invalid UTF-8 replaced rather than rejected, or `None` at EOF. A last
+    /// line without a newline still counts.
+    async fn next_line(&mut self) -> io::Result<Option<String>> {
+        if self.reader.read_until(b'\n', &mut self.buf).await? == 0 && self.buf.is_empty() {
+            return Ok(None);
+        }
+        let line = decode_line(&self.buf);
+        self.buf.clear();
+        Ok(Some(line))
+    }
+
+    /// The action for the next line, or `None` at EOF. A line that doesn't make a valid action
+    /// (a bad format, say) is an inner error, so the caller can report it and keep reading.
+    pub async fn next_action(&mut self) -> io::Result<Option<anyhow::Result<Action>>> {
+        let line = self.next_line().await?;
+        Ok(line.map(|line| self.kind.action(line)))
+    }
+}
+
+fn decode_line(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_lines_lossily() {
+        assert_eq!(decode_line(b"12:00\n"), "12:00");
+        assert_eq!(decode_line(b"crlf\r\n"), "crlf");
+        assert_eq!(decode_line(b"no newline"), "no newline");
+        assert_eq!(decode_line(b"caf\xe9 \xff\n"), "caf\u{fffd} \u{fffd}");
+    }
+
+    #[test]
+    fn format_lines_are_validated() {
+        assert_eq!(
+            FeedKind::Format.action("%H:%M".to_owned()).unwrap(),
+            Action::SetFormat("%H:%M".to_owned())
+        );
+        assert!(FeedKind::Format.action("%Q".to_owned()).is_err());
+        assert_eq!(
+            FeedKind::Text.action("%Q".to_owned()).unwrap(),
+            Action::SetText("%Q".to_owned())
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::{Duration, Instant};
+
+use ratatui::style::{Color, Style};
+
+/// How long each half of a blinking flash lasts, for a blink of about 2 Hz.
+pub const BLINK_PHASE: Duration = Duration::from_millis(250);
+
+/// How the screen looks while it flashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlashStyle {
+    /// Every cell's colors swapped.
+    #[default]
+    Inverted,
+    /// Everything painted over in one color.
+    Solid(Color),
+}
+
+impl FlashStyle {
+    /// The style laid over the whole frame.
+    pub fn style(self) -> Style {
+        match self {
+            Self::Inverted => Style::new().reversed(),
+            Self::Solid(color) => Style::new().fg(color).bg(color),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Flash {
+    style: FlashStyle,
+    started: Instant,
+    /// When a flash of fixed length ends. Without one it blinks until stopped.
+    until: Option<Instant>,
+}
+
+/// The whole-screen flash that alarms, the chime, and expired timers share. Only one flash runs
+/// at a time; starting another replaces it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FlashState {
+    flash: Option<Flash>,
+}
+
+impl FlashState {
+    /// Flashes the screen steadily for `duration` from `now`, or, without one, blinks it until
+    /// [`FlashState::stop`] is called.
+    pub fn start(&mut self, duration: Option<Duration>, style: FlashStyle, now: Instant) {
+        self.flash = Some(Flash {
+            style,
+            started: now,
+            until: duration.map(|duration| now + duration),
+        });
+    }
+
+    pub fn stop(&mut self) {
+        self.flash = None;
+    }
+
+    /// Forgets a flash that has run its length.
+    pub fn update(&mut self, now: Instant) {
+        if self
+            .flash
+            .is_some_and(|flash| flash.until.is_some_and(|until| now >= until))
+        {
+            self.flash = None;
+        }
+    }
+
+    /// Whether a blinking flash is running, as opposed to one of fixed length or none.
+    pub fn is_blinking(&self) -> bool {
+        self.flash.is_some_and(|flash| flash.until.is_none())
+    }
+
+    /// The style to draw the frame in at `now`, if it's flashed right then. A blinking flash is
+    /// only on for the first half of each blink.
+    pub fn active(&self, now: Instant) -> Option<FlashStyle> {
+        let flash = self.flash?;
+        let on = match flash.until {
+            Some(until) => now < until,
+            None => phases(flash.started, now).is_multiple_of(2),
+        };
+        on.then_some(flash.style)
+    }
+
+    /// When the flash next turns on or off, so a frame can be drawn right then.
+    pub fn next_frame(&self, now: Instant) -> Option<Instant> {
+        let flash = self.flash?;
+        match flash.until {
+            Some(until) => (now < until).then_some(until),
+            None => {
+                let next = phases(flash.started, now) + 1;
+                Some(flash.started + BLINK_PHASE * next as u32)
+            },
+        }
+    }
+}
+
+/// How many whole blink phases have passed since `started`.
+pub fn phases(started: Instant, now: Instant) -> u128 {
+    now.saturating_duration_since(started).as_nanos() / BLINK_PHASE.as_nanos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_flashes_end_on_their_own() {
+        let start = Instant::now();
+        let mut flash = FlashState::default();
+        flash.start(
+            Some(Duration::from_secs(1)),
+            FlashStyle::Solid(Color::Red),
+            start,
+        );
+        assert_eq!(flash.active(start), Some(FlashStyle::Solid(Color::Red)));
+        assert_eq!(
+            flash.next_frame(start),
+            Some(start + Duration::from_secs(1))
+        );
+
+        let end = start + Duration::from_secs(1);
+        assert_eq!(flash.active(end), None);
+        flash.update(end);
+        assert_eq!(flash, FlashState::default());
+    }
+
+    #[test]
+    fn blinking_flashes_last_until_stopped() {
+        let start = Instant::now();
+        let mut flash = FlashState::default();
+        flash.start(None, FlashStyle::Inverted, start);
+        assert!(flash.is_blinking());
+        assert_eq!(flash.active(start), Some(FlashStyle::Inverted));
+        assert_eq!(flash.active(start + BLINK_PHASE), None);
+        assert_eq!(
+            flash.active(start + BLINK_PHASE * 2),
+            Some(FlashStyle::Inverted)
+        );
+        assert_eq!(
+            flash.next_frame(start + BLINK_PHASE * 3 / 2),
+            Some(start + BLINK_PHASE * 2)
+        );
+
+        flash.update(start + Duration::from_secs(3600));
+        assert!(flash.is_blinking());
+        flash.stop();
+        assert_eq!(flash.active(start), None);
+    }
+}
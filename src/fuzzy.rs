@@ -0,0 +1,146 @@
+//! `--fuzzy`: a relaxed clock, rounded to the nearest few minutes before it's formatted, and with
+//! `--fuzzy-words` spelled out like "quarter past ten".
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeDelta, Timelike};
+use clap::ValueEnum;
+
+/// How often the rounded time can change. Rounding to the nearest minute moves on halfway
+/// through one, so however many minutes it rounds to, that's on the half minute.
+pub const INTERVAL: Duration = Duration::from_secs(30);
+
+/// What `--fuzzy` rounds to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Granularity {
+    #[value(name = "1")]
+    One,
+    #[default]
+    #[value(name = "5")]
+    Five,
+    #[value(name = "15")]
+    Fifteen,
+}
+
+impl Granularity {
+    pub fn minutes(self) -> u32 {
+        match self {
+            Self::One => 1,
+            Self::Five => 5,
+            Self::Fifteen => 15,
+        }
+    }
+}
+
+/// `local` rounded to the nearest multiple of `granularity` minutes past the hour, halfway
+/// rounding up, like 10:02:30 to 10:05 and 10:57:30 to 11:00.
+pub fn round(local: DateTime<Local>, granularity: Granularity) -> DateTime<Local> {
+    let step = i64::from(granularity.minutes()) * 60;
+    let into_hour = i64::from(local.minute() * 60 + local.second());
+    let rounded = (into_hour + step / 2) / step * step;
+    let hour =
+        local - TimeDelta::seconds(into_hour) - TimeDelta::nanoseconds(local.nanosecond().into());
+    hour + TimeDelta::seconds(rounded)
+}
+
+const NUMBERS: [&str; 30] = [
+    "",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+    "twenty",
+    "twenty-one",
+    "twenty-two",
+    "twenty-three",
+    "twenty-four",
+    "twenty-five",
+    "twenty-six",
+    "twenty-seven",
+    "twenty-eight",
+    "twenty-nine",
+];
+
+/// The time of day at `local`, already rounded, in words: "ten o'clock", "five past ten",
+/// "quarter past ten", "half past ten", "twenty to eleven". The hours are a 12-hour clock's
+/// either way, with noon and midnight named on the hour.
+pub fn words(local: DateTime<Local>) -> String {
+    let (minute, hour) = match local.minute() {
+        minute @ 0..=30 => (minute, local.hour()),
+        minute => (60 - minute, (local.hour() + 1) % 24),
+    };
+    let hour_word = match hour % 12 {
+        0 => "twelve",
+        hour => NUMBERS[hour as usize],
+    };
+    let past = local.minute() <= 30;
+    let relation = if past { "past" } else { "to" };
+    match minute {
+        0 if hour == 0 => "midnight".to_owned(),
+        0 if hour == 12 => "noon".to_owned(),
+        0 => format!("{hour_word} o'clock"),
+        15 => format!("quarter {relation} {hour_word}"),
+        30 => format!("half past {hour_word}"),
+        minute => format!("{} {relation} {hour_word}", NUMBERS[minute as usize]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 1, h, m, s).unwrap()
+    }
+
+    #[test]
+    fn rounds_to_the_nearest_few_minutes() {
+        let round = |h, m, s, granularity| {
+            round(at(h, m, s), granularity)
+                .format("%H:%M:%S")
+                .to_string()
+        };
+        assert_eq!(round(10, 2, 29, Granularity::Five), "10:00:00");
+        assert_eq!(round(10, 2, 30, Granularity::Five), "10:05:00");
+        assert_eq!(round(10, 57, 30, Granularity::Five), "11:00:00");
+        assert_eq!(round(10, 7, 29, Granularity::Fifteen), "10:00:00");
+        assert_eq!(round(10, 7, 30, Granularity::Fifteen), "10:15:00");
+        assert_eq!(round(10, 44, 59, Granularity::One), "10:45:00");
+        // Over midnight into the next day
+        assert_eq!(round(23, 59, 45, Granularity::One), "00:00:00");
+    }
+
+    #[test]
+    fn spells_out_the_time() {
+        let words = |h, m| words(at(h, m, 0));
+        assert_eq!(words(10, 0), "ten o'clock");
+        assert_eq!(words(10, 5), "five past ten");
+        assert_eq!(words(10, 15), "quarter past ten");
+        assert_eq!(words(22, 30), "half past ten");
+        assert_eq!(words(10, 40), "twenty to eleven");
+        assert_eq!(words(10, 45), "quarter to eleven");
+        assert_eq!(words(10, 37), "twenty-three to eleven");
+        assert_eq!(words(0, 0), "midnight");
+        assert_eq!(words(23, 55), "five to twelve");
+        assert_eq!(words(12, 0), "noon");
+        assert_eq!(words(12, 10), "ten past twelve");
+        assert_eq!(words(13, 0), "one o'clock");
+    }
+}
@@ -0,0 +1,194 @@
+//! `--grid`: several clocks at once, each in a cell of its own with a label under it. The cells
+//! come from the config's `[[cell]]` entries: the time here or in another zone, a countdown, or
+//! a stopwatch.
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::bail;
+use serde::Deserialize;
+
+use crate::{
+    countdown::Countdown,
+    duration::parse_duration,
+    stopwatch::Stopwatch,
+    zone::{Comparison, Zone},
+};
+
+/// The shape `--grid` lays the cells out in, like `3x2` for three columns of two rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grid {
+    pub columns: u16,
+    pub rows: u16,
+}
+
+impl Grid {
+    /// How many cells the grid has room for.
+    pub fn capacity(self) -> usize {
+        usize::from(self.columns) * usize::from(self.rows)
+    }
+}
+
+impl FromStr for Grid {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let parsed = s
+            .split_once(['x', 'X'])
+            .and_then(|(columns, rows)| Some((columns.parse().ok()?, rows.parse().ok()?)));
+        match parsed {
+            Some((columns @ 1..=8, rows @ 1..=8)) => Ok(Self { columns, rows }),
+            _ => bail!("invalid grid {s:?} (expected COLUMNSxROWS, each 1 to 8, e.g. 2x2)"),
+        }
+    }
+}
+
+/// What a `[[cell]]` shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CellMode {
+    #[default]
+    Clock,
+    Timer,
+    Stopwatch,
+}
+
+/// A `[[cell]]` entry as written in the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CellConfig {
+    pub mode: CellMode,
+    /// For a clock, the zone to show the time in, rather than the local one.
+    pub zone: Option<String>,
+    /// Shown under the cell. A clock's zone or a timer's duration unless set.
+    pub label: Option<String>,
+    /// For a timer, how long it counts down from, like `25m`.
+    pub duration: Option<String>,
+}
+
+impl CellConfig {
+    /// The cell, with a timer or stopwatch started at `now`.
+    pub fn cell(&self, now: Instant) -> anyhow::Result<Cell> {
+        if self.zone.is_some() && self.mode != CellMode::Clock {
+            bail!("only a clock has a zone");
+        }
+        if self.duration.is_some() && self.mode != CellMode::Timer {
+            bail!("only a timer has a duration");
+        }
+        let (kind, label) = match self.mode {
+            CellMode::Clock => match &self.zone {
+                Some(zone) => {
+                    let Comparison { label, zone } = zone.parse()?;
+                    (CellKind::Clock(Some(zone)), label)
+                },
+                None => (CellKind::Clock(None), "Local".to_owned()),
+            },
+            CellMode::Timer => {
+                let Some(duration) = &self.duration else {
+                    bail!("a timer needs a duration");
+                };
+                let countdown = Countdown::start(parse_duration(duration)?, now);
+                (CellKind::Timer(countdown), format!("{duration} timer"))
+            },
+            CellMode::Stopwatch => (
+                CellKind::Stopwatch(Stopwatch::start(now)),
+                "Stopwatch".to_owned(),
+            ),
+        };
+        Ok(Cell {
+            label: self.label.clone().unwrap_or(label),
+            kind,
+        })
+    }
+}
+
+/// One of the grid's clocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    pub label: String,
+    pub kind: CellKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellKind {
+    /// The time in the zone, or the local time without one.
+    Clock(Option<Zone>),
+    Timer(Countdown),
+    Stopwatch(Stopwatch),
+}
+
+impl Cell {
+    /// Catches a timer up after the machine was suspended for `slept`, and a stopwatch if
+    /// `stopwatch_counts` it, the same as the main clock's.
+    pub fn slept(&mut self, slept: Duration, stopwatch_counts: bool) {
+        match &mut self.kind {
+            CellKind::Timer(countdown) => countdown.slept(slept),
+            CellKind::Stopwatch(stopwatch) if stopwatch_counts => stopwatch.slept(slept),
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_grid_shape() {
+        assert_eq!(
+            "2x2".parse::<Grid>().unwrap(),
+            Grid {
+                columns: 2,
+                rows: 2
+            }
+        );
+        assert_eq!("3X1".parse::<Grid>().unwrap().capacity(), 3);
+        for bad in ["", "2", "0x2", "2x9", "2x2x2", "twoxtwo", "-1x2"] {
+            assert!(bad.parse::<Grid>().is_err(), "{bad:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn cells_are_labeled_and_started() {
+        let now = Instant::now();
+        let cell = |config: CellConfig| config.cell(now);
+        let utc = cell(CellConfig {
+            zone: Some("UTC".to_owned()),
+            ..CellConfig::default()
+        })
+        .unwrap();
+        assert_eq!(utc.label, "UTC");
+        assert!(matches!(utc.kind, CellKind::Clock(Some(_))));
+        assert_eq!(cell(CellConfig::default()).unwrap().label, "Local");
+
+        let tea = cell(CellConfig {
+            mode: CellMode::Timer,
+            duration: Some("3m".to_owned()),
+            label: Some("Tea".to_owned()),
+            ..CellConfig::default()
+        })
+        .unwrap();
+        assert_eq!(tea.label, "Tea");
+        assert_eq!(
+            tea.kind,
+            CellKind::Timer(Countdown::start(Duration::from_secs(180), now))
+        );
+
+        for (mode, zone, duration) in [
+            (CellMode::Timer, None, None),
+            (CellMode::Stopwatch, Some("UTC"), None),
+            (CellMode::Clock, None, Some("3m")),
+            (CellMode::Timer, None, Some("soon")),
+        ] {
+            let config = CellConfig {
+                mode,
+                zone: zone.map(str::to_owned),
+                duration: duration.map(str::to_owned),
+                ..CellConfig::default()
+            };
+            assert!(cell(config).is_err(), "{mode:?} {zone:?} {duration:?}");
+        }
+    }
+}
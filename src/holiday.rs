@@ -0,0 +1,366 @@
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{Context, bail};
+use chrono::{Datelike, Days, Month, NaiveDate, Weekday};
+
+/// When a holiday falls, as a `--holidays` file writes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// The same day every year, like `12-25`.
+    Yearly { month: u32, day: u32 },
+    /// One day only, like `2024-06-03`.
+    Once(NaiveDate),
+    /// A weekday's nth occurrence in a month, like `last-monday-of-may`. `nth` counts from 1, or
+    /// is -1 for the last.
+    NthWeekday {
+        nth: i8,
+        weekday: Weekday,
+        month: u32,
+    },
+    /// The first of a weekday on or after a day of a month, like `monday-on-or-after-may-24`.
+    WeekdayOnOrAfter {
+        weekday: Weekday,
+        month: u32,
+        day: u32,
+    },
+    /// Some days from Easter Sunday, like `easter`, `easter-2`, or `easter+1`.
+    Easter { offset: i64 },
+}
+
+impl Rule {
+    /// The day the holiday falls on in `year`, if it falls in it at all.
+    pub fn date_in(self, year: i32) -> Option<NaiveDate> {
+        match self {
+            Self::Yearly { month, day } => NaiveDate::from_ymd_opt(year, month, day),
+            Self::Once(date) => (date.year() == year).then_some(date),
+            Self::NthWeekday {
+                nth: -1,
+                weekday,
+                month,
+            } => {
+                let next_month = match month {
+                    12 => NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+                    _ => NaiveDate::from_ymd_opt(year, month + 1, 1)?,
+                };
+                let last = next_month.pred_opt()?;
+                let back = (last.weekday().num_days_from_monday() + 7
+                    - weekday.num_days_from_monday())
+                    % 7;
+                last.checked_sub_days(Days::new(back.into()))
+            },
+            Self::NthWeekday {
+                nth,
+                weekday,
+                month,
+            } => {
+                let nth = u8::try_from(nth).ok()?;
+                NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth)
+            },
+            Self::WeekdayOnOrAfter {
+                weekday,
+                month,
+                day,
+            } => {
+                let from = NaiveDate::from_ymd_opt(year, month, day)?;
+                let ahead = (weekday.num_days_from_monday() + 7
+                    - from.weekday().num_days_from_monday())
+                    % 7;
+                from.checked_add_days(Days::new(ahead.into()))
+            },
+            Self::Easter { offset } => {
+                let easter = easter_sunday(year)?;
+                match u64::try_from(offset) {
+                    Ok(ahead) => easter.checked_add_days(Days::new(ahead)),
+                    Err(_) => easter.checked_sub_days(Days::new(offset.unsigned_abs())),
+                }
+            },
+        }
+    }
+
+    pub fn falls_on(self, date: NaiveDate) -> bool {
+        self.date_in(date.year()) == Some(date)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(offset) = s.strip_prefix("easter") {
+            let offset = match offset {
+                "" => 0,
+                offset if offset.starts_with(['+', '-']) => offset
+                    .parse()
+                    .with_context(|| format!("invalid offset in {s:?}"))?,
+                _ => bail!("expected easter, easter+N, or easter-N, found {s:?}"),
+            };
+            return Ok(Self::Easter { offset });
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+            return Ok(Self::Once(date));
+        }
+        // Any year will do to check the day exists, as long as it has a 29th of February
+        if let Ok(date) = NaiveDate::parse_from_str(&format!("2000-{s}"), "%Y-%m-%d") {
+            return Ok(Self::Yearly {
+                month: date.month(),
+                day: date.day(),
+            });
+        }
+
+        let parts: Vec<&str> = s.split('-').collect();
+        match parts[..] {
+            [nth, weekday, "of", month] => {
+                let nth = match nth {
+                    "first" => 1,
+                    "second" => 2,
+                    "third" => 3,
+                    "fourth" => 4,
+                    "fifth" => 5,
+                    "last" => -1,
+                    _ => bail!("unknown {nth:?} in {s:?} (expected first to fifth, or last)"),
+                };
+                Ok(Self::NthWeekday {
+                    nth,
+                    weekday: parse_weekday(weekday, &s)?,
+                    month: parse_month(month, &s)?,
+                })
+            },
+            [weekday, "on", "or", "after", month, day] => {
+                let month = parse_month(month, &s)?;
+                let day = day
+                    .parse()
+                    .ok()
+                    .filter(|&day| NaiveDate::from_ymd_opt(2000, month, day).is_some())
+                    .with_context(|| format!("invalid day {day:?} in {s:?}"))?;
+                Ok(Self::WeekdayOnOrAfter {
+                    weekday: parse_weekday(weekday, &s)?,
+                    month,
+                    day,
+                })
+            },
+            _ => bail!(
+                "unknown date {s:?} (expected e.g. 12-25, 2024-06-03, last-monday-of-may, \
+                 monday-on-or-after-may-24, or easter+1)"
+            ),
+        }
+    }
+}
+
+fn parse_weekday(name: &str, rule: &str) -> anyhow::Result<Weekday> {
+    name.parse()
+        .ok()
+        .with_context(|| format!("unknown weekday {name:?} in {rule:?}"))
+}
+
+fn parse_month(name: &str, rule: &str) -> anyhow::Result<u32> {
+    name.parse::<Month>()
+        .ok()
+        .map(|month| month.number_from_month())
+        .with_context(|| format!("unknown month {name:?} in {rule:?}"))
+}
+
+/// Easter Sunday in the Gregorian calendar, by the anonymous Gregorian algorithm.
+pub fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let (a, b, c) = (
+        year.rem_euclid(19),
+        year.div_euclid(100),
+        year.rem_euclid(100),
+    );
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+/// The holidays in a `--holidays` file: TOML with a line per holiday like
+/// `last-monday-of-may = "Spring Bank Holiday"`. Entries that can't be read are skipped with a
+/// warning rather than losing the rest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Holidays {
+    holidays: Vec<(Rule, String)>,
+}
+
+impl Holidays {
+    /// The holidays in the file at `path`, and warnings about anything in it that was skipped.
+    /// A file that can't be read or isn't TOML at all is one warning and no holidays.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        let read = fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))
+            .and_then(|text| Self::parse(&text));
+        match read {
+            Ok((holidays, warnings)) => (
+                holidays,
+                warnings
+                    .into_iter()
+                    .map(|warning| format!("in {}: {warning}", path.display()))
+                    .collect(),
+            ),
+            Err(err) => (
+                Self::default(),
+                vec![format!("holidays not loaded: {err:#}")],
+            ),
+        }
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<(Self, Vec<String>)> {
+        let table: toml::Table = toml::from_str(text)?;
+        let mut holidays = Vec::new();
+        let mut warnings = Vec::new();
+        for (date, name) in table {
+            let Some(name) = name.as_str() else {
+                warnings.push(format!("{date}'s name isn't a string, so it's skipped"));
+                continue;
+            };
+            match date.parse() {
+                Ok(rule) => holidays.push((rule, name.to_owned())),
+                Err(err) => warnings.push(format!("{err:#}, so {name:?} is skipped")),
+            }
+        }
+        Ok((Self { holidays }, warnings))
+    }
+
+    /// The names of the holidays on `date`, joined if there are several.
+    pub fn on(&self, date: NaiveDate) -> Option<String> {
+        let names: Vec<&str> = self
+            .holidays
+            .iter()
+            .filter(|(rule, _)| rule.falls_on(date))
+            .map(|(_, name)| name.as_str())
+            .collect();
+        (!names.is_empty()).then(|| names.join(", "))
+    }
+
+    /// The first holiday after `date` and no more than `within` days after it, with how many
+    /// days away it is.
+    pub fn upcoming(&self, date: NaiveDate, within: u32) -> Option<(String, u32)> {
+        (1..=within).find_map(|days| {
+            let day = date.checked_add_days(Days::new(days.into()))?;
+            self.on(day).map(|names| (names, days))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn rule(s: &str) -> Rule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn evaluates_each_kind_of_rule() {
+        assert_eq!(rule("12-25").date_in(2024), Some(date(2024, 12, 25)));
+        assert_eq!(rule("02-29").date_in(2023), None);
+        assert_eq!(rule("2024-06-03").date_in(2024), Some(date(2024, 6, 3)));
+        assert_eq!(rule("2024-06-03").date_in(2025), None);
+
+        assert_eq!(
+            rule("last-monday-of-may").date_in(2024),
+            Some(date(2024, 5, 27))
+        );
+        assert_eq!(
+            rule("last-friday-of-december").date_in(2021),
+            Some(date(2021, 12, 31))
+        );
+        assert_eq!(
+            rule("first-monday-of-may").date_in(2024),
+            Some(date(2024, 5, 6))
+        );
+        assert_eq!(
+            rule("fourth-thursday-of-november").date_in(2024),
+            Some(date(2024, 11, 28))
+        );
+        // Not every month has a fifth of every weekday
+        assert_eq!(rule("fifth-monday-of-february").date_in(2024), None);
+
+        // Canada's Victoria Day is the Monday before the 25th of May
+        assert_eq!(
+            rule("monday-on-or-after-may-18").date_in(2024),
+            Some(date(2024, 5, 20))
+        );
+        assert_eq!(
+            rule("monday-on-or-after-may-20").date_in(2024),
+            Some(date(2024, 5, 20))
+        );
+
+        assert_eq!(easter_sunday(2024), Some(date(2024, 3, 31)));
+        assert_eq!(easter_sunday(2025), Some(date(2025, 4, 20)));
+        assert_eq!(easter_sunday(2038), Some(date(2038, 4, 25)));
+        assert_eq!(rule("easter-2").date_in(2024), Some(date(2024, 3, 29)));
+        assert_eq!(rule("Easter+1").date_in(2024), Some(date(2024, 4, 1)));
+    }
+
+    #[test]
+    fn rejects_rules_it_cannot_read() {
+        for s in [
+            "",
+            "13-01",
+            "02-30",
+            "last-monday-of-maytime",
+            "sixth-monday-of-may",
+            "first-someday-of-may",
+            "monday-on-or-after-may-32",
+            "easter2",
+            "easter+x",
+            "christmas",
+        ] {
+            assert!(s.parse::<Rule>().is_err(), "{s:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn skips_bad_entries_with_a_warning() {
+        let (holidays, warnings) = Holidays::parse(
+            r#"
+            12-25 = "Christmas Day"
+            12-26 = "Boxing Day"
+            last-monday-of-may = "Spring Bank Holiday"
+            2024-05-27 = "Team day"
+            someday = "Never"
+            01-01 = 1
+            "#,
+        )
+        .unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(
+            warnings
+                .iter()
+                .any(|warning| warning.contains("\"someday\"")),
+            "{warnings:?}"
+        );
+
+        assert_eq!(
+            holidays.on(date(2024, 12, 25)).as_deref(),
+            Some("Christmas Day")
+        );
+        assert_eq!(
+            holidays.on(date(2024, 5, 27)).as_deref(),
+            Some("Team day, Spring Bank Holiday")
+        );
+        assert_eq!(holidays.on(date(2024, 5, 28)), None);
+        assert_eq!(
+            holidays.upcoming(date(2024, 12, 22), 3),
+            Some(("Christmas Day".to_owned(), 3))
+        );
+        assert_eq!(holidays.upcoming(date(2024, 12, 22), 2), None);
+        // Today doesn't count as upcoming
+        assert_eq!(
+            holidays.upcoming(date(2024, 12, 25), 1),
+            Some(("Boxing Day".to_owned(), 1))
+        );
+
+        assert!(Holidays::parse("12-25 = ").is_err());
+    }
+}
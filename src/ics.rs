@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::{duration::format_until, zone::Zone};
+
+/// One VEVENT from an iCalendar file. Recurrence rules are ignored, so a recurring event only
+/// counts at its first occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    /// Whether the event lasts the whole day, starting at local midnight.
+    pub all_day: bool,
+}
+
+/// The events in an `.ics` file, sorted by start.
+pub fn load(path: &Path) -> anyhow::Result<Vec<Event>> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    parse(&text).with_context(|| format!("in {}", path.display()))
+}
+
+pub fn parse(text: &str) -> anyhow::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    let mut zones = HashMap::new();
+    // The VEVENT being read, if inside one
+    let mut current: Option<Draft> = None;
+    for (number, line) in unfold(text).iter().enumerate() {
+        let context = || format!("on line {}", number + 1);
+        let ContentLine {
+            name,
+            params,
+            value,
+        } = ContentLine::parse(line).with_context(context)?;
+        match (name.to_ascii_uppercase().as_str(), &mut current) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(Draft::default());
+            },
+            ("END", Some(draft)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some((start, all_day)) = draft.start else {
+                    bail!("event {:?} has no DTSTART ({})", draft.summary, context());
+                };
+                events.push(Event {
+                    summary: draft
+                        .summary
+                        .take()
+                        .unwrap_or_else(|| "(untitled)".to_owned()),
+                    start,
+                    all_day,
+                });
+                current = None;
+            },
+            ("SUMMARY", Some(draft)) => draft.summary = Some(unescape(value)),
+            ("DTSTART", Some(draft)) => {
+                draft.start = Some(
+                    parse_start(&params, value, &mut zones)
+                        .with_context(|| format!("invalid DTSTART {value:?} ({})", context()))?,
+                );
+            },
+            _ => {},
+        }
+    }
+    if let Some(draft) = current {
+        bail!("event {:?} is missing its END:VEVENT", draft.summary);
+    }
+    events.sort_by_key(|event| event.start);
+    Ok(events)
+}
+
+/// An event's fields, as far as they've been read.
+#[derive(Default)]
+struct Draft {
+    summary: Option<String>,
+    /// And whether it's a whole day.
+    start: Option<(DateTime<Utc>, bool)>,
+}
+
+/// The logical lines of `text`, with folded continuation lines (starting with a space or tab)
+/// joined back on and CRLF or LF endings alike.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ if line.is_empty() => {},
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// A line like `NAME;PARAM=VALUE:value`.
+struct ContentLine<'a> {
+    name: &'a str,
+    params: Vec<(&'a str, &'a str)>,
+    value: &'a str,
+}
+
+impl<'a> ContentLine<'a> {
+    /// Parameter values may be quoted, hiding any `:` or `;` in them.
+    fn parse(line: &'a str) -> anyhow::Result<Self> {
+        let mut quoted = false;
+        let mut parts = Vec::new();
+        let mut part_start = 0;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' | ':' if !quoted => {
+                    parts.push(&line[part_start..i]);
+                    part_start = i + 1;
+                    if c == ':' {
+                        let (name, params) = parts.split_first().context("line has no name")?;
+                        let params = params
+                            .iter()
+                            .map(|param| {
+                                let (key, value) = param.split_once('=').unwrap_or((param, ""));
+                                (key, value.trim_matches('"'))
+                            })
+                            .collect();
+                        return Ok(Self {
+                            name,
+                            params,
+                            value: &line[part_start..],
+                        });
+                    }
+                },
+                _ => {},
+            }
+        }
+        bail!("expected NAME:value, found {line:?}")
+    }
+}
+
+/// A DTSTART's instant, and whether it's a whole day. Times are UTC (`Z`), in a `TZID`, or
+/// floating in local time, as are whole days.
+fn parse_start(
+    params: &[(&str, &str)],
+    value: &str,
+    zones: &mut HashMap<String, Option<Zone>>,
+) -> anyhow::Result<(DateTime<Utc>, bool)> {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    };
+    if param("VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d")?;
+        return Ok((local_to_utc(date.and_time(Default::default()))?, true));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")?;
+        return Ok((time.and_utc(), false));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")?;
+    // Zones the tz database doesn't know (like Windows names) are taken as local time
+    let zone = param("TZID").and_then(|tzid| {
+        zones
+            .entry(tzid.to_owned())
+            .or_insert_with(|| Zone::load(tzid).ok())
+            .as_ref()
+    });
+    match zone {
+        Some(zone) => Ok((zone.to_utc(time), false)),
+        None => Ok((local_to_utc(time)?, false)),
+    }
+}
+
+fn local_to_utc(time: NaiveDateTime) -> anyhow::Result<DateTime<Utc>> {
+    let local = Local
+        .from_local_datetime(&time)
+        .earliest()
+        .with_context(|| format!("{time} doesn't exist in the local time zone"))?;
+    Ok(local.to_utc())
+}
+
+/// Undoes the backslash escapes in a TEXT value.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => out.push(' '),
+                Some(escaped) => out.push(escaped),
+                None => {},
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+/// The `--ics` calendar, read again whenever the file changes. A file that can't be read or
+/// parsed is kept as its error, to show in place of the next event.
+#[derive(Debug)]
+pub struct Agenda {
+    path: PathBuf,
+    events: Result<Vec<Event>, String>,
+}
+
+impl Agenda {
+    pub fn load(path: PathBuf) -> Self {
+        let mut agenda = Self {
+            path,
+            events: Ok(Vec::new()),
+        };
+        agenda.reload();
+        agenda
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn reload(&mut self) {
+        self.events = load(&self.path).map_err(|err| format!("{err:#}"));
+    }
+
+    /// The first event that hasn't started by `now`.
+    pub fn next(&self, now: DateTime<Utc>) -> Option<&Event> {
+        let events = self.events.as_ref().ok()?;
+        events.iter().find(|event| event.start > now)
+    }
+
+    /// The line under the clock, e.g. `Standup in 23m`, and whether it's an error instead.
+    pub fn line(&self, now: DateTime<Utc>) -> Option<(String, bool)> {
+        if let Err(err) = &self.events {
+            // Only the first line, since errors can quote whole chunks of the file
+            let err = err.lines().next().unwrap_or_default();
+            return Some((format!("⚠ calendar: {err}"), true));
+        }
+        let event = self.next(now)?;
+        let until = (event.start - now).to_std().unwrap_or_default();
+        let when = if until < Duration::from_secs(24 * 60 * 60) {
+            format!("in {}", format_until(until))
+        } else if event.all_day {
+            event
+                .start
+                .with_timezone(&Local)
+                .format("on %a %-d %b")
+                .to_string()
+        } else {
+            let start = event.start.with_timezone(&Local);
+            start.format("on %a %-d %b at %H:%M").to_string()
+        };
+        Some((format!("{} {when}", event.summary), false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+        VERSION:2.0\r\n\
+        BEGIN:VEVENT\r\n\
+        SUMMARY:Retro\\, then\r\n  lunch\r\n\
+        DTSTART;TZID=UTC:20240301T140000\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        SUMMARY:Standup\r\n\
+        DTSTART:20240301T093000Z\r\n\
+        DTEND:20240301T094500Z\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART;VALUE=DATE:20240305\r\n\
+        SUMMARY:Holiday\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+    #[test]
+    fn parses_utc_zoned_and_all_day_events() {
+        let events = parse(CALENDAR).unwrap();
+        let holiday = Local
+            .with_ymd_and_hms(2024, 3, 5, 0, 0, 0)
+            .unwrap()
+            .to_utc();
+        assert_eq!(
+            events,
+            [
+                Event {
+                    summary: "Standup".to_owned(),
+                    start: utc(2024, 3, 1, 9, 30),
+                    all_day: false,
+                },
+                Event {
+                    summary: "Retro, then lunch".to_owned(),
+                    start: utc(2024, 3, 1, 14, 0),
+                    all_day: false,
+                },
+                Event {
+                    summary: "Holiday".to_owned(),
+                    start: holiday,
+                    all_day: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rolls_to_the_next_event_once_one_starts() {
+        let path = std::env::temp_dir().join(format!("tui-time-{}.ics", std::process::id()));
+        fs::write(&path, CALENDAR).unwrap();
+        let agenda = Agenda::load(path.clone());
+        fs::remove_file(&path).unwrap();
+
+        let line = |now| agenda.line(now).map(|(text, _)| text);
+        assert_eq!(
+            line(utc(2024, 3, 1, 9, 6)).as_deref(),
+            Some("Standup in 24m")
+        );
+        assert_eq!(
+            line(utc(2024, 3, 1, 9, 30)).as_deref(),
+            Some("Retro, then lunch in 4h 30m")
+        );
+        assert_eq!(line(utc(2024, 3, 9, 0, 0)), None);
+    }
+
+    #[test]
+    fn malformed_files_become_an_error_line() {
+        for (text, expected) in [
+            ("BEGIN:VEVENT\nSUMMARY:Oops\n", "missing its END:VEVENT"),
+            (
+                "BEGIN:VEVENT\nDTSTART:tomorrow\nEND:VEVENT\n",
+                "invalid DTSTART",
+            ),
+            ("BEGIN:VEVENT\nSUMMARY:Oops\nEND:VEVENT\n", "has no DTSTART"),
+            ("no colon here\n", "expected NAME:value"),
+        ] {
+            let err = format!("{:#}", parse(text).unwrap_err());
+            assert!(err.contains(expected), "{err:?} for {text:?}");
+        }
+
+        let agenda = Agenda {
+            path: PathBuf::from("cal.ics"),
+            events: Err("in cal.ics: on line 3\nmore detail".to_owned()),
+        };
+        assert_eq!(
+            agenda.line(utc(2024, 3, 1, 0, 0)),
+            Some(("⚠ calendar: in cal.ics: on line 3".to_owned(), true))
+        );
+    }
+}
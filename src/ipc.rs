@@ -0,0 +1,185 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::UnixStream as StdUnixStream,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader as AsyncBufReader},
+    net::{UnixListener, UnixStream},
+    sync::mpsc::UnboundedSender,
+};
+
+use crate::{
+    app::{Action, validate_format},
+    duration::parse_duration,
+    keys::action_by_name,
+};
+
+/// Where the control socket lives: `$XDG_RUNTIME_DIR/tui-time.sock`. There's no fallback when the
+/// runtime dir is unset, since a shared directory like `/tmp` would let another user take the path
+/// first.
+pub fn socket_path() -> anyhow::Result<PathBuf> {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => Ok(Path::new(&dir).join("tui-time.sock")),
+        _ => {
+            bail!("$XDG_RUNTIME_DIR isn't set, so there's no private place for the control socket")
+        },
+    }
+}
+
+/// Parses one line of the control protocol into an [`Action`].
+pub fn parse_command(line: &str) -> anyhow::Result<Action> {
+    let line = line.trim();
+    let (command, arg) = match line.split_once(char::is_whitespace) {
+        Some((command, arg)) => (command, Some(arg.trim())),
+        None => (line, None),
+    };
+
+    if let Some(action) = action_by_name(command) {
+        return match arg {
+            None => Ok(action),
+            Some(_) => bail!("{command} takes no argument"),
+        };
+    }
+
+    match (command, arg) {
+        ("set-format", Some(format)) => {
+            validate_format(format)?;
+            Ok(Action::SetFormat(format.to_owned()))
+        },
+        ("set-text", Some(text)) => Ok(Action::SetText(text.to_owned())),
+        ("start-timer", Some(duration)) => Ok(Action::StartTimer(parse_duration(duration)?)),
+        ("set-format" | "set-text" | "start-timer", None) => bail!("{command} needs an argument"),
+        ("", _) => bail!("empty command"),
+        _ => bail!("unknown command {command:?}"),
+    }
+}
+
+/// A bound control socket. The socket file is removed when this is dropped.
+pub struct ControlSocket {
+    path: PathBuf,
+    listener: Option<UnixListener>,
+}
+
+impl ControlSocket {
+    /// Binds the socket at `path`, replacing a stale socket left behind by a crashed instance but
+    /// refusing to steal one that another instance is still serving.
+    pub fn bind(path: PathBuf) -> anyhow::Result<Self> {
+        if path.exists() {
+            match StdUnixStream::connect(&path) {
+                Ok(_) => bail!(
+                    "another tui-time instance is listening on {}",
+                    path.display()
+                ),
+                Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                    fs::remove_file(&path)
+                        .with_context(|| format!("removing stale socket {}", path.display()))?;
+                },
+                Err(err) => {
+                    return Err(err).with_context(|| format!("probing {}", path.display()));
+                },
+            }
+        }
+
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("binding control socket {}", path.display()))?;
+
+        Ok(Self {
+            path,
+            listener: Some(listener),
+        })
+    }
+
+    /// Spawns the accept loop on the current runtime, forwarding parsed commands to `tx`.
+    pub fn serve(&mut self, tx: UnboundedSender<Action>) {
+        let Some(listener) = self.listener.take() else {
+            return;
+        };
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream, tx.clone()));
+            }
+        });
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Answers each line with `ok` or `error: <reason>`. A bad command never ends the connection,
+/// let alone the server.
+async fn handle_connection(stream: UnixStream, tx: UnboundedSender<Action>) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = AsyncBufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let reply = match parse_command(&line).map(|action| tx.send(action)) {
+            Ok(Ok(())) => "ok\n".to_owned(),
+            Ok(Err(_)) => "error: clock is shutting down\n".to_owned(),
+            Err(err) => format!("error: {err:#}\n"),
+        };
+        writer.write_all(reply.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// The `tui-time ctl` client: sends one command and prints the reply.
+pub fn send_command(path: &Path, command: &str) -> anyhow::Result<()> {
+    let mut stream = StdUnixStream::connect(path)
+        .with_context(|| format!("connecting to {} (is tui-time running?)", path.display()))?;
+    writeln!(stream, "{command}").context("sending command")?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .context("reading reply")?;
+
+    match reply.trim_end().strip_prefix("error: ") {
+        Some(err) => bail!("{err}"),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn parses_known_commands() {
+        assert_eq!(parse_command("quit").unwrap(), Action::Quit);
+        assert_eq!(parse_command(" pause \n").unwrap(), Action::Pause);
+        assert_eq!(
+            parse_command("toggle-seconds").unwrap(),
+            Action::ToggleSeconds
+        );
+        assert_eq!(
+            parse_command("set-format %H:%M").unwrap(),
+            Action::SetFormat("%H:%M".to_owned())
+        );
+        assert_eq!(
+            parse_command("start-timer 10m").unwrap(),
+            Action::StartTimer(Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        for line in [
+            "",
+            "frobnicate",
+            "quit now",
+            "set-format",
+            "set-format %Q",
+            "start-timer soon",
+        ] {
+            assert!(parse_command(line).is_err(), "{line:?} should not parse");
+        }
+    }
+}
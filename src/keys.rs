@@ -0,0 +1,312 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use anyhow::{Context, bail};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    app::{Action, NAMED_ACTIONS},
+    config::KeyList,
+};
+
+/// Keys each action gets unless the config's `[keys]` table says otherwise.
+const DEFAULT_KEYS: &[(&str, &[&str])] = &[
+    ("quit", &["q"]),
+    ("toggle-seconds", &["s"]),
+    ("toggle-format", &["f"]),
+    // `d` already toggles do-not-disturb
+    ("cycle-content", &["D"]),
+    ("pause", &["space"]),
+    ("hold", &["p"]),
+    ("help", &["?"]),
+    ("toggle-day-night", &["n"]),
+    ("lap", &["l"]),
+    ("export-laps", &["e"]),
+    ("cycle-theme", &["c"]),
+    ("previous-preset", &["["]),
+    ("next-preset", &["]"]),
+    ("start-preset", &["enter"]),
+    ("restart-timer", &["r"]),
+    ("add-minute", &["a"]),
+    ("snooze", &["z"]),
+    ("toggle-do-not-disturb", &["d"]),
+    ("copy", &["y"]),
+    // `c` already cycles themes
+    ("toggle-calendar", &["C"]),
+    ("previous-month", &["pageup"]),
+    ("next-month", &["pagedown"]),
+    ("current-month", &["home"]),
+    ("toggle-uptime", &["u"]),
+    ("pick-zone", &["t"]),
+    ("scroll-notes-up", &["up"]),
+    ("scroll-notes-down", &["down"]),
+    ("suspend", &["ctrl-z"]),
+];
+
+/// A key plus modifiers, written like `q`, `ctrl-c`, `F1`, or `space`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeySpec {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    /// The spec a terminal key event corresponds to. Shift is dropped for characters since it is
+    /// already reflected in the character itself (`?` rather than `shift-/`).
+    pub fn from_event(event: &KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        if let KeyCode::Char(_) = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl FromStr for KeySpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // Peel modifiers off the front, leaving a lone `-` (or `ctrl--`) as the key itself
+        while let Some((modifier, tail)) = rest.split_once('-').filter(|(_, tail)| !tail.is_empty())
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier {modifier:?} in key {s:?}"),
+            };
+            rest = tail;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => bail!("unknown key {rest:?} in {s:?}"),
+                },
+            },
+        };
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            KeyCode::BackTab => f.write_str("backtab"),
+            code => write!(f, "{}", code.to_string().to_lowercase()),
+        }
+    }
+}
+
+/// The key dispatch table, in the order bindings are listed in help.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeySpec, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new()).expect("built-in key bindings are valid")
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap from the defaults with `overrides` (the config's `[keys]` table) applied,
+    /// rejecting unknown action names, unparsable keys, and keys bound to more than one action.
+    pub fn new(overrides: &BTreeMap<String, KeyList>) -> anyhow::Result<Self> {
+        if let Some(name) = overrides.keys().find(|name| action_by_name(name).is_none()) {
+            let known: Vec<_> = NAMED_ACTIONS.iter().map(Action::name).collect();
+            bail!(
+                "unknown action {name:?} in [keys] (expected one of: {})",
+                known.join(", ")
+            );
+        }
+
+        let mut bindings: Vec<(KeySpec, Action)> = Vec::new();
+        for action in NAMED_ACTIONS {
+            let name = action.name();
+            let specs: Vec<&str> = match overrides.get(name) {
+                Some(keys) => keys.specs().iter().map(String::as_str).collect(),
+                None => DEFAULT_KEYS
+                    .iter()
+                    .find(|(action, _)| *action == name)
+                    .map_or(Vec::new(), |(_, keys)| keys.to_vec()),
+            };
+
+            for spec in specs {
+                let key: KeySpec = spec
+                    .parse()
+                    .with_context(|| format!("invalid key for {name}"))?;
+                if let Some((_, other)) = bindings.iter().find(|(bound, _)| *bound == key) {
+                    bail!("key {key} is bound to both {} and {name}", other.name());
+                }
+                bindings.push((key, action.clone()));
+            }
+        }
+
+        Ok(Self { bindings })
+    }
+
+    pub fn action_for(&self, event: &KeyEvent) -> Option<Action> {
+        let key = KeySpec::from_event(event);
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| action.clone())
+    }
+
+    /// The first key bound to `action`, if any.
+    pub fn key_for(&self, action: &Action) -> Option<KeySpec> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| bound == action)
+            .map(|(key, _)| *key)
+    }
+
+    pub fn bindings(&self) -> &[(KeySpec, Action)] {
+        &self.bindings
+    }
+}
+
+/// The argument-less action with the given config/control name.
+pub fn action_by_name(name: &str) -> Option<Action> {
+    NAMED_ACTIONS
+        .iter()
+        .find(|action| action.name() == name)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> KeySpec {
+        s.parse().unwrap()
+    }
+
+    fn overrides(entries: &[(&str, &[&str])]) -> BTreeMap<String, KeyList> {
+        entries
+            .iter()
+            .map(|(name, keys)| {
+                let keys = keys.iter().map(|k| k.to_string()).collect();
+                (name.to_string(), KeyList::Many(keys))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_key_specs() {
+        assert_eq!(
+            key("q"),
+            KeySpec {
+                code: KeyCode::Char('q'),
+                modifiers: KeyModifiers::NONE,
+            }
+        );
+        assert_eq!(
+            key("ctrl-c"),
+            KeySpec {
+                code: KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+            }
+        );
+        assert_eq!(key("F1").code, KeyCode::F(1));
+        assert_eq!(key("space").code, KeyCode::Char(' '));
+        assert_eq!(key("-").code, KeyCode::Char('-'));
+        assert_eq!(
+            key("Ctrl-Alt-PageUp"),
+            KeySpec {
+                code: KeyCode::PageUp,
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            }
+        );
+        for bad in ["", "hyper-q", "f25", "spacebar"] {
+            assert!(bad.parse::<KeySpec>().is_err(), "{bad:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn key_specs_round_trip_through_display() {
+        for spec in ["q", "?", "ctrl-c", "F1", "space", "alt-enter", "pagedown"] {
+            assert_eq!(key(spec).to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn overrides_replace_default_keys() {
+        let keymap = Keymap::new(&overrides(&[("quit", &["x", "ctrl-c"])])).unwrap();
+        let press = |code, modifiers| keymap.action_for(&KeyEvent::new(code, modifiers));
+
+        assert_eq!(
+            press(KeyCode::Char('x'), KeyModifiers::NONE),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            press(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(Action::Quit)
+        );
+        assert_eq!(press(KeyCode::Char('q'), KeyModifiers::NONE), None);
+        assert_eq!(
+            press(KeyCode::Char('?'), KeyModifiers::SHIFT),
+            Some(Action::Help)
+        );
+    }
+
+    #[test]
+    fn empty_list_unbinds_an_action() {
+        let keymap = Keymap::new(&overrides(&[("help", &[])])).unwrap();
+        assert!(
+            keymap
+                .bindings()
+                .iter()
+                .all(|(_, action)| *action != Action::Help)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_actions_and_duplicate_keys() {
+        let err = Keymap::new(&overrides(&[("explode", &["x"])])).unwrap_err();
+        assert!(err.to_string().contains("unknown action \"explode\""));
+
+        let err = Keymap::new(&overrides(&[("pause", &["q"])])).unwrap_err();
+        assert_eq!(err.to_string(), "key q is bound to both quit and pause");
+    }
+}
@@ -0,0 +1,751 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use ratatui::{
+    layout::{Alignment, Rect, Size},
+    text::Line,
+};
+use tui_big_text::PixelSize;
+use unicode_width::UnicodeWidthStr;
+
+use crate::segment_font::SegmentFont;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HAlign {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+impl From<HAlign> for Alignment {
+    fn from(align: HAlign) -> Self {
+        match align {
+            HAlign::Left => Self::Left,
+            HAlign::Center => Self::Center,
+            HAlign::Right => Self::Right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VAlign {
+    Top,
+    #[default]
+    Middle,
+    Bottom,
+}
+
+/// How far `--optical-center` without a value raises a vertically centered clock, in percent of the
+/// rows left free around it.
+pub const OPTICAL_CENTER: u8 = 8;
+
+/// Where the clock sits within the frame, parsed from names like `center`, `top`, `right`, or
+/// `bottom-left` (vertical part first).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Align {
+    pub horizontal: HAlign,
+    pub vertical: VAlign,
+    /// The percentage of the free rows a vertically centered clock is raised by. Cells are about
+    /// twice as tall as wide, so text centered exactly tends to look like it sits low.
+    pub optical_center: u8,
+}
+
+impl FromStr for Align {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let vertical = |part| match part {
+            "top" => Some(VAlign::Top),
+            "center" => Some(VAlign::Middle),
+            "bottom" => Some(VAlign::Bottom),
+            _ => None,
+        };
+        let horizontal = |part| match part {
+            "left" => Some(HAlign::Left),
+            "center" => Some(HAlign::Center),
+            "right" => Some(HAlign::Right),
+            _ => None,
+        };
+
+        let (v, h) = match s.split_once('-') {
+            Some((v, h)) => (vertical(v), horizontal(h)),
+            // A lone name centers along the other axis
+            None => match (vertical(s), horizontal(s)) {
+                (None, None) => (None, None),
+                (v, h) => (Some(v.unwrap_or_default()), Some(h.unwrap_or_default())),
+            },
+        };
+        match (v, h) {
+            (Some(vertical), Some(horizontal)) => Ok(Self {
+                horizontal,
+                vertical,
+                optical_center: 0,
+            }),
+            _ => {
+                bail!("invalid alignment {s:?} (expected e.g. center, top, left, or bottom-right)")
+            },
+        }
+    }
+}
+
+/// Blank cells kept clear along each side of the frame, parsed from `--margin` like CSS: one
+/// number for every side, two for vertical and horizontal, or four for top, right, bottom, and
+/// left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Margins {
+    pub top: u16,
+    pub right: u16,
+    pub bottom: u16,
+    pub left: u16,
+}
+
+impl Margins {
+    pub fn uniform(margin: u16) -> Self {
+        Self {
+            top: margin,
+            right: margin,
+            bottom: margin,
+            left: margin,
+        }
+    }
+
+    /// `area` with the margins taken off. Margins wider or taller than the area leave an empty
+    /// rect inside it rather than underflowing.
+    pub fn inset(self, area: Rect) -> Rect {
+        let x = area.x + self.left.min(area.width);
+        let y = area.y + self.top.min(area.height);
+        Rect {
+            x,
+            y,
+            width: area.right().saturating_sub(self.right).saturating_sub(x),
+            height: area.bottom().saturating_sub(self.bottom).saturating_sub(y),
+        }
+    }
+
+    /// The margins with the left and right replaced by `x` and the top and bottom by `y`, where
+    /// they're given.
+    pub fn with_axes(self, x: Option<u16>, y: Option<u16>) -> Self {
+        Self {
+            top: y.unwrap_or(self.top),
+            right: x.unwrap_or(self.right),
+            bottom: y.unwrap_or(self.bottom),
+            left: x.unwrap_or(self.left),
+        }
+    }
+
+    pub fn horizontal(self) -> u16 {
+        self.left.saturating_add(self.right)
+    }
+
+    pub fn vertical(self) -> u16 {
+        self.top.saturating_add(self.bottom)
+    }
+}
+
+/// A fixed part of the frame to draw the clock in, from `--x`, `--y`, `--width`, and `--height`,
+/// leaving the rest of the terminal blank. Without a width or height it reaches the frame's edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Region {
+    pub x: u16,
+    pub y: u16,
+    pub width: Option<u16>,
+    pub height: Option<u16>,
+}
+
+impl Region {
+    /// The region within `area`, cut short wherever it runs past the edges.
+    pub fn within(self, area: Rect) -> Rect {
+        let x = area.x.saturating_add(self.x).min(area.right());
+        let y = area.y.saturating_add(self.y).min(area.bottom());
+        let width = self.width.unwrap_or(u16::MAX).min(area.right() - x);
+        let height = self.height.unwrap_or(u16::MAX).min(area.bottom() - y);
+        Rect::new(x, y, width, height)
+    }
+
+    /// Whether the whole region lies within a frame of `size`, with room for anything at all.
+    pub fn fits(self, size: Size) -> bool {
+        let ends = |start: u16, length: Option<u16>, limit: u16| {
+            start < limit && length.is_none_or(|length| start.saturating_add(length) <= limit)
+        };
+        ends(self.x, self.width, size.width) && ends(self.y, self.height, size.height)
+    }
+}
+
+impl FromStr for Margins {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let sides = s
+            .split(',')
+            .map(|side| side.trim().parse::<u16>())
+            .collect::<Result<Vec<_>, _>>();
+        match sides.as_deref() {
+            Ok(&[all]) => Ok(Self::uniform(all)),
+            Ok(&[vertical, horizontal]) => Ok(Self {
+                top: vertical,
+                right: horizontal,
+                bottom: vertical,
+                left: horizontal,
+            }),
+            Ok(&[top, right, bottom, left]) => Ok(Self {
+                top,
+                right,
+                bottom,
+                left,
+            }),
+            _ => bail!("invalid margin {s:?} (expected e.g. 2, 1,4, or 1,2,0,2)"),
+        }
+    }
+}
+
+/// The size in cells of one 8x8 glyph rendered with `pixel_size`.
+fn glyph_size(pixel_size: PixelSize) -> (u16, u16) {
+    let (pixels_x, pixels_y) = match pixel_size {
+        PixelSize::Full => (1, 1),
+        PixelSize::HalfHeight => (1, 2),
+        PixelSize::HalfWidth => (2, 1),
+        PixelSize::Quadrant => (2, 2),
+        PixelSize::ThirdHeight => (1, 3),
+        PixelSize::Sextant => (2, 3),
+        PixelSize::QuarterHeight => (1, 4),
+        PixelSize::Octant => (2, 4),
+    };
+    (8_u16.div_ceil(pixels_x), 8_u16.div_ceil(pixels_y))
+}
+
+/// The size in cells that `lines` occupy when rendered as `BigText` with `pixel_size`.
+pub fn big_text_size(lines: &[Line], pixel_size: PixelSize) -> (u16, u16) {
+    let (glyph_width, glyph_height) = glyph_size(pixel_size);
+
+    let widest = lines.iter().map(Line::width).max().unwrap_or(0);
+    let width = u16::try_from(widest)
+        .unwrap_or(u16::MAX)
+        .saturating_mul(glyph_width);
+    let height = u16::try_from(lines.len())
+        .unwrap_or(u16::MAX)
+        .saturating_mul(glyph_height);
+    (width, height)
+}
+
+/// The size in cells of `text`, a line per `\n`, drawn in `font` or as big text with
+/// `pixel_size`, or as plain text when there's no pixel size. Lines in a font are a blank row
+/// apart, as tui-big-text's glyphs already have one.
+pub fn text_size(
+    text: &str,
+    pixel_size: Option<PixelSize>,
+    font: Option<&SegmentFont>,
+) -> (u16, u16) {
+    let lines = text.split('\n');
+    match (pixel_size, font) {
+        (None, _) => {
+            let widest = lines.clone().map(UnicodeWidthStr::width).max().unwrap_or(0);
+            (widest as u16, lines.count() as u16)
+        },
+        (Some(_), Some(font)) => lines.fold((0, 0), |(width, height): (u16, u16), line| {
+            let (line_width, line_height) = font.size(line);
+            let gap = u16::from(height > 0);
+            (width.max(line_width), height + gap + line_height)
+        }),
+        (Some(pixel_size), None) => {
+            big_text_size(&lines.map(Line::from).collect::<Vec<_>>(), pixel_size)
+        },
+    }
+}
+
+/// The size in cells of `text` as big text, a line per `\n`, each line at its pixel size in
+/// `line_sizes` or at `pixel_size` where that has none.
+pub fn stacked_size(
+    text: &str,
+    pixel_size: PixelSize,
+    line_sizes: &[Option<PixelSize>],
+) -> (u16, u16) {
+    text.split('\n')
+        .enumerate()
+        .fold((0, 0), |(width, height): (u16, u16), (i, line)| {
+            let pixel_size = line_sizes.get(i).copied().flatten().unwrap_or(pixel_size);
+            let (line_width, line_height) = big_text_size(&[Line::from(line)], pixel_size);
+            (width.max(line_width), height.saturating_add(line_height))
+        })
+}
+
+/// What the clock would show given the room, for [`fit`] to cut down to the space there is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions<'a> {
+    pub text: &'a str,
+    /// Lines to show instead of `text`, but only at full size: the time above the date that
+    /// `text` takes turns with.
+    pub both: Option<&'a str>,
+    /// `text` without its seconds, when it shows them.
+    pub without_seconds: Option<&'a str>,
+    /// Bare `HH:MM`, for when not even plain text fits.
+    pub short: Option<&'a str>,
+    pub pixel_size: PixelSize,
+    /// Pixel sizes for each line of `text` in turn, taking `pixel_size`'s place until the digits
+    /// have to shrink.
+    pub line_sizes: &'a [Option<PixelSize>],
+    /// A bitmap font replacing the big digits, which only comes in the one size.
+    pub font: Option<&'a SegmentFont>,
+    /// The digits are drawn in ASCII, where a pixel can only be a whole cell.
+    pub ascii: bool,
+    pub header: bool,
+    pub status_bar: bool,
+    /// Rows needed around the digits for messages, laps, and the sun indicator.
+    pub extra_rows: u16,
+    /// Columns needed beside the digits, for an AM or PM drawn apart from them.
+    pub extra_columns: u16,
+}
+
+/// How the clock fits in the space there is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fit<'a> {
+    pub text: &'a str,
+    /// The size of the big digits' pixels, or `None` for plain text.
+    pub pixel_size: Option<PixelSize>,
+    pub header: bool,
+    pub status_bar: bool,
+}
+
+/// Pixel sizes from largest to smallest, stepping down in both directions in turn.
+const SHRINKING_PIXELS: [PixelSize; 5] = [
+    PixelSize::Full,
+    PixelSize::HalfHeight,
+    PixelSize::Quadrant,
+    PixelSize::Sextant,
+    PixelSize::Octant,
+];
+
+/// Picks the biggest presentation of the clock that fits in `area`, starting with `both` lines
+/// when there are any. The header and status bar go first, then the seconds, then the digits
+/// shrink through smaller pixel sizes, and finally the time is shown as plain text, down to just
+/// `HH:MM`. Lines dropped along the way come back if the smaller clock leaves room for them.
+pub fn fit<'a>(area: Rect, options: &DisplayOptions<'a>) -> Fit<'a> {
+    let lines = [
+        (options.header, options.status_bar),
+        (false, options.status_bar),
+        (options.header, false),
+        (false, false),
+    ];
+    let rows = |(header, status_bar): (bool, bool)| {
+        area.height
+            .saturating_sub(u16::from(header) + u16::from(status_bar))
+            .saturating_sub(options.extra_rows)
+    };
+    // The first line combination (most shown first) leaving `size` room
+    let with_lines = |text, pixel_size, (width, height): (u16, u16)| {
+        let fits = |shown| {
+            width <= area.width.saturating_sub(options.extra_columns) && height <= rows(shown)
+        };
+        lines
+            .into_iter()
+            .find(|&shown| fits(shown))
+            .map(|(header, status_bar)| Fit {
+                text,
+                pixel_size,
+                header,
+                status_bar,
+            })
+    };
+
+    let measure = |text, pixel_size| match options.font.is_none()
+        && !options.ascii
+        && pixel_size == options.pixel_size
+    {
+        true => stacked_size(text, pixel_size, options.line_sizes),
+        false => text_size(text, Some(pixel_size), options.font),
+    };
+    let both = options.both.and_then(|both| {
+        with_lines(
+            both,
+            Some(options.pixel_size),
+            measure(both, options.pixel_size),
+        )
+    });
+    if let Some(fit) = both {
+        return fit;
+    }
+    let full = measure(options.text, options.pixel_size);
+    if let Some(fit) = with_lines(options.text, Some(options.pixel_size), full) {
+        return fit;
+    }
+
+    let text = options.without_seconds.unwrap_or(options.text);
+    let (glyph_width, glyph_height) = glyph_size(options.pixel_size);
+    let mut pixel_sizes = std::iter::once(options.pixel_size).chain(
+        SHRINKING_PIXELS.into_iter().filter(|&smaller| {
+            let (width, height) = glyph_size(smaller);
+            width <= glyph_width && height <= glyph_height && smaller != options.pixel_size
+        }),
+    );
+    if options.without_seconds.is_none() {
+        pixel_sizes.next();
+    }
+    let mut pixel_sizes = pixel_sizes.take(if options.font.is_some() || options.ascii {
+        1
+    } else {
+        usize::MAX
+    });
+    let big = pixel_sizes.find_map(|pixel_size| {
+        let size = measure(text, pixel_size);
+        with_lines(text, Some(pixel_size), size)
+    });
+
+    big.or_else(|| with_lines(text, None, text_size(text, None, None)))
+        .unwrap_or_else(|| {
+            let short = options.short.unwrap_or(text);
+            with_lines(short, None, text_size(short, None, None)).unwrap_or(Fit {
+                text: short,
+                pixel_size: None,
+                header: false,
+                status_bar: false,
+            })
+        })
+}
+
+/// The rect of `size` placed within `area` (shrunk by `margin` on every side) per `align`,
+/// clipped so it never extends outside the area however small the terminal gets. A centered
+/// rect is raised by `align.optical_center` percent of the rows free around it, capped at half
+/// so it never goes above the top.
+pub fn aligned_rect(area: Rect, (width, height): (u16, u16), align: Align, margin: u16) -> Rect {
+    let area = Margins::uniform(margin).inset(area);
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    let x = match align.horizontal {
+        HAlign::Left => 0,
+        HAlign::Center => (area.width - width) / 2,
+        HAlign::Right => area.width - width,
+    };
+    let y = match align.vertical {
+        VAlign::Top => 0,
+        VAlign::Middle => {
+            let free = area.height - height;
+            let lift = (u32::from(free) * u32::from(align.optical_center.min(50)) + 50) / 100;
+            (free / 2).saturating_sub(lift as u16)
+        },
+        VAlign::Bottom => area.height - height,
+    };
+
+    Rect::new(area.x + x, area.y + y, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn align(s: &str) -> Align {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_single_and_combined_names() {
+        assert_eq!(align("center"), Align::default());
+        assert_eq!(
+            align("top"),
+            Align {
+                horizontal: HAlign::Center,
+                vertical: VAlign::Top,
+                ..Align::default()
+            }
+        );
+        assert_eq!(
+            align("right"),
+            Align {
+                horizontal: HAlign::Right,
+                vertical: VAlign::Middle,
+                ..Align::default()
+            }
+        );
+        assert_eq!(
+            align("bottom-left"),
+            Align {
+                horizontal: HAlign::Left,
+                vertical: VAlign::Bottom,
+                ..Align::default()
+            }
+        );
+        for bad in ["", "middle", "left-top", "top-left-ish"] {
+            assert!(bad.parse::<Align>().is_err(), "{bad:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn measures_big_text() {
+        let lines = [Line::from("12:34 PM")];
+        assert_eq!(big_text_size(&lines, PixelSize::Full), (64, 8));
+        assert_eq!(big_text_size(&lines, PixelSize::Quadrant), (32, 4));
+        assert_eq!(big_text_size(&lines, PixelSize::Sextant), (32, 3));
+    }
+
+    #[test]
+    fn fit_steps_down_the_ladder_as_the_area_shrinks() {
+        let options = DisplayOptions {
+            text: "12:34:56 PM",
+            both: None,
+            without_seconds: Some("12:34 PM"),
+            short: Some("12:34"),
+            pixel_size: PixelSize::Full,
+            line_sizes: &[],
+            font: None,
+            ascii: false,
+            header: true,
+            status_bar: true,
+            extra_rows: 0,
+            extra_columns: 0,
+        };
+        let fit_in = |width, height| {
+            let fit = fit(Rect::new(0, 0, width, height), &options);
+            (fit.text, fit.pixel_size, fit.header, fit.status_bar)
+        };
+
+        let full = Some(PixelSize::Full);
+        assert_eq!(fit_in(100, 12), ("12:34:56 PM", full, true, true));
+        // The lines go before the seconds do
+        assert_eq!(fit_in(90, 9), ("12:34:56 PM", full, false, true));
+        assert_eq!(fit_in(90, 8), ("12:34:56 PM", full, false, false));
+        assert_eq!(fit_in(70, 10), ("12:34 PM", full, true, true));
+        // Smaller digits leave room for the lines again
+        let quadrant = Some(PixelSize::Quadrant);
+        assert_eq!(fit_in(40, 8), ("12:34 PM", quadrant, true, true));
+        assert_eq!(fit_in(40, 4), ("12:34 PM", quadrant, false, false));
+        assert_eq!(
+            fit_in(40, 3),
+            ("12:34 PM", Some(PixelSize::Sextant), false, false)
+        );
+        assert_eq!(fit_in(20, 3), ("12:34 PM", None, true, true));
+        assert_eq!(fit_in(6, 1), ("12:34", None, false, false));
+        assert_eq!(fit_in(3, 1), ("12:34", None, false, false));
+        assert_eq!(fit_in(0, 0), ("12:34", None, false, false));
+    }
+
+    #[test]
+    fn fit_leaves_room_for_extra_rows_and_smaller_configured_pixels() {
+        let options = DisplayOptions {
+            text: "12:34",
+            both: None,
+            without_seconds: None,
+            short: None,
+            pixel_size: PixelSize::Quadrant,
+            line_sizes: &[],
+            font: None,
+            ascii: false,
+            header: false,
+            status_bar: false,
+            extra_rows: 2,
+            extra_columns: 0,
+        };
+        let fit_in = |width, height| {
+            let fit = fit(Rect::new(0, 0, width, height), &options);
+            (fit.text, fit.pixel_size)
+        };
+        assert_eq!(fit_in(20, 6), ("12:34", Some(PixelSize::Quadrant)));
+        // Never back up to pixels bigger than the ones asked for
+        assert_eq!(fit_in(20, 5), ("12:34", Some(PixelSize::Sextant)));
+        assert_eq!(fit_in(20, 4), ("12:34", Some(PixelSize::Octant)));
+        assert_eq!(fit_in(20, 3), ("12:34", None));
+
+        // An AM or PM beside the digits takes columns from them
+        let beside = DisplayOptions {
+            extra_columns: 3,
+            ..options
+        };
+        let fit_in = |width| fit(Rect::new(0, 0, width, 6), &beside).pixel_size;
+        assert_eq!(fit_in(23), Some(PixelSize::Quadrant));
+        assert_eq!(fit_in(22), None);
+    }
+
+    #[test]
+    fn fit_counts_every_line_of_a_template() {
+        let options = DisplayOptions {
+            text: "Focus\n12:34",
+            both: None,
+            without_seconds: None,
+            short: Some("12:34"),
+            pixel_size: PixelSize::Full,
+            line_sizes: &[],
+            font: None,
+            ascii: false,
+            header: false,
+            status_bar: false,
+            extra_rows: 0,
+            extra_columns: 0,
+        };
+        let fit_in = |width, height| {
+            let fit = fit(Rect::new(0, 0, width, height), &options);
+            (fit.text, fit.pixel_size)
+        };
+        assert_eq!(
+            text_size(options.text, Some(PixelSize::Full), None),
+            (40, 16)
+        );
+        assert_eq!(fit_in(40, 16), ("Focus\n12:34", Some(PixelSize::Full)));
+        assert_eq!(
+            fit_in(40, 15),
+            ("Focus\n12:34", Some(PixelSize::HalfHeight))
+        );
+        assert_eq!(fit_in(10, 2), ("Focus\n12:34", None));
+        assert_eq!(fit_in(10, 1), ("12:34", None));
+
+        let font = SegmentFont::seven_segment(1);
+        assert_eq!(
+            text_size("12\n34", Some(PixelSize::Full), Some(&font)),
+            (11, 15)
+        );
+    }
+
+    #[test]
+    fn places_text_at_each_corner() {
+        let area = Rect::new(0, 0, 100, 30);
+        let size = (40, 8);
+        assert_eq!(
+            aligned_rect(area, size, align("top-left"), 2),
+            Rect::new(2, 2, 40, 8)
+        );
+        assert_eq!(
+            aligned_rect(area, size, align("center"), 0),
+            Rect::new(30, 11, 40, 8)
+        );
+        assert_eq!(
+            aligned_rect(area, size, align("bottom-right"), 2),
+            Rect::new(58, 20, 40, 8)
+        );
+    }
+
+    #[test]
+    fn optical_centering_raises_only_centered_text() {
+        let area = Rect::new(0, 0, 100, 30);
+        let size = (40, 8);
+        let lifted = |name, optical_center| Align {
+            optical_center,
+            ..align(name)
+        };
+        // 22 free rows: 11 above exactly centered, 2 fewer at 8%
+        assert_eq!(
+            aligned_rect(area, size, lifted("center", OPTICAL_CENTER), 0),
+            Rect::new(30, 9, 40, 8)
+        );
+        assert_eq!(
+            aligned_rect(area, size, lifted("center", 0), 0),
+            Rect::new(30, 11, 40, 8)
+        );
+        // Half the free rows takes it all the way up, and more is capped there
+        for optical_center in [50, 100] {
+            assert_eq!(
+                aligned_rect(area, size, lifted("center", optical_center), 0).y,
+                0
+            );
+        }
+        // Margins shrink the free rows it's a fraction of
+        assert_eq!(
+            aligned_rect(area, size, lifted("center", OPTICAL_CENTER), 5),
+            Rect::new(30, 10, 40, 8)
+        );
+        for name in ["top", "bottom-left"] {
+            assert_eq!(
+                aligned_rect(area, size, lifted(name, OPTICAL_CENTER), 0),
+                aligned_rect(area, size, align(name), 0)
+            );
+        }
+    }
+
+    #[test]
+    fn parses_and_clamps_margins() {
+        assert_eq!("2".parse::<Margins>().unwrap(), Margins::uniform(2));
+        let margins = "1,2,3,4".parse::<Margins>().unwrap();
+        assert_eq!(
+            margins,
+            Margins {
+                top: 1,
+                right: 2,
+                bottom: 3,
+                left: 4,
+            }
+        );
+        assert_eq!(
+            "1, 4"
+                .parse::<Margins>()
+                .unwrap()
+                .inset(Rect::new(0, 0, 20, 10)),
+            Rect::new(4, 1, 12, 8)
+        );
+        for bad in ["", "-1", "1,2,3", "a,b"] {
+            assert!(bad.parse::<Margins>().is_err(), "{bad:?} should not parse");
+        }
+
+        let area = Rect::new(5, 5, 10, 3);
+        assert_eq!(margins.inset(area), Rect::new(9, 6, 4, 0));
+        assert_eq!(
+            margins.with_axes(Some(6), None),
+            Margins {
+                top: 1,
+                right: 6,
+                bottom: 3,
+                left: 6,
+            }
+        );
+        assert_eq!(margins.with_axes(Some(0), Some(0)), Margins::default());
+        let inset = Margins::uniform(40).inset(area);
+        assert_eq!(inset.area(), 0);
+        assert!(inset.right() <= area.right() && inset.bottom() <= area.bottom());
+    }
+
+    #[test]
+    fn clamps_the_region_to_the_frame() {
+        let frame = Rect::new(0, 0, 80, 24);
+        let corner = Region {
+            x: 60,
+            y: 0,
+            width: Some(20),
+            height: Some(5),
+        };
+        assert_eq!(corner.within(frame), Rect::new(60, 0, 20, 5));
+        assert!(corner.fits(frame.as_size()));
+        // Without a size it runs to the edges
+        let rest = Region {
+            x: 10,
+            y: 4,
+            ..Region::default()
+        };
+        assert_eq!(rest.within(frame), Rect::new(10, 4, 70, 20));
+        assert!(rest.fits(frame.as_size()));
+
+        let small = Rect::new(0, 0, 70, 3);
+        assert!(!corner.fits(small.as_size()));
+        assert_eq!(corner.within(small), Rect::new(60, 0, 10, 3));
+        let off_screen = Region { x: 90, ..corner };
+        assert!(!off_screen.fits(frame.as_size()));
+        assert_eq!(off_screen.within(frame).area(), 0);
+    }
+
+    #[test]
+    fn never_underflows_on_small_terminals() {
+        for name in [
+            "top-left",
+            "top",
+            "top-right",
+            "left",
+            "center",
+            "right",
+            "bottom-left",
+            "bottom",
+            "bottom-right",
+        ] {
+            for (area, margin) in [
+                (Rect::new(0, 0, 10, 3), 0),
+                (Rect::new(0, 0, 10, 3), 4),
+                (Rect::new(5, 5, 0, 0), 1),
+                (Rect::new(0, 0, 10, 9), 0),
+            ] {
+                for optical_center in [0, OPTICAL_CENTER, 50] {
+                    let align = Align {
+                        optical_center,
+                        ..align(name)
+                    };
+                    let rect = aligned_rect(area, (64, 8), align, margin);
+                    assert!(area.contains(rect.as_position()) || rect.area() == 0);
+                    assert!(rect.right() <= area.right() && rect.bottom() <= area.bottom());
+                }
+            }
+        }
+    }
+}
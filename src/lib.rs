@@ -0,0 +1,64 @@
+pub mod alarm;
+pub mod alternate;
+pub mod analog;
+pub mod animate;
+pub mod app;
+pub mod battery;
+pub mod bounce;
+pub mod calendar;
+pub mod check;
+pub mod chess;
+pub mod chime;
+pub mod cli;
+pub mod clipboard;
+pub mod config;
+pub mod content;
+pub mod countdown;
+pub mod cron;
+pub mod decimal;
+pub mod dots;
+pub mod dst;
+pub mod duration;
+pub mod failure;
+pub mod feed;
+pub mod flash;
+pub mod fuzzy;
+pub mod grid;
+pub mod holiday;
+pub mod ics;
+pub mod ipc;
+pub mod keys;
+pub mod layout;
+pub mod locale;
+pub mod logging;
+pub mod matrix;
+pub mod mirror;
+pub mod moon;
+pub mod notes;
+pub mod notify;
+pub mod output;
+pub mod palette;
+pub mod persist;
+pub mod picker;
+pub mod rainbow;
+pub mod record;
+pub mod reminder;
+pub mod retry;
+pub mod ring;
+pub mod run;
+pub mod segment_font;
+pub mod serve;
+pub mod seven_segment;
+pub mod stopwatch;
+pub mod sun;
+pub mod suspend;
+pub mod sync;
+pub mod theme;
+pub mod tick;
+pub mod timer;
+pub mod title;
+pub mod ui;
+pub mod uptime;
+pub mod watch;
+pub mod widget;
+pub mod zone;
@@ -0,0 +1,127 @@
+//! Picking between the 12- and 24-hour formats from the locale, for `24-hour = "auto"`.
+
+use serde::Deserialize;
+
+/// The locales that write the time on a 12-hour clock. Any other locale with a territory, like
+/// `de_DE` or `en_GB`, gets the 24-hour one.
+const TWELVE_HOUR: &[&str] = &[
+    "ar_EG", "ar_SA", "bn_BD", "en_AU", "en_CA", "en_IN", "en_NZ", "en_PH", "en_US", "es_US",
+    "hi_IN", "ko_KR", "ms_MY", "ur_PK", "zh_TW",
+];
+
+/// Which of the 12- and 24-hour formats the clock starts on, as `24-hour` in the config and
+/// `TUI_TIME_24H` set it: `true`, `false`, or `"auto"` to go by the locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "HourSetting")]
+pub enum HourFormat {
+    #[default]
+    Auto,
+    Twelve,
+    TwentyFour,
+}
+
+/// How `24-hour` is written in the config, before it's checked.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HourSetting {
+    Fixed(bool),
+    Named(String),
+}
+
+impl TryFrom<HourSetting> for HourFormat {
+    type Error = String;
+
+    fn try_from(setting: HourSetting) -> Result<Self, Self::Error> {
+        match setting {
+            HourSetting::Fixed(hour24) => Ok(hour24.into()),
+            HourSetting::Named(name) if name == "auto" => Ok(Self::Auto),
+            HourSetting::Named(name) => {
+                Err(format!("expected true, false or \"auto\", not {name:?}"))
+            },
+        }
+    }
+}
+
+impl From<bool> for HourFormat {
+    fn from(hour24: bool) -> Self {
+        if hour24 {
+            Self::TwentyFour
+        } else {
+            Self::Twelve
+        }
+    }
+}
+
+impl HourFormat {
+    /// Whether to start on the 24-hour format, looking up the locale's variables with `var` for
+    /// `Auto`.
+    pub fn hour24(self, var: impl Fn(&str) -> Option<String>) -> bool {
+        match self {
+            Self::Auto => locale_hour24(time_locale(var).as_deref()),
+            Self::Twelve => false,
+            Self::TwentyFour => true,
+        }
+    }
+}
+
+/// The locale times are written in: `LC_ALL`, `LC_TIME`, or `LANG`, the first that's set, as
+/// the C library picks it.
+fn time_locale(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()))
+}
+
+/// Whether `locale`, like `en_US.UTF-8` or `de_DE@euro`, writes the time on a 24-hour clock.
+/// Without a locale, or with one naming no territory (`C`, `POSIX`, a bare `en`), it's the
+/// 12-hour clock, as it was before the locale was looked at.
+fn locale_hour24(locale: Option<&str>) -> bool {
+    let Some(locale) = locale else {
+        return false;
+    };
+    let name = locale.split(['.', '@']).next().unwrap_or_default();
+    match name.split_once('_') {
+        Some((language, territory)) if !language.is_empty() && !territory.is_empty() => {
+            !TWELVE_HOUR.contains(&name)
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goes_by_the_locales_territory() {
+        assert!(!locale_hour24(Some("en_US.UTF-8")));
+        assert!(!locale_hour24(Some("en_AU")));
+        assert!(locale_hour24(Some("en_GB.UTF-8")));
+        assert!(locale_hour24(Some("de_DE@euro")));
+        assert!(locale_hour24(Some("fr_CA.UTF-8")));
+        // Anything unrecognized keeps the 12-hour default
+        for locale in [None, Some("C"), Some("POSIX"), Some("C.UTF-8"), Some("en")] {
+            assert!(!locale_hour24(locale), "{locale:?}");
+        }
+    }
+
+    #[test]
+    fn lc_all_then_lc_time_then_lang() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_owned())
+            }
+        };
+        let auto = HourFormat::Auto;
+        assert!(auto.hour24(env(&[("LANG", "de_DE.UTF-8")])));
+        assert!(!auto.hour24(env(&[("LC_TIME", "en_US"), ("LANG", "de_DE")])));
+        assert!(auto.hour24(env(&[("LC_ALL", "sv_SE"), ("LC_TIME", "en_US")])));
+        // Empty ones count as unset
+        assert!(auto.hour24(env(&[("LC_ALL", ""), ("LANG", "nl_NL")])));
+        // Only auto looks at all
+        assert!(!HourFormat::Twelve.hour24(env(&[("LANG", "de_DE")])));
+        assert!(HourFormat::TwentyFour.hour24(env(&[])));
+    }
+}
@@ -0,0 +1,102 @@
+//! `--log-file`: what the ticker and the loop are doing, written as lines to a file for chasing
+//! down missed or late ticks. Nothing is ever logged to the terminal, which the clock is drawn
+//! on, and without `--log-file` no subscriber is installed at all, so the events compiled into
+//! the ticker and the loop cost no more than a check of the level they're filtered out at.
+
+use std::{
+    fs::{File, OpenOptions},
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::{Context, anyhow};
+use tracing::{Subscriber, level_filters::LevelFilter};
+use tracing_subscriber::EnvFilter;
+
+/// The crate's own events, which are all that's logged unless `RUST_LOG` asks for others.
+const TARGET: &str = "tui_time";
+
+/// The level logged at when neither `--log-level` nor `RUST_LOG` says.
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+/// What to log: the crate's events at the level `--log-level` asks for, or failing that whatever
+/// `RUST_LOG` does, like `debug` or `tui_time=trace,hyper=warn`. Directives it can't read are
+/// skipped.
+pub fn filter(flag: Option<LevelFilter>, rust_log: Option<&str>) -> EnvFilter {
+    match flag {
+        Some(level) => EnvFilter::new(format!("{TARGET}={level}")),
+        None => EnvFilter::builder()
+            .with_default_directive(
+                format!("{TARGET}={DEFAULT_LEVEL}")
+                    .parse()
+                    .expect("the default directive is valid"),
+            )
+            .parse_lossy(rust_log.unwrap_or_default()),
+    }
+}
+
+/// Writes events allowed by `filter` to `file`, a line each, like
+/// `2024-03-01T14:41:00.002Z DEBUG tui_time::timer: tick tick=Interval latency=2.1ms`.
+pub fn subscriber(file: File, filter: EnvFilter) -> impl Subscriber + Send + Sync {
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_env_filter(filter)
+        .with_ansi(false)
+        .finish()
+}
+
+/// Logs what `filter` allows to `path` for the rest of the run, appended to what's already there.
+pub fn init(path: &Path, filter: EnvFilter) -> anyhow::Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening the log file {} failed", path.display()))?;
+    tracing::subscriber::set_global_default(subscriber(file, filter))
+        .map_err(|err| anyhow!(err))
+        .context("logging was already set up")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_flag_then_rust_log_pick_the_level() {
+        let level = |flag, rust_log| filter(flag, rust_log).max_level_hint();
+        assert_eq!(level(None, None), Some(LevelFilter::INFO));
+        assert_eq!(level(None, Some("debug")), Some(LevelFilter::DEBUG));
+        assert_eq!(
+            level(Some(LevelFilter::WARN), Some("trace")),
+            Some(LevelFilter::WARN)
+        );
+        assert_eq!(
+            level(None, Some("error,tui_time::timer=debug")),
+            Some(LevelFilter::DEBUG)
+        );
+        // Unreadable ones leave the default
+        assert_eq!(level(None, Some("tui_time=loud")), Some(LevelFilter::INFO));
+    }
+
+    #[test]
+    fn writes_events_at_or_above_the_level() {
+        let path = std::env::temp_dir().join(format!("tui-time-log-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let file = File::create(&path).unwrap();
+        let log = subscriber(file, filter(Some(LevelFilter::DEBUG), None));
+        tracing::subscriber::with_default(log, || {
+            tracing::debug!(expirations = 3_u64, clock = "realtime", "tick");
+            tracing::trace!("too fine to keep");
+            tracing::debug!(target: "hyper", "someone else's");
+        });
+        let logged = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = logged.lines().collect();
+        assert_eq!(lines.len(), 1, "{logged}");
+        let (_, line) = lines[0].split_once(' ').unwrap();
+        assert_eq!(
+            line.trim_start(),
+            "DEBUG tui_time::logging::tests: tick expirations=3 clock=\"realtime\""
+        );
+    }
+}
@@ -1,146 +1,1022 @@
 use std::{
-    io,
-    os::fd::{AsRawFd, FromRawFd, OwnedFd},
-    time::{SystemTime, UNIX_EPOCH},
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
-use anyhow::Context;
-use chrono::Local;
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Local, Utc};
+use clap::Parser;
 use ratatui::{
-    Frame,
-    crossterm::event::{self, Event, KeyCode},
-    text::Line,
+    DefaultTerminal, TerminalOptions, Viewport,
+    crossterm::{
+        event::{self, DisableFocusChange, EnableFocusChange, Event, KeyEvent, KeyEventKind},
+        execute,
+        terminal::{EnterAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+};
+use tokio::{
+    signal::unix::{Signal, SignalKind, signal},
+    sync::{mpsc, watch},
+};
+use tui_time::{
+    alarm::{self, Alarm, Weekdays},
+    alternate::Alternation,
+    app::{self, Action, App, Mode},
+    battery::{self, BatteryWatch},
+    bounce::Bounce,
+    check::{self, Report},
+    chess::ChessClock,
+    chime::Chime,
+    cli::{Cli, Command},
+    config::{self, Config, Layer},
+    content::{ClockLine, ContentMode},
+    countdown::{Preset, Timer},
+    dots::SecondsDots,
+    feed::StdinFeed,
+    fuzzy::Granularity,
+    grid::{Cell, Grid},
+    holiday::Holidays,
+    ics::Agenda,
+    ipc::{self, ControlSocket},
+    keys::Keymap,
+    layout::{Align, Region},
+    logging,
+    matrix::Matrix,
+    mirror::Mirror,
+    notes::Notes,
+    notify::{self, Notifier, NotifyMethod},
+    output::{OutputFifo, OutputFile},
+    palette::ColorDepth,
+    persist::{self, Boot},
+    picker,
+    rainbow::Rainbow,
+    record::{self, Recorder},
+    reminder::Reminders,
+    retry::{ReadRetry, Retry},
+    ring::Ring,
+    run::{Exit, Host, LoopEvent, LoopOptions, run_loop},
+    segment_font::SegmentFont,
+    serve::{Shown, TimeServer},
+    stopwatch::{Precision, Stopwatch},
+    sun::Coordinates,
+    sync::SyncWatch,
+    theme::{self, Theme},
+    tick,
+    timer::{Arming, Ticker},
+    title::TitleClock,
+    watch::ConfigWatcher,
+    widget,
+    zone::{self, Zone},
 };
-use tokio::io::unix::AsyncFd;
-use tui_big_text::{BigText, PixelSize};
+
+/// Loads and validates the config the clock would start with, for `--check`.
+fn check_config(cli: &Cli) -> anyhow::Result<String> {
+    let Some(path) = cli.config.clone().or_else(config::default_path) else {
+        return Ok("none, since $HOME isn't set".to_owned());
+    };
+    let profile = config::profile_name(cli, |name| std::env::var(name).ok());
+    let config = config::load(&path, profile.as_deref())?;
+    let env_layer = Layer::from_env(|name| std::env::var(name).ok())?;
+    config::resolve(&config, &env_layer, cli)?;
+    Settings::new(&config, cli, &env_layer, None)?;
+    if let Some(grid) = cli.grid {
+        grid_cells(&config, grid)?;
+    }
+    Ok(match path.exists() {
+        true => path.display().to_string(),
+        false => format!("{} doesn't exist, so the defaults are used", path.display()),
+    })
+}
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> anyhow::Result<()> {
-    let tfd = every_minute_timerfd_create().context("timerfd_create failed")?;
+    let cli = Rc::new(Cli::parse());
+    if let Some(Command::Ctl { command }) = &cli.command {
+        return ipc::send_command(&ipc::socket_path()?, &command.join(" "));
+    }
+    if let Some(filter) = &cli.list_timezones {
+        let lines = zone::listing(filter, cli.with_offset, Utc::now());
+        let mut stdout = io::stdout().lock();
+        for line in lines {
+            writeln!(stdout, "{line}")?;
+        }
+        return Ok(());
+    }
+    if cli.check {
+        let mut report = Report::default();
+        report.add("config", check_config(&cli));
+        report.add("timerfd", check::timerfd().await);
+        report.add("clock changes", check::clock_change_wakeups());
+        report.add(
+            "terminal",
+            check::terminal(
+                io::stdin().is_terminal(),
+                ratatui::crossterm::terminal::size(),
+                &std::env::var("TERM").unwrap_or_default(),
+                &std::env::var("COLORTERM").unwrap_or_default(),
+            ),
+        );
+        report.write(&mut io::stdout().lock())?;
+        anyhow::ensure!(report.passed(), "some checks failed");
+        return Ok(());
+    }
+    if let Some(path) = &cli.log_file {
+        let rust_log = std::env::var("RUST_LOG").ok();
+        logging::init(path, logging::filter(cli.log_level, rust_log.as_deref()))?;
+        tracing::info!(version = env!("CARGO_PKG_VERSION"), "starting");
+    }
+    let config_path = cli.config.clone().or_else(config::default_path);
+    // Before the terminal is touched, so a mistyped profile is reported plainly
+    let profile = config::profile_name(&cli, |name| std::env::var(name).ok());
+    let config = match &config_path {
+        Some(path) => config::load(path, profile.as_deref())?,
+        None => config::parse("", profile.as_deref())?,
+    };
+    let env_layer = Rc::new(Layer::from_env(|name| std::env::var(name).ok())?);
+    let layers = config::resolve(&config, &env_layer, &cli)?;
+    if let Some(zone) = &layers.timezone {
+        // SAFETY: nothing else is running yet to read the environment concurrently; the runtime
+        // is single-threaded and no other threads have been spawned.
+        unsafe { std::env::set_var("TZ", zone) };
+    }
+    let Settings {
+        keymap,
+        themes,
+        theme,
+        presets,
+        chime,
+        header,
+        alarms,
+        reminders,
+        day_night,
+        location,
+        sun_times,
+        zone_label,
+        content,
+        lines,
+        no_leading_zero,
+    } = Settings::new(&config, &cli, &env_layer, None)?;
+    let cells = match cli.grid {
+        Some(grid) => grid_cells(&config, grid)?,
+        None => Vec::new(),
+    };
+
+    let font = SegmentFont::from_arg(&cli.font, cli.segment_thickness).context("invalid --font")?;
+
+    let exit = match (cli.exit_after, cli.exit_at) {
+        (Some(after), _) => Some(Exit::After(Instant::now() + after)),
+        (None, Some(at)) => {
+            let next = alarm::next_occurrence(at, Weekdays::ALL, &Local::now());
+            Some(Exit::At(next))
+        },
+        (None, None) => None,
+    };
+    let feed = match cli.stdin {
+        Some(_) if io::stdin().is_terminal() => {
+            anyhow::bail!("--stdin needs input piped in, not a terminal")
+        },
+        Some(kind) => Some(StdinFeed::new(kind)),
+        None => None,
+    };
+    let signals = ShutdownSignals::new().context("installing signal handlers failed")?;
+    let job_signals = JobSignals::new().context("installing signal handlers failed")?;
 
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let tick_interval = tick::MINUTE;
+    // Unaligned ticks are counted on the monotonic clock, as the app asks for once it's running
+    let (clock, arming) = if cli.no_align {
+        (libc::CLOCK_MONOTONIC, Arming::Relative)
+    } else {
+        (libc::CLOCK_REALTIME, Arming::ALIGNED)
+    };
+    let mut ticker = Ticker::new(clock, tick_interval, arming)?;
+
+    // Another clock already listening is no reason not to run this one, so a socket that can't be
+    // had is told about once the app is up
+    let (ipc_tx, ipc_rx) = mpsc::unbounded_channel();
+    let mut control_problem = None;
+    let _control_socket = if cli.control {
+        match ipc::socket_path().and_then(ControlSocket::bind) {
+            Ok(mut socket) => {
+                socket.serve(ipc_tx);
+                Some(socket)
+            },
+            Err(err) => {
+                control_problem = Some(err);
+                None
+            },
+        }
+    } else {
+        None
+    };
 
-    // Spawn event-listening thread
+    let (key_tx, key_rx) = mpsc::unbounded_channel();
+    let (resize_tx, resize_rx) = mpsc::unbounded_channel();
+    let (focus_tx, focus_rx) = mpsc::unbounded_channel();
+    let (keymap_tx, keymap_rx) = std::sync::mpsc::channel();
+    let mut thread_keymap = keymap.clone();
+    let confirm_quit = !cli.no_confirm;
+    // Whether keys are being typed into the zone picker, where `q` is just a letter
+    let picking = Arc::new(AtomicBool::new(false));
+    let thread_picking = Arc::clone(&picking);
+
+    // Spawn event-listening thread. It polls rather than blocking in `event::read` so it notices
+    // the receiver going away when the loop exits for some other reason (e.g. a `quit` command).
+    // A read failing is tried again after a pause, and only enough of them in a row end it,
+    // which quits the loop as its receivers close.
     let event_thread_handle = std::thread::spawn(move || -> anyhow::Result<()> {
-        loop {
-            if matches!(event::read()?, Event::Key(key_event) if key_event.code == KeyCode::Char('q'))
-            {
-                tx.send(())?;
+        let mut retry = ReadRetry::default();
+        while !key_tx.is_closed() {
+            // A reloaded config may have rebound keys
+            if let Some(keymap) = keymap_rx.try_iter().last() {
+                thread_keymap = keymap;
+            }
+            let read = event::poll(Duration::from_millis(250))
+                .and_then(|ready| ready.then(event::read).transpose());
+            let event = match retry.check(read)? {
+                Retry::Ready(Some(event)) => event,
+                Retry::Ready(None) => continue,
+                Retry::After(backoff) => {
+                    std::thread::sleep(backoff);
+                    continue;
+                },
+            };
+            let key_event = match event {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+                // The layout depends on the terminal's size, so redraw straight away
+                Event::Resize(..) => {
+                    resize_tx.send(())?;
+                    continue;
+                },
+                Event::FocusGained | Event::FocusLost => {
+                    focus_tx.send(matches!(event, Event::FocusGained))?;
+                    continue;
+                },
+                _ => continue,
+            };
+            let action = thread_keymap
+                .action_for(&key_event)
+                .unwrap_or(Action::Acknowledge);
+            // Set here as well as by the loop, so keys typed straight after opening the picker
+            // can't race it
+            if action == Action::PickZone {
+                thread_picking.store(true, Ordering::Relaxed);
+            }
+            // Quitting may be asked about first, and then more keys are needed
+            let quit =
+                action == Action::Quit && !confirm_quit && !thread_picking.load(Ordering::Relaxed);
+            key_tx.send((key_event, action))?;
+            if quit {
                 return Ok(());
             }
         }
+        Ok(())
     });
 
-    let mut terminal = ratatui::init();
-    loop {
-        terminal.draw(draw)?;
-        tokio::select! {
-            _ = wait_then_consume_tfd_read(&tfd) => continue,
-            _ = rx.recv() => {
-                break;
+    let color_depth = cli.color_depth.resolve(
+        &std::env::var("COLORTERM").unwrap_or_default(),
+        &std::env::var("TERM").unwrap_or_default(),
+    );
+    let mut app = App {
+        align: Align {
+            optical_center: cli.optical_center.unwrap_or(0),
+            ..cli.align
+        },
+        margin: cli.margin.with_axes(cli.margin_x, cli.margin_y),
+        region: [cli.x, cli.y, cli.width, cli.height]
+            .iter()
+            .any(Option::is_some)
+            .then(|| Region {
+                x: cli.x.unwrap_or(0),
+                y: cli.y.unwrap_or(0),
+                width: cli.width,
+                height: cli.height,
+            }),
+        keymap,
+        day_night,
+        location,
+        sun_times,
+        moon: cli.moon,
+        ascii: cli.ascii
+            || std::env::var("TERM").is_ok_and(|term| widget::term_lacks_blocks(&term)),
+        sync: cli.sync_status.then(SyncWatch::default),
+        battery: cli
+            .battery
+            .then(|| BatteryWatch::new(battery::POWER_SUPPLY_DIR, cli.battery_low)),
+        theme,
+        themes,
+        rainbow: cli.rainbow.then(|| Rainbow {
+            truecolor: color_depth == ColorDepth::Truecolor,
+        }),
+        color_depth,
+        config_path,
+        profile,
+        chime,
+        alarms,
+        reminders,
+        flash: !cli.no_flash,
+        confirm_quit: !cli.no_confirm,
+        status_bar: cli.status_bar,
+        calendar: cli.calendar,
+        agenda: cli.ics.clone().map(Agenda::load),
+        alternation: cli
+            .alternate
+            .map(|secs| Alternation::new(Duration::from_secs(secs))),
+        notes: cli.agenda.clone().map(Notes::load),
+        notes_width: cli.agenda_width,
+        holidays_ahead: cli.holidays_ahead,
+        header,
+        tick_fallback: !ticker.uses_timerfd(),
+        relative_ticks: ticker.arming() == Arming::Relative,
+        // A format from the config or environment yields to the flag, which conflicts with --format
+        custom_format: layers.format.filter(|_| !cli.decimal && !cli.fuzzy_words),
+        decimal: cli.decimal,
+        fuzzy: cli.fuzzy.or(cli.fuzzy_words.then_some(Granularity::Five)),
+        fuzzy_words: cli.fuzzy_words,
+        hour24: layers
+            .hour24
+            .unwrap_or_default()
+            .hour24(|name| std::env::var(name).ok()),
+        comparisons: cli.compare.clone(),
+        grid: cli.grid,
+        cells,
+        zone_label,
+        content,
+        lines,
+        date_weekday: cli.weekday,
+        local_zone: Zone::local().ok(),
+        no_leading_zero,
+        ampm: cli.ampm,
+        ampm_position: cli.ampm_position,
+        pixel_size: cli.pixel_size.into(),
+        font,
+        segment_off_color: cli.segment_off_color,
+        bounce: cli.bounce.map(|period| Bounce::new(period, Instant::now())),
+        // Different rain each run
+        matrix: cli
+            .matrix
+            .then(|| Matrix::new(Local::now().timestamp_micros() as u64, Instant::now())),
+        analog: cli.analog,
+        sweep: cli.sweep,
+        ring: cli.ring.map(|span| Ring {
+            span,
+            color: cli.ring_color,
+            radius: cli.ring_radius,
+        }),
+        seconds_dots: cli.seconds_dots.then_some(SecondsDots {
+            filled: cli.dots_color,
+            empty: cli.dots_empty_color,
+        }),
+        animate: cli.animate,
+        ..App::default()
+    };
+    match &cli.command {
+        Some(Command::Timer { duration }) => {
+            let mut timer = Timer::new(presets.clone());
+            if let Some(duration) = *duration {
+                timer.start(duration, Instant::now());
             }
+            app.mode = Mode::Timer(timer);
+        },
+        Some(Command::Stopwatch {
+            lap_file,
+            precision,
+            fps,
+            count_suspend,
+        }) => {
+            app.mode = Mode::Stopwatch(Stopwatch::start(Instant::now()));
+            app.lap_file = lap_file.clone();
+            app.stopwatch_millis = (*precision == Precision::Millis).then_some(*fps);
+            app.stopwatch_counts_suspend = *count_suspend;
+        },
+        Some(Command::Chess { time, increment }) => {
+            app.mode = Mode::Chess(ChessClock::new(*time, *increment));
+        },
+        None | Some(Command::Clock | Command::Ctl { .. }) if cli.uptime => app.mode = Mode::Uptime,
+        None | Some(Command::Clock | Command::Ctl { .. }) if let Some(since) = cli.since => {
+            app.mode = Mode::Since(since);
+        },
+        None | Some(Command::Clock | Command::Ctl { .. }) => {},
+    }
+    app.presets = presets;
+    // What was saved last time is offered back, and only saved over once that's answered
+    let persist_path = match cli.persist {
+        true => Some(
+            persist::default_path()
+                .context("--persist needs $XDG_STATE_HOME or $HOME to save to")?,
+        ),
+        false => None,
+    };
+    if let Some(path) = &persist_path {
+        match persist::load(path) {
+            Ok(Some(snapshot)) => {
+                let away = snapshot.away(Utc::now(), Boot::now().as_ref());
+                tracing::info!(?away, "offering to resume the saved state");
+                app.resuming = Some(snapshot.resume(away, Instant::now(), app.presets.clone()));
+            },
+            Ok(None) => {},
+            Err(err) => app.report_error(&err.context("the saved state can't be resumed")),
+        }
+    }
+    app.recents_path = picker::recents_path();
+    if let Some(path) = &app.recents_path {
+        match picker::load_recents(path) {
+            Ok(recent) => app.recent_zones = recent,
+            Err(err) => app.report_error(&err.context("the recent zones can't be read")),
+        }
+    }
+    if let Some(separator) = &cli.separator {
+        app.set_separator(separator, Instant::now());
+    }
+    // stderr is the terminal being drawn on, so the fallback is told about on the screen
+    if app.tick_fallback {
+        app.notify(
+            "timerfd unavailable, ticking on a timer that won't follow clock changes".to_owned(),
+            Instant::now(),
+        );
+    }
+    if let Some(err) = control_problem {
+        app.notify(format!("no control socket: {err:#}"), Instant::now());
+    }
+    // A bad holidays file only costs the holiday names, not the clock
+    if let Some(path) = &cli.holidays {
+        let (holidays, warnings) = Holidays::load(path);
+        app.holidays = holidays;
+        for warning in warnings {
+            app.notify(warning, Instant::now());
+        }
+    }
+
+    // Laps and finished timers are written out on the runtime, and failing only shows a notice
+    let (record_err_tx, record_err_rx) = mpsc::unbounded_channel();
+    let record_writer = cli.record.clone().map(|path| {
+        let (recorder, records) = Recorder::new();
+        app.recorder = Some(recorder);
+        tokio::spawn(record::write_records(path, records, record_err_tx))
+    });
+
+    // Likewise desktop notifications, and failing rings the bell instead
+    let (notify_err_tx, notify_err_rx) = mpsc::unbounded_channel();
+    app.notifier = match cli.notify {
+        NotifyMethod::Desktop => {
+            let (notifier, messages) = Notifier::desktop(cli.notify_message.clone());
+            tokio::spawn(notify::send_desktop(messages, notify_err_tx));
+            notifier
+        },
+        method => Notifier::new(method),
+    };
+
+    let (reload_tx, reload_rx) = mpsc::unbounded_channel();
+    if let Some(path) = app.config_path.as_ref().filter(|_| !cli.no_watch) {
+        match ConfigWatcher::new(path) {
+            Ok(watcher) => {
+                tokio::spawn(async move {
+                    while watcher.changed().await.is_ok() && reload_tx.send(()).is_ok() {}
+                });
+            },
+            // No config directory means there's nothing to edit yet
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {},
+            Err(err) => app.notify(
+                format!("not watching the config for changes: {err}"),
+                Instant::now(),
+            ),
+        }
+    }
+
+    // The calendar file is watched regardless of --no-watch, which is about the config
+    let (ics_tx, ics_rx) = mpsc::unbounded_channel();
+    if let Some(agenda) = &app.agenda {
+        match ConfigWatcher::new(agenda.path()) {
+            Ok(watcher) => {
+                tokio::spawn(async move {
+                    while watcher.changed().await.is_ok() && ics_tx.send(()).is_ok() {}
+                });
+            },
+            Err(err) => app.notify(
+                format!("not watching the calendar for changes: {err}"),
+                Instant::now(),
+            ),
+        }
+    }
+
+    let (notes_tx, notes_rx) = mpsc::unbounded_channel();
+    if let Some(notes) = &app.notes {
+        match ConfigWatcher::new(notes.path()) {
+            Ok(watcher) => {
+                tokio::spawn(async move {
+                    while watcher.changed().await.is_ok() && notes_tx.send(()).is_ok() {}
+                });
+            },
+            Err(err) => app.notify(
+                format!("not watching the agenda for changes: {err}"),
+                Instant::now(),
+            ),
         }
     }
-    ratatui::restore();
-    event_thread_handle.join().unwrap()?;
+
+    let output_file = cli.output_file.clone().map(OutputFile::new);
+    let output_fifo = match &cli.output_fifo {
+        Some(path) => Some(
+            OutputFifo::open(path.clone())
+                .with_context(|| format!("can't write to {}", path.display()))?,
+        ),
+        None => None,
+    };
+    let mirrors = cli
+        .output
+        .iter()
+        .map(|path| Mirror::open(path.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    // Bound before the terminal is taken over, so a port in use is reported plainly
+    let (shown_tx, shown_rx) = watch::channel(Shown::default());
+    let time_server = match cli.serve {
+        Some(addr) => Some(TimeServer::bind(addr, shown_rx).await?),
+        None => None,
+    };
+
+    let mut terminal = match cli.inline {
+        Some(rows) => ratatui::init_with_options(TerminalOptions {
+            viewport: Viewport::Inline(rows),
+        }),
+        None => ratatui::init(),
+    };
+    // Terminals that don't report focus changes ignore asking for them
+    execute!(io::stdout(), EnableFocusChange)?;
+    let mut host = MainHost {
+        keys: key_rx,
+        resizes: resize_rx,
+        focus: focus_rx,
+        commands: ipc_rx,
+        reloads: reload_rx,
+        record_errors: record_err_rx,
+        notify_errors: notify_err_rx,
+        ics_changes: ics_rx,
+        notes_changes: notes_rx,
+        feed,
+        signals,
+        job_signals,
+        picking,
+        keymap_tx,
+        cli: Rc::clone(&cli),
+        env_layer: Rc::clone(&env_layer),
+        title_clock: cli.title_clock.then(TitleClock::start).flatten(),
+        output_file,
+        output_fifo,
+        mirrors,
+        shown: time_server.is_some().then_some(shown_tx),
+        persist_path,
+    };
+    let options = LoopOptions {
+        tick_interval,
+        max_fps: cli.max_fps,
+        inline: cli.inline,
+        exit,
+    };
+    // Whatever ends the loop, the terminal is put back before it's reported
+    let looped = run_loop(&mut terminal, &mut app, &mut ticker, &mut host, options).await;
+    restore_terminal(&mut terminal, cli.inline.is_some())?;
+    looped?;
+    // Closing the key queue lets the event thread finish
+    drop(host);
+    // Nothing's shown any more to answer with
+    if let Some(time_server) = time_server {
+        time_server.stop();
+    }
+    event_thread_handle
+        .join()
+        .map_err(|_| anyhow!("the event thread panicked"))??;
+
+    app.export_laps().context("exporting laps failed")?;
+    // Closing the queue lets the writer finish what's left in it
+    app.recorder = None;
+    if let Some(writer) = record_writer {
+        writer.await?;
+    }
 
     Ok(())
 }
 
-async fn wait_then_consume_tfd_read(tfd: &AsyncFd<OwnedFd>) -> anyhow::Result<()> {
-    let mut guard = tfd.readable().await.context("tfd.readable failed")?;
-    let mut buf = 0_u64;
-    let ret = match unsafe { libc::read(tfd.as_raw_fd(), &raw mut buf as _, 8) } {
-        ..0 => {
-            let err = io::Error::last_os_error();
+/// Puts the terminal back as it was. An inline clock is left where it was drawn, with the cursor
+/// on the line after it so the prompt comes back below it; leaving the alternate screen, which
+/// it was never on, would send the cursor back over it instead.
+fn restore_terminal(terminal: &mut DefaultTerminal, inline: bool) -> io::Result<()> {
+    execute!(io::stdout(), DisableFocusChange)?;
+    if !inline {
+        ratatui::restore();
+        return Ok(());
+    }
+    let area = terminal.get_frame().area();
+    terminal.set_cursor_position((0, area.bottom().saturating_sub(1)))?;
+    terminal.show_cursor()?;
+    disable_raw_mode()?;
+    println!();
+    Ok(())
+}
 
-            // Check if this was from a discontinuous change to the kernel RT clock
-            if err.raw_os_error() == Some(libc::ECANCELED) {
-                // Clear readiness then re-arm
-                guard.clear_ready();
+/// The clock's events from the terminal, the control socket, signals, and the files it watches,
+/// and the window title and outputs that mirror it.
+struct MainHost {
+    keys: mpsc::UnboundedReceiver<(KeyEvent, Action)>,
+    resizes: mpsc::UnboundedReceiver<()>,
+    focus: mpsc::UnboundedReceiver<bool>,
+    commands: mpsc::UnboundedReceiver<Action>,
+    reloads: mpsc::UnboundedReceiver<()>,
+    record_errors: mpsc::UnboundedReceiver<String>,
+    notify_errors: mpsc::UnboundedReceiver<String>,
+    ics_changes: mpsc::UnboundedReceiver<()>,
+    notes_changes: mpsc::UnboundedReceiver<()>,
+    feed: Option<StdinFeed>,
+    signals: ShutdownSignals,
+    job_signals: JobSignals,
+    /// Shared with the event thread, so `q` typed into the zone picker doesn't end it.
+    picking: Arc<AtomicBool>,
+    /// Rebound keys for the event thread, after the config is reloaded.
+    keymap_tx: std::sync::mpsc::Sender<Keymap>,
+    cli: Rc<Cli>,
+    env_layer: Rc<Layer>,
+    title_clock: Option<TitleClock>,
+    output_file: Option<OutputFile>,
+    output_fifo: Option<OutputFifo>,
+    /// The other terminals from `--output`, each put back as it was when dropped.
+    mirrors: Vec<Mirror>,
+    /// What the `--serve` endpoint answers with.
+    shown: Option<watch::Sender<Shown>>,
+    /// Where `--persist` saves what's being timed.
+    persist_path: Option<PathBuf>,
+}
 
-                arm_tfd_to_every_minute(tfd).context("arm_tfd_to_every_minute failed")?;
-                return Ok(());
-            }
+impl Host for MainHost {
+    async fn next_event(&mut self) -> anyhow::Result<LoopEvent> {
+        let event = tokio::select! {
+            _ = self.signals.recv() => LoopEvent::Quit,
+            _ = self.job_signals.stop.recv() => LoopEvent::Action(Action::Suspend),
+            _ = self.job_signals.cont.recv() => LoopEvent::Continued,
+            key = self.keys.recv() => match key {
+                Some((key_event, action)) => LoopEvent::Key(key_event, action),
+                None => LoopEvent::Quit,
+            },
+            Some(action) = self.commands.recv() => LoopEvent::Action(action),
+            Some(()) = self.resizes.recv() => LoopEvent::Resize,
+            Some(focused) = self.focus.recv() => LoopEvent::Focus(focused),
+            Some(()) = self.reloads.recv() => {
+                let cli = Rc::clone(&self.cli);
+                let env_layer = Rc::clone(&self.env_layer);
+                let keymap_tx = self.keymap_tx.clone();
+                LoopEvent::Update(Box::new(move |app| {
+                    reload_config(app, &cli, &env_layer, &keymap_tx);
+                }))
+            },
+            Some(err) = self.record_errors.recv() => LoopEvent::Notice(err),
+            Some(err) = self.notify_errors.recv() => {
+                notify::ring_bell()?;
+                LoopEvent::Update(Box::new(move |app| app.report_error(&anyhow!(err))))
+            },
+            Some(()) = self.ics_changes.recv() => LoopEvent::Update(Box::new(|app| {
+                if let Some(agenda) = &mut app.agenda {
+                    agenda.reload();
+                }
+            })),
+            Some(()) = self.notes_changes.recv() => LoopEvent::Update(Box::new(|app| {
+                if let Some(notes) = &mut app.notes {
+                    notes.reload();
+                }
+            })),
+            fed = next_fed_action(self.feed.as_mut()) => match fed.context("reading stdin failed")? {
+                Some(Ok(action)) => LoopEvent::Action(action),
+                Some(Err(err)) => LoopEvent::Notice(format!("{err:#}")),
+                None => LoopEvent::Quit,
+            },
+        };
+        Ok(event)
+    }
 
-            Err(err)
-        },
-        0..8 => Err(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "short read on timer fd",
-        )),
-        8 => Ok(()),
-        _ => Err(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "longer than 8 read on timer fd",
-        )),
-    };
+    fn ring_bell(&mut self) -> io::Result<()> {
+        notify::ring_bell()
+    }
+
+    fn suspend(&mut self) -> io::Result<()> {
+        // Put back as on quitting, so the prompt and scrollback look right while stopped
+        let inline = self.cli.inline.is_some();
+        execute!(io::stdout(), DisableFocusChange)?;
+        if inline {
+            disable_raw_mode()?;
+            println!();
+        } else {
+            ratatui::restore();
+        }
+        // SIGTSTP is caught to get here, so it's the stop that can't be that's raised. This
+        // returns once the shell continues the process
+        // SAFETY: raise only sends a signal to this process, and SIGSTOP has no handler to run
+        unsafe { libc::raise(libc::SIGSTOP) };
+        enable_raw_mode()?;
+        if !inline {
+            execute!(io::stdout(), EnterAlternateScreen)?;
+        }
+        execute!(io::stdout(), EnableFocusChange)
+    }
+
+    fn drew(&mut self, app: &mut App, line: &str) {
+        if let Some(shown) = &self.shown {
+            shown.send_replace(Shown {
+                text: line.to_owned(),
+                at: Some(app.shown_moment(app.local_now(), Instant::now()).0),
+                sync: app.sync.as_ref().and_then(SyncWatch::status),
+            });
+        }
+        if let Some(title_clock) = &mut self.title_clock {
+            title_clock.set(line);
+        }
+        if let Some(output) = &mut self.output_file
+            && let Err(err) = output.write(line)
+        {
+            app.notify(
+                format!("writing the output file failed: {err}"),
+                Instant::now(),
+            );
+        }
+        if let Some(output) = &mut self.output_fifo
+            && let Err(err) = output.write(line)
+        {
+            app.notify(
+                format!("writing the output pipe failed: {err}"),
+                Instant::now(),
+            );
+        }
+    }
 
-    guard.clear_ready();
+    fn mirror(&mut self, app: &mut App, local: DateTime<Local>, now: Instant) {
+        // One that's gone, like a closed pty, is dropped and the rest carry on
+        self.mirrors
+            .retain_mut(|mirror| match mirror.draw(app, local, now) {
+                Ok(()) => true,
+                Err(err) => {
+                    let path = mirror.path().display();
+                    app.notify(format!("stopped drawing on {path}: {err}"), Instant::now());
+                    false
+                },
+            });
+    }
+
+    fn persist(&mut self, app: &mut App) {
+        if let Some(path) = &self.persist_path
+            && let Err(err) = persist::save(path, &app.mode, Instant::now())
+        {
+            app.report_error(&err.context("saving the state failed"));
+        }
+    }
 
-    Ok(ret?)
+    fn picking(&mut self, picking: bool) {
+        self.picking.store(picking, Ordering::Relaxed);
+    }
+}
+
+/// Swaps in the settings from the edited config. A broken one leaves the old settings running
+/// until the file is fixed.
+fn reload_config(
+    app: &mut App,
+    cli: &Cli,
+    env_layer: &Layer,
+    keymap_tx: &std::sync::mpsc::Sender<Keymap>,
+) {
+    let path = app
+        .config_path
+        .clone()
+        .expect("only watched when there is a path");
+    match config::load(&path, app.profile.as_deref())
+        .and_then(|config| Settings::new(&config, cli, env_layer, Some(app)))
+    {
+        Ok(settings) => {
+            let _ = keymap_tx.send(settings.keymap.clone());
+            settings.apply(app);
+        },
+        Err(err) => app.report_error(&err.context("config not reloaded")),
+    }
 }
 
-fn draw(frame: &mut Frame) {
-    const TEXTHEIGHT: u16 = 5;
-    let now = Local::now();
-
-    let big_text = BigText::builder()
-        .pixel_size(PixelSize::Full)
-        .lines(&[Line::from(now.format("%I:%M %p").to_string())])
-        .centered()
-        .build();
-    let mut area = frame.area();
-    area.y = (area.height.saturating_sub(TEXTHEIGHT)) / 2;
-    frame.render_widget(big_text, area);
+/// The action for the next line of the `--stdin` feed, or never if there isn't one.
+async fn next_fed_action(
+    feed: Option<&mut StdinFeed>,
+) -> io::Result<Option<anyhow::Result<Action>>> {
+    match feed {
+        Some(feed) => feed.next_action().await,
+        None => std::future::pending().await,
+    }
 }
 
-fn every_minute_timerfd_create() -> anyhow::Result<AsyncFd<OwnedFd>> {
-    let fd = unsafe {
-        libc::timerfd_create(libc::CLOCK_REALTIME, libc::TFD_CLOEXEC | libc::TFD_NONBLOCK)
-    };
-    if fd < 0 {
-        return Err(io::Error::last_os_error().into());
+/// The config's `[[cell]]` clocks for `--grid`, which needs at least one and room for them all.
+fn grid_cells(config: &Config, grid: Grid) -> anyhow::Result<Vec<Cell>> {
+    let cells = config
+        .cells(Instant::now())
+        .context("invalid [[cell]] config")?;
+    if cells.is_empty() {
+        anyhow::bail!("--grid needs [[cell]] entries in the config to lay out");
     }
+    if cells.len() > grid.capacity() {
+        anyhow::bail!(
+            "a {}x{} grid has room for {} cells, not all {} in the config",
+            grid.columns,
+            grid.rows,
+            grid.capacity(),
+            cells.len()
+        );
+    }
+    Ok(cells)
+}
+
+/// Everything taken from the config file, gathered so an edited config can be swapped in while
+/// running. The `TUI_TIME_*` variables and command-line flags still take precedence over the file.
+struct Settings {
+    keymap: Keymap,
+    themes: Vec<Theme>,
+    theme: Theme,
+    presets: Vec<Preset>,
+    chime: Option<Chime>,
+    header: Option<String>,
+    alarms: Vec<Alarm>,
+    reminders: Reminders,
+    day_night: bool,
+    location: Option<Coordinates>,
+    sun_times: bool,
+    zone_label: bool,
+    content: ContentMode,
+    lines: Vec<ClockLine>,
+    no_leading_zero: bool,
+}
+
+impl Settings {
+    /// The settings `config` describes, under the environment's `env_layer`. When reloading,
+    /// `running` is the app being reloaded, whose theme is kept if the config doesn't name one
+    /// and whose alarms keep ringing or snoozing if they are still configured.
+    fn new(
+        config: &Config,
+        cli: &Cli,
+        env_layer: &Layer,
+        running: Option<&App>,
+    ) -> anyhow::Result<Self> {
+        let now = Local::now();
+        let layers = config::resolve(config, env_layer, cli)?;
+        let keymap = Keymap::new(&config.keys).context("invalid [keys] config")?;
+        let mut themes = theme::themes(&config.themes).context("invalid [themes] config")?;
+        let bg = match (cli.bg, &config.bg) {
+            (Some(bg), _) => Some(bg),
+            (None, Some(bg)) => Some(theme::parse_background(bg).context("invalid bg in config")?),
+            (None, None) => None,
+        };
+        // Every theme gets the background, so it lasts through cycle-theme
+        if let Some(bg) = bg {
+            for theme in &mut themes {
+                theme.bg = bg;
+            }
+        }
+        let presets = config.presets().context("invalid [presets] config")?;
+        let chime = config.chime.chime()?;
+        let header = match (config.header.clone(), cli.header) {
+            (Some(header), _) => Some(header),
+            (None, true) => Some(app::DEFAULT_HEADER.to_owned()),
+            (None, false) => None,
+        };
+        if let Some(header) = &header {
+            app::validate_format(header).context("invalid header in config")?;
+        }
+
+        let snooze = cli.snooze.unwrap_or(alarm::DEFAULT_SNOOZE);
+        let mut alarms = config
+            .alarms(snooze, now)
+            .context("invalid [[alarms]] config")?;
+        if let Some(time) = cli.alarm {
+            alarms.push(Alarm::new(time, Weekdays::ALL, None, snooze, now));
+        }
+        if let Some(app) = running {
+            for alarm in &mut alarms {
+                if let Some(old) = app.alarms.iter().find(|old| old.same_schedule(alarm)) {
+                    *alarm = old.clone();
+                }
+            }
+        }
+
+        let reminders = config
+            .reminders(now)
+            .context("invalid [reminders] config")?;
+        let lines = config.lines().context("invalid [[lines]] config")?;
+
+        let name = config
+            .theme
+            .as_deref()
+            .or(running.map(|app| app.theme.name.as_str()));
+        let mut theme = match name {
+            Some(name) if config.theme.is_some() => themes[theme::find(&themes, name)?].clone(),
+            // The running theme may have been removed from the config since
+            Some(name) => {
+                theme::find(&themes, name).map_or_else(|_| Theme::default(), |i| themes[i].clone())
+            },
+            None => Theme::default(),
+        };
+        if let Some(color) = layers.color {
+            theme.fg = color;
+        }
+        if let Some(bg) = bg {
+            theme.bg = bg;
+        }
+
+        let location = match (cli.lat, cli.lon) {
+            (Some(latitude), Some(longitude)) => Some(Coordinates {
+                latitude,
+                longitude,
+            }),
+            _ => config.location,
+        };
+        let sun_times = config.sun_times || cli.sun_times;
+        if sun_times && location.is_none() {
+            anyhow::bail!("sunrise and sunset need a location, from --lat and --lon or the config");
+        }
 
-    let tfd = AsyncFd::new(unsafe { OwnedFd::from_raw_fd(fd) }).context("AsyncFd::new failed")?;
+        Ok(Self {
+            keymap,
+            themes,
+            theme,
+            presets,
+            chime,
+            header,
+            alarms,
+            reminders,
+            day_night: config.day_night,
+            location,
+            sun_times,
+            zone_label: config.zone_label || cli.zone_label,
+            content: match cli.big_date {
+                true => ContentMode::Date,
+                false => config.content,
+            },
+            lines,
+            no_leading_zero: !layers.leading_zero.unwrap_or(true),
+        })
+    }
 
-    arm_tfd_to_every_minute(&tfd).context("arm_tfd_to_every_minute call failed")?;
+    fn apply(self, app: &mut App) {
+        app.keymap = self.keymap;
+        app.themes = self.themes;
+        app.theme = self.theme;
+        if let Mode::Timer(timer) = &mut app.mode {
+            timer.set_presets(self.presets.clone());
+        }
+        app.presets = self.presets;
+        app.chime = self.chime;
+        app.header = self.header;
+        app.alarms = self.alarms;
+        app.reminders.reschedule(self.reminders);
+        app.day_night = self.day_night;
+        app.location = self.location;
+        app.sun_times = self.sun_times;
+        app.zone_label = self.zone_label;
+        app.content = self.content;
+        app.lines = self.lines;
+        app.no_leading_zero = self.no_leading_zero;
+    }
+}
 
-    Ok(tfd)
+/// The signals that end the loop just like `quit` does, so the terminal is restored and laps are
+/// still exported. Ctrl-C arrives as a key in raw mode; SIGINT only comes from `kill`.
+struct ShutdownSignals {
+    interrupt: Signal,
+    terminate: Signal,
+    hangup: Signal,
 }
 
-fn arm_tfd_to_every_minute(tfd: &impl AsRawFd) -> anyhow::Result<()> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .context("SystemTime::duration_since failed")?;
+impl ShutdownSignals {
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
 
-    let now_secs = now.as_secs();
-    let next_minute_secs = (now_secs / 60 + 1) * 60;
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.interrupt.recv() => {},
+            _ = self.terminate.recv() => {},
+            _ = self.hangup.recv() => {},
+        }
+    }
+}
 
-    let new_itimerspec = libc::itimerspec {
-        it_value: libc::timespec {
-            tv_sec: next_minute_secs as libc::time_t,
-            tv_nsec: 0,
-        },
-        it_interval: libc::timespec {
-            tv_sec: 60,
-            tv_nsec: 0,
-        },
-    };
+/// Stops and continues from outside: SIGTSTP, as `kill -TSTP` sends, suspends like Ctrl+Z, and
+/// SIGCONT, however the process was stopped, has the screen drawn again.
+struct JobSignals {
+    stop: Signal,
+    cont: Signal,
+}
 
-    let flags = libc::TFD_TIMER_ABSTIME | libc::TFD_TIMER_CANCEL_ON_SET;
-    if unsafe {
-        libc::timerfd_settime(
-            tfd.as_raw_fd(),
-            flags,
-            &new_itimerspec,
-            std::ptr::null_mut(),
-        )
-    } < 0
-    {
-        Err(io::Error::last_os_error().into())
-    } else {
-        Ok(())
+impl JobSignals {
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            stop: signal(SignalKind::from_raw(libc::SIGTSTP))?,
+            cont: signal(SignalKind::from_raw(libc::SIGCONT))?,
+        })
     }
 }
@@ -0,0 +1,260 @@
+//! `--matrix`: green glyphs raining down the screen behind the clock, a drop per column falling
+//! at its own speed and fading out behind its head.
+
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+/// How often the rain moves. Slower drops move every few of these.
+pub const STEP: Duration = Duration::from_millis(100);
+
+/// What the drops are made of: half-width katakana, as in the film, with digits mixed in.
+const GLYPHS: &[char] = &[
+    'ｱ', 'ｲ', 'ｳ', 'ｴ', 'ｵ', 'ｶ', 'ｷ', 'ｸ', 'ｹ', 'ｺ', 'ｻ', 'ｼ', 'ｽ', 'ｾ', 'ｿ', 'ﾀ', 'ﾁ', 'ﾂ', 'ﾃ',
+    'ﾄ', 'ﾅ', 'ﾆ', 'ﾇ', 'ﾈ', 'ﾉ', 'ﾊ', 'ﾋ', 'ﾌ', 'ﾍ', 'ﾎ', 'ﾏ', 'ﾐ', 'ﾑ', 'ﾒ', 'ﾓ', 'ﾔ', 'ﾕ', 'ﾖ',
+    'ﾗ', 'ﾘ', 'ﾙ', 'ﾚ', 'ﾛ', 'ﾜ', 'ﾝ', '0', '1', '2', '3', '4', '5', '7', '8', '9',
+];
+
+/// The head of a drop, brighter than the trail behind it.
+const HEAD: Color = Color::Rgb(200, 255, 200);
+
+/// A drop falling down one column.
+#[derive(Debug, Clone, Copy)]
+struct Drop {
+    /// The row of its head, above the top while it's yet to fall into view.
+    head: i32,
+    /// How many cells long its trail is, head included.
+    length: u16,
+    /// Falls a row every this many steps.
+    slowness: u8,
+}
+
+/// The rain's state. It fills however many columns the frame has, so the drops follow resizes,
+/// and it's driven by a small xorshift generator rather than a crate for one effect.
+#[derive(Debug)]
+pub struct Matrix {
+    rng: u64,
+    drops: Vec<Drop>,
+    /// The rows the drops fall through, to start new ones just above them.
+    height: u16,
+    steps: u64,
+    next_step: Instant,
+}
+
+impl Matrix {
+    /// Rain seeded with `seed`, first moving a step after `now`.
+    pub fn new(seed: u64, now: Instant) -> Self {
+        Self {
+            // Xorshift never leaves zero
+            rng: seed.max(1),
+            drops: Vec::new(),
+            height: 0,
+            steps: 0,
+            next_step: now + STEP,
+        }
+    }
+
+    /// Fits the rain to a frame `width` by `height`, then moves it along a step if it's time to.
+    /// Like [`Bounce`], steps missed while the loop was busy are skipped.
+    ///
+    /// [`Bounce`]: crate::bounce::Bounce
+    pub fn update(&mut self, width: u16, height: u16, now: Instant) {
+        self.height = height;
+        while self.drops.len() < usize::from(width) {
+            let drop = self.spawn();
+            self.drops.push(drop);
+        }
+        self.drops.truncate(width.into());
+        if now < self.next_step {
+            return;
+        }
+        self.next_step += STEP;
+        if self.next_step <= now {
+            self.next_step = now + STEP;
+        }
+        self.steps += 1;
+        for i in 0..self.drops.len() {
+            let drop = self.drops[i];
+            if !self.steps.is_multiple_of(drop.slowness.into()) {
+                continue;
+            }
+            self.drops[i].head += 1;
+            // Once the tail's fallen off the bottom, a new drop starts from the top
+            if drop.head - i32::from(drop.length) >= i32::from(height) {
+                self.drops[i] = self.spawn();
+            }
+        }
+    }
+
+    /// A new drop somewhere above the top of the screen, so they don't all arrive at once.
+    fn spawn(&mut self) -> Drop {
+        let above = self.next() % (u64::from(self.height) + 1);
+        Drop {
+            head: -(above as i32) - 1,
+            length: 4 + (self.next() % 12) as u16,
+            slowness: 1 + (self.next() % 3) as u8,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    /// The glyph at `x`, `y`. It stays put as the drop passes over it, except at the head,
+    /// which flickers from step to step.
+    fn glyph(&self, x: u16, y: u16, head: bool) -> char {
+        let mut hash = (u64::from(x) << 32 | u64::from(y)) ^ 0x9e37_79b9_7f4a_7c15;
+        if head {
+            hash ^= self.steps.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        }
+        hash = (hash ^ hash >> 33).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^= hash >> 29;
+        GLYPHS[(hash % GLYPHS.len() as u64) as usize]
+    }
+}
+
+/// The color `distance` cells behind a drop's head, fading from bright to dark green down a
+/// trail `length` long. They're drawn as 24-bit colors and mapped down with the rest of the
+/// frame for `--color-depth`, so fewer shades of green are left on smaller palettes.
+fn trail_color(distance: u16, length: u16) -> Color {
+    if distance == 0 {
+        return HEAD;
+    }
+    let fade = f64::from(distance) / f64::from(length);
+    let green = 230.0 - 170.0 * fade;
+    Color::Rgb(0, green as u8, (green / 4.0) as u8)
+}
+
+impl Widget for &Matrix {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (x, drop) in (area.left()..area.right()).zip(&self.drops) {
+            for y in area.top()..area.bottom() {
+                let distance = drop.head - i32::from(y - area.top());
+                if !(0..i32::from(drop.length)).contains(&distance) {
+                    continue;
+                }
+                let distance = distance as u16;
+                let mut style = Style::new().fg(trail_color(distance, drop.length));
+                // With no colors at all, the fading half of the trail is still dimmer
+                if distance == 0 {
+                    style = style.add_modifier(Modifier::BOLD);
+                } else if distance > drop.length / 2 {
+                    style = style.add_modifier(Modifier::DIM);
+                }
+                let glyph = self.glyph(x - area.left(), y - area.top(), distance == 0);
+                buf[(x, y)].set_char(glyph).set_style(style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(matrix: &Matrix, area: Rect) -> Vec<Vec<bool>> {
+        let mut buf = Buffer::empty(area);
+        matrix.render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol() != " ")
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drops_fall_and_start_again_from_the_top() {
+        let now = Instant::now();
+        let mut matrix = Matrix::new(7, now);
+        matrix.update(3, 10, now);
+        let heads: Vec<_> = matrix.drops.iter().map(|drop| drop.head).collect();
+        assert!(heads.iter().all(|&head| head < 0), "{heads:?}");
+
+        // Every drop falls a row within three steps
+        for step in 1..=3 {
+            matrix.update(3, 10, now + STEP * step);
+        }
+        for (drop, head) in matrix.drops.iter().zip(heads) {
+            assert!(drop.head > head);
+        }
+
+        // And given long enough, each falls off the bottom and is replaced from the top
+        for step in 4..200 {
+            matrix.update(3, 10, now + STEP * step);
+            let drop = matrix.drops[0];
+            assert!(drop.head - i32::from(drop.length) <= 10, "{drop:?}");
+        }
+    }
+
+    #[test]
+    fn follows_the_frame_width() {
+        let now = Instant::now();
+        let mut matrix = Matrix::new(1, now);
+        matrix.update(20, 5, now);
+        assert_eq!(matrix.drops.len(), 20);
+        matrix.update(8, 5, now);
+        assert_eq!(matrix.drops.len(), 8);
+
+        // Drawing a wider area than that leaves the rest blank
+        for step in 1..40 {
+            matrix.update(8, 5, now + STEP * step);
+        }
+        let lit = lit(&matrix, Rect::new(0, 0, 12, 5));
+        assert!(lit.iter().any(|row| row[..8].contains(&true)));
+        assert!(lit.iter().all(|row| !row[8..].contains(&true)));
+    }
+
+    #[test]
+    fn fades_down_the_trail() {
+        let now = Instant::now();
+        let mut matrix = Matrix::new(1, now);
+        matrix.drops = vec![Drop {
+            head: 5,
+            length: 4,
+            slowness: 1,
+        }];
+        let area = Rect::new(0, 0, 1, 8);
+        let mut buf = Buffer::empty(area);
+        matrix.render(area, &mut buf);
+        let colors: Vec<_> = (0..8).map(|y| buf[(0, y)].fg).collect();
+        assert_eq!(colors[5], HEAD);
+        assert!(buf[(0, 5)].modifier.contains(Modifier::BOLD));
+        let greens: Vec<_> = colors[2..5]
+            .iter()
+            .map(|color| match color {
+                Color::Rgb(0, g, _) => *g,
+                color => panic!("{color:?}"),
+            })
+            .collect();
+        assert!(greens.is_sorted(), "{greens:?}");
+        // Above the trail and below the head is left alone
+        assert_eq!(colors[1], Color::Reset);
+        assert_eq!(colors[6], Color::Reset);
+        assert!(buf[(0, 2)].modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn the_same_seed_rains_the_same() {
+        let now = Instant::now();
+        let area = Rect::new(0, 0, 16, 6);
+        let rain = |seed| {
+            let mut matrix = Matrix::new(seed, now);
+            for step in 0..20 {
+                matrix.update(area.width, area.height, now + STEP * step);
+            }
+            lit(&matrix, area)
+        };
+        assert_eq!(rain(3), rain(3));
+        assert_ne!(rain(3), rain(4));
+    }
+}
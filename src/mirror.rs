@@ -0,0 +1,212 @@
+//! `--output`: the clock drawn again on other terminals, like the ptys of a wall of displays,
+//! besides the one it runs in. Each is laid out for its own size, and keys are still only read
+//! from the clock's own terminal.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, IsTerminal},
+    mem,
+    os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Local};
+use ratatui::{
+    Terminal, TerminalOptions, Viewport,
+    backend::CrosstermBackend,
+    crossterm::{
+        cursor::{Hide, Show},
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::Rect,
+};
+
+use crate::{app::App, ui};
+
+/// Another terminal the clock is drawn on. Its size is read from the terminal itself before each
+/// frame, since the backend would otherwise ask the one the clock runs in. It's written to without
+/// blocking, so a terminal that stops taking output, like one scrolled back in a paused pager or
+/// stopped with ^S, can't hold up the clock: the frames it can't take are skipped.
+pub struct Mirror {
+    path: PathBuf,
+    /// The terminal again, to read its size from.
+    tty: File,
+    terminal: Terminal<CrosstermBackend<File>>,
+    area: Rect,
+    /// Whether a frame was cut off partway, so what's on the terminal isn't what was last drawn
+    /// and the next frame has to be drawn whole.
+    torn: bool,
+}
+
+impl Mirror {
+    /// Takes over the terminal at `path`, switching it to its alternate screen.
+    pub fn open(path: PathBuf) -> anyhow::Result<Self> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .with_context(|| format!("can't open {}", path.display()))?;
+        if !file.is_terminal() {
+            bail!("{} isn't a terminal", path.display());
+        }
+        let area = size(&file).with_context(|| format!("can't size {}", path.display()))?;
+        let tty = file.try_clone()?;
+        execute!(file, EnterAlternateScreen, Hide)?;
+        let terminal = Terminal::with_options(
+            CrosstermBackend::new(file),
+            TerminalOptions {
+                viewport: Viewport::Fixed(area),
+            },
+        )?;
+        Ok(Self {
+            path,
+            tty,
+            terminal,
+            area,
+            torn: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Draws the screen for `app` as of `local` and `now`, laid out afresh if the terminal has
+    /// been resized since the last frame. A frame the terminal has no room for is skipped.
+    pub fn draw(&mut self, app: &App, local: DateTime<Local>, now: Instant) -> io::Result<()> {
+        match self.try_draw(app, local, now) {
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                tracing::debug!(path = %self.path.display(), "mirror not keeping up, frame skipped");
+                self.torn = true;
+                Ok(())
+            },
+            result => result,
+        }
+    }
+
+    fn try_draw(&mut self, app: &App, local: DateTime<Local>, now: Instant) -> io::Result<()> {
+        let area = size(&self.tty)?;
+        if area != self.area {
+            self.terminal.resize(area)?;
+            self.area = area;
+        }
+        if self.torn {
+            self.terminal.clear()?;
+            self.torn = false;
+        }
+        self.terminal
+            .draw(|frame| ui::draw(frame, app, local, now))?;
+        Ok(())
+    }
+}
+
+/// Puts the terminal back as it was found, when the clock exits or gives up on it. One that has
+/// gone away can't be, which is fine.
+impl Drop for Mirror {
+    fn drop(&mut self) {
+        let _ = execute!(self.terminal.backend_mut(), Show, LeaveAlternateScreen);
+    }
+}
+
+/// The size of the terminal `file` is open on.
+fn size(file: &File) -> io::Result<Rect> {
+    // SAFETY: TIOCGWINSZ fills in the winsize it's given, and nothing else
+    let mut winsize: libc::winsize = unsafe { mem::zeroed() };
+    if unsafe { libc::ioctl(file.as_raw_fd(), libc::TIOCGWINSZ, &mut winsize) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Rect::new(0, 0, winsize.ws_col, winsize.ws_row))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::CStr, io::Read, os::fd::FromRawFd};
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// A new pty of `columns` by `rows`: its controlling end, and the path of the other.
+    fn pty(columns: u16, rows: u16) -> (File, PathBuf) {
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(fd >= 0 && libc::grantpt(fd) == 0 && libc::unlockpt(fd) == 0);
+            let path = PathBuf::from(CStr::from_ptr(libc::ptsname(fd)).to_str().unwrap());
+            let master = File::from_raw_fd(fd);
+            resize(&master, columns, rows);
+            (master, path)
+        }
+    }
+
+    fn resize(master: &File, columns: u16, rows: u16) {
+        let winsize = libc::winsize {
+            ws_row: rows,
+            ws_col: columns,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        assert_eq!(
+            unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &winsize) },
+            0
+        );
+    }
+
+    #[test]
+    fn draws_at_the_terminals_own_size_until_it_goes() {
+        let (mut master, path) = pty(40, 10);
+        let mut mirror = Mirror::open(path).unwrap();
+        assert_eq!(mirror.area, Rect::new(0, 0, 40, 10));
+        let app = App::default();
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 9, 41, 0).unwrap();
+        mirror.draw(&app, local, Instant::now()).unwrap();
+        let mut drawn = vec![0; 1 << 16];
+        let read = master.read(&mut drawn).unwrap();
+        assert!(String::from_utf8_lossy(&drawn[..read]).contains('█'));
+
+        resize(&master, 20, 5);
+        mirror.draw(&app, local, Instant::now()).unwrap();
+        assert_eq!(mirror.area, Rect::new(0, 0, 20, 5));
+
+        // With the other end closed, there's no one to draw for
+        drop(master);
+        assert!(mirror.draw(&app, local, Instant::now()).is_err());
+    }
+
+    #[test]
+    fn a_terminal_that_stops_reading_only_misses_frames() {
+        let (mut master, path) = pty(200, 60);
+        let mut mirror = Mirror::open(path).unwrap();
+        let app = App {
+            seconds: true,
+            ..App::default()
+        };
+        let start = Local.with_ymd_and_hms(2024, 3, 1, 9, 41, 0).unwrap();
+        // Nothing reads the other end, so its buffer fills and the frames after are skipped
+        for second in 0..2000 {
+            let local = start + chrono::Duration::seconds(second);
+            mirror.draw(&app, local, Instant::now()).unwrap();
+            if mirror.torn {
+                break;
+            }
+        }
+        assert!(mirror.torn);
+
+        // Once it's read again, the next frame is drawn whole
+        let mut drawn = vec![0; 1 << 16];
+        while master
+            .read(&mut drawn)
+            .is_ok_and(|read| read == drawn.len())
+        {}
+        mirror.draw(&app, start, Instant::now()).unwrap();
+        assert!(!mirror.torn);
+    }
+
+    #[test]
+    fn only_terminals_are_mirrored_to() {
+        let err = Mirror::open(PathBuf::from("/dev/null")).err().unwrap();
+        assert_eq!(err.to_string(), "/dev/null isn't a terminal");
+    }
+}
@@ -0,0 +1,161 @@
+use std::f64::consts::TAU;
+
+use chrono::{DateTime, Utc};
+
+/// The mean length of a lunar cycle, new moon to new moon, in days.
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+/// A known new moon, 2000-01-06 18:14 UTC, that others are counted from.
+const NEW_MOON_EPOCH: i64 = 947_182_440;
+
+/// The eight named phases, each an eighth of the cycle centered on its namesake moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl Phase {
+    const ALL: [Self; 8] = [
+        Self::New,
+        Self::WaxingCrescent,
+        Self::FirstQuarter,
+        Self::WaxingGibbous,
+        Self::Full,
+        Self::WaningGibbous,
+        Self::LastQuarter,
+        Self::WaningCrescent,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::New => "New moon",
+            Self::WaxingCrescent => "Waxing crescent",
+            Self::FirstQuarter => "First quarter",
+            Self::WaxingGibbous => "Waxing gibbous",
+            Self::Full => "Full moon",
+            Self::WaningGibbous => "Waning gibbous",
+            Self::LastQuarter => "Last quarter",
+            Self::WaningCrescent => "Waning crescent",
+        }
+    }
+
+    /// The phase's emoji, or with `ascii`, a drawing of the lit side in plain characters for
+    /// terminals and fonts that don't give the emoji a consistent width.
+    pub fn glyph(self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (Self::New, false) => "🌑",
+            (Self::WaxingCrescent, false) => "🌒",
+            (Self::FirstQuarter, false) => "🌓",
+            (Self::WaxingGibbous, false) => "🌔",
+            (Self::Full, false) => "🌕",
+            (Self::WaningGibbous, false) => "🌖",
+            (Self::LastQuarter, false) => "🌗",
+            (Self::WaningCrescent, false) => "🌘",
+            (Self::New, true) => "( )",
+            (Self::WaxingCrescent, true) => "  )",
+            (Self::FirstQuarter, true) => " |)",
+            (Self::WaxingGibbous, true) => " 0)",
+            (Self::Full, true) => "(O)",
+            (Self::WaningGibbous, true) => "(0 ",
+            (Self::LastQuarter, true) => "(| ",
+            (Self::WaningCrescent, true) => "(  ",
+        }
+    }
+}
+
+/// The moon as seen at some moment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moon {
+    /// Days since the last new moon.
+    pub age: f64,
+    pub phase: Phase,
+    /// The lit fraction of the disc, from 0 to 1.
+    pub illumination: f64,
+}
+
+impl Moon {
+    /// The moon at `time`, from the mean length of the cycle. The real moon runs up to about
+    /// half a day either side of that, which is close enough for a glyph and a percentage.
+    pub fn at(time: DateTime<Utc>) -> Self {
+        let days = (time.timestamp() - NEW_MOON_EPOCH) as f64 / 86_400.0;
+        let age = days.rem_euclid(SYNODIC_MONTH);
+        let cycle = age / SYNODIC_MONTH;
+        let phase = Phase::ALL[((cycle * 8.0).round() as usize) % 8];
+        Self {
+            age,
+            phase,
+            illumination: (1.0 - (cycle * TAU).cos()) / 2.0,
+        }
+    }
+
+    /// A line like `🌔 Waxing gibbous · 78%`.
+    pub fn line(&self, ascii: bool) -> String {
+        format!(
+            "{} {} · {:.0}%",
+            self.phase.glyph(ascii),
+            self.phase.name(),
+            self.illumination * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moon(at: &str) -> Moon {
+        Moon::at(at.parse().unwrap())
+    }
+
+    /// How far `age` is from `target` days into the cycle, going either way round.
+    fn days_from(age: f64, target: f64) -> f64 {
+        let off = (age - target).rem_euclid(SYNODIC_MONTH);
+        off.min(SYNODIC_MONTH - off)
+    }
+
+    #[test]
+    fn matches_known_new_and_full_moons_within_a_day() {
+        for new in [
+            "2024-01-11T11:57:00Z",
+            "2024-04-08T18:21:00Z",
+            "2024-10-02T18:49:00Z",
+            "2025-01-29T12:36:00Z",
+            "1999-08-11T11:08:00Z",
+        ] {
+            let moon = moon(new);
+            assert!(days_from(moon.age, 0.0) < 1.0, "{new}: {moon:?}");
+            assert_eq!(moon.phase, Phase::New, "{new}");
+            assert!(moon.illumination < 0.02, "{new}: {moon:?}");
+        }
+        for full in [
+            "2024-01-25T17:54:00Z",
+            "2024-08-19T18:26:00Z",
+            "2024-12-15T09:02:00Z",
+            "2025-03-14T06:55:00Z",
+        ] {
+            let moon = moon(full);
+            assert!(
+                days_from(moon.age, SYNODIC_MONTH / 2.0) < 1.0,
+                "{full}: {moon:?}"
+            );
+            assert_eq!(moon.phase, Phase::Full, "{full}");
+            assert!(moon.illumination > 0.98, "{full}: {moon:?}");
+        }
+    }
+
+    #[test]
+    fn names_the_phase_between() {
+        // First quarter was 2024-03-17 04:11 UTC, so four days later it's waxing gibbous
+        let moon = moon("2024-03-21T04:00:00Z");
+        assert_eq!(moon.phase, Phase::WaxingGibbous);
+        assert_eq!(moon.line(false), "🌔 Waxing gibbous · 83%");
+        assert_eq!(moon.line(true), " 0) Waxing gibbous · 83%");
+    }
+}
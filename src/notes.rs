@@ -0,0 +1,100 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How many columns the `--agenda` panel takes unless `--agenda-width` says otherwise.
+pub const DEFAULT_WIDTH: u16 = 32;
+
+/// The `--agenda` notes file shown in a panel beside the clock, read again whenever it changes.
+/// A missing file leaves the panel empty, so it can be created while the clock runs; any other
+/// failure to read it is kept as its error, to show in the panel instead.
+#[derive(Debug)]
+pub struct Notes {
+    path: PathBuf,
+    lines: Result<Vec<String>, String>,
+    /// How many lines are scrolled off the top of the panel.
+    scroll: u16,
+}
+
+impl Notes {
+    pub fn load(path: PathBuf) -> Self {
+        let mut notes = Self {
+            path,
+            lines: Ok(Vec::new()),
+            scroll: 0,
+        };
+        notes.reload();
+        notes
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the file again, keeping the scroll position as far as the new text reaches.
+    pub fn reload(&mut self) {
+        self.lines = match fs::read_to_string(&self.path) {
+            Ok(text) => Ok(text.lines().map(str::to_owned).collect()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(format!("⚠ {}: {err}", self.path.display())),
+        };
+        self.scroll_by(0);
+    }
+
+    /// The lines to show, or the error reading the file.
+    pub fn lines(&self) -> Result<&[String], &str> {
+        self.lines.as_deref().map_err(String::as_str)
+    }
+
+    pub fn scroll(&self) -> u16 {
+        self.scroll
+    }
+
+    /// Scrolls down `lines` lines, or up for negative ones, stopping with the last line at the
+    /// top.
+    pub fn scroll_by(&mut self, lines: i16) {
+        let last = self
+            .lines
+            .as_ref()
+            .map_or(0, |lines| lines.len())
+            .saturating_sub(1);
+        let last = u16::try_from(last).unwrap_or(u16::MAX);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloads_and_keeps_scrolling_within_the_text() {
+        let path = std::env::temp_dir().join(format!("tui-time-notes-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut notes = Notes::load(path.clone());
+        assert_eq!(notes.lines(), Ok(&[][..]));
+
+        fs::write(&path, "Standup 9:30\nReview PRs\nLunch\n").unwrap();
+        notes.reload();
+        assert_eq!(
+            notes.lines().unwrap(),
+            ["Standup 9:30", "Review PRs", "Lunch"]
+        );
+        notes.scroll_by(5);
+        assert_eq!(notes.scroll(), 2);
+        notes.scroll_by(-1);
+        assert_eq!(notes.scroll(), 1);
+        notes.scroll_by(-3);
+        assert_eq!(notes.scroll(), 0);
+
+        notes.scroll_by(2);
+        fs::write(&path, "Lunch\n").unwrap();
+        notes.reload();
+        assert_eq!(notes.scroll(), 0);
+
+        fs::remove_file(&path).unwrap();
+        notes.reload();
+        assert_eq!(notes.lines(), Ok(&[][..]));
+    }
+}
@@ -0,0 +1,153 @@
+//! `--notify`: how the clock gets attention when a countdown finishes or an alarm rings, for when
+//! the terminal isn't in view.
+
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use tokio::sync::mpsc;
+
+/// What a desktop notification says unless `--notify-message` says otherwise.
+pub const DEFAULT_MESSAGE: &str = "{event}";
+
+/// How to notify, as `--notify` spells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum NotifyMethod {
+    /// Ring the terminal bell, which many terminals turn into an urgency hint when unfocused.
+    #[default]
+    Bell,
+    /// Send a desktop notification, or ring the bell when that fails.
+    Desktop,
+    /// Neither; the screen still flashes.
+    None,
+}
+
+/// Notifies of alarms and finished timers by the chosen method. Bells are left for the main loop
+/// to ring after drawing, and desktop notifications go to the task running [`send_desktop`], so
+/// the UI never waits on the notification daemon.
+#[derive(Debug, Default)]
+pub struct Notifier {
+    method: NotifyMethod,
+    /// The message, with `{event}` standing for what happened.
+    message: String,
+    desktop: Option<mpsc::UnboundedSender<String>>,
+    bell: bool,
+}
+
+impl Notifier {
+    /// A notifier that rings the bell or stays quiet. Desktop notifications need [`desktop`].
+    ///
+    /// [`desktop`]: Self::desktop
+    pub fn new(method: NotifyMethod) -> Self {
+        Self {
+            method,
+            ..Self::default()
+        }
+    }
+
+    /// A notifier sending desktop notifications saying `message`, and the queue they arrive on,
+    /// for [`send_desktop`].
+    pub fn desktop(message: String) -> (Self, mpsc::UnboundedReceiver<String>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let notifier = Self {
+            method: NotifyMethod::Desktop,
+            message,
+            desktop: Some(tx),
+            bell: false,
+        };
+        (notifier, rx)
+    }
+
+    /// Notifies that `event` happened, like `Alarm: 07:30 Standup`. A desktop notification that
+    /// can't be queued, because the task sending them has gone, rings the bell instead.
+    pub fn notify(&mut self, event: &str) {
+        match self.method {
+            NotifyMethod::Bell => self.bell = true,
+            NotifyMethod::Desktop => {
+                let message = self.message.replace("{event}", event);
+                let sent = self
+                    .desktop
+                    .as_ref()
+                    .is_some_and(|tx| tx.send(message).is_ok());
+                self.bell |= !sent;
+            },
+            NotifyMethod::None => {},
+        }
+    }
+
+    /// Whether a bell is due, handing it out only once.
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell)
+    }
+}
+
+/// Shows each message that arrives on `messages` as a desktop notification, on the blocking pool.
+/// Failures, like there being no notification daemon, are sent to `errors` as messages for the
+/// status line, for the main loop to ring the bell instead.
+pub async fn send_desktop(
+    mut messages: mpsc::UnboundedReceiver<String>,
+    errors: mpsc::UnboundedSender<String>,
+) {
+    while let Some(message) = messages.recv().await {
+        let result = tokio::task::spawn_blocking(move || show(&message)).await;
+        let err = match result {
+            Ok(Ok(())) => continue,
+            Ok(Err(err)) => format!("{err:#}"),
+            Err(err) => err.to_string(),
+        };
+        if errors
+            .send(format!("desktop notification failed: {err}"))
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(feature = "desktop-notifications")]
+fn show(message: &str) -> anyhow::Result<()> {
+    notify_rust::Notification::new()
+        .appname("tui-time")
+        .summary("tui-time")
+        .body(message)
+        .show()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "desktop-notifications"))]
+fn show(_message: &str) -> anyhow::Result<()> {
+    anyhow::bail!("built without desktop notification support")
+}
+
+/// Rings the terminal bell.
+pub fn ring_bell() -> io::Result<()> {
+    let mut out = io::stdout();
+    out.write_all(b"\x07")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatches_on_the_method() {
+        let mut bell = Notifier::new(NotifyMethod::Bell);
+        bell.notify("Timer done: 5m");
+        assert!(bell.take_bell());
+        assert!(!bell.take_bell());
+
+        let mut quiet = Notifier::new(NotifyMethod::None);
+        quiet.notify("Timer done: 5m");
+        assert!(!quiet.take_bell());
+
+        let (mut desktop, mut messages) = Notifier::desktop("tea: {event}".to_owned());
+        desktop.notify("Timer done: 5m");
+        assert!(!desktop.take_bell());
+        assert_eq!(messages.try_recv().unwrap(), "tea: Timer done: 5m");
+
+        // With nothing left to send them, it falls back to the bell
+        drop(messages);
+        desktop.notify("Alarm: 07:30");
+        assert!(desktop.take_bell());
+    }
+}
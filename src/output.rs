@@ -0,0 +1,212 @@
+//! `--output-file` and `--output-fifo`: the clock's text, written out as it's drawn for status
+//! bars and overlays to show.
+
+use std::{
+    ffi::CString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    os::unix::{ffi::OsStrExt, fs::FileTypeExt, fs::OpenOptionsExt},
+    path::{Path, PathBuf},
+};
+
+/// Keeps a file holding the clock's text, replacing it whole each time it changes so a reader
+/// never sees half of one: the text goes to a temporary file beside it, which is renamed over it.
+/// The file is left behind on exit, holding the last time shown.
+#[derive(Debug)]
+pub struct OutputFile {
+    path: PathBuf,
+    temp: PathBuf,
+    /// The text last written, so frames that don't change it don't touch the disk.
+    written: Option<String>,
+}
+
+impl OutputFile {
+    pub fn new(path: PathBuf) -> Self {
+        let mut name = path.file_name().unwrap_or_default().to_owned();
+        name.push(format!(".{}.tmp", std::process::id()));
+        Self {
+            temp: path.with_file_name(name),
+            path,
+            written: None,
+        }
+    }
+
+    /// Replaces the file's contents with `text` and a newline, unless that's what it holds. A
+    /// write that fails is tried again on the next call.
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        if self.written.as_deref() == Some(text) {
+            return Ok(());
+        }
+        fs::write(&self.temp, format!("{text}\n"))?;
+        fs::rename(&self.temp, &self.path)?;
+        self.written = Some(text.to_owned());
+        Ok(())
+    }
+}
+
+/// Writes the clock's text to a named pipe as a line at a time, without ever waiting on the
+/// reader. The pipe is opened when there's a reader to open it for, and lines written while
+/// there isn't one, or while the reader is too far behind to take another, are dropped. A reader
+/// that goes away is noticed on the next line, and the next reader to come along gets the lines
+/// from then on. Lines are written whole, since they're far shorter than the pipe's buffer.
+#[derive(Debug)]
+pub struct OutputFifo {
+    path: PathBuf,
+    pipe: Option<File>,
+    /// Whether the pipe was made for the clock, and so is removed when it's dropped.
+    created: bool,
+}
+
+impl OutputFifo {
+    /// Writes to the named pipe at `path`, making it if there's nothing there.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let created = match fs::metadata(&path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => false,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "it exists and isn't a named pipe",
+                ));
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                mkfifo(&path)?;
+                true
+            },
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            path,
+            pipe: None,
+            created,
+        })
+    }
+
+    /// Writes `text` as a line if a reader is there to take it.
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        let pipe = match &mut self.pipe {
+            Some(pipe) => pipe,
+            None => {
+                let opened = OpenOptions::new()
+                    .write(true)
+                    .custom_flags(libc::O_NONBLOCK)
+                    .open(&self.path);
+                match opened {
+                    Ok(pipe) => self.pipe.insert(pipe),
+                    // No reader has it open
+                    Err(err) if err.raw_os_error() == Some(libc::ENXIO) => return Ok(()),
+                    Err(err) => return Err(err),
+                }
+            },
+        };
+        match pipe.write(format!("{text}\n").as_bytes()) {
+            Ok(_) => Ok(()),
+            // The reader isn't keeping up
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            // The reader has gone
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {
+                self.pipe = None;
+                Ok(())
+            },
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl Drop for OutputFifo {
+    fn drop(&mut self) {
+        if self.created {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn mkfifo(path: &Path) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `path` is a valid NUL-terminated string
+    if unsafe { libc::mkfifo(path.as_ptr(), 0o644) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tui-time-{name}-{}", std::process::id()))
+    }
+
+    fn reader(path: &Path) -> File {
+        OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)
+            .unwrap()
+    }
+
+    fn read_all(reader: &mut File) -> String {
+        let mut buf = [0; 256];
+        match reader.read(&mut buf) {
+            Ok(n) => String::from_utf8(buf[..n].to_vec()).unwrap(),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => String::new(),
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    #[test]
+    fn replaces_the_file_whole() {
+        let path = temp_path("output.txt");
+        let mut output = OutputFile::new(path.clone());
+        output.write("12:00").unwrap();
+        output.write("12:01").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "12:01\n");
+        assert!(!output.temp.exists());
+
+        // The same text again leaves the file alone
+        fs::remove_file(&path).unwrap();
+        output.write("12:01").unwrap();
+        assert!(!path.exists());
+        output.write("12:02").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "12:02\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn fifo_lines_go_to_whichever_reader_is_there() {
+        let path = temp_path("output.fifo");
+        let mut fifo = OutputFifo::open(path.clone()).unwrap();
+        assert!(fs::metadata(&path).unwrap().file_type().is_fifo());
+
+        // Nobody reading: dropped, without blocking
+        fifo.write("12:00").unwrap();
+
+        let mut first = reader(&path);
+        fifo.write("12:01").unwrap();
+        fifo.write("12:02").unwrap();
+        assert_eq!(read_all(&mut first), "12:01\n12:02\n");
+
+        // The reader leaves, and the lines until the next one arrives are dropped
+        drop(first);
+        fifo.write("12:03").unwrap();
+        fifo.write("12:04").unwrap();
+        let mut second = reader(&path);
+        assert_eq!(read_all(&mut second), "");
+        fifo.write("12:05").unwrap();
+        assert_eq!(read_all(&mut second), "12:05\n");
+
+        // A pipe made for the clock goes with it
+        drop(fifo);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn fifo_refuses_other_files() {
+        let path = temp_path("not-a-fifo");
+        fs::write(&path, "").unwrap();
+        assert!(OutputFifo::open(path.clone()).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+use clap::ValueEnum;
+use ratatui::{buffer::Buffer, style::Color};
+
+/// The 16 ANSI colors as xterm draws them by default, in palette order.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// The levels each channel of the 256-color palette's 6×6×6 cube takes.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors the terminal can show, as `--color-depth` spells it. Colors beyond it are
+/// drawn as the nearest it has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ColorDepth {
+    /// Work it out from `$COLORTERM` and `$TERM`. Left unresolved, colors are drawn as given.
+    #[default]
+    Auto,
+    /// Any 24-bit color.
+    Truecolor,
+    /// The 256-color palette.
+    #[value(name = "256")]
+    Ansi256,
+    /// The 16 ANSI colors, as on the Linux console.
+    #[value(name = "16")]
+    Ansi16,
+    /// No colors at all, only the terminal's own foreground and background.
+    Mono,
+}
+
+impl ColorDepth {
+    /// The depth `$COLORTERM` and `$TERM` point to, if this is `Auto`.
+    pub fn resolve(self, colorterm: &str, term: &str) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        match term {
+            _ if matches!(colorterm, "truecolor" | "24bit") => Self::Truecolor,
+            "" | "dumb" => Self::Mono,
+            term if term.contains("256color") => Self::Ansi256,
+            _ => Self::Ansi16,
+        }
+    }
+
+    /// The nearest color to `color` at this depth.
+    pub fn map(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Auto | Self::Truecolor, color) => color,
+            (_, Color::Reset) => Color::Reset,
+            (Self::Mono, _) => Color::Reset,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (Self::Ansi256, color) => color,
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest_16((r, g, b)),
+            (Self::Ansi16, Color::Indexed(i)) => nearest_16(indexed_to_rgb(i)),
+            (Self::Ansi16, color) => color,
+        }
+    }
+
+    /// Brings every cell of `buf` within this depth.
+    pub fn apply(self, buf: &mut Buffer) {
+        if matches!(self, Self::Auto | Self::Truecolor) {
+            return;
+        }
+        for cell in &mut buf.content {
+            cell.fg = self.map(cell.fg);
+            cell.bg = self.map(cell.bg);
+        }
+    }
+}
+
+/// The nearest color in the 256-color palette: in its 6×6×6 cube, or on its grayscale ramp for
+/// colors close to gray.
+pub fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| match c {
+        0..48 => 0,
+        48..115 => 1,
+        c => (c - 35) / 40,
+    };
+    let (lr, lg, lb) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * lr + 6 * lg + lb;
+    let cube_rgb = (
+        CUBE_LEVELS[usize::from(lr)],
+        CUBE_LEVELS[usize::from(lg)],
+        CUBE_LEVELS[usize::from(lb)],
+    );
+
+    // The ramp runs from 8 to 238 in steps of 10
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * step;
+    if distance((r, g, b), (gray, gray, gray)) < distance((r, g, b), cube_rgb) {
+        232 + step
+    } else {
+        cube
+    }
+}
+
+/// The color that entry `i` of the 256-color palette stands for.
+pub fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..16 => ANSI16[usize::from(i)].1,
+        16..232 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[usize::from(i / 36)],
+                CUBE_LEVELS[usize::from(i / 6 % 6)],
+                CUBE_LEVELS[usize::from(i % 6)],
+            )
+        },
+        _ => {
+            let gray = 8 + 10 * (i - 232);
+            (gray, gray, gray)
+        },
+    }
+}
+
+/// The nearest of the 16 ANSI colors.
+pub fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(Color::Reset, |&(color, _)| color)
+}
+
+/// The squared distance between two colors.
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        layout::Rect,
+        style::{Modifier, Style},
+    };
+
+    use super::*;
+
+    #[test]
+    fn maps_to_the_256_color_palette() {
+        assert_eq!(rgb_to_256(255, 0, 0), 196);
+        assert_eq!(rgb_to_256(0, 128, 255), 33);
+        assert_eq!(rgb_to_256(0, 0, 0), 16);
+        assert_eq!(rgb_to_256(255, 255, 255), 231);
+        // Grays go to the ramp, which has finer steps than the cube
+        assert_eq!(rgb_to_256(128, 128, 128), 244);
+        assert_eq!(rgb_to_256(30, 30, 32), 234);
+
+        assert_eq!(indexed_to_rgb(196), (255, 0, 0));
+        assert_eq!(indexed_to_rgb(244), (128, 128, 128));
+        assert_eq!(indexed_to_rgb(9), (255, 0, 0));
+        for i in 16..=255 {
+            let (r, g, b) = indexed_to_rgb(i);
+            assert_eq!(rgb_to_256(r, g, b), i, "palette entry {i}");
+        }
+    }
+
+    #[test]
+    fn maps_down_to_16_colors() {
+        assert_eq!(nearest_16((250, 10, 10)), Color::LightRed);
+        assert_eq!(nearest_16((180, 20, 10)), Color::Red);
+        assert_eq!(nearest_16((120, 120, 130)), Color::DarkGray);
+        assert_eq!(nearest_16((20, 20, 20)), Color::Black);
+
+        let depth = ColorDepth::Ansi16;
+        assert_eq!(depth.map(Color::Rgb(255, 165, 0)), Color::Yellow);
+        assert_eq!(depth.map(Color::Indexed(46)), Color::LightGreen);
+        assert_eq!(depth.map(Color::Indexed(4)), Color::Blue);
+        assert_eq!(depth.map(Color::Cyan), Color::Cyan);
+        assert_eq!(
+            ColorDepth::Ansi256.map(Color::Rgb(255, 0, 0)),
+            Color::Indexed(196)
+        );
+        assert_eq!(ColorDepth::Ansi256.map(Color::Green), Color::Green);
+    }
+
+    #[test]
+    fn mono_strips_every_color() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+        buf.set_style(
+            buf.area,
+            Style::new()
+                .fg(Color::Rgb(255, 200, 0))
+                .bg(Color::Blue)
+                .bold(),
+        );
+        ColorDepth::Mono.apply(&mut buf);
+        for cell in &buf.content {
+            assert_eq!((cell.fg, cell.bg), (Color::Reset, Color::Reset));
+            assert!(cell.modifier.contains(Modifier::BOLD));
+        }
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        buf.set_style(buf.area, Style::new().fg(Color::Rgb(255, 200, 0)));
+        ColorDepth::Truecolor.apply(&mut buf);
+        assert_eq!(buf.content[0].fg, Color::Rgb(255, 200, 0));
+    }
+
+    #[test]
+    fn detects_the_depth_from_the_environment() {
+        let auto = ColorDepth::Auto;
+        assert_eq!(
+            auto.resolve("truecolor", "xterm-256color"),
+            ColorDepth::Truecolor
+        );
+        assert_eq!(auto.resolve("24bit", "linux"), ColorDepth::Truecolor);
+        assert_eq!(auto.resolve("", "xterm-256color"), ColorDepth::Ansi256);
+        assert_eq!(auto.resolve("", "linux"), ColorDepth::Ansi16);
+        assert_eq!(auto.resolve("", "dumb"), ColorDepth::Mono);
+        assert_eq!(auto.resolve("", ""), ColorDepth::Mono);
+        assert_eq!(
+            ColorDepth::Ansi16.resolve("truecolor", "xterm-256color"),
+            ColorDepth::Ansi16
+        );
+    }
+}
@@ -0,0 +1,485 @@
+//! `--persist`: whatever's being timed, saved after every change and on exit so a terminal or SSH
+//! session dying doesn't lose it, and offered back the next time the clock starts.
+//!
+//! Monotonic instants mean nothing to another process, so a snapshot holds each reading as it
+//! stood when saved (a stopwatch's elapsed time, a countdown's remainder, the overtime since one
+//! ran out) along with when that was. On resuming, whatever was running is moved on by the time
+//! away. Within the same boot that's read off `CLOCK_BOOTTIME`, which clock steps don't touch and
+//! suspends don't stop, so it's exact. After a reboot only the wall clock is left to go by, and it
+//! is taken at its word, except that a clock stepped back to before the snapshot counts as no
+//! time away rather than as time undone.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::Mode,
+    countdown::{Countdown, Preset, Timer, TimerState},
+    duration::millis,
+    stopwatch::{Lap, Stopwatch},
+    timer::clock_now,
+};
+
+/// Where the kernel keeps an id that's new each boot.
+const BOOT_ID: &str = "/proc/sys/kernel/random/boot_id";
+
+/// `$XDG_STATE_HOME/tui-time`, falling back to `~/.local/state` when unset, where whatever the
+/// clock keeps from one run to the next is saved.
+pub fn state_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => Path::new(&env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+    Some(dir.join("tui-time"))
+}
+
+/// `state.json` in the [`state_dir`].
+pub fn default_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("state.json"))
+}
+
+/// Which boot the machine is in, and how long it's been up, suspends included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Boot {
+    #[serde(rename = "boot_id")]
+    pub id: String,
+    #[serde(rename = "uptime_ms", with = "millis")]
+    pub uptime: Duration,
+}
+
+impl Boot {
+    /// The current boot, if the kernel says which it is.
+    pub fn now() -> Option<Self> {
+        let id = fs::read_to_string(BOOT_ID).ok()?.trim().to_owned();
+        let uptime = clock_now(libc::CLOCK_BOOTTIME).ok()?;
+        Some(Self { id, uptime })
+    }
+}
+
+/// Where a started timer stood when saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum SavedTimer {
+    Running {
+        #[serde(rename = "remaining_ms", with = "millis")]
+        remaining: Duration,
+    },
+    Paused {
+        #[serde(rename = "remaining_ms", with = "millis")]
+        remaining: Duration,
+    },
+    Expired {
+        #[serde(rename = "overtime_ms", with = "millis")]
+        overtime: Duration,
+        acknowledged: bool,
+    },
+}
+
+/// What was being timed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum Timing {
+    Stopwatch {
+        #[serde(rename = "elapsed_ms", with = "millis")]
+        elapsed: Duration,
+        running: bool,
+        #[serde(default)]
+        laps: Vec<Lap>,
+    },
+    Timer {
+        #[serde(flatten)]
+        timer: SavedTimer,
+        /// The duration it was last started with, for restarting it.
+        #[serde(
+            rename = "started_ms",
+            default,
+            skip_serializing_if = "Option::is_none",
+            with = "millis::option"
+        )]
+        last_started: Option<Duration>,
+    },
+}
+
+/// What was being timed, and when and in which boot it was saved, as JSON like
+/// `{"saved_at":"2024-03-01T14:41:00Z","mode":"stopwatch","elapsed_ms":872000,"running":true,
+/// "laps":[]}`. Fields it doesn't know are skipped when read, so a snapshot from a later version
+/// still resumes as far as this one understands it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub saved_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub boot: Option<Boot>,
+    #[serde(flatten)]
+    pub timing: Timing,
+}
+
+impl Snapshot {
+    /// `mode` as it stands at `now`, if it's timing anything worth resuming: a stopwatch, or a
+    /// timer that's been started.
+    pub fn take(
+        mode: &Mode,
+        now: Instant,
+        saved_at: DateTime<Utc>,
+        boot: Option<Boot>,
+    ) -> Option<Self> {
+        let timing = match mode {
+            Mode::Stopwatch(stopwatch) => Timing::Stopwatch {
+                elapsed: stopwatch.elapsed(now),
+                running: stopwatch.is_running(),
+                laps: stopwatch.laps().to_vec(),
+            },
+            Mode::Timer(timer) => {
+                let saved = match timer.state() {
+                    TimerState::Idle => return None,
+                    TimerState::Running(Countdown::Running { deadline }) => SavedTimer::Running {
+                        remaining: deadline.saturating_duration_since(now),
+                    },
+                    TimerState::Running(Countdown::Paused { remaining }) => {
+                        SavedTimer::Paused { remaining }
+                    },
+                    TimerState::Expired { at, acknowledged } => SavedTimer::Expired {
+                        overtime: now.saturating_duration_since(at),
+                        acknowledged,
+                    },
+                };
+                Timing::Timer {
+                    timer: saved,
+                    last_started: timer.last_started(),
+                }
+            },
+            _ => return None,
+        };
+        Some(Self {
+            saved_at,
+            boot,
+            timing,
+        })
+    }
+
+    /// How long it's been since the snapshot was saved, given the wall clock's `wall` and the
+    /// current `boot`: exactly, by the boot clock, if the machine hasn't restarted since, and by
+    /// the wall clock otherwise, never less than nothing.
+    pub fn away(&self, wall: DateTime<Utc>, boot: Option<&Boot>) -> Duration {
+        match (&self.boot, boot) {
+            (Some(then), Some(now)) if then.id == now.id => now.uptime.saturating_sub(then.uptime),
+            _ => (wall - self.saved_at).to_std().unwrap_or_default(),
+        }
+    }
+
+    /// The mode to pick up at `now`, `away` after the snapshot, with whatever was running moved
+    /// on by that. A countdown that ran out meanwhile is expired as of when it would have.
+    pub fn resume(&self, away: Duration, now: Instant, presets: Vec<Preset>) -> Mode {
+        let before_now = |by: Duration| now.checked_sub(by).unwrap_or(now);
+        match &self.timing {
+            Timing::Stopwatch {
+                elapsed,
+                running,
+                laps,
+            } => {
+                let elapsed = match running {
+                    true => *elapsed + away,
+                    false => *elapsed,
+                };
+                Mode::Stopwatch(Stopwatch::resumed(elapsed, *running, laps.clone(), now))
+            },
+            Timing::Timer {
+                timer,
+                last_started,
+            } => {
+                let state = match *timer {
+                    SavedTimer::Running { remaining } if remaining > away => {
+                        TimerState::Running(Countdown::start(remaining - away, now))
+                    },
+                    SavedTimer::Running { remaining } => TimerState::Expired {
+                        at: before_now(away - remaining),
+                        acknowledged: false,
+                    },
+                    SavedTimer::Paused { remaining } => {
+                        TimerState::Running(Countdown::Paused { remaining })
+                    },
+                    SavedTimer::Expired {
+                        overtime,
+                        acknowledged,
+                    } => TimerState::Expired {
+                        at: before_now(overtime + away),
+                        acknowledged,
+                    },
+                };
+                Mode::Timer(Timer::resumed(presets, state, *last_started))
+            },
+        }
+    }
+}
+
+/// What a mode is timing as it was set, rather than as it reads at any one moment: a stopwatch as
+/// started, paused and lapped, or a timer's state. It changes only when a snapshot has to be saved
+/// again, whether for a key or for a timer running out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fingerprint {
+    Stopwatch(Stopwatch),
+    Timer {
+        state: TimerState,
+        last_started: Option<Duration>,
+    },
+}
+
+impl Fingerprint {
+    /// The fingerprint of `mode`, if it's timing anything.
+    pub fn of(mode: &Mode) -> Option<Self> {
+        match mode {
+            Mode::Stopwatch(stopwatch) => Some(Self::Stopwatch(stopwatch.clone())),
+            Mode::Timer(timer) => Some(Self::Timer {
+                state: timer.state(),
+                last_started: timer.last_started(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Saves what `mode` is timing at `now` to `path`, or removes what was saved there if it's timing
+/// nothing. The snapshot is written alongside and moved into place, so a crash partway through
+/// leaves the last one whole.
+pub fn save(path: &Path, mode: &Mode, now: Instant) -> anyhow::Result<()> {
+    let Some(snapshot) = Snapshot::take(mode, now, Utc::now(), Boot::now()) else {
+        return match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("removing {}", path.display()))
+            },
+            _ => Ok(()),
+        };
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let partial = path.with_extension("json.tmp");
+    fs::write(&partial, serde_json::to_string(&snapshot)?)
+        .with_context(|| format!("writing {}", partial.display()))?;
+    fs::rename(&partial, path).with_context(|| format!("writing {}", path.display()))
+}
+
+/// The snapshot saved at `path`, if there is one.
+pub fn load(path: &Path) -> anyhow::Result<Option<Snapshot>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+    };
+    serde_json::from_str(&text)
+        .with_context(|| format!("in {}", path.display()))
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn saved_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 14, 41, 0).unwrap()
+    }
+
+    fn boot(id: &str, uptime: u64) -> Boot {
+        Boot {
+            id: id.to_owned(),
+            uptime: secs(uptime),
+        }
+    }
+
+    fn snapshot(timing: Timing) -> Snapshot {
+        Snapshot {
+            saved_at: saved_at(),
+            boot: Some(boot("4a1c", 3600)),
+            timing,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let laps = vec![
+            Lap {
+                split: Duration::from_millis(61_250),
+                total: Duration::from_millis(61_250),
+            },
+            Lap {
+                split: secs(30),
+                total: Duration::from_millis(91_250),
+            },
+        ];
+        let stopwatch = snapshot(Timing::Stopwatch {
+            elapsed: secs(872),
+            running: true,
+            laps,
+        });
+        let json = serde_json::to_string(&stopwatch).unwrap();
+        assert_eq!(
+            json,
+            "{\"saved_at\":\"2024-03-01T14:41:00Z\",\"boot_id\":\"4a1c\",\
+             \"uptime_ms\":3600000,\"mode\":\"stopwatch\",\"elapsed_ms\":872000,\
+             \"running\":true,\"laps\":[{\"split_ms\":61250,\"total_ms\":61250},\
+             {\"split_ms\":30000,\"total_ms\":91250}]}"
+        );
+        assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), stopwatch);
+
+        for timer in [
+            SavedTimer::Running {
+                remaining: secs(240),
+            },
+            SavedTimer::Paused {
+                remaining: secs(90),
+            },
+            SavedTimer::Expired {
+                overtime: secs(5),
+                acknowledged: true,
+            },
+        ] {
+            for last_started in [Some(secs(300)), None] {
+                let snapshot = Snapshot {
+                    boot: None,
+                    ..snapshot(Timing::Timer {
+                        timer,
+                        last_started,
+                    })
+                };
+                let json = serde_json::to_string(&snapshot).unwrap();
+                assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+            }
+        }
+    }
+
+    #[test]
+    fn skips_fields_from_later_versions() {
+        let json = "{\"version\":3,\"saved_at\":\"2024-03-01T15:41:00.000+01:00\",\
+                    \"mode\":\"timer\",\"state\":\"paused\",\"remaining_ms\":90000,\
+                    \"label\":\"tea\",\"sounds\":{\"end\":[\"bell\",null]}}";
+        assert_eq!(
+            serde_json::from_str::<Snapshot>(json).unwrap(),
+            Snapshot {
+                boot: None,
+                ..snapshot(Timing::Timer {
+                    timer: SavedTimer::Paused {
+                        remaining: secs(90)
+                    },
+                    last_started: None,
+                })
+            }
+        );
+
+        // But not ones it can't do without
+        for (json, expected) in [
+            (
+                "{\"saved_at\":\"2024-03-01T14:41:00Z\",\"mode\":\"chess\"}",
+                "unknown variant `chess`",
+            ),
+            (
+                "{\"saved_at\":\"2024-03-01T14:41:00Z\",\"mode\":\"stopwatch\",\"running\":true}",
+                "missing field `elapsed_ms`",
+            ),
+        ] {
+            let err = serde_json::from_str::<Snapshot>(json).unwrap_err();
+            assert!(err.to_string().starts_with(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn time_away_trusts_the_boot_clock_over_the_wall_clock() {
+        let snapshot = snapshot(Timing::Stopwatch {
+            elapsed: secs(0),
+            running: true,
+            laps: Vec::new(),
+        });
+        let later = |secs: i64| saved_at() + chrono::Duration::seconds(secs);
+        // Same boot: the wall clock stepped an hour while closed doesn't matter
+        let same = boot("4a1c", 3660);
+        assert_eq!(snapshot.away(later(3660), Some(&same)), secs(60));
+        assert_eq!(snapshot.away(later(-3600), Some(&same)), secs(60));
+        // Rebooted since, only the wall clock can say, and going back counts as nothing
+        let rebooted = boot("77e0", 30);
+        assert_eq!(snapshot.away(later(600), Some(&rebooted)), secs(600));
+        assert_eq!(snapshot.away(later(-600), Some(&rebooted)), Duration::ZERO);
+        assert_eq!(snapshot.away(later(45), None), secs(45));
+    }
+
+    #[test]
+    fn resumes_moved_on_by_the_time_away() {
+        let now = Instant::now();
+        let stopwatch = snapshot(Timing::Stopwatch {
+            elapsed: secs(872),
+            running: true,
+            laps: Vec::new(),
+        });
+        let Mode::Stopwatch(resumed) = stopwatch.resume(secs(60), now, Vec::new()) else {
+            panic!("not a stopwatch");
+        };
+        assert_eq!(resumed.elapsed(now), secs(932));
+        assert!(resumed.is_running());
+
+        let timer = |timer| {
+            let snapshot = snapshot(Timing::Timer {
+                timer,
+                last_started: Some(secs(300)),
+            });
+            let Mode::Timer(resumed) = snapshot.resume(secs(60), now, Vec::new()) else {
+                panic!("not a timer");
+            };
+            resumed
+        };
+        let running = timer(SavedTimer::Running {
+            remaining: secs(240),
+        });
+        assert_eq!(running.remaining(now), secs(180));
+        assert_eq!(running.last_started(), Some(secs(300)));
+        let paused = timer(SavedTimer::Paused {
+            remaining: secs(240),
+        });
+        assert_eq!(paused.remaining(now), secs(240));
+        // It ran out while closed, and has been over since
+        let ran_out = timer(SavedTimer::Running {
+            remaining: secs(20),
+        });
+        assert_eq!(ran_out.overtime(now), Some(secs(40)));
+        let expired = timer(SavedTimer::Expired {
+            overtime: secs(5),
+            acknowledged: true,
+        });
+        assert_eq!(expired.overtime(now), Some(secs(65)));
+    }
+
+    #[test]
+    fn saves_while_timing_and_clears_once_not() {
+        let dir = env::temp_dir().join(format!("tui-time-persist-{}", std::process::id()));
+        let path = dir.join("state.json");
+        let now = Instant::now();
+        assert_eq!(load(&path).unwrap(), None);
+
+        let mode = Mode::Stopwatch(Stopwatch::start(now));
+        save(&path, &mode, now + secs(14)).unwrap();
+        let loaded = load(&path).unwrap().unwrap();
+        assert_eq!(
+            loaded.timing,
+            Timing::Stopwatch {
+                elapsed: secs(14),
+                running: true,
+                laps: Vec::new(),
+            }
+        );
+
+        save(&path, &Mode::Clock, now).unwrap();
+        assert_eq!(load(&path).unwrap(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+//! `t`: the zone picker, an overlay listing every zone in the tz database, narrowed down by a
+//! fuzzy match on what's typed. The zones picked last are listed first, and saved in the state
+//! directory so they still are the next time the clock runs.
+
+use std::{
+    cell::Cell,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::persist;
+
+/// How many of the last zones picked are kept at the top of the picker's list.
+pub const RECENT_ZONES: usize = 5;
+
+/// How well `query` matches `name`, or `None` if its characters don't all appear in `name` in
+/// order. Case is ignored and a space matches `_`, so `new york` finds `America/New_York`. Runs
+/// of consecutive characters and ones starting a part of the name (after `/`, `_`, or `-`) score
+/// higher, so `berl` puts `Europe/Berlin` ahead of names that merely contain the letters.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let name: Vec<char> = name.chars().collect();
+    let mut next = 0;
+    let mut previous = None;
+    let mut score = 0;
+    for wanted in query.chars() {
+        let found = (next..name.len()).find(|&i| {
+            name[i].eq_ignore_ascii_case(&wanted) || (wanted == ' ' && name[i] == '_')
+        })?;
+        score += 1;
+        if found > 0 && previous == Some(found - 1) {
+            score += 4;
+        }
+        if found == 0 || matches!(name[found - 1], '/' | '_' | '-') {
+            score += 3;
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Moves `zone` to the front of the recently picked zones, keeping the last [`RECENT_ZONES`].
+pub fn remember(recent: &mut Vec<String>, zone: &str) {
+    recent.retain(|name| name != zone);
+    recent.insert(0, zone.to_owned());
+    recent.truncate(RECENT_ZONES);
+}
+
+/// `recent-zones` in the state directory.
+pub fn recents_path() -> Option<PathBuf> {
+    Some(persist::state_dir()?.join("recent-zones"))
+}
+
+/// The recently picked zones saved at `path`, a name a line, most recent first. Nothing saved
+/// yet is none.
+pub fn load_recents(path: &Path) -> anyhow::Result<Vec<String>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("reading {}", path.display())),
+    };
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .take(RECENT_ZONES)
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Saves the `recent` zones to `path` for [`load_recents`].
+pub fn save_recents(path: &Path, recent: &[String]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+    let lines: String = recent.iter().map(|name| format!("{name}\n")).collect();
+    fs::write(path, lines).with_context(|| format!("writing {}", path.display()))
+}
+
+/// The overlay for switching the clock to another zone: every zone name, narrowed down by a
+/// fuzzy match on what's been typed, with one selected.
+#[derive(Debug)]
+pub struct ZonePicker {
+    /// Every zone, the recently picked ones first.
+    names: Vec<String>,
+    query: String,
+    /// Indexes into `names` of the ones matching the query, best first.
+    matches: Vec<usize>,
+    /// Which of `matches` is selected.
+    selected: usize,
+    /// The first row of the list on screen, kept from one draw to the next so the list only
+    /// scrolls as far as the selection needs.
+    pub offset: Cell<usize>,
+}
+
+impl ZonePicker {
+    /// A picker over `zones`, with the `recent` ones moved to the top.
+    pub fn new(zones: Vec<String>, recent: &[String]) -> Self {
+        let mut names: Vec<String> = recent
+            .iter()
+            .filter(|name| zones.contains(name))
+            .cloned()
+            .collect();
+        names.extend(zones.into_iter().filter(|name| !recent.contains(name)));
+        let mut picker = Self {
+            names,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+            offset: Cell::new(0),
+        };
+        picker.refilter();
+        picker
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refilter();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.refilter();
+    }
+
+    /// Moves the selection `by` rows down, or up if negative, stopping at either end.
+    pub fn move_selection(&mut self, by: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(by).min(last);
+    }
+
+    /// The row selected, unless nothing matches.
+    pub fn selected_row(&self) -> Option<usize> {
+        (!self.matches.is_empty()).then_some(self.selected)
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.matches
+            .get(self.selected)
+            .map(|&i| self.names[i].as_str())
+    }
+
+    /// The zones matching the query, best first.
+    pub fn matches(&self) -> impl Iterator<Item = &str> {
+        self.matches.iter().map(|&i| self.names[i].as_str())
+    }
+
+    /// Matches the names against the query again, and selects the best. Equally good matches
+    /// keep their order, so recent zones stay ahead of the rest.
+    fn refilter(&mut self) {
+        let mut scored: Vec<(u32, usize)> = self
+            .names
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| fuzzy_score(&self.query, name).map(|score| (score, i)))
+            .collect();
+        scored.sort_by_key(|&(score, i)| (std::cmp::Reverse(score), i));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+        self.offset.set(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zones() -> Vec<String> {
+        [
+            "America/Argentina/Buenos_Aires",
+            "America/New_York",
+            "Asia/Tokyo",
+            "Europe/Belgrade",
+            "Europe/Berlin",
+            "Europe/Paris",
+            "Pacific/Auckland",
+        ]
+        .map(str::to_owned)
+        .to_vec()
+    }
+
+    #[test]
+    fn matches_characters_in_order() {
+        assert!(fuzzy_score("berl", "Europe/Berlin").is_some());
+        assert!(fuzzy_score("BERL", "Europe/Berlin").is_some());
+        assert!(fuzzy_score("eupa", "Europe/Paris").is_some());
+        assert_eq!(fuzzy_score("lreb", "Europe/Berlin"), None);
+        assert_eq!(fuzzy_score("berlin!", "Europe/Berlin"), None);
+        assert_eq!(fuzzy_score("", "Europe/Berlin"), Some(0));
+        assert!(fuzzy_score("new york", "America/New_York").is_some());
+
+        // A run at the start of a part beats the same letters scattered
+        assert!(fuzzy_score("ber", "Europe/Berlin") > fuzzy_score("ber", "Europe/Belgrade"));
+    }
+
+    #[test]
+    fn narrows_the_list_as_the_query_grows() {
+        let mut picker = ZonePicker::new(zones(), &[]);
+        assert_eq!(picker.matches().count(), 7);
+        assert_eq!(picker.selected(), Some("America/Argentina/Buenos_Aires"));
+
+        for c in "berl".chars() {
+            picker.push(c);
+        }
+        assert_eq!(picker.matches().collect::<Vec<_>>(), ["Europe/Berlin"]);
+        picker.pop();
+        picker.pop();
+        assert_eq!(
+            picker.matches().collect::<Vec<_>>(),
+            [
+                "Europe/Belgrade",
+                "Europe/Berlin",
+                "America/Argentina/Buenos_Aires"
+            ]
+        );
+        picker.move_selection(1);
+        assert_eq!(picker.selected(), Some("Europe/Berlin"));
+        picker.move_selection(5);
+        assert_eq!(picker.selected(), Some("America/Argentina/Buenos_Aires"));
+        picker.move_selection(-5);
+        assert_eq!(picker.selected(), Some("Europe/Belgrade"));
+
+        picker.push('x');
+        assert_eq!(picker.selected(), None);
+        assert_eq!(picker.selected_row(), None);
+    }
+
+    #[test]
+    fn recent_zones_come_first() {
+        let mut recent = Vec::new();
+        for zone in [
+            "Asia/Tokyo",
+            "Europe/Paris",
+            "Asia/Tokyo",
+            "Mars/Olympus_Mons",
+        ] {
+            remember(&mut recent, zone);
+        }
+        assert_eq!(recent, ["Mars/Olympus_Mons", "Asia/Tokyo", "Europe/Paris"]);
+        for i in 0..RECENT_ZONES {
+            remember(&mut recent, &format!("Etc/GMT-{i}"));
+        }
+        assert_eq!(recent.len(), RECENT_ZONES);
+
+        // Zones no longer in the database are left out
+        let recent = ["Mars/Olympus_Mons", "Asia/Tokyo", "Europe/Paris"].map(str::to_owned);
+        let mut picker = ZonePicker::new(zones(), &recent);
+        assert_eq!(
+            picker.matches().take(3).collect::<Vec<_>>(),
+            [
+                "Asia/Tokyo",
+                "Europe/Paris",
+                "America/Argentina/Buenos_Aires"
+            ]
+        );
+        assert_eq!(picker.matches().count(), 7);
+        // Among equal matches too
+        picker.push('a');
+        assert_eq!(picker.selected(), Some("Asia/Tokyo"));
+    }
+
+    #[test]
+    fn recent_zones_are_saved_between_runs() {
+        let dir = std::env::temp_dir().join(format!("tui-time-recents-{}", std::process::id()));
+        let path = dir.join("recent-zones");
+        assert!(load_recents(&path).unwrap().is_empty());
+        let recent = ["Europe/Paris", "Asia/Tokyo"].map(str::to_owned);
+        save_recents(&path, &recent).unwrap();
+        assert_eq!(load_recents(&path).unwrap(), recent);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
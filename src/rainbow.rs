@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use ratatui::style::Color;
+
+use crate::palette::rgb_to_256;
+
+/// How far round the hue wheel each step goes, in degrees. A full turn takes 72 steps: 72
+/// minutes normally, or 72 seconds with the seconds showing.
+pub const STEP_DEGREES: u32 = 5;
+
+/// The `--rainbow` color mode, which replaces the theme's digit color with one that cycles
+/// through the hues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rainbow {
+    /// Output 24-bit colors rather than the nearest of the 256.
+    pub truecolor: bool,
+}
+
+impl Rainbow {
+    /// The color at `timestamp`, in seconds since the epoch, stepping every `step`. Going by the
+    /// wall clock keeps side-by-side clocks in step with each other and across restarts.
+    pub fn color(&self, timestamp: i64, step: Duration) -> Color {
+        let steps = timestamp.div_euclid(step.as_secs().max(1) as i64);
+        let hue = steps.rem_euclid(360 / i64::from(STEP_DEGREES)) * i64::from(STEP_DEGREES);
+        let (r, g, b) = hsv_to_rgb(hue as f64, 1.0, 1.0);
+        if self.truecolor {
+            Color::Rgb(r, g, b)
+        } else {
+            Color::Indexed(rgb_to_256(r, g, b))
+        }
+    }
+}
+
+/// A color given by hue in degrees, and saturation and value from 0 to 1, as RGB.
+pub fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> (u8, u8, u8) {
+    let chroma = value * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_hsv_round_the_wheel() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(60.0, 1.0, 1.0), (255, 255, 0));
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(210.0, 1.0, 1.0), (0, 128, 255));
+        assert_eq!(hsv_to_rgb(300.0, 1.0, 1.0), (255, 0, 255));
+        assert_eq!(hsv_to_rgb(360.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(0.0, 0.0, 0.5), (128, 128, 128));
+    }
+
+    #[test]
+    fn steps_with_the_wall_clock() {
+        let rainbow = Rainbow { truecolor: true };
+        let minute = Duration::from_secs(60);
+        // Anywhere within the same minute is the same color
+        assert_eq!(rainbow.color(0, minute), Color::Rgb(255, 0, 0));
+        assert_eq!(rainbow.color(59, minute), Color::Rgb(255, 0, 0));
+        assert_eq!(rainbow.color(60 * 12, minute), Color::Rgb(255, 255, 0));
+        // A full turn later, it's back where it started
+        assert_eq!(
+            rainbow.color(72, Duration::from_secs(1)),
+            Color::Rgb(255, 0, 0)
+        );
+
+        let fallback = Rainbow { truecolor: false };
+        assert_eq!(fallback.color(0, minute), Color::Indexed(196));
+    }
+}
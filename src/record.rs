@@ -0,0 +1,151 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use tokio::sync::mpsc;
+
+use crate::duration::format_seconds;
+
+/// The first line of a `--record` file, written when it's created.
+const HEADER: &str = "event,wallclock,elapsed";
+
+/// What a record marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A stopwatch lap.
+    Lap,
+    /// A countdown reaching zero.
+    TimerDone,
+}
+
+impl Event {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lap => "lap",
+            Self::TimerDone => "timer-done",
+        }
+    }
+}
+
+/// One line of a `--record` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub event: Event,
+    pub wallclock: DateTime<Local>,
+    /// The stopwatch's reading at a lap, or the length a countdown was started with.
+    pub elapsed: Duration,
+}
+
+impl Record {
+    /// The record as CSV, like `lap,2024-03-01T12:00:00.250+01:00,62.345`.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.event.name(),
+            self.wallclock.to_rfc3339_opts(SecondsFormat::Millis, false),
+            format_seconds(self.elapsed)
+        )
+    }
+}
+
+/// Hands records to the task writing them out, so the UI never waits on the disk.
+#[derive(Debug)]
+pub struct Recorder {
+    tx: mpsc::UnboundedSender<Record>,
+}
+
+impl Recorder {
+    /// A recorder and the queue its records arrive on, for [`write_records`].
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<Record>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx }, rx)
+    }
+
+    pub fn record(&self, record: Record) {
+        // Only fails once the writer has gone, when the app is shutting down
+        let _ = self.tx.send(record);
+    }
+}
+
+/// Appends each record that arrives on `records` to `path`, on the blocking pool, flushing after
+/// each batch. Failures are sent to `errors` as messages for the status line, and the records
+/// after them are still tried, so a full disk that frees up loses only what came in meanwhile.
+pub async fn write_records(
+    path: PathBuf,
+    mut records: mpsc::UnboundedReceiver<Record>,
+    errors: mpsc::UnboundedSender<String>,
+) {
+    while let Some(record) = records.recv().await {
+        let mut lines = vec![record.to_csv()];
+        while let Ok(record) = records.try_recv() {
+            lines.push(record.to_csv());
+        }
+        let path = path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            append(&path, &lines)
+                .map_err(|err| format!("recording to {} failed: {err}", path.display()))
+        })
+        .await;
+        let err = match result {
+            Ok(Ok(())) => continue,
+            Ok(Err(err)) => err,
+            Err(err) => format!("recording failed: {err}"),
+        };
+        if errors.send(err).is_err() {
+            return;
+        }
+    }
+}
+
+/// Appends `lines` to the file at `path`, starting it with the header if it's new or empty.
+pub fn append(path: &Path, lines: &[String]) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let empty = file.metadata()?.len() == 0;
+    let mut out = BufWriter::new(file);
+    if empty {
+        writeln!(out, "{HEADER}")?;
+    }
+    for line in lines {
+        writeln!(out, "{line}")?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn appends_under_a_single_header() {
+        let path = std::env::temp_dir().join(format!("tui-time-record-{}.csv", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let wallclock = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let lap = Record {
+            event: Event::Lap,
+            wallclock,
+            elapsed: Duration::from_millis(62_345),
+        };
+        let done = Record {
+            event: Event::TimerDone,
+            wallclock,
+            elapsed: Duration::from_secs(300),
+        };
+        append(&path, &[lap.to_csv()]).unwrap();
+        append(&path, &[done.to_csv()]).unwrap();
+
+        let stamp = wallclock.to_rfc3339_opts(SecondsFormat::Millis, false);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{HEADER}\nlap,{stamp},62.345\ntimer-done,{stamp},300.000\n")
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::cron::Schedule;
+
+/// How long a reminder's message stays up unless the config says otherwise.
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(10);
+
+/// A message from the config's `[reminders]`, shown whenever its schedule comes round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    schedule: Schedule,
+    message: String,
+    /// When it's next due, or `None` if its schedule never comes round again.
+    next: Option<DateTime<Local>>,
+}
+
+impl Reminder {
+    pub fn new(schedule: Schedule, message: String, now: DateTime<Local>) -> Self {
+        Self {
+            schedule,
+            message,
+            next: schedule.next_after(&now),
+        }
+    }
+}
+
+/// The reminders, and the banner across the bottom of the screen showing each one's message in
+/// turn. Messages that come due while another is up wait for it rather than replacing it.
+#[derive(Debug, Default)]
+pub struct Reminders {
+    reminders: Vec<Reminder>,
+    /// How long each message stays up.
+    duration: Duration,
+    waiting: VecDeque<String>,
+    /// The message up now, and when it comes down.
+    showing: Option<(String, Instant)>,
+}
+
+impl Reminders {
+    pub fn new(reminders: Vec<Reminder>, duration: Duration) -> Self {
+        Self {
+            reminders,
+            duration,
+            ..Self::default()
+        }
+    }
+
+    /// Takes the schedules and duration of `reloaded`, keeping the message up and any waiting.
+    pub fn reschedule(&mut self, reloaded: Self) {
+        self.reminders = reloaded.reminders;
+        self.duration = reloaded.duration;
+    }
+
+    /// Queues the messages of the reminders due by `local`, in the order they're configured,
+    /// and brings the next one up once the last has been up for its duration.
+    pub fn update(&mut self, local: DateTime<Local>, now: Instant) {
+        for reminder in &mut self.reminders {
+            if reminder.next.is_some_and(|next| local >= next) {
+                self.waiting.push_back(reminder.message.clone());
+                reminder.next = reminder.schedule.next_after(&local);
+            }
+        }
+        if self
+            .showing
+            .as_ref()
+            .is_some_and(|&(_, until)| now >= until)
+        {
+            self.showing = None;
+        }
+        if self.showing.is_none()
+            && let Some(message) = self.waiting.pop_front()
+        {
+            self.showing = Some((message, now + self.duration));
+        }
+    }
+
+    /// The message up now, if any.
+    pub fn banner(&self) -> Option<&str> {
+        self.showing.as_ref().map(|(message, _)| message.as_str())
+    }
+
+    /// When the message up comes down or the next reminder comes due, as of `local` and `now`,
+    /// for a redraw then. Ticks counted from startup don't land on the minute boundaries
+    /// reminders come due on, so they can't be left to the clock's.
+    pub fn next_tick(&self, local: DateTime<Local>, now: Instant) -> Option<Instant> {
+        let due = self
+            .reminders
+            .iter()
+            .filter_map(|reminder| reminder.next)
+            .min()
+            .map(|next| now + (next - local).to_std().unwrap_or(Duration::ZERO));
+        let down = self.showing.as_ref().map(|&(_, until)| until);
+        down.into_iter().chain(due).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn messages_wait_their_turn() {
+        let local = |h, m| Local.with_ymd_and_hms(2024, 3, 1, h, m, 0).unwrap();
+        let start = Instant::now();
+        let secs = |secs| start + Duration::from_secs(secs);
+        let reminder = |schedule: &str, message: &str| {
+            Reminder::new(schedule.parse().unwrap(), message.to_owned(), local(9, 0))
+        };
+        let mut reminders = Reminders::new(
+            vec![
+                reminder("*/30 * * * *", "stretch"),
+                reminder("0 10 * * *", "stand-up"),
+            ],
+            Duration::from_secs(10),
+        );
+
+        reminders.update(local(9, 29), start);
+        assert_eq!(reminders.banner(), None);
+        // Woken when the first comes due
+        assert_eq!(reminders.next_tick(local(9, 29), start), Some(secs(60)));
+        reminders.update(local(9, 30), start);
+        assert_eq!(reminders.banner(), Some("stretch"));
+        assert_eq!(reminders.next_tick(local(9, 30), start), Some(secs(10)));
+        reminders.update(local(9, 30), secs(10));
+        assert_eq!(reminders.banner(), None);
+
+        // Both come due at 10:00, and the second waits for the first to come down
+        reminders.update(local(10, 0), secs(20));
+        assert_eq!(reminders.banner(), Some("stretch"));
+        reminders.update(local(10, 0), secs(29));
+        assert_eq!(reminders.banner(), Some("stretch"));
+        reminders.update(local(10, 0), secs(30));
+        assert_eq!(reminders.banner(), Some("stand-up"));
+        reminders.update(local(10, 0), secs(40));
+        assert_eq!(reminders.banner(), None);
+
+        // Reloading keeps what's up
+        reminders.update(local(10, 30), secs(50));
+        reminders.reschedule(Reminders::new(Vec::new(), Duration::from_secs(5)));
+        assert_eq!(reminders.banner(), Some("stretch"));
+        reminders.update(local(11, 0), secs(60));
+        assert_eq!(reminders.banner(), None);
+    }
+}
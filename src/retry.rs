@@ -0,0 +1,86 @@
+//! Riding out failures reading terminal input, which some SSH and mosh setups produce now and
+//! then, rather than letting the first one end a clock that's been running for days.
+
+use std::{io, time::Duration};
+
+use anyhow::anyhow;
+
+/// How many reads in a row can fail before giving up.
+pub const MAX_FAILURES: u32 = 5;
+
+/// The wait after the first failure, doubling with each one after.
+const FIRST_BACKOFF: Duration = Duration::from_millis(50);
+
+/// What to do with a read's result.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Retry<T> {
+    /// It worked, and here's what was read.
+    Ready(T),
+    /// It failed, so wait this long and read again.
+    After(Duration),
+}
+
+/// The failures in a row so far, which a successful read clears.
+#[derive(Debug, Default)]
+pub struct ReadRetry {
+    failures: u32,
+}
+
+impl ReadRetry {
+    /// Takes a read's `result`: what was read, how long to wait before reading again, or once
+    /// [`MAX_FAILURES`] reads in a row have failed, the last failure to give up with.
+    pub fn check<T>(&mut self, result: io::Result<T>) -> anyhow::Result<Retry<T>> {
+        match result {
+            Ok(read) => {
+                self.failures = 0;
+                Ok(Retry::Ready(read))
+            },
+            Err(err) => {
+                self.failures += 1;
+                if self.failures >= MAX_FAILURES {
+                    return Err(anyhow!(err).context(format!(
+                        "reading terminal input failed {MAX_FAILURES} times in a row"
+                    )));
+                }
+                Ok(Retry::After(FIRST_BACKOFF * 2_u32.pow(self.failures - 1)))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spurious() -> io::Result<char> {
+        Err(io::Error::other("resource temporarily unavailable"))
+    }
+
+    #[test]
+    fn backs_off_and_recovers_from_intermittent_errors() {
+        let mut retry = ReadRetry::default();
+        let ms = |ms| Retry::After(Duration::from_millis(ms));
+        assert_eq!(retry.check(Ok('a')).unwrap(), Retry::Ready('a'));
+        assert_eq!(retry.check(spurious()).unwrap(), ms(50));
+        assert_eq!(retry.check(spurious()).unwrap(), ms(100));
+        assert_eq!(retry.check(Ok('b')).unwrap(), Retry::Ready('b'));
+        // A read getting through starts the count again
+        for wait in [50, 100, 200, 400] {
+            assert_eq!(retry.check(spurious()).unwrap(), ms(wait));
+        }
+        assert_eq!(retry.check(Ok('c')).unwrap(), Retry::Ready('c'));
+    }
+
+    #[test]
+    fn gives_up_after_too_many_in_a_row() {
+        let mut retry = ReadRetry::default();
+        for _ in 1..MAX_FAILURES {
+            assert!(matches!(retry.check(spurious()), Ok(Retry::After(_))));
+        }
+        let err = retry.check(spurious()).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "reading terminal input failed 5 times in a row: resource temporarily unavailable"
+        );
+    }
+}
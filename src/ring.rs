@@ -0,0 +1,158 @@
+use std::f64::consts::TAU;
+
+use chrono::{DateTime, Local, Timelike};
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    symbols::Marker,
+    widgets::{
+        Widget,
+        canvas::{Canvas, Painter, Shape},
+    },
+};
+
+/// What a progress ring fills up over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum RingSpan {
+    #[default]
+    Minute,
+    Hour,
+}
+
+impl RingSpan {
+    /// How far through the span `time` is, from 0 up to (but not including) 1. Whole seconds
+    /// only, since the ring is redrawn once a second.
+    pub fn progress(self, time: DateTime<Local>) -> f64 {
+        let second = f64::from(time.second());
+        match self {
+            Self::Minute => second / 60.0,
+            Self::Hour => (f64::from(time.minute()) * 60.0 + second) / 3600.0,
+        }
+    }
+}
+
+/// The `--ring` settings. The ring sits above the digits, or is drawn as the rim of an analog
+/// face, where `radius` doesn't apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ring {
+    pub span: RingSpan,
+    /// The theme's digit color when unset.
+    pub color: Option<Color>,
+    /// In rows. The ring is twice as many columns across, as cells are about twice as tall as
+    /// they are wide.
+    pub radius: u16,
+}
+
+impl Ring {
+    /// The cells the ring takes up above the digits.
+    pub fn size(&self) -> (u16, u16) {
+        (self.radius * 4, self.radius * 2)
+    }
+}
+
+/// Part of a circle, swept clockwise from twelve o'clock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arc {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    /// How much of the circle to draw, from 0 to 1.
+    pub turns: f64,
+    pub color: Color,
+}
+
+impl Shape for Arc {
+    fn draw(&self, painter: &mut Painter<'_, '_>) {
+        // Half-degree steps, fine enough to leave no gaps between dots on a full-screen face
+        let steps = (self.turns.clamp(0.0, 1.0) * 720.0).round() as u32;
+        for step in 0..=steps {
+            let angle = f64::from(step) / 720.0 * TAU;
+            let x = self.radius.mul_add(angle.sin(), self.x);
+            let y = self.radius.mul_add(angle.cos(), self.y);
+            if let Some((x, y)) = painter.get_point(x, y) {
+                painter.paint(x, y, self.color);
+            }
+        }
+    }
+}
+
+/// A ring filling up clockwise through the current minute or hour.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressRing {
+    pub time: DateTime<Local>,
+    pub span: RingSpan,
+    pub color: Color,
+}
+
+impl ProgressRing {
+    /// The ring as a shape to draw on a canvas whose bounds are ±1 on each axis.
+    pub fn arc(&self) -> Arc {
+        Arc {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+            turns: self.span.progress(self.time),
+            color: self.color,
+        }
+    }
+}
+
+impl Widget for ProgressRing {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([-1.0, 1.0])
+            .y_bounds([-1.0, 1.0])
+            .paint(|ctx| ctx.draw(&self.arc()))
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+
+    #[test]
+    fn progress_through_the_minute_or_hour() {
+        let time = Local.with_ymd_and_hms(2024, 3, 1, 15, 30, 15).unwrap();
+        assert_eq!(RingSpan::Minute.progress(time), 0.25);
+        assert_eq!(RingSpan::Hour.progress(time), 1815.0 / 3600.0);
+        let top = Local.with_ymd_and_hms(2024, 3, 1, 16, 0, 0).unwrap();
+        assert_eq!(RingSpan::Minute.progress(top), 0.0);
+        assert_eq!(RingSpan::Hour.progress(top), 0.0);
+    }
+
+    #[test]
+    fn half_a_minute_fills_the_right_side() {
+        let ring = Ring {
+            span: RingSpan::Minute,
+            color: None,
+            radius: 3,
+        };
+        let (width, height) = ring.size();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let time = Local.with_ymd_and_hms(2024, 3, 1, 15, 30, 30).unwrap();
+        terminal
+            .draw(|frame| {
+                frame.render_widget(
+                    ProgressRing {
+                        time,
+                        span: ring.span,
+                        color: Color::Reset,
+                    },
+                    frame.area(),
+                );
+            })
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let painted = |x: u16| (0..height).any(|y| buffer[(x, y)].symbol() != " ");
+        assert!((width / 2 + 1..width).all(painted));
+        assert!(!(0..width / 2 - 1).any(painted));
+    }
+}
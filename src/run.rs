@@ -0,0 +1,397 @@
+//! The main loop: draw, then wait for whichever comes first of a tick, a key, or anything else
+//! that changes what's shown. It's generic over the terminal's backend, the ticker, and where
+//! events come from, so tests can drive it without a terminal.
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use ratatui::{Terminal, backend::Backend, crossterm::event::KeyEvent};
+use tokio::time::{Interval, MissedTickBehavior};
+
+use crate::{
+    app::{Action, App},
+    failure,
+    persist::Fingerprint,
+    suspend::{self, SuspendCheck},
+    tick::FrameLimiter,
+    timer::{Arming, Tick, Ticks},
+    ui,
+};
+
+/// Something that happened outside the loop, for it to act on.
+pub enum LoopEvent {
+    /// A key pressed, and the action it's bound to.
+    Key(KeyEvent, Action),
+    /// An action from somewhere other than the keyboard, like the control socket.
+    Action(Action),
+    /// The terminal was resized.
+    Resize,
+    /// The process was continued after being stopped, and the screen may have been drawn over
+    /// meanwhile.
+    Continued,
+    /// The terminal gained focus, or lost it.
+    Focus(bool),
+    /// A message for the status line, like something failing in the background.
+    Notice(String),
+    /// A change to make to the app, like settings reloaded from an edited config.
+    Update(Box<dyn FnOnce(&mut App)>),
+    /// Time to quit, as on a signal or at the end of the input.
+    Quit,
+}
+
+/// Where the loop's events come from, and where what it does besides drawing goes.
+pub trait Host {
+    /// Waits for the next event. Cancel-safe, as the loop stops waiting whenever something else
+    /// comes first.
+    fn next_event(&mut self) -> impl Future<Output = anyhow::Result<LoopEvent>>;
+
+    /// Rings the terminal bell.
+    fn ring_bell(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Stops the process for the suspend action, putting the terminal back for the shell first
+    /// and taking it over again once continued. Returns after that.
+    fn suspend(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called after each frame with the clock's text on a single line, for anything mirroring it.
+    fn drew(&mut self, _app: &mut App, _line: &str) {}
+
+    /// Called after each frame with the moment it showed, to draw the same on any other
+    /// terminals.
+    fn mirror(&mut self, _app: &mut App, _local: DateTime<Local>, _now: Instant) {}
+
+    /// Called whenever what's being timed changes, whether for a key or a timer running out, and
+    /// once the loop ends, for `--persist` to save it. Not called while the question whether to
+    /// resume what it saved is open.
+    fn persist(&mut self, _app: &mut App) {}
+
+    /// Called before each wait with whether the zone picker is open, where keys are typed rather
+    /// than bound to actions.
+    fn picking(&mut self, _picking: bool) {}
+}
+
+/// When `--exit-after` or `--exit-at` says to quit.
+#[derive(Debug, Clone, Copy)]
+pub enum Exit {
+    After(Instant),
+    At(DateTime<Local>),
+}
+
+impl Exit {
+    /// The monotonic instant to quit at. `At` is measured from the current wall-clock time on
+    /// every call, so it still fires on time if the clock is stepped while waiting.
+    pub fn deadline(&self, local: DateTime<Local>, now: Instant) -> Instant {
+        match self {
+            Self::After(deadline) => *deadline,
+            Self::At(at) => now + (*at - local).to_std().unwrap_or(Duration::ZERO),
+        }
+    }
+}
+
+/// How the loop runs, from the command line.
+#[derive(Debug, Clone, Copy)]
+pub struct LoopOptions {
+    /// The interval the ticker was first armed with.
+    pub tick_interval: Duration,
+    pub max_fps: u32,
+    /// The rows drawn under the prompt with `--inline`, or `None` for the whole screen.
+    pub inline: Option<u16>,
+    pub exit: Option<Exit>,
+}
+
+/// Runs the clock on `terminal` until it's quit, redrawing on each of `ticker`'s ticks and each
+/// of `host`'s events.
+pub async fn run_loop<B>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    ticker: &mut impl Ticks,
+    host: &mut impl Host,
+    options: LoopOptions,
+) -> anyhow::Result<()>
+where
+    B: Backend,
+    B::Error: Send + Sync + 'static,
+{
+    let mut tick_interval = options.tick_interval;
+    let mut limiter = FrameLimiter::new(options.max_fps);
+    // Whether the coming redraw is for a tick, which the frame rate cap applies to
+    let mut tick_redraw = false;
+    // Frames drawn between ticks, only while something needs them
+    let mut frames: Option<Interval> = None;
+    // Warned about once each time the terminal becomes too small for the region
+    let mut region_fits = true;
+    let mut suspend = SuspendCheck::default();
+    // Whether the screen has to be drawn again from scratch, after the process was stopped
+    let mut redraw_all = false;
+    // What was being timed when it was last saved, if it has been yet
+    let mut saved: Option<Option<Fingerprint>> = None;
+    loop {
+        host.picking(app.zone_picker.is_some());
+        if std::mem::take(&mut app.suspending) {
+            tracing::info!("suspending");
+            let shown = terminal.show_cursor().map_err(anyhow::Error::new);
+            match shown.and_then(|()| host.suspend().map_err(anyhow::Error::new)) {
+                Ok(()) => redraw_all = true,
+                Err(err) => carry_on(app, err.context("suspending failed"))?,
+            }
+        }
+        // The ticks due while stopped have been missed, and the screen may have been drawn over
+        if std::mem::take(&mut redraw_all) {
+            if let Err(err) = terminal.clear() {
+                carry_on(
+                    app,
+                    anyhow::Error::new(err).context("clearing the screen failed"),
+                )?;
+            }
+            ticker.set_arming(ticker.arming(), tick_interval)?;
+            app.dst.invalidate();
+            app.skip_transition();
+        }
+        match terminal.size() {
+            Ok(size) => app.frame_size = size,
+            Err(err) => carry_on(
+                app,
+                anyhow::Error::new(err).context("reading the size failed"),
+            )?,
+        }
+        if let Some(rows) = options.inline {
+            app.frame_size.height = app.frame_size.height.min(rows);
+        }
+        if let Some(region) = app.region {
+            let fits = region.fits(app.frame_size);
+            if region_fits && !fits {
+                app.notify(
+                    format!(
+                        "the --x/--y region doesn't fit in {}x{}, so it's cut short",
+                        app.frame_size.width, app.frame_size.height
+                    ),
+                    Instant::now(),
+                );
+            }
+            region_fits = fits;
+        }
+        // Ticks measured on the monotonic clock stop while the machine is asleep, so after a
+        // resume what's shown catches up and the ticker is re-armed from now
+        if let Some(slept) = suspend.check() {
+            tracing::info!(?slept, "resumed from suspend");
+            app.resumed(slept);
+            ticker.set_arming(ticker.arming(), tick_interval)?;
+        }
+        // Decimal boundaries follow local midnight, so they move with the UTC offset
+        let arming = match ticker.arming() {
+            Arming::Boundary { .. } => Arming::Boundary {
+                phase: app.tick_phase(app.local_now()),
+            },
+            Arming::Relative => Arming::Relative,
+        };
+        if app.tick_clock() != ticker.clock() {
+            tick_interval = app.tick_interval();
+            tracing::debug!(clock = app.tick_clock(), interval = ?tick_interval, "switching clocks");
+            ticker.set_clock(app.tick_clock(), tick_interval)?;
+        }
+        if app.tick_interval() != tick_interval || arming != ticker.arming() {
+            tick_interval = app.tick_interval();
+            tracing::debug!(interval = ?tick_interval, ?arming, "re-arming the ticker");
+            // The frame drawn below covers a tick that was due; a clock step still has to be
+            // heard about
+            if let Some(Tick::ClockStepped) = ticker.set_arming(arming, tick_interval)? {
+                app.dst.invalidate();
+            }
+        }
+        app.update(app.local_now(), Instant::now());
+        let timing = Fingerprint::of(&app.mode);
+        if app.resuming.is_none() && saved.as_ref() != Some(&timing) {
+            host.persist(app);
+            saved = Some(timing);
+        }
+        // Not timerfd ticks: frames needn't line up with the wall clock, and whatever they draw
+        // is read from the clocks as it's drawn, so a late frame only shows a later time
+        let period = app
+            .frame_period()
+            .map(|period| period.max(Duration::from_secs(1) / options.max_fps));
+        if frames.as_ref().map(Interval::period) != period {
+            frames = period.map(|period| {
+                let mut interval =
+                    tokio::time::interval_at((Instant::now() + period).into(), period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                interval
+            });
+        }
+        // A frozen display only changes for keys and whatever unfreezes it, so ticks meanwhile
+        // are let go rather than saved up
+        let frozen_tick = tick_redraw && app.frozen.is_some();
+        let deferred = (tick_redraw && !frozen_tick)
+            .then(|| limiter.defer(Instant::now()))
+            .flatten();
+        tick_redraw = false;
+        if deferred.is_none() && !frozen_tick {
+            let (local, now) = app.shown_moment(app.local_now(), Instant::now());
+            match terminal.draw(|frame| ui::draw(frame, app, local, now)) {
+                Ok(_) => {
+                    limiter.drew(Instant::now());
+                    host.mirror(app, local, now);
+                    // Titles and the like are a single line
+                    let text = app.display_text(local, now);
+                    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    host.drew(app, &line);
+                },
+                Err(err) => carry_on(app, anyhow::Error::new(err).context("drawing failed"))?,
+            }
+        }
+        let chime_bell = app
+            .chime
+            .as_mut()
+            .is_some_and(|chime| chime.take_bell(Instant::now()));
+        let bells = [chime_bell, app.notifier.take_bell()];
+        for _ in bells.into_iter().filter(|&bell| bell) {
+            if let Err(err) = host.ring_bell() {
+                carry_on(
+                    app,
+                    anyhow::Error::new(err).context("ringing the bell failed"),
+                )?;
+            }
+        }
+        let exit_deadline = options
+            .exit
+            .as_ref()
+            .map(|exit| exit.deadline(app.local_now(), Instant::now()));
+        // Without a wall-clock timerfd for a resume to cancel, a long wait could sleep through
+        // one, so the loop looks in now and then
+        let resume_poll = (!ticker.wakes_on_resume() && tick_interval > suspend::POLL)
+            .then(|| Instant::now() + suspend::POLL);
+        let event = tokio::select! {
+            _ = sleep_until(exit_deadline) => {
+                tracing::info!("exit deadline reached");
+                break;
+            },
+            _ = sleep_until(resume_poll) => continue,
+            _ = sleep_until(deferred) => {
+                tracing::trace!("woke for a deferred frame");
+                continue;
+            },
+            tick = ticker.tick(tick_interval) => {
+                tracing::trace!(?tick, "woke for a tick");
+                tick_redraw = true;
+                match tick {
+                    Ok(Tick::ClockStepped) => app.dst.invalidate(),
+                    Ok(Tick::Interval) => {},
+                    // Drawn straight away, rather than held to the frame rate cap, to jump to
+                    // the right time
+                    Ok(Tick::Behind { expirations }) => {
+                        app.missed_ticks += expirations - 1;
+                        tick_redraw = false;
+                    },
+                    Err(err) => carry_on(app, err)?,
+                }
+                continue;
+            },
+            _ = sleep_until(app.next_tick(app.local_now(), Instant::now())) => {
+                tracing::trace!("woke for the display's own deadline");
+                tick_redraw = true;
+                continue;
+            },
+            _ = next_frame(frames.as_mut()) => continue,
+            event = host.next_event() => match event {
+                Ok(event) => event,
+                Err(err) => {
+                    carry_on(app, err)?;
+                    continue;
+                },
+            },
+        };
+        let mode = app.mode.name();
+        let action = match event {
+            LoopEvent::Key(key_event, action) => {
+                tracing::debug!(key = ?key_event.code, action = action.name(), "key");
+                let flow = app.press(&key_event, action, app.local_now(), Instant::now());
+                log_mode_change(mode, app);
+                if flow.is_break() {
+                    break;
+                }
+                continue;
+            },
+            LoopEvent::Action(action) => action,
+            LoopEvent::Resize => {
+                tracing::debug!("resized");
+                app.skip_transition();
+                continue;
+            },
+            LoopEvent::Continued => {
+                tracing::info!("continued");
+                redraw_all = true;
+                continue;
+            },
+            // Drawn straight away, to be right the moment it's seen
+            LoopEvent::Focus(focused) => {
+                tracing::debug!(focused, "focus");
+                app.unfocused = !focused;
+                continue;
+            },
+            LoopEvent::Notice(text) => {
+                app.notify(text, Instant::now());
+                continue;
+            },
+            LoopEvent::Update(update) => {
+                update(app);
+                continue;
+            },
+            LoopEvent::Quit => {
+                tracing::info!("quitting");
+                break;
+            },
+        };
+        tracing::debug!(action = action.name(), "action");
+        let flow = app.apply(action, app.local_now(), Instant::now());
+        log_mode_change(mode, app);
+        if flow.is_break() {
+            break;
+        }
+    }
+    if app.resuming.is_none() {
+        host.persist(app);
+    }
+    Ok(())
+}
+
+/// Logs the app leaving mode `from`, if it has.
+fn log_mode_change(from: &'static str, app: &App) {
+    let to = app.mode.name();
+    if to != from {
+        tracing::info!(from, to, "mode changed");
+    }
+}
+
+/// Shows `err` under the clock and carries on if it's likely to pass, as when the terminal is
+/// being reattached, or ends the loop with it if not.
+fn carry_on(app: &mut App, err: anyhow::Error) -> anyhow::Result<()> {
+    if !failure::is_transient(&err) {
+        return Err(err);
+    }
+    tracing::warn!("carrying on after an error: {err:#}");
+    app.report_error(&err);
+    Ok(())
+}
+
+/// Sleeps until `deadline`, or forever if there is none.
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for the next frame of an animation, or forever if nothing is animating.
+async fn next_frame(frames: Option<&mut Interval>) {
+    match frames {
+        Some(frames) => {
+            frames.tick().await;
+        },
+        None => std::future::pending().await,
+    }
+}
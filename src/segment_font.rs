@@ -0,0 +1,221 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, bail, ensure};
+use ratatui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+
+use crate::seven_segment;
+
+/// A bitmap font for the clock, one terminal cell per pixel, for looks tui-big-text's 8x8 font
+/// can't give. Font files list each glyph as a line naming the character (or `space`) followed
+/// by one line per row, `#` for a lit pixel and `.` for an unlit one, with a blank line between
+/// glyphs. Every glyph must be the same height. An `o` is a pixel of a segment left unlit, drawn
+/// only with `--segment-off-color`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentFont {
+    height: u16,
+    glyphs: BTreeMap<char, Glyph>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Glyph {
+    width: u16,
+    rows: Vec<Vec<Pixel>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pixel {
+    Blank,
+    Lit,
+    /// Part of a segment the glyph leaves unlit.
+    Off,
+}
+
+impl SegmentFont {
+    /// The built-in seven-segment digits, with segments `thickness` cells thick.
+    pub fn seven_segment(thickness: u16) -> Self {
+        Self::parse(&seven_segment::definition(thickness)).expect("the built-in font is valid")
+    }
+
+    /// The font `--font` names: `seven-segment`, with segments `thickness` cells thick, or a path
+    /// to a font file. `big` means tui-big-text's own font, so there's no segment font to load.
+    pub fn from_arg(arg: &str, thickness: u16) -> anyhow::Result<Option<Self>> {
+        match arg {
+            "big" => Ok(None),
+            "seven-segment" => Ok(Some(Self::seven_segment(thickness))),
+            path => Self::load(Path::new(path)).map(Some),
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("in font {}", path.display()))
+    }
+
+    pub fn parse(definition: &str) -> anyhow::Result<Self> {
+        let mut glyphs = BTreeMap::new();
+        let mut height = None;
+        for block in definition
+            .split("\n\n")
+            .filter(|block| !block.trim().is_empty())
+        {
+            let mut lines = block.lines();
+            let name = lines.next().unwrap_or_default();
+            let c = match name {
+                "space" => ' ',
+                _ => {
+                    let mut chars = name.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => c,
+                        _ => bail!("expected a single character or `space`, found {name:?}"),
+                    }
+                },
+            };
+
+            let rows = lines
+                .map(|line| {
+                    line.chars()
+                        .map(|pixel| match pixel {
+                            '#' => Ok(Pixel::Lit),
+                            '.' => Ok(Pixel::Blank),
+                            'o' => Ok(Pixel::Off),
+                            _ => bail!(
+                                "unexpected {pixel:?} in glyph {name:?} (expected #, ., or o)"
+                            ),
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let glyph_height = rows.len() as u16;
+            ensure!(glyph_height > 0, "glyph {name:?} has no rows");
+            let expected = *height.get_or_insert(glyph_height);
+            ensure!(
+                glyph_height == expected,
+                "glyph {name:?} is {glyph_height} rows tall but the first glyph is {expected}"
+            );
+
+            let width = rows.iter().map(Vec::len).max().unwrap_or(0) as u16;
+            if glyphs.insert(c, Glyph { width, rows }).is_some() {
+                bail!("glyph {name:?} is defined twice");
+            }
+        }
+        let Some(height) = height else {
+            bail!("the font has no glyphs");
+        };
+        Ok(Self { height, glyphs })
+    }
+
+    /// The size in cells of `text` in this font, with a blank column between glyphs.
+    pub fn size(&self, text: &str) -> (u16, u16) {
+        let widths = text.chars().map(|c| self.width_of(c));
+        let (count, total) = widths.fold((0_u16, 0_u16), |(count, total), width| {
+            (count + 1, total.saturating_add(width))
+        });
+        (total.saturating_add(count.saturating_sub(1)), self.height)
+    }
+
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    /// Characters the font lacks take up as much room as a space, so they leave a gap.
+    fn width_of(&self, c: char) -> u16 {
+        match self.glyphs.get(&c).or_else(|| self.glyphs.get(&' ')) {
+            Some(glyph) => glyph.width,
+            None => self.height / 2,
+        }
+    }
+}
+
+/// `text` drawn in a [`SegmentFont`], from the area's top-left corner and clipped to it.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentText<'a> {
+    pub text: &'a str,
+    pub font: &'a SegmentFont,
+    pub style: Style,
+    /// The style of unlit segments, or `None` to leave them blank.
+    pub off_style: Option<Style>,
+}
+
+impl Widget for SegmentText<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut x = area.x;
+        for c in self.text.chars() {
+            if let Some(glyph) = self.font.glyphs.get(&c) {
+                for (dy, row) in glyph.rows.iter().enumerate() {
+                    for (dx, pixel) in row.iter().enumerate() {
+                        let style = match pixel {
+                            Pixel::Lit => self.style,
+                            Pixel::Off => match self.off_style {
+                                Some(style) => style,
+                                None => continue,
+                            },
+                            Pixel::Blank => continue,
+                        };
+                        let (cell_x, cell_y) = (x + dx as u16, area.y + dy as u16);
+                        if cell_x < area.right() && cell_y < area.bottom() {
+                            buf[(cell_x, cell_y)].set_symbol("█").set_style(style);
+                        }
+                    }
+                }
+            }
+            x = x.saturating_add(self.font.width_of(c) + 1);
+            if x >= area.right() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::widget::tests::rows;
+
+    #[test]
+    fn renders_noon_in_seven_segments() {
+        let font = SegmentFont::seven_segment(1);
+        assert_eq!(font.size("12:00"), (25, 7));
+
+        let mut terminal = Terminal::new(TestBackend::new(25, 7)).unwrap();
+        let text = SegmentText {
+            text: "12:00",
+            font: &font,
+            style: Style::new(),
+            off_style: None,
+        };
+        terminal
+            .draw(|frame| frame.render_widget(text, frame.area()))
+            .unwrap();
+        assert_eq!(
+            rows(terminal.backend().buffer()),
+            [
+                "       ███     ███   ███ ",
+                "    █     █   █   █ █   █",
+                "    █     █ █ █   █ █   █",
+                "       ███               ",
+                "    █ █     █ █   █ █   █",
+                "    █ █       █   █ █   █",
+                "       ███     ███   ███ ",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_fonts() {
+        let err = SegmentFont::parse("0\n#x#").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unexpected 'x' in glyph \"0\" (expected #, ., or o)"
+        );
+        let err = SegmentFont::parse("0\n#\n#\n\n1\n#").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("1 rows tall but the first glyph is 2")
+        );
+        assert!(SegmentFont::parse("10\n#").is_err());
+        assert!(SegmentFont::parse("\n\n").is_err());
+    }
+}
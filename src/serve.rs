@@ -0,0 +1,269 @@
+//! `--serve`: a small HTTP endpoint answering GETs with the time the clock is showing, as JSON,
+//! for dashboards polling for it. Built with the `serve` feature, which brings in hyper to speak
+//! HTTP/1.1, one request per connection.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::Serialize;
+
+use crate::{duration::millis, sync::SyncStatus};
+
+/// The clock as last drawn, which the endpoint answers with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Shown {
+    /// The clock's text on a single line, as in the terminal's title.
+    pub text: String,
+    /// The moment shown, which lags the wall clock while the display is frozen.
+    pub at: Option<DateTime<Local>>,
+    /// With `--sync-status`, how the system clock's synchronization was last read.
+    pub sync: Option<SyncStatus>,
+}
+
+impl Shown {
+    /// The JSON body, like `{"text":"9:41 AM","at":"2024-03-01T09:41:00-05:00"}`, with `synced`
+    /// and `estimated_error_ms` added when the sync status is known.
+    pub fn json(&self) -> String {
+        let body = Body {
+            text: &self.text,
+            at: self
+                .at
+                .map(|at| at.to_rfc3339_opts(SecondsFormat::Millis, false)),
+            synced: self.sync.map(|sync| sync.synced),
+            estimated_error_ms: self.sync.map(|sync| sync.estimated_error),
+        };
+        serde_json::to_string(&body).expect("the body is plain JSON")
+    }
+}
+
+#[derive(Serialize)]
+struct Body<'a> {
+    text: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    synced: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", with = "millis::option")]
+    estimated_error_ms: Option<Duration>,
+}
+
+#[cfg(feature = "serve")]
+mod server {
+    use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+
+    use anyhow::Context;
+    use hyper::{
+        Method, Request, Response, StatusCode, header, server::conn::http1, service::service_fn,
+    };
+    use hyper_util::rt::{TokioIo, TokioTimer};
+    use serde_json::json;
+    use tokio::{
+        net::TcpListener,
+        sync::{Semaphore, watch},
+        task::JoinHandle,
+    };
+
+    use super::Shown;
+
+    /// How long a client has to send a request's headers before it's hung up on.
+    const HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// The most connections answered at once. Any more wait to be accepted until one closes.
+    const MAX_CONNECTIONS: usize = 32;
+
+    /// The running endpoint. Dropping it stops accepting connections, so it goes when the rest
+    /// of the clock does.
+    pub struct TimeServer {
+        addr: SocketAddr,
+        task: JoinHandle<()>,
+    }
+
+    impl TimeServer {
+        /// Listens on `addr`, answering with whatever `shown` holds at the time of each request.
+        pub async fn bind(addr: SocketAddr, shown: watch::Receiver<Shown>) -> anyhow::Result<Self> {
+            let listener = TcpListener::bind(addr)
+                .await
+                .with_context(|| format!("listening on {addr} failed"))?;
+            let addr = listener.local_addr()?;
+            let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+            let task = tokio::spawn(async move {
+                while let Ok(permit) = Arc::clone(&connections).acquire_owned().await
+                    && let Ok((stream, _)) = listener.accept().await
+                {
+                    let shown = shown.clone();
+                    let service = service_fn(move |request| {
+                        let response = respond(&request, &shown.borrow());
+                        async move { Ok::<_, Infallible>(response) }
+                    });
+                    tokio::spawn(async move {
+                        let served = http1::Builder::new()
+                            .timer(TokioTimer::new())
+                            .header_read_timeout(HEADER_TIMEOUT)
+                            .keep_alive(false)
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                        if let Err(err) = served {
+                            tracing::debug!("answering a request failed: {err}");
+                        }
+                        drop(permit);
+                    });
+                }
+            });
+            Ok(Self { addr, task })
+        }
+
+        /// Where it's listening, with the port filled in if it was left to the system.
+        pub fn addr(&self) -> SocketAddr {
+            self.addr
+        }
+
+        /// Stops accepting connections, as dropping it does.
+        pub fn stop(self) {
+            drop(self);
+        }
+    }
+
+    impl Drop for TimeServer {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    /// The response to `request`: the time for a GET of `/` or `/time`, and an error for anything
+    /// else.
+    pub fn respond<B>(request: &Request<B>, shown: &Shown) -> Response<String> {
+        let (status, body) = match (request.method(), request.uri().path()) {
+            (&Method::GET, "/" | "/time") => (StatusCode::OK, shown.json()),
+            (&Method::GET, _) => (
+                StatusCode::NOT_FOUND,
+                json!({"error": "not found"}).to_string(),
+            ),
+            _ => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                json!({"error": "only GET is allowed"}).to_string(),
+            ),
+        };
+        let mut response = Response::builder()
+            .status(status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CACHE_CONTROL, "no-store");
+        if status == StatusCode::METHOD_NOT_ALLOWED {
+            response = response.header(header::ALLOW, "GET");
+        }
+        response.body(body).expect("the headers are all valid")
+    }
+}
+
+#[cfg(feature = "serve")]
+pub use server::TimeServer;
+
+/// Stands in for the endpoint in builds without it, failing to start, so there's never one.
+#[cfg(not(feature = "serve"))]
+pub enum TimeServer {}
+
+#[cfg(not(feature = "serve"))]
+impl TimeServer {
+    pub async fn bind(
+        _addr: std::net::SocketAddr,
+        _shown: tokio::sync::watch::Receiver<Shown>,
+    ) -> anyhow::Result<Self> {
+        anyhow::bail!("built without --serve support")
+    }
+
+    pub fn stop(self) {
+        match self {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn shown() -> Shown {
+        Shown {
+            text: "9:41 \"AM\"".to_owned(),
+            at: Some(Local.with_ymd_and_hms(2024, 3, 1, 9, 41, 0).unwrap()),
+            sync: Some(SyncStatus {
+                synced: true,
+                estimated_error: Duration::from_micros(1500),
+            }),
+        }
+    }
+
+    #[test]
+    fn the_body_is_the_time_shown() {
+        let at = shown()
+            .at
+            .unwrap()
+            .to_rfc3339_opts(SecondsFormat::Millis, false);
+        assert_eq!(
+            shown().json(),
+            format!(
+                "{{\"text\":\"9:41 \\\"AM\\\"\",\"at\":\"{at}\",\"synced\":true,\"estimated_error_ms\":1}}"
+            )
+        );
+
+        // Nothing shown yet is just empty text
+        assert_eq!(Shown::default().json(), "{\"text\":\"\"}");
+    }
+
+    #[cfg(feature = "serve")]
+    #[test]
+    fn answers_gets_of_the_time_and_refuses_anything_else() {
+        use hyper::{Request, StatusCode, header};
+
+        use super::server::respond;
+
+        let response = |method, uri| {
+            let request = Request::builder().method(method).uri(uri).body(()).unwrap();
+            respond(&request, &shown())
+        };
+        let time = response("GET", "/time?fresh");
+        assert_eq!(time.status(), StatusCode::OK);
+        assert_eq!(time.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(time.body(), &shown().json());
+
+        assert_eq!(response("GET", "/metrics").status(), StatusCode::NOT_FOUND);
+        let post = response("POST", "/");
+        assert_eq!(post.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(post.headers()[header::ALLOW], "GET");
+    }
+
+    #[cfg(feature = "serve")]
+    #[tokio::test]
+    async fn serves_what_was_last_drawn() {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpStream,
+            sync::watch,
+        };
+
+        let (tx, rx) = watch::channel(Shown::default());
+        let server = TimeServer::bind("127.0.0.1:0".parse().unwrap(), rx)
+            .await
+            .unwrap();
+        let get = || async {
+            let mut stream = TcpStream::connect(server.addr()).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response.split_once("\r\n\r\n").unwrap().1.to_owned()
+        };
+        assert_eq!(get().await, "{\"text\":\"\"}");
+        tx.send_replace(Shown {
+            text: "12:00".to_owned(),
+            ..Shown::default()
+        });
+        assert_eq!(get().await, "{\"text\":\"12:00\"}");
+
+        let addr = server.addr();
+        server.stop();
+        tokio::task::yield_now().await;
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+}
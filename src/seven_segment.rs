@@ -0,0 +1,231 @@
+//! The built-in `seven-segment` font, built from the rectangles of an LCD digit's segments so
+//! they can be made thicker, and so the segments a digit leaves unlit are known and can be drawn
+//! faintly with `--segment-off-color`.
+
+use ratatui::layout::{Position, Rect};
+
+/// The segments lit for each digit, as bits 0 to 6 for segments a to g: a along the top, then
+/// clockwise round b, c, d, e, and f, and g across the middle.
+const DIGITS: [u8; 10] = [
+    0b011_1111, // 0
+    0b000_0110, // 1
+    0b101_1011, // 2
+    0b100_1111, // 3
+    0b110_0110, // 4
+    0b110_1101, // 5
+    0b111_1101, // 6
+    0b000_0111, // 7
+    0b111_1111, // 8
+    0b110_1111, // 9
+];
+
+/// The letters of AM and PM that segments can spell. `M` can't be, so it's drawn freehand.
+const LETTERS: [(char, u8); 2] = [('A', 0b111_0111), ('P', 0b111_0011)];
+
+/// The segments a to g of a digit with segments `thickness` cells thick. The horizontal ones run
+/// between the vertical ones, leaving the corners empty, and both grow a little longer with
+/// their thickness so thick segments don't crowd the digit's middle.
+fn segments(thickness: u16) -> [Rect; 7] {
+    let (t, across, down) = (thickness, thickness + 2, thickness + 1);
+    [
+        Rect::new(t, 0, across, t),
+        Rect::new(t + across, t, t, down),
+        Rect::new(t + across, 2 * t + down, t, down),
+        Rect::new(t, 2 * (t + down), across, t),
+        Rect::new(0, 2 * t + down, t, down),
+        Rect::new(0, t, t, down),
+        Rect::new(t, t + down, across, t),
+    ]
+}
+
+/// The font's definition, in the format [`SegmentFont::parse`] reads, with segments `thickness`
+/// cells thick. A digit's unlit segments are `o`s.
+///
+/// [`SegmentFont::parse`]: crate::segment_font::SegmentFont::parse
+pub fn definition(thickness: u16) -> String {
+    let segments = segments(thickness);
+    let bounds = segments
+        .iter()
+        .fold(Rect::ZERO, |bounds, segment| bounds.union(*segment));
+    let (width, height) = (bounds.width, bounds.height);
+    let middle = segments[6];
+
+    let glyph = |lit: &dyn Fn(u16, u16) -> char, width: u16| {
+        (0..height)
+            .map(|y| (0..width).map(|x| lit(x, y)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let segmented = |mask: u8| {
+        glyph(
+            &|x, y| match segments
+                .iter()
+                .position(|s| s.contains(Position::new(x, y)))
+            {
+                Some(i) if mask & 1 << i != 0 => '#',
+                Some(_) => 'o',
+                None => '.',
+            },
+            width,
+        )
+    };
+
+    let mut glyphs: Vec<(String, String)> = DIGITS
+        .iter()
+        .enumerate()
+        .map(|(digit, &mask)| (digit.to_string(), segmented(mask)))
+        .collect();
+    glyphs.extend(LETTERS.map(|(c, mask)| (c.to_string(), segmented(mask))));
+    // Dots hugging the middle segment from above and below
+    let colon = |_, y| {
+        let above = middle.y - thickness..middle.y;
+        let below = middle.bottom()..middle.bottom() + thickness;
+        if above.contains(&y) || below.contains(&y) {
+            '#'
+        } else {
+            '.'
+        }
+    };
+    glyphs.push((":".to_owned(), glyph(&colon, thickness)));
+    let point = |_, y| if y >= height - thickness { '#' } else { '.' };
+    glyphs.push((".".to_owned(), glyph(&point, thickness)));
+    let dash = |_, y| {
+        if (middle.y..middle.bottom()).contains(&y) {
+            '#'
+        } else {
+            '.'
+        }
+    };
+    glyphs.push(("-".to_owned(), glyph(&dash, middle.width)));
+    glyphs.push(("space".to_owned(), glyph(&|_, _| '.', thickness + 1)));
+    // Full-height sides, and a V between them from the top meeting in the middle
+    let m = |x: u16, y: u16| {
+        let inner = x.wrapping_sub(thickness);
+        let down = y.wrapping_sub(thickness);
+        let side = x < thickness || x >= width - thickness;
+        let diagonal =
+            down <= (middle.width - 1) / 2 && (inner == down || inner == middle.width - 1 - down);
+        if side || diagonal { '#' } else { '.' }
+    };
+    glyphs.push(("M".to_owned(), glyph(&m, width)));
+
+    glyphs
+        .iter()
+        .map(|(name, rows)| format!("{name}\n{rows}\n"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        Terminal,
+        backend::TestBackend,
+        style::{Color, Style},
+    };
+
+    use crate::{
+        segment_font::{SegmentFont, SegmentText},
+        widget::tests::rows,
+    };
+
+    fn render(text: &str, font: &SegmentFont, off: Option<Style>) -> Terminal<TestBackend> {
+        let (width, height) = font.size(text);
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let text = SegmentText {
+            text,
+            font,
+            style: Style::new(),
+            off_style: off,
+        };
+        terminal
+            .draw(|frame| frame.render_widget(text, frame.area()))
+            .unwrap();
+        terminal
+    }
+
+    #[test]
+    fn lights_each_digits_segments() {
+        let font = SegmentFont::seven_segment(1);
+        let digits: Vec<_> = (0..10)
+            .map(|digit| rows(render(&digit.to_string(), &font, None).backend().buffer()))
+            .collect();
+        #[rustfmt::skip]
+        let expected = [
+            [" ███ ", "█   █", "█   █", "     ", "█   █", "█   █", " ███ "],
+            ["     ", "    █", "    █", "     ", "    █", "    █", "     "],
+            [" ███ ", "    █", "    █", " ███ ", "█    ", "█    ", " ███ "],
+            [" ███ ", "    █", "    █", " ███ ", "    █", "    █", " ███ "],
+            ["     ", "█   █", "█   █", " ███ ", "    █", "    █", "     "],
+            [" ███ ", "█    ", "█    ", " ███ ", "    █", "    █", " ███ "],
+            [" ███ ", "█    ", "█    ", " ███ ", "█   █", "█   █", " ███ "],
+            [" ███ ", "    █", "    █", "     ", "    █", "    █", "     "],
+            [" ███ ", "█   █", "█   █", " ███ ", "█   █", "█   █", " ███ "],
+            [" ███ ", "█   █", "█   █", " ███ ", "    █", "    █", " ███ "],
+        ];
+        assert_eq!(digits, expected);
+    }
+
+    #[test]
+    fn thickens_the_segments() {
+        let font = SegmentFont::seven_segment(2);
+        assert_eq!(font.size("8"), (8, 12));
+        let terminal = render("2:", &font, None);
+        assert_eq!(
+            rows(terminal.backend().buffer()),
+            [
+                "  ████     ",
+                "  ████     ",
+                "      ██   ",
+                "      ██ ██",
+                "      ██ ██",
+                "  ████     ",
+                "  ████     ",
+                "██       ██",
+                "██       ██",
+                "██         ",
+                "  ████     ",
+                "  ████     ",
+            ]
+        );
+    }
+
+    #[test]
+    fn draws_unlit_segments_in_the_off_style() {
+        let font = SegmentFont::seven_segment(1);
+        let off = Style::new().fg(Color::DarkGray);
+        let terminal = render("7", &font, Some(off));
+        let buffer = terminal.backend().buffer();
+        // All of an 8 is drawn, and only the 7's segments in the digits' color
+        assert_eq!(
+            rows(buffer),
+            rows(render("8", &font, None).backend().buffer())
+        );
+        assert_eq!(buffer[(1, 0)].fg, Color::Reset);
+        assert_eq!(buffer[(4, 1)].fg, Color::Reset);
+        assert_eq!(buffer[(0, 1)].fg, Color::DarkGray);
+        assert_eq!(buffer[(1, 3)].fg, Color::DarkGray);
+        assert_eq!(buffer[(1, 6)].fg, Color::DarkGray);
+
+        // Without one, they're left blank
+        let plain = render("7", &font, None);
+        assert_eq!(plain.backend().buffer()[(0, 1)].symbol(), " ");
+    }
+
+    #[test]
+    fn spells_am_and_pm() {
+        let font = SegmentFont::seven_segment(1);
+        assert_eq!(
+            rows(render("AM", &font, None).backend().buffer()),
+            [
+                " ███  █   █",
+                "█   █ ██ ██",
+                "█   █ █ █ █",
+                " ███  █   █",
+                "█   █ █   █",
+                "█   █ █   █",
+                "      █   █",
+            ]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::duration::{format_seconds, millis};
+
+/// The default for `--fps`, how often a millisecond stopwatch is redrawn. Faster than this the
+/// last digits are a blur anyway.
+pub const DEFAULT_FPS: u32 = 20;
+
+/// How finely the stopwatch shows the time, as `--precision` spells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Precision {
+    /// Whole seconds, like `01:02`.
+    #[default]
+    #[value(name = "s")]
+    Seconds,
+    /// Milliseconds, like `01:02.345`.
+    #[value(name = "ms")]
+    Millis,
+}
+
+/// One recorded lap: the time since the previous lap and since the stopwatch started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lap {
+    #[serde(rename = "split_ms", with = "millis")]
+    pub split: Duration,
+    #[serde(rename = "total_ms", with = "millis")]
+    pub total: Duration,
+}
+
+/// A stopwatch on the monotonic clock. Laps are taken from the same clock as the display, at the
+/// moment of the key press rather than from whatever was last drawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stopwatch {
+    /// Time accumulated before the current run, i.e. across earlier pauses.
+    banked: Duration,
+    /// When the current run started, or `None` while paused.
+    running_since: Option<Instant>,
+    laps: Vec<Lap>,
+}
+
+impl Stopwatch {
+    pub fn start(now: Instant) -> Self {
+        Self {
+            banked: Duration::ZERO,
+            running_since: Some(now),
+            laps: Vec::new(),
+        }
+    }
+
+    /// A stopwatch picked up at `elapsed` with `laps`, as an earlier run left it, running on
+    /// from `now` if it was.
+    pub fn resumed(elapsed: Duration, running: bool, laps: Vec<Lap>, now: Instant) -> Self {
+        Self {
+            banked: elapsed,
+            running_since: running.then_some(now),
+            laps,
+        }
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.banked
+            + self
+                .running_since
+                .map_or(Duration::ZERO, |since| now.saturating_duration_since(since))
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        match self.running_since.take() {
+            Some(since) => self.banked += now.saturating_duration_since(since),
+            None => self.running_since = Some(now),
+        }
+    }
+
+    /// Adds `slept`, time the machine spent suspended that the monotonic clock missed, if the
+    /// stopwatch is running.
+    pub fn slept(&mut self, slept: Duration) {
+        if self.is_running() {
+            self.banked += slept;
+        }
+    }
+
+    pub fn lap(&mut self, now: Instant) {
+        let total = self.elapsed(now);
+        let previous = self.laps.last().map_or(Duration::ZERO, |lap| lap.total);
+        self.laps.push(Lap {
+            split: total - previous,
+            total,
+        });
+    }
+
+    pub fn laps(&self) -> &[Lap] {
+        &self.laps
+    }
+
+    /// When the displayed whole-second count next changes, or `None` while paused.
+    pub fn next_tick(&self, now: Instant) -> Option<Instant> {
+        let into_second = Duration::from_nanos(self.elapsed(now).subsec_nanos().into());
+        self.is_running()
+            .then(|| now + (Duration::from_secs(1) - into_second))
+    }
+}
+
+/// Writes `laps` as CSV with split and cumulative times in seconds.
+pub fn write_laps_csv(laps: &[Lap], path: &Path) -> anyhow::Result<()> {
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, "lap,split_seconds,total_seconds")?;
+    for (i, lap) in laps.iter().enumerate() {
+        writeln!(
+            out,
+            "{},{},{}",
+            i + 1,
+            format_seconds(lap.split),
+            format_seconds(lap.total)
+        )?;
+    }
+    out.flush()
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn laps_split_from_the_previous_lap() {
+        let start = Instant::now();
+        let mut stopwatch = Stopwatch::start(start);
+        stopwatch.lap(start + Duration::from_millis(1500));
+        stopwatch.lap(start + Duration::from_millis(4000));
+
+        assert_eq!(
+            stopwatch.laps(),
+            [
+                Lap {
+                    split: Duration::from_millis(1500),
+                    total: Duration::from_millis(1500),
+                },
+                Lap {
+                    split: Duration::from_millis(2500),
+                    total: Duration::from_millis(4000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn paused_time_is_not_counted() {
+        let start = Instant::now();
+        let mut stopwatch = Stopwatch::start(start);
+        stopwatch.toggle_pause(start + Duration::from_secs(2));
+        assert_eq!(stopwatch.next_tick(start + Duration::from_secs(3)), None);
+        stopwatch.toggle_pause(start + Duration::from_secs(10));
+        stopwatch.lap(start + Duration::from_secs(11));
+
+        assert_eq!(stopwatch.laps()[0].total, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn writes_laps_as_csv() {
+        let path = std::env::temp_dir().join(format!("tui-time-laps-{}.csv", std::process::id()));
+        let laps = [
+            Lap {
+                split: Duration::from_millis(1500),
+                total: Duration::from_millis(1500),
+            },
+            Lap {
+                split: Duration::from_millis(61_250),
+                total: Duration::from_millis(62_750),
+            },
+        ];
+        write_laps_csv(&laps, &path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "lap,split_seconds,total_seconds\n1,1.500,1.500\n2,61.250,62.750\n"
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
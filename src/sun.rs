@@ -0,0 +1,206 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Timelike, Utc};
+use serde::Deserialize;
+
+/// Hours (local time) treated as daytime when no coordinates are configured.
+const SIMPLE_DAY_HOURS: std::ops::Range<u32> = 6..18;
+
+/// A position on Earth in degrees, north and east positive.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Sunrise and sunset for one day, or which polar case applies when the sun never crosses the
+/// horizon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SunTimes {
+    RiseAndSet {
+        rise: DateTime<Utc>,
+        set: DateTime<Utc>,
+    },
+    PolarDay,
+    PolarNight,
+}
+
+/// Computes sunrise and sunset on `date` at `coords` with the standard sunrise equation, which is
+/// accurate to within a minute or two away from the poles.
+pub fn sun_times(date: NaiveDate, coords: Coordinates) -> SunTimes {
+    const J2000: f64 = 2451545.0;
+    const UNIX_EPOCH_JULIAN: f64 = 2440587.5;
+
+    let days_since_j2000 = date
+        .signed_duration_since(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap())
+        .num_days() as f64;
+
+    // Mean solar noon, then the sun's anomaly, ecliptic longitude, and declination at it
+    let mean_noon = days_since_j2000 + 0.0008 - coords.longitude / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_noon)
+        .rem_euclid(360.0)
+        .to_radians();
+    let center =
+        1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic_longitude = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit =
+        J2000 + mean_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+    let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+
+    // Hour angle at which the sun's upper limb touches the horizon, allowing for refraction
+    let latitude = coords.latitude.to_radians();
+    let cos_hour_angle = ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    if cos_hour_angle > 1.0 {
+        return SunTimes::PolarNight;
+    }
+    if cos_hour_angle < -1.0 {
+        return SunTimes::PolarDay;
+    }
+
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+    let to_utc = |julian: f64| {
+        let millis = ((julian - UNIX_EPOCH_JULIAN) * 86_400_000.0).round() as i64;
+        Utc.timestamp_millis_opt(millis).unwrap()
+    };
+    SunTimes::RiseAndSet {
+        rise: to_utc(transit - half_day),
+        set: to_utc(transit + half_day),
+    }
+}
+
+impl SunTimes {
+    /// A line like `☀ 06:42 ↑ · 19:55 ↓`, with the times in `zone`. When the sun doesn't cross
+    /// the horizon it says which way instead.
+    pub fn line<Tz: TimeZone>(&self, zone: &Tz) -> String
+    where
+        Tz::Offset: Display,
+    {
+        match self {
+            Self::RiseAndSet { rise, set } => format!(
+                "☀ {} ↑ · {} ↓",
+                rise.with_timezone(zone).format("%H:%M"),
+                set.with_timezone(zone).format("%H:%M")
+            ),
+            Self::PolarDay => "☀ no sunset".to_owned(),
+            Self::PolarNight => "🌙 no sunrise".to_owned(),
+        }
+    }
+}
+
+/// Whether it is daytime at `now`: between today's sunrise and sunset when coordinates are
+/// known, or within a fixed range of hours when they aren't.
+pub fn is_day(now: DateTime<Local>, coords: Option<Coordinates>) -> bool {
+    let Some(coords) = coords else {
+        return SIMPLE_DAY_HOURS.contains(&now.hour());
+    };
+    match sun_times(now.date_naive(), coords) {
+        SunTimes::RiseAndSet { rise, set } => (rise..set).contains(&now.to_utc()),
+        SunTimes::PolarDay => true,
+        SunTimes::PolarNight => false,
+    }
+}
+
+/// The glyph shown above the clock.
+pub fn indicator(now: DateTime<Local>, coords: Option<Coordinates>) -> &'static str {
+    if is_day(now, coords) { "☀" } else { "🌙" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BERLIN: Coordinates = Coordinates {
+        latitude: 52.52,
+        longitude: 13.405,
+    };
+    const TROMSO: Coordinates = Coordinates {
+        latitude: 69.65,
+        longitude: 18.96,
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn assert_near(actual: DateTime<Utc>, expected: &str) {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        let off = (actual - expected).num_seconds().abs();
+        assert!(off <= 180, "{actual} is {off}s from {expected}");
+    }
+
+    #[test]
+    fn berlin_midsummer_and_midwinter() {
+        let SunTimes::RiseAndSet { rise, set } = sun_times(date(2024, 6, 21), BERLIN) else {
+            panic!("Berlin has a sunrise in June");
+        };
+        assert_near(rise, "2024-06-21T02:43:00Z");
+        assert_near(set, "2024-06-21T19:33:00Z");
+
+        let SunTimes::RiseAndSet { rise, set } = sun_times(date(2024, 12, 21), BERLIN) else {
+            panic!("Berlin has a sunrise in December");
+        };
+        assert_near(rise, "2024-12-21T07:15:00Z");
+        assert_near(set, "2024-12-21T14:54:00Z");
+    }
+
+    #[test]
+    fn polar_day_and_night() {
+        assert_eq!(sun_times(date(2024, 6, 21), TROMSO), SunTimes::PolarDay);
+        assert_eq!(sun_times(date(2024, 12, 21), TROMSO), SunTimes::PolarNight);
+    }
+
+    #[test]
+    fn lines_give_local_times_or_the_polar_case() {
+        let cest = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            sun_times(date(2024, 6, 21), BERLIN).line(&cest),
+            "☀ 04:44 ↑ · 21:34 ↓"
+        );
+        assert_eq!(
+            sun_times(date(2024, 6, 21), TROMSO).line(&Utc),
+            "☀ no sunset"
+        );
+        assert_eq!(
+            sun_times(date(2024, 12, 21), TROMSO).line(&Utc),
+            "🌙 no sunrise"
+        );
+    }
+
+    #[test]
+    fn equator_and_southern_hemisphere() {
+        // Quito has about twelve hours of daylight all year round
+        let quito = Coordinates {
+            latitude: -0.18,
+            longitude: -78.47,
+        };
+        let SunTimes::RiseAndSet { rise, set } = sun_times(date(2024, 3, 20), quito) else {
+            panic!("Quito has a sunrise");
+        };
+        assert_near(rise, "2024-03-20T11:19:00Z");
+        assert_near(set, "2024-03-20T23:26:00Z");
+
+        // Sydney's midsummer is in December
+        let sydney = Coordinates {
+            latitude: -33.87,
+            longitude: 151.21,
+        };
+        let SunTimes::RiseAndSet { rise, set } = sun_times(date(2024, 12, 21), sydney) else {
+            panic!("Sydney has a sunrise");
+        };
+        assert_near(rise, "2024-12-20T18:41:00Z");
+        assert_near(set, "2024-12-21T09:05:00Z");
+    }
+
+    #[test]
+    fn falls_back_to_fixed_hours_without_coordinates() {
+        let at = |h| Local.with_ymd_and_hms(2024, 3, 1, h, 0, 0).unwrap();
+        assert!(!is_day(at(5), None));
+        assert!(is_day(at(6), None));
+        assert!(is_day(at(17), None));
+        assert!(!is_day(at(18), None));
+    }
+}
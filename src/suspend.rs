@@ -0,0 +1,88 @@
+//! Noticing the machine was suspended. `CLOCK_MONOTONIC` stops while it's asleep and
+//! `CLOCK_BOOTTIME` doesn't, so the gap between the two grows by however long it slept, and the
+//! loop looks at it each time it wakes to catch the clock and anything being timed up.
+
+use std::time::Duration;
+
+use crate::timer::clock_now;
+
+/// How much the gap has to grow to count as a suspend. Reading the two clocks one after the
+/// other puts a little noise in it, but never anywhere near this much.
+pub const THRESHOLD: Duration = Duration::from_secs(1);
+
+/// How often the loop wakes to look, when nothing else would wake it soon after a resume. A
+/// timerfd on the wall clock is cancelled by one, so this is only for the other tickers.
+pub const POLL: Duration = Duration::from_secs(5);
+
+/// The gap between the clocks when a suspend was last noticed, or the first look.
+#[derive(Debug, Default)]
+pub struct SuspendCheck {
+    gap: Option<Duration>,
+}
+
+impl SuspendCheck {
+    /// Reads the clocks, returning how long the machine was suspended since the last suspend
+    /// noticed, if it was at all. The first call only takes the starting gap.
+    pub fn check(&mut self) -> Option<Duration> {
+        let monotonic = clock_now(libc::CLOCK_MONOTONIC).ok()?;
+        let boottime = clock_now(libc::CLOCK_BOOTTIME).ok()?;
+        self.observe(monotonic, boottime)
+    }
+
+    /// [`SuspendCheck::check`] with the clocks already read. The gap it's measured from only
+    /// moves on once a suspend is reported, so short ones that each fall under [`THRESHOLD`]
+    /// add up until together they don't.
+    pub fn observe(&mut self, monotonic: Duration, boottime: Duration) -> Option<Duration> {
+        let gap = boottime.saturating_sub(monotonic);
+        let Some(last) = self.gap else {
+            self.gap = Some(gap);
+            return None;
+        };
+        let slept = gap.saturating_sub(last);
+        if slept < THRESHOLD {
+            return None;
+        }
+        self.gap = Some(gap);
+        Some(slept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn notices_the_boot_clock_pulling_ahead() {
+        let mut check = SuspendCheck::default();
+        // Booted after some earlier suspends, so the clocks already differ
+        assert_eq!(check.observe(secs(100.0), secs(130.0)), None);
+        // Both moving together is just time passing
+        assert_eq!(check.observe(secs(160.0), secs(190.0)), None);
+        // A few milliseconds' noise from reading them in turn is nothing
+        assert_eq!(check.observe(secs(161.0), secs(191.004)), None);
+        // A minute asleep
+        assert_eq!(check.observe(secs(162.0), secs(252.0)), Some(secs(60.0)));
+        // And it's only reported the once
+        assert_eq!(check.observe(secs(163.0), secs(253.0)), None);
+    }
+
+    #[test]
+    fn short_suspends_add_up() {
+        let mut check = SuspendCheck::default();
+        check.observe(secs(10.0), secs(10.0));
+        assert_eq!(check.observe(secs(11.0), secs(11.6)), None);
+        assert_eq!(check.observe(secs(12.0), secs(13.2)), Some(secs(1.2)));
+        assert_eq!(check.observe(secs(13.0), secs(14.2)), None);
+    }
+
+    #[test]
+    fn the_real_clocks_agree_while_awake() {
+        let mut check = SuspendCheck::default();
+        assert_eq!(check.check(), None);
+        assert_eq!(check.check(), None);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    io, mem,
+    time::{Duration, Instant},
+};
+
+use libc::c_int;
+
+/// How often the kernel is asked again. Sync state changes slowly, and the estimated error is only
+/// updated by the NTP daemon every few minutes at most.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whether the kernel considers the system clock synchronized, as an NTP daemon like chrony or
+/// systemd-timesyncd reports it through `adjtimex(2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStatus {
+    pub synced: bool,
+    /// How far off the clock is thought to be, as last estimated by the daemon.
+    pub estimated_error: Duration,
+}
+
+impl SyncStatus {
+    /// Reads the clock's state without changing it.
+    pub fn read() -> io::Result<Self> {
+        // SAFETY: timex is plain integers, for which all zeroes is valid; with modes zeroed,
+        // adjtimex only fills it in and changes nothing
+        let mut timex: libc::timex = unsafe { mem::zeroed() };
+        let state = unsafe { libc::adjtimex(&mut timex) };
+        if state < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self::from_timex(state, &timex))
+    }
+
+    /// The status from what `adjtimex` returned and filled in. The clock is unsynced when the
+    /// daemon has flagged it so, or when the kernel reports `TIME_ERROR`, which it does once the
+    /// maximum error has grown past 16 seconds without an update.
+    pub fn from_timex(state: c_int, timex: &libc::timex) -> Self {
+        Self {
+            synced: state != libc::TIME_ERROR && timex.status & libc::STA_UNSYNC == 0,
+            estimated_error: Duration::from_micros(timex.esterror.max(0) as u64),
+        }
+    }
+
+    /// The corner tag, e.g. `sync ✓ (±2ms)` or `unsynced`.
+    pub fn tag(&self) -> String {
+        if !self.synced {
+            return "unsynced".to_owned();
+        }
+        let micros = self.estimated_error.as_micros();
+        let error = if micros < 1000 {
+            format!("{micros}µs")
+        } else if micros < 1_000_000 {
+            format!("{}ms", micros.div_ceil(1000))
+        } else {
+            format!("{:.1}s", self.estimated_error.as_secs_f64())
+        };
+        format!("sync ✓ (±{error})")
+    }
+}
+
+/// The `--sync-status` tag's reading, refreshed every [`POLL_INTERVAL`].
+#[derive(Debug, Default)]
+pub struct SyncWatch {
+    status: Option<io::Result<SyncStatus>>,
+    checked_at: Option<Instant>,
+}
+
+impl SyncWatch {
+    /// Reads the status again if the last reading is a minute old, or there isn't one yet.
+    pub fn update(&mut self, now: Instant) {
+        if self
+            .checked_at
+            .is_some_and(|at| now.saturating_duration_since(at) < POLL_INTERVAL)
+        {
+            return;
+        }
+        self.status = Some(SyncStatus::read());
+        self.checked_at = Some(now);
+    }
+
+    /// The last reading, if it succeeded.
+    pub fn status(&self) -> Option<SyncStatus> {
+        self.status.as_ref()?.as_ref().ok().copied()
+    }
+
+    /// The tag's text, and whether the clock is synced. A failed read shows as unknown rather
+    /// than unsynced, since the clock itself may well be fine.
+    pub fn tag(&self) -> Option<(String, bool)> {
+        match self.status.as_ref()? {
+            Ok(status) => Some((status.tag(), status.synced)),
+            Err(_) => Some(("sync unknown".to_owned(), true)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timex(status: c_int, esterror: libc::c_long) -> libc::timex {
+        let mut timex: libc::timex = unsafe { mem::zeroed() };
+        timex.status = status;
+        timex.esterror = esterror;
+        timex
+    }
+
+    #[test]
+    fn reads_the_unsync_flag_and_error_state() {
+        let synced = SyncStatus::from_timex(libc::TIME_OK, &timex(libc::STA_PLL, 1500));
+        assert_eq!(
+            synced,
+            SyncStatus {
+                synced: true,
+                estimated_error: Duration::from_micros(1500),
+            }
+        );
+        let flagged = timex(libc::STA_PLL | libc::STA_UNSYNC, 1500);
+        assert!(!SyncStatus::from_timex(libc::TIME_OK, &flagged).synced);
+        assert!(!SyncStatus::from_timex(libc::TIME_ERROR, &timex(0, 1500)).synced);
+        // A negative estimate is nonsense, not a reason to fail
+        let negative = SyncStatus::from_timex(libc::TIME_OK, &timex(0, -1));
+        assert_eq!(negative.estimated_error, Duration::ZERO);
+    }
+
+    #[test]
+    fn tags_with_the_error_bound_rounded_up() {
+        let tag = |status: c_int, esterror| {
+            SyncStatus::from_timex(libc::TIME_OK, &timex(status, esterror)).tag()
+        };
+        assert_eq!(tag(0, 1500), "sync ✓ (±2ms)");
+        assert_eq!(tag(0, 250), "sync ✓ (±250µs)");
+        assert_eq!(tag(0, 2_400_000), "sync ✓ (±2.4s)");
+        assert_eq!(tag(libc::STA_UNSYNC, 1500), "unsynced");
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, bail};
+use ratatui::style::{Color, Style};
+use serde::Deserialize;
+
+/// Colors for the clock and everything drawn around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub name: String,
+    pub fg: Color,
+    pub bg: Color,
+    /// Color for secondary text (notices, tables, indicators). Secondary text is merely dimmed
+    /// when unset.
+    pub dim: Option<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        builtin("default", Color::Reset, Color::Reset, None)
+    }
+}
+
+impl Theme {
+    pub fn style(&self) -> Style {
+        Style::new().fg(self.fg).bg(self.bg)
+    }
+
+    pub fn dim_style(&self) -> Style {
+        match self.dim {
+            Some(dim) => Style::new().fg(dim),
+            None => Style::new().fg(self.fg).dim(),
+        }
+    }
+}
+
+/// A theme as written in the config's `[themes.<name>]` tables. Colors are anything ratatui can
+/// parse: names like `red`, `#rrggbb`, or a 256-color index.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub fg: String,
+    #[serde(default)]
+    pub bg: Option<String>,
+    #[serde(default)]
+    pub dim: Option<String>,
+}
+
+fn builtin(name: &str, fg: Color, bg: Color, dim: Option<Color>) -> Theme {
+    Theme {
+        name: name.to_owned(),
+        fg,
+        bg,
+        dim,
+    }
+}
+
+fn builtins() -> Vec<Theme> {
+    vec![
+        Theme::default(),
+        builtin(
+            "green-phosphor",
+            Color::Rgb(0x33, 0xff, 0x66),
+            Color::Rgb(0x06, 0x14, 0x0a),
+            Some(Color::Rgb(0x1a, 0x80, 0x33)),
+        ),
+        builtin(
+            "amber",
+            Color::Rgb(0xff, 0xb0, 0x00),
+            Color::Rgb(0x1a, 0x12, 0x00),
+            Some(Color::Rgb(0x8c, 0x61, 0x00)),
+        ),
+        builtin(
+            "blue",
+            Color::Rgb(0x5a, 0xb4, 0xff),
+            Color::Rgb(0x00, 0x14, 0x28),
+            Some(Color::Rgb(0x2d, 0x5a, 0x80)),
+        ),
+        builtin(
+            "high-contrast",
+            Color::White,
+            Color::Black,
+            Some(Color::Gray),
+        ),
+    ]
+}
+
+/// A color name like `red`, an index like `208`, or a hex code like `#ffb000`.
+pub fn parse_color(s: &str) -> anyhow::Result<Color> {
+    s.parse()
+        .ok()
+        .with_context(|| format!("invalid color {s:?}"))
+}
+
+/// A background color as [`parse_color`] reads it, or `none` (or `transparent`) for the
+/// terminal's own background.
+pub fn parse_background(s: &str) -> anyhow::Result<Color> {
+    match s {
+        "none" | "transparent" => Ok(Color::Reset),
+        _ => parse_color(s),
+    }
+}
+
+/// The themes cycled through at runtime: the built-ins in order, then any from the config. A
+/// config theme sharing a built-in's name replaces that built-in in place.
+pub fn themes(custom: &BTreeMap<String, ThemeConfig>) -> anyhow::Result<Vec<Theme>> {
+    let mut themes = builtins();
+    for (name, config) in custom {
+        let theme = Theme {
+            name: name.clone(),
+            fg: parse_color(&config.fg).with_context(|| format!("in theme {name}"))?,
+            bg: match &config.bg {
+                Some(bg) => parse_color(bg).with_context(|| format!("in theme {name}"))?,
+                None => Color::Reset,
+            },
+            dim: match &config.dim {
+                Some(dim) => Some(parse_color(dim).with_context(|| format!("in theme {name}"))?),
+                None => None,
+            },
+        };
+        match themes.iter_mut().find(|existing| existing.name == *name) {
+            Some(existing) => *existing = theme,
+            None => themes.push(theme),
+        }
+    }
+    Ok(themes)
+}
+
+/// The index of the theme called `name`.
+pub fn find(themes: &[Theme], name: &str) -> anyhow::Result<usize> {
+    match themes.iter().position(|theme| theme.name == name) {
+        Some(i) => Ok(i),
+        None => {
+            let names: Vec<_> = themes.iter().map(|theme| theme.name.as_str()).collect();
+            bail!(
+                "unknown theme {name:?} (expected one of: {})",
+                names.join(", ")
+            )
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(entries: &[(&str, &str, Option<&str>)]) -> BTreeMap<String, ThemeConfig> {
+        entries
+            .iter()
+            .map(|(name, fg, bg)| {
+                let config = ThemeConfig {
+                    fg: fg.to_string(),
+                    bg: bg.map(str::to_owned),
+                    dim: None,
+                };
+                (name.to_string(), config)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn config_themes_follow_builtins_or_replace_them() {
+        let themes = themes(&custom(&[
+            ("amber", "yellow", None),
+            ("zebra", "#ffffff", Some("black")),
+        ]))
+        .unwrap();
+        let names: Vec<_> = themes.iter().map(|theme| theme.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "default",
+                "green-phosphor",
+                "amber",
+                "blue",
+                "high-contrast",
+                "zebra"
+            ]
+        );
+        assert_eq!(themes[2].fg, Color::Yellow);
+        assert_eq!(themes[5].fg, Color::Rgb(0xff, 0xff, 0xff));
+        assert_eq!(themes[5].bg, Color::Black);
+    }
+
+    #[test]
+    fn rejects_bad_colors_and_unknown_names() {
+        assert!(themes(&custom(&[("oops", "not-a-color", None)])).is_err());
+
+        let themes = themes(&BTreeMap::new()).unwrap();
+        assert_eq!(find(&themes, "blue").unwrap(), 3);
+        assert!(find(&themes, "plaid").is_err());
+    }
+
+    #[test]
+    fn backgrounds_can_be_left_transparent() {
+        assert_eq!(parse_background("none").unwrap(), Color::Reset);
+        assert_eq!(parse_background("transparent").unwrap(), Color::Reset);
+        assert_eq!(parse_background("#061408").unwrap(), Color::Rgb(6, 20, 8));
+        assert!(parse_background("clear").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+
+pub const MINUTE: Duration = Duration::from_secs(60);
+pub const SECOND: Duration = Duration::from_secs(1);
+/// How often fractional seconds are redrawn. Faster than this is just a blur.
+pub const SUBSECOND: Duration = Duration::from_millis(100);
+
+/// The coarsest wall-clock tick that still catches every visible change to a time drawn with
+/// `format`: every minute unless it shows seconds or fractions of one.
+pub fn interval_for_format(format: &str) -> Duration {
+    StrftimeItems::new(format)
+        .map(|item| match item {
+            Item::Fixed(
+                Fixed::Nanosecond
+                | Fixed::Nanosecond3
+                | Fixed::Nanosecond6
+                | Fixed::Nanosecond9
+                | Fixed::RFC3339
+                | Fixed::Internal(_),
+            ) => SUBSECOND,
+            Item::Numeric(Numeric::Second | Numeric::Timestamp, _)
+            | Item::Fixed(Fixed::RFC2822) => SECOND,
+            _ => MINUTE,
+        })
+        .min()
+        .unwrap_or(MINUTE)
+}
+
+/// The default cap on how often ticks redraw, in frames per second.
+pub const DEFAULT_MAX_FPS: u32 = 30;
+
+/// Caps the frame rate of tick-driven redraws, so fast modes like a sweeping second hand don't
+/// redraw more often than the eye can follow. Keys and resizes redraw straight away regardless,
+/// and don't count against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLimiter {
+    interval: Duration,
+    last_draw: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / max_fps.max(1),
+            last_draw: None,
+        }
+    }
+
+    /// When a tick arriving at `now` may redraw, or `None` if it can straight away.
+    pub fn defer(&self, now: Instant) -> Option<Instant> {
+        let next = self.last_draw? + self.interval;
+        (now < next).then_some(next)
+    }
+
+    pub fn drew(&mut self, now: Instant) {
+        self.last_draw = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limiter_defers_ticks_that_come_too_soon() {
+        let start = Instant::now();
+        let mut limiter = FrameLimiter::new(DEFAULT_MAX_FPS);
+        assert_eq!(limiter.defer(start), None);
+
+        limiter.drew(start);
+        let frame = Duration::from_secs(1) / DEFAULT_MAX_FPS;
+        assert_eq!(
+            limiter.defer(start + Duration::from_millis(10)),
+            Some(start + frame)
+        );
+        assert_eq!(limiter.defer(start + frame), None);
+
+        // Zero would divide by zero, so it means one frame a second
+        let mut slowest = FrameLimiter::new(0);
+        slowest.drew(start);
+        assert_eq!(
+            slowest.defer(start + Duration::from_millis(500)),
+            Some(start + SECOND)
+        );
+    }
+
+    #[test]
+    fn picks_the_interval_from_the_format() {
+        for (format, interval) in [
+            ("%H:%M", MINUTE),
+            ("%I:%M %p", MINUTE),
+            ("%a %d %b", MINUTE),
+            ("", MINUTE),
+            ("100%%S", MINUTE),
+            ("%H:%M:%S", SECOND),
+            ("%T", SECOND),
+            ("%r", SECOND),
+            ("%s", SECOND),
+            ("%H:%M:%S%.3f", SUBSECOND),
+            ("%S.%3f", SUBSECOND),
+            ("%+", SUBSECOND),
+        ] {
+            assert_eq!(interval_for_format(format), interval, "for {format:?}");
+        }
+    }
+}
@@ -0,0 +1,754 @@
+//! Waking the main loop when the display changes. The clock only needs redrawing on the
+//! wall-clock boundaries its format can show (each minute, second, or tenth), so rather than
+//! polling, a `CLOCK_REALTIME` timerfd is armed on those boundaries with
+//! `TFD_TIMER_CANCEL_ON_SET`, which also wakes the loop whenever the clock is stepped. Displays
+//! that don't follow the wall clock, like the uptime, and ticks counted from startup with
+//! `--no-align`, tick on `CLOCK_MONOTONIC` instead, which stepping the wall clock doesn't disturb.
+
+use std::{
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    time::Duration,
+};
+
+use anyhow::Context;
+use tokio::io::unix::AsyncFd;
+
+/// Where ticks fall in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arming {
+    /// On the wall-clock boundaries of the interval (the top of each minute, each second, ...) so
+    /// redraws land exactly when the display changes. The boundaries are multiples of the
+    /// interval since the clock's zero, moved `phase` later for displays counting from
+    /// elsewhere, like decimal time from local midnight.
+    Boundary { phase: Duration },
+    /// Every interval counted from when the timer was armed, whatever the wall clock reads.
+    Relative,
+}
+
+impl Arming {
+    /// Boundary arming on the interval's own multiples.
+    pub const ALIGNED: Self = Self::Boundary {
+        phase: Duration::ZERO,
+    };
+}
+
+impl Default for Arming {
+    fn default() -> Self {
+        Self::ALIGNED
+    }
+}
+
+/// Why the ticker woke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tick {
+    /// The interval came round.
+    Interval,
+    /// The interval came round `expirations` times since the last wait, as when the process was
+    /// stopped or the loop held up, so the display is behind.
+    Behind { expirations: u64 },
+    /// The wall clock was stepped, so anything computed from it may be stale.
+    ClockStepped,
+}
+
+/// What wakes the loop on the display's ticks. A timerfd is used where the kernel allows one;
+/// some sandboxes forbid `timerfd_create`, and there a tokio interval stands in, which keeps the
+/// clock running but can't notice the wall clock being stepped.
+pub struct Ticker {
+    source: TickSource,
+    arming: Arming,
+    clock: libc::clockid_t,
+}
+
+enum TickSource {
+    TimerFd(TimerFd),
+    Interval(tokio::time::Interval),
+}
+
+impl Ticker {
+    /// Creates a timerfd on `clock` armed to fire every `interval`, falling back to an interval
+    /// timer (with a warning logged) where timerfds aren't allowed. Only the wall clock's
+    /// timers are cancelled by it being stepped.
+    pub fn new(clock: libc::clockid_t, interval: Duration, arming: Arming) -> anyhow::Result<Self> {
+        let source = match TimerFd::new(clock, interval, arming, clock == libc::CLOCK_REALTIME) {
+            Ok(tfd) => TickSource::TimerFd(tfd),
+            Err(err) if timerfd_unavailable(&err) => {
+                tracing::warn!("timerfd unavailable, falling back to an interval timer: {err:#}");
+                TickSource::Interval(tokio_interval(clock, interval, arming)?)
+            },
+            Err(err) => return Err(err.context("timerfd_create failed")),
+        };
+        Ok(Self {
+            source,
+            arming,
+            clock,
+        })
+    }
+
+    /// Whether ticks come from a timerfd, rather than the fallback interval timer.
+    pub fn uses_timerfd(&self) -> bool {
+        matches!(self.source, TickSource::TimerFd(_))
+    }
+
+    pub fn arming(&self) -> Arming {
+        self.arming
+    }
+
+    /// The clock the ticks are measured on.
+    pub fn clock(&self) -> libc::clockid_t {
+        self.clock
+    }
+
+    /// Switches to firing every `interval`, from the next boundary of it or from now. Returns the
+    /// tick that was due but not yet waited for, if one was, since it won't come after this.
+    pub fn rearm(&mut self, interval: Duration) -> anyhow::Result<Option<Tick>> {
+        match &mut self.source {
+            TickSource::TimerFd(tfd) => {
+                let pending = tfd
+                    .rearm(interval, self.arming)
+                    .context("re-arming the timer fd failed")?;
+                Ok(pending.map(expired))
+            },
+            TickSource::Interval(ticks) => {
+                *ticks = tokio_interval(self.clock, interval, self.arming)?;
+                Ok(None)
+            },
+        }
+    }
+
+    /// Switches to placing ticks per `arming`, every `interval`, returning any tick that was due
+    /// as [`Ticker::rearm`] does.
+    pub fn set_arming(
+        &mut self,
+        arming: Arming,
+        interval: Duration,
+    ) -> anyhow::Result<Option<Tick>> {
+        self.arming = arming;
+        self.rearm(interval)
+    }
+
+    /// Switches to ticking on `clock` every `interval`. A timerfd can't change clocks, so it's
+    /// replaced with a new one; the fallback timer just measures its boundaries on `clock`.
+    pub fn set_clock(&mut self, clock: libc::clockid_t, interval: Duration) -> anyhow::Result<()> {
+        self.source = match self.source {
+            TickSource::TimerFd(_) => TickSource::TimerFd(
+                TimerFd::new(clock, interval, self.arming, clock == libc::CLOCK_REALTIME)
+                    .context("timerfd_create failed")?,
+            ),
+            TickSource::Interval(_) => {
+                TickSource::Interval(tokio_interval(clock, interval, self.arming)?)
+            },
+        };
+        self.clock = clock;
+        Ok(())
+    }
+
+    /// Waits for the next tick. `interval` must be what the ticker was last armed with. A timerfd
+    /// cancelled by a clock change re-arms itself. Cancel-safe.
+    pub async fn tick(&mut self, interval: Duration) -> anyhow::Result<Tick> {
+        let tick = match &mut self.source {
+            TickSource::TimerFd(tfd) => expired(tfd.wait().await?),
+            TickSource::Interval(ticks) => {
+                ticks.tick().await;
+                Tick::Interval
+            },
+        };
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            self.log_tick(tick, interval);
+        }
+        Ok(tick)
+    }
+
+    /// Logs `tick` with, on boundary arming, how long after its boundary the loop woke for it.
+    fn log_tick(&self, tick: Tick, interval: Duration) {
+        let Arming::Boundary { phase } = self.arming else {
+            tracing::debug!(?tick, ?interval, "tick");
+            return;
+        };
+        let Ok(now) = clock_now(self.clock) else {
+            return;
+        };
+        let boundary = next_boundary(now, interval, phase).saturating_sub(interval);
+        let latency = now.saturating_sub(boundary);
+        tracing::debug!(?tick, ?interval, ?latency, "tick");
+    }
+}
+
+/// What the main loop needs of a [`Ticker`], so tests can tick it by hand.
+pub trait Ticks {
+    fn arming(&self) -> Arming;
+    fn clock(&self) -> libc::clockid_t;
+    fn set_arming(&mut self, arming: Arming, interval: Duration) -> anyhow::Result<Option<Tick>>;
+    fn set_clock(&mut self, clock: libc::clockid_t, interval: Duration) -> anyhow::Result<()>;
+    fn tick(&mut self, interval: Duration) -> impl Future<Output = anyhow::Result<Tick>>;
+
+    /// Whether resuming from suspend wakes the ticker, as it cancels a timerfd on the wall clock.
+    fn wakes_on_resume(&self) -> bool {
+        true
+    }
+}
+
+impl Ticks for Ticker {
+    fn arming(&self) -> Arming {
+        Ticker::arming(self)
+    }
+
+    fn clock(&self) -> libc::clockid_t {
+        Ticker::clock(self)
+    }
+
+    fn set_arming(&mut self, arming: Arming, interval: Duration) -> anyhow::Result<Option<Tick>> {
+        Ticker::set_arming(self, arming, interval)
+    }
+
+    fn set_clock(&mut self, clock: libc::clockid_t, interval: Duration) -> anyhow::Result<()> {
+        Ticker::set_clock(self, clock, interval)
+    }
+
+    fn wakes_on_resume(&self) -> bool {
+        self.uses_timerfd()
+            && self.clock == libc::CLOCK_REALTIME
+            && matches!(self.arming, Arming::Boundary { .. })
+    }
+
+    async fn tick(&mut self, interval: Duration) -> anyhow::Result<Tick> {
+        Ticker::tick(self, interval).await
+    }
+}
+
+/// Whether `timerfd_create` failed because it isn't allowed or doesn't exist here, rather than
+/// for some reason worth stopping over.
+fn timerfd_unavailable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>()
+        .and_then(io::Error::raw_os_error)
+        .is_some_and(|errno| errno == libc::EPERM || errno == libc::ENOSYS)
+}
+
+/// A tokio interval ticking when a [`TimerFd`] would fire, with boundaries measured from
+/// `clock` when it's created.
+fn tokio_interval(
+    clock: libc::clockid_t,
+    interval: Duration,
+    arming: Arming,
+) -> anyhow::Result<tokio::time::Interval> {
+    let until_boundary = match arming {
+        Arming::Boundary { phase } => {
+            let now = clock_now(clock).context("clock_gettime failed")?;
+            next_boundary(now, interval, phase) - now
+        },
+        Arming::Relative => interval,
+    };
+
+    let mut ticks =
+        tokio::time::interval_at(tokio::time::Instant::now() + until_boundary, interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    Ok(ticks)
+}
+
+/// A timerfd, a timer on one of the kernel's clocks that the loop waits on like any other file.
+/// Each wait returns how many times it expired since the last, so intervals the loop slept
+/// through are counted rather than lost.
+pub struct TimerFd {
+    fd: AsyncFd<OwnedFd>,
+    schedule: Schedule,
+}
+
+/// How a [`TimerFd`] is armed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Schedule {
+    clock: libc::clockid_t,
+    interval: Duration,
+    arming: Arming,
+    /// Whether the clock being stepped cancels the timer (`TFD_TIMER_CANCEL_ON_SET`). The kernel
+    /// only does that for boundary arming on `CLOCK_REALTIME`, and ignores it elsewhere.
+    cancel_on_set: bool,
+}
+
+impl TimerFd {
+    /// A non-blocking timerfd on `clock`, firing every `interval` placed per `arming`. With
+    /// `cancel_on_set`, stepping the wall clock cancels it, waking [`TimerFd::wait`] early.
+    pub fn new(
+        clock: libc::clockid_t,
+        interval: Duration,
+        arming: Arming,
+        cancel_on_set: bool,
+    ) -> anyhow::Result<Self> {
+        let fd = unsafe { libc::timerfd_create(clock, libc::TFD_CLOEXEC | libc::TFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        // SAFETY: timerfd_create just returned the fd, and nothing else owns it
+        let fd =
+            AsyncFd::new(unsafe { OwnedFd::from_raw_fd(fd) }).context("AsyncFd::new failed")?;
+        let schedule = Schedule {
+            clock,
+            interval,
+            arming,
+            cancel_on_set,
+        };
+        arm_tfd(fd.get_ref(), schedule).context("arm_tfd call failed")?;
+        Ok(Self { fd, schedule })
+    }
+
+    /// The clock the timer runs on.
+    pub fn clock(&self) -> libc::clockid_t {
+        self.schedule.clock
+    }
+
+    /// Switches to firing every `interval`, placed per `arming`. Returns what a wait would have
+    /// for the expirations that were pending, if any were, since re-arming drops them.
+    pub fn rearm(&mut self, interval: Duration, arming: Arming) -> anyhow::Result<Option<u64>> {
+        self.schedule = Schedule {
+            interval,
+            arming,
+            ..self.schedule
+        };
+        reconfigure_timer(self.fd.get_ref(), self.schedule)
+    }
+
+    /// Waits for the timer to expire, returning how many times it has since the last wait: one,
+    /// or more if the wait came late. Zero means the wall clock was stepped, cancelling the
+    /// timer, and it's been re-armed from the new time. Cancel-safe.
+    pub async fn wait(&self) -> anyhow::Result<u64> {
+        loop {
+            let mut guard = self.fd.readable().await.context("tfd.readable failed")?;
+            match consume_tfd_read(self.fd.get_ref(), self.schedule, || guard.clear_ready()) {
+                // Readiness left over from a tick that re-arming drained, now cleared
+                Err(err) if would_block(&err) => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+fn would_block(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.kind() == io::ErrorKind::WouldBlock)
+}
+
+/// The operations [`consume_tfd_read`] needs from a timer fd, split out so the read handling can
+/// be exercised without a real timerfd.
+trait RawTimer {
+    /// Reads the 8-byte expiration count into `buf`, returning the number of bytes read.
+    fn read(&self, buf: &mut u64) -> io::Result<usize>;
+
+    /// Arms the timer per `schedule`.
+    fn arm(&self, schedule: Schedule) -> anyhow::Result<()>;
+}
+
+impl RawTimer for OwnedFd {
+    fn read(&self, buf: &mut u64) -> io::Result<usize> {
+        match unsafe { libc::read(self.as_raw_fd(), buf as *mut u64 as _, 8) } {
+            ..0 => Err(io::Error::last_os_error()),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn arm(&self, schedule: Schedule) -> anyhow::Result<()> {
+        arm_tfd(self, schedule)
+    }
+}
+
+/// Consumes one pending read from `tfd`, returning the expirations it counted, calling
+/// `clear_ready` exactly once after the read (and before any re-arm).
+fn consume_tfd_read(
+    tfd: &impl RawTimer,
+    schedule: Schedule,
+    clear_ready: impl FnOnce(),
+) -> anyhow::Result<u64> {
+    let mut buf = 0_u64;
+    let ret = match tfd.read(&mut buf) {
+        // Check if this was from a discontinuous change to the kernel RT clock
+        Err(err) if err.raw_os_error() == Some(libc::ECANCELED) => {
+            // Clear readiness then re-arm
+            clear_ready();
+            tracing::info!("wall clock stepped, re-arming the timer fd");
+            tfd.arm(schedule).context("arm_tfd failed")?;
+            return Ok(0);
+        },
+        Err(err) => Err(err),
+        Ok(0..8) => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "short read on timer fd",
+        )),
+        Ok(8) => {
+            tracing::trace!(expirations = buf, "timer fd read");
+            Ok(buf)
+        },
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "longer than 8 read on timer fd",
+        )),
+    };
+
+    clear_ready();
+
+    Ok(ret?)
+}
+
+/// The tick for a wait on a [`TimerFd`] that counted `expirations`. More than one means the loop
+/// slept through some boundaries; none, that the timer was cancelled by a clock step.
+fn expired(expirations: u64) -> Tick {
+    match expirations {
+        0 => Tick::ClockStepped,
+        1 => Tick::Interval,
+        _ => {
+            tracing::info!(missed = expirations - 1, "ticks missed since the last read");
+            Tick::Behind { expirations }
+        },
+    }
+}
+
+/// Re-arms `tfd` per `schedule` when what the display needs changes. Setting the timer zeroes
+/// its count of expirations, so one that fired but wasn't read yet is read first and returned
+/// (as zero if the clock was stepped) rather than silently dropped; the new arming starts after
+/// now, so it can't fire for the same boundary again. The readiness that tick left behind is
+/// cleared by the next wait, which finds nothing to read.
+fn reconfigure_timer(tfd: &impl RawTimer, schedule: Schedule) -> anyhow::Result<Option<u64>> {
+    let mut buf = 0_u64;
+    let pending = match tfd.read(&mut buf) {
+        Ok(8) => Some(buf),
+        Ok(n) => {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("read of {n} bytes on timer fd"),
+            )
+            .into());
+        },
+        Err(err) if err.raw_os_error() == Some(libc::ECANCELED) => Some(0),
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => None,
+        Err(err) => return Err(err.into()),
+    };
+    tracing::debug!(?schedule, ?pending, "re-arming the timer fd");
+    tfd.arm(schedule)?;
+    Ok(pending)
+}
+
+/// Arms `tfd`, a timerfd, per `schedule`. Cancelling on a step of the clock needs boundary
+/// arming, since the kernel only offers that for absolute timers.
+fn arm_tfd(tfd: &impl AsRawFd, schedule: Schedule) -> anyhow::Result<()> {
+    let now = clock_now(schedule.clock).context("clock_gettime failed")?;
+
+    let (first, flags) = first_expiry(schedule, now);
+    tracing::debug!(
+        clock = schedule.clock,
+        interval = ?schedule.interval,
+        ?now,
+        deadline = ?first,
+        absolute = flags & libc::TFD_TIMER_ABSTIME != 0,
+        "arming the timer fd"
+    );
+    let timespec = |duration: Duration| libc::timespec {
+        tv_sec: duration.as_secs() as libc::time_t,
+        tv_nsec: duration.subsec_nanos().into(),
+    };
+
+    let new_itimerspec = libc::itimerspec {
+        it_value: timespec(first),
+        it_interval: timespec(schedule.interval),
+    };
+
+    if unsafe {
+        libc::timerfd_settime(
+            tfd.as_raw_fd(),
+            flags,
+            &new_itimerspec,
+            std::ptr::null_mut(),
+        )
+    } < 0
+    {
+        Err(io::Error::last_os_error().into())
+    } else {
+        Ok(())
+    }
+}
+
+/// The first expiry to arm a timerfd with per `schedule`, given the time its clock reads now, and
+/// the `timerfd_settime` flags saying how to read it.
+fn first_expiry(schedule: Schedule, now: Duration) -> (Duration, libc::c_int) {
+    match schedule.arming {
+        Arming::Boundary { phase } => {
+            let cancel_on_set = match schedule.cancel_on_set {
+                true => libc::TFD_TIMER_CANCEL_ON_SET,
+                false => 0,
+            };
+            (
+                next_boundary(now, schedule.interval, phase),
+                libc::TFD_TIMER_ABSTIME | cancel_on_set,
+            )
+        },
+        Arming::Relative => (schedule.interval, 0),
+    }
+}
+
+/// The first boundary after `now` of the `interval`s counted from `phase`, with any fraction of
+/// a second kept so boundaries like decimal time's 0.864s land exactly.
+fn next_boundary(now: Duration, interval: Duration, phase: Duration) -> Duration {
+    let step = interval.as_nanos();
+    let phase = phase.as_nanos() % step;
+    // Counted from one interval before the phase, so it never goes below zero
+    let since = now.as_nanos() + step - phase;
+    Duration::from_nanos((since / step * step + phase) as u64)
+}
+
+/// What `clock` reads now: the time since the epoch for `CLOCK_REALTIME`, or since boot for the
+/// monotonic clocks.
+pub fn clock_now(clock: libc::clockid_t) -> io::Result<Duration> {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(clock, &mut now) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Duration::new(now.tv_sec as u64, now.tv_nsec as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+    use crate::tick;
+
+    /// A [`RawTimer`] whose read returns a canned byte count or errno, recording each call made.
+    struct FakeTimerFd {
+        read_result: Result<usize, i32>,
+        /// The expiration count a successful read fills in.
+        expirations: u64,
+        calls: RefCell<Vec<&'static str>>,
+        rearmed_with: Cell<Option<Schedule>>,
+    }
+
+    /// A schedule on `clock`, cancelled by steps of it if it's the wall clock.
+    fn schedule(clock: libc::clockid_t, interval: Duration, arming: Arming) -> Schedule {
+        Schedule {
+            clock,
+            interval,
+            arming,
+            cancel_on_set: clock == libc::CLOCK_REALTIME,
+        }
+    }
+
+    impl FakeTimerFd {
+        fn new(read_result: Result<usize, i32>) -> Self {
+            Self {
+                read_result,
+                expirations: 1,
+                calls: RefCell::new(Vec::new()),
+                rearmed_with: Cell::new(None),
+            }
+        }
+
+        fn consume(&self) -> anyhow::Result<u64> {
+            let schedule = schedule(libc::CLOCK_REALTIME, tick::SECOND, Arming::Relative);
+            consume_tfd_read(self, schedule, || {
+                self.calls.borrow_mut().push("clear_ready")
+            })
+        }
+    }
+
+    impl RawTimer for FakeTimerFd {
+        fn read(&self, buf: &mut u64) -> io::Result<usize> {
+            self.calls.borrow_mut().push("read");
+            *buf = self.expirations;
+            self.read_result.map_err(io::Error::from_raw_os_error)
+        }
+
+        fn arm(&self, schedule: Schedule) -> anyhow::Result<()> {
+            self.calls.borrow_mut().push("rearm");
+            self.rearmed_with.set(Some(schedule));
+            Ok(())
+        }
+    }
+
+    fn io_error_kind(result: anyhow::Result<u64>) -> io::ErrorKind {
+        result
+            .unwrap_err()
+            .downcast_ref::<io::Error>()
+            .expect("error should be an io::Error")
+            .kind()
+    }
+
+    #[test]
+    fn arming_picks_absolute_boundaries_or_a_relative_delay() {
+        let now = Duration::from_millis(90_500);
+        let realtime = libc::CLOCK_REALTIME;
+        assert_eq!(
+            first_expiry(schedule(realtime, tick::MINUTE, Arming::ALIGNED), now),
+            (
+                Duration::from_secs(120),
+                libc::TFD_TIMER_ABSTIME | libc::TFD_TIMER_CANCEL_ON_SET
+            )
+        );
+        assert_eq!(
+            first_expiry(schedule(realtime, tick::MINUTE, Arming::Relative), now),
+            (tick::MINUTE, 0)
+        );
+        // Only the wall clock gets stepped, so only its timers ask to hear about it
+        let monotonic = schedule(libc::CLOCK_MONOTONIC, tick::SECOND, Arming::ALIGNED);
+        assert_eq!(
+            first_expiry(monotonic, now),
+            (Duration::from_secs(91), libc::TFD_TIMER_ABSTIME)
+        );
+        let uncancelled = Schedule {
+            cancel_on_set: false,
+            ..schedule(realtime, tick::MINUTE, Arming::ALIGNED)
+        };
+        assert_eq!(
+            first_expiry(uncancelled, now),
+            (Duration::from_secs(120), libc::TFD_TIMER_ABSTIME)
+        );
+    }
+
+    #[test]
+    fn phased_boundaries_keep_their_fraction() {
+        let interval = Duration::from_millis(864);
+        let phase = Duration::from_millis(288);
+        // 90.5s is 0.356s past the 104th boundary after the phase, so the next is 0.508s away
+        assert_eq!(
+            next_boundary(Duration::from_millis(90_500), interval, phase),
+            Duration::from_millis(91_008)
+        );
+        // Exactly on a boundary waits for the next
+        assert_eq!(
+            next_boundary(Duration::from_millis(91_008), interval, phase),
+            Duration::from_millis(91_872)
+        );
+        assert_eq!(
+            next_boundary(Duration::ZERO, interval, phase),
+            Duration::from_millis(288)
+        );
+    }
+
+    #[test]
+    fn only_forbidden_or_missing_timerfds_fall_back() {
+        let errno = |errno| anyhow::Error::from(io::Error::from_raw_os_error(errno));
+        assert!(timerfd_unavailable(&errno(libc::EPERM)));
+        assert!(timerfd_unavailable(
+            &errno(libc::ENOSYS).context("arm_tfd call failed")
+        ));
+        assert!(!timerfd_unavailable(&errno(libc::EMFILE)));
+        assert!(!timerfd_unavailable(&anyhow::anyhow!(
+            "AsyncFd::new failed"
+        )));
+    }
+
+    #[test]
+    fn zero_byte_read_is_unexpected_eof() {
+        let tfd = FakeTimerFd::new(Ok(0));
+        assert_eq!(io_error_kind(tfd.consume()), io::ErrorKind::UnexpectedEof);
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready"]);
+    }
+
+    #[test]
+    fn short_read_is_unexpected_eof() {
+        let tfd = FakeTimerFd::new(Ok(4));
+        assert_eq!(io_error_kind(tfd.consume()), io::ErrorKind::UnexpectedEof);
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready"]);
+    }
+
+    #[test]
+    fn full_read_is_ok() {
+        let tfd = FakeTimerFd::new(Ok(8));
+        assert_eq!(tfd.consume().unwrap(), 1);
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready"]);
+    }
+
+    #[test]
+    fn expirations_are_counted_from_the_read() {
+        let tfd = FakeTimerFd {
+            expirations: 3,
+            ..FakeTimerFd::new(Ok(8))
+        };
+        assert_eq!(tfd.consume().unwrap(), 3);
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready"]);
+        let realtime = schedule(libc::CLOCK_REALTIME, tick::SECOND, Arming::ALIGNED);
+        assert_eq!(reconfigure_timer(&tfd, realtime).unwrap(), Some(3));
+        assert_eq!(expired(3), Tick::Behind { expirations: 3 });
+        assert_eq!(expired(1), Tick::Interval);
+    }
+
+    #[test]
+    fn over_read_is_unexpected_eof() {
+        let tfd = FakeTimerFd::new(Ok(12));
+        assert_eq!(io_error_kind(tfd.consume()), io::ErrorKind::UnexpectedEof);
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready"]);
+    }
+
+    #[test]
+    fn ecanceled_clears_readiness_then_rearms() {
+        let tfd = FakeTimerFd::new(Err(libc::ECANCELED));
+        assert_eq!(tfd.consume().unwrap(), 0);
+        assert_eq!(expired(0), Tick::ClockStepped);
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready", "rearm"]);
+        assert_eq!(
+            tfd.rearmed_with.get(),
+            Some(schedule(
+                libc::CLOCK_REALTIME,
+                tick::SECOND,
+                Arming::Relative
+            ))
+        );
+    }
+
+    #[test]
+    fn reconfiguring_keeps_the_pending_tick_and_rearms_on_the_new_boundary() {
+        let seconds = schedule(libc::CLOCK_REALTIME, tick::SECOND, Arming::ALIGNED);
+        // The minute timer fired just before the switch to seconds
+        let tfd = FakeTimerFd::new(Ok(8));
+        assert_eq!(reconfigure_timer(&tfd, seconds).unwrap(), Some(1));
+        assert_eq!(*tfd.calls.borrow(), ["read", "rearm"]);
+        assert_eq!(tfd.rearmed_with.get(), Some(seconds));
+        let now = Duration::from_millis(90_500);
+        assert_eq!(first_expiry(seconds, now).0, Duration::from_secs(91));
+
+        let idle = FakeTimerFd::new(Err(libc::EAGAIN));
+        assert_eq!(reconfigure_timer(&idle, seconds).unwrap(), None);
+        let stepped = FakeTimerFd::new(Err(libc::ECANCELED));
+        assert_eq!(reconfigure_timer(&stepped, seconds).unwrap(), Some(0));
+
+        // The readiness the drained tick left is cleared without waking the loop
+        assert!(would_block(&idle.consume().unwrap_err()));
+        assert_eq!(
+            *idle.calls.borrow(),
+            ["read", "rearm", "read", "clear_ready"]
+        );
+    }
+
+    #[test]
+    fn other_errno_is_propagated() {
+        let tfd = FakeTimerFd::new(Err(libc::EIO));
+        let err = tfd.consume().unwrap_err();
+        let err = err.downcast_ref::<io::Error>().unwrap();
+        assert_eq!(err.raw_os_error(), Some(libc::EIO));
+        assert_eq!(*tfd.calls.borrow(), ["read", "clear_ready"]);
+    }
+
+    #[tokio::test]
+    async fn a_monotonic_timerfd_ticks_and_counts_what_it_missed() {
+        let interval = Duration::from_millis(10);
+        let start = std::time::Instant::now();
+        let mut tfd = match TimerFd::new(libc::CLOCK_MONOTONIC, interval, Arming::Relative, false) {
+            Err(err) if timerfd_unavailable(&err) => return,
+            tfd => tfd.unwrap(),
+        };
+        assert_eq!(tfd.clock(), libc::CLOCK_MONOTONIC);
+        assert!(tfd.wait().await.unwrap() >= 1);
+        assert!(start.elapsed() >= interval);
+
+        // Held up for several intervals, the next wait counts them all
+        std::thread::sleep(interval * 5);
+        let expirations = tfd.wait().await.unwrap();
+        assert!(expirations >= 4, "{expirations}");
+
+        // Re-arming on the monotonic clock's boundaries hands back what fired in the meantime
+        std::thread::sleep(interval * 2);
+        let pending = tfd.rearm(interval, Arming::ALIGNED).unwrap();
+        assert!(
+            pending.is_some_and(|expirations| expirations >= 1),
+            "{pending:?}"
+        );
+        assert!(tfd.wait().await.unwrap() >= 1);
+    }
+}
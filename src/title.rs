@@ -0,0 +1,49 @@
+use std::io::{self, IsTerminal};
+
+use ratatui::crossterm::{execute, style::Print};
+
+/// XTWINOPS: push the window title onto the terminal's title stack, and pop it back off. The icon
+/// name isn't touched, so it isn't saved either.
+const PUSH_TITLE: &str = "\x1b[22;2t";
+const POP_TITLE: &str = "\x1b[23;2t";
+
+/// OSC 2, which sets just the window title. crossterm's `SetTitle` sends OSC 0, which sets the
+/// icon name along with it.
+fn set_title(title: &str) -> String {
+    format!("\x1b]2;{title}\x07")
+}
+
+/// Mirrors the clock into the terminal's window title for `--title-clock`. The old title is saved
+/// on the terminal's title stack and put back when this is dropped. Terminals without a title
+/// stack ignore those sequences, so the title is cleared first to leave a blank one there rather
+/// than a stale time. Write errors are ignored too, since a missing title is no reason to stop
+/// the clock.
+pub struct TitleClock {
+    shown: Option<String>,
+}
+
+impl TitleClock {
+    /// Starts mirroring, or returns `None` when stdout isn't a terminal to have a title.
+    pub fn start() -> Option<Self> {
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+        let _ = execute!(io::stdout(), Print(PUSH_TITLE));
+        Some(Self { shown: None })
+    }
+
+    /// Sets the title to `title`, writing nothing if it's already showing.
+    pub fn set(&mut self, title: &str) {
+        if self.shown.as_deref() == Some(title) {
+            return;
+        }
+        let _ = execute!(io::stdout(), Print(set_title(title)));
+        self.shown = Some(title.to_owned());
+    }
+}
+
+impl Drop for TitleClock {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Print(set_title("")), Print(POP_TITLE));
+    }
+}
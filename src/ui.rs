@@ -0,0 +1,1578 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Local, Timelike};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect, Size},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListState, Padding, Paragraph, Row, Table, Wrap},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    analog,
+    app::{self, ALARM_FLASH_NANOS, Action, AmPmPosition, App, Mode},
+    calendar::{self, MonthCalendar},
+    chess::{ChessClock, GameState, Side},
+    countdown::Timer,
+    dots, duration,
+    flash::FlashStyle,
+    grid::Grid,
+    layout::{self, Align, DisplayOptions, Fit, VAlign},
+    notes::Notes,
+    palette::ColorDepth,
+    picker::ZonePicker,
+    stopwatch::Lap,
+    sun,
+    sync::SyncWatch,
+    widget::{ClockWidget, LineStyle},
+};
+
+/// Draws the whole screen for `app` as of the wall-clock time `local` and monotonic time `now`.
+pub fn draw(frame: &mut Frame, app: &App, local: DateTime<Local>, now: Instant) {
+    const LAP_TABLE_WIDTH: u16 = 30;
+    // Everything is drawn in the --x/--y region, when there is one, leaving the rest blank
+    let screen = app.screen_area(frame.area());
+    frame.render_widget(Block::new().style(app.theme.style()), screen);
+    if let Some(matrix) = &app.matrix {
+        frame.render_widget(matrix, screen);
+    }
+
+    let mut area = app.margin.inset(screen);
+    // The notes panel takes the right of the screen, and the clock is laid out in the rest
+    if let Some(notes) = &app.notes {
+        let [rest, panel] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(app.notes_panel_width(area.width)),
+        ])
+        .areas(area);
+        draw_notes(frame, app, notes, panel);
+        area = rest;
+    }
+    // A reminder's message runs across the bottom, under everything else
+    if let Some(message) = app.reminders.banner() {
+        let [rest, banner] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        frame.render_widget(
+            Paragraph::new(message)
+                .alignment(Alignment::Center)
+                .style(app.theme.style().reversed()),
+            banner,
+        );
+        area = rest;
+    }
+    // A chess clock's two readings take the whole screen
+    if let Mode::Chess(chess) = &app.mode {
+        draw_chess(frame, app, chess, area, now);
+        draw_overlays(frame, app, screen, now);
+        return;
+    }
+    // So do the cells of a --grid, in place of the clock
+    if let Some(grid) = app.grid.filter(|_| !app.grid_cells().is_empty()) {
+        draw_grid(frame, app, grid, area, local, now);
+        draw_overlays(frame, app, screen, now);
+        return;
+    }
+
+    // Each optional element gets its own rows plus a blank one separating it from the digits
+    let indicator = app.day_night.then(|| sun::indicator(local, app.location));
+    let laps = match &app.mode {
+        Mode::Stopwatch(stopwatch) => stopwatch.laps(),
+        _ => &[],
+    };
+    // The alarm's line takes the notice's place while it is ringing or snoozed
+    let message = match app.alarm_status() {
+        Some((text, ringing)) => {
+            let style = if ringing && local.timestamp_subsec_nanos() < ALARM_FLASH_NANOS {
+                app.theme.style().reversed()
+            } else if ringing {
+                app.theme.style()
+            } else {
+                app.theme.dim_style()
+            };
+            Some((text, style))
+        },
+        // Errors the loop carried on after stay until dismissed. With flashing off, the expiry
+        // banner stays inverted instead
+        None => match (&app.error, app.expiry_banner()) {
+            (Some(error), _) => Some((error.to_string(), Style::new().fg(Color::Red))),
+            (None, Some(text)) if app.flash => Some((text, app.theme.style())),
+            (None, Some(text)) => Some((text, app.theme.style().reversed())),
+            (None, None) => app
+                .visible_notice(now)
+                .map(|notice| notice.text.clone())
+                .or_else(|| app.dst.notice(local.to_utc()))
+                .map(|text| (text, app.theme.dim_style())),
+        },
+    };
+    let indicator_rows = if indicator.is_some() { 2 } else { 0 };
+    // With a blank row between it and the digits
+    let progress_ring = app.progress_ring(local);
+    let ring_rows = progress_ring.map_or(0, |(ring, _)| ring.size().1 + 1);
+    let lap_rows = if laps.is_empty() {
+        0
+    } else {
+        laps.len() as u16 + 2
+    };
+    let notice_rows = if message.is_some() { 2 } else { 0 };
+    // As wide as the screen allows, rather than the block, so wide screens get spaced-out dots
+    let seconds_dots = app.visible_seconds_dots().map(|dots| {
+        let filled = Style::new().fg(dots.filled.unwrap_or(app.theme.fg));
+        let empty = match dots.empty {
+            Some(color) => Style::new().fg(color),
+            None => app.theme.dim_style(),
+        };
+        dots::line(local.second(), area.width, app.ascii, filled, empty)
+    });
+    let dots_rows = if seconds_dots.is_some() { 2 } else { 0 };
+    // Under a big date, the time is a line of its own
+    let time_under = app.time_under_date(local);
+    let time_rows = u16::from(time_under.is_some());
+    let zone_label = app.zone_abbreviation(local);
+    let zone_rows = u16::from(zone_label.is_some());
+    let info_line = app.info_line(local);
+    let info_rows = u16::from(info_line.is_some());
+    let comparisons = app.comparison_lines(local);
+    let comparison_rows = if comparisons.is_empty() {
+        0
+    } else {
+        comparisons.len() as u16 + 1
+    };
+    let agenda = app
+        .agenda
+        .as_ref()
+        .and_then(|agenda| agenda.line(local.to_utc()));
+    let agenda_rows = if agenda.is_some() { 2 } else { 0 };
+    let calendar_rows = if app.calendar {
+        calendar::SIZE.1 + 1
+    } else {
+        0
+    };
+
+    // An analog face grows to fill whatever the other elements leave, while digits shrink to
+    // fit, dropping the header and status bar first
+    let face = app.analog_face(local);
+    // With --ampm-position, AM or PM is drawn small beside or below the digits rather than in
+    // them
+    let ampm = app.ampm_apart(local).filter(|_| face.is_none());
+    let is_apart = ampm.is_some();
+    let apart = |text: String| match is_apart {
+        true => app::without_ampm(&text),
+        false => text,
+    };
+    let text = apart(app.display_text(local, now));
+    let (without_seconds, short) = (
+        app.without_seconds_text(local).map(apart),
+        app.short_text(local),
+    );
+    // With --alternate the date takes the time's place every other period, unless both fit
+    let date = app.date_text(local);
+    let both = date
+        .as_ref()
+        .map(|date| format!("{text}\n{date}"))
+        .or_else(|| app.date_with_weekday(local));
+    let (shown, without_seconds, short, ampm) = match date {
+        Some(date) if app.alternation.is_some_and(|a| a.showing_date(local)) => {
+            (date, None, None, None)
+        },
+        _ => (text.clone(), without_seconds, short, ampm),
+    };
+    let ampm_columns = match &ampm {
+        Some(ampm) if app.ampm_position == AmPmPosition::Beside => ampm.width() as u16 + 1,
+        _ => 0,
+    };
+    let ampm_rows = u16::from(ampm.is_some() && app.ampm_position == AmPmPosition::Below);
+    // Lines from the config's [[lines]] are each drawn in their own color and size
+    let line_styles: Vec<_> = app.clock_lines().iter().map(|line| line.style).collect();
+    let line_sizes: Vec<_> = line_styles.iter().map(|style| style.pixel_size).collect();
+    let header = app.header_text(local);
+    let fit = match face {
+        Some(_) => Fit {
+            text: &text,
+            pixel_size: Some(app.pixel_size),
+            header: header.is_some(),
+            status_bar: app.status_bar,
+        },
+        None => layout::fit(
+            area,
+            &DisplayOptions {
+                text: &shown,
+                both: both.as_deref(),
+                without_seconds: without_seconds.as_deref(),
+                short: short.as_deref(),
+                pixel_size: app.pixel_size,
+                line_sizes: &line_sizes,
+                font: app.font.as_ref(),
+                ascii: app.ascii,
+                header: header.is_some(),
+                status_bar: app.status_bar,
+                extra_rows: indicator_rows
+                    + ring_rows
+                    + ampm_rows
+                    + dots_rows
+                    + time_rows
+                    + zone_rows
+                    + info_rows
+                    + comparison_rows
+                    + agenda_rows
+                    + calendar_rows
+                    + notice_rows,
+                extra_columns: ampm_columns,
+            },
+        ),
+    };
+
+    // The status bar takes the bottom row, and everything else is laid out in what's left
+    if fit.status_bar {
+        let [rest, status_row] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        clear_rain(frame, app, status_row);
+        frame.render_widget(
+            Paragraph::new(app.status_bar_text(local, status_row.width.into()))
+                .style(app.theme.dim_style()),
+            status_row,
+        );
+        area = rest;
+    }
+    // Likewise the header takes the top row, so the clock centers below it
+    if let Some(header) = header.filter(|_| fit.header) {
+        let [header_row, rest] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+        clear_rain(frame, app, header_row);
+        frame.render_widget(
+            Paragraph::new(header)
+                .alignment(app.align.horizontal.into())
+                .style(app.theme.dim_style()),
+            header_row,
+        );
+        area = rest;
+    }
+
+    // A shrunk clock isn't what the transition started from
+    let from = app.transition.as_ref().map(|t| apart(t.from.clone()));
+    let transition = app
+        .transition
+        .as_ref()
+        .zip(from.as_deref())
+        .filter(|_| fit.text == text)
+        .map(|(t, from)| (from, t.animation, t.progress(now)));
+    // Shrunk to fit, the lines are all the one size
+    let line_styles = match fit.pixel_size == Some(app.pixel_size) {
+        true => line_styles,
+        false => line_styles
+            .into_iter()
+            .map(|style| LineStyle {
+                pixel_size: None,
+                ..style
+            })
+            .collect(),
+    };
+    let clock = ClockWidget::text(fit.text)
+        .style(Style::new().fg(app.digit_color(local)))
+        .line_styles(line_styles)
+        .font(app.font.as_ref())
+        .off_style(app.segment_off_style())
+        .ascii(app.ascii)
+        .transition(transition);
+    let clock = match fit.pixel_size {
+        Some(pixel_size) => clock.pixel_size(pixel_size),
+        None => clock.plain(),
+    };
+    let (width, height) = match face {
+        Some(_) => analog::face_size(Size::new(
+            area.width,
+            area.height.saturating_sub(
+                indicator_rows
+                    + ring_rows
+                    + dots_rows
+                    + time_rows
+                    + zone_rows
+                    + info_rows
+                    + comparison_rows
+                    + agenda_rows
+                    + calendar_rows
+                    + notice_rows,
+            ),
+        )),
+        None => clock.size(),
+    };
+    // The digits and an AM or PM beside them are placed as one
+    let clock_width = width + ampm_columns;
+
+    let block_width = if laps.is_empty() {
+        clock_width
+    } else {
+        clock_width.max(LAP_TABLE_WIDTH)
+    };
+    // A long message (like a config error) widens the block rather than being cut off
+    let message_width = message.as_ref().map_or(0, |(text, _)| text.width() as u16);
+    let comparison_width = comparisons.iter().map(|line| line.width() as u16).max();
+    let info_width = info_line.as_ref().map_or(0, |line| line.width() as u16);
+    let time_width = time_under.as_ref().map_or(0, |line| line.width() as u16);
+    let ring_width = progress_ring.map_or(0, |(ring, _)| ring.size().0);
+    let dots_width = seconds_dots.as_ref().map_or(0, |line| line.width() as u16);
+    let agenda_width = agenda.as_ref().map_or(0, |(text, _)| text.width() as u16);
+    let calendar_width = if app.calendar { calendar::SIZE.0 } else { 0 };
+    let block_width = block_width
+        .max(message_width)
+        .max(comparison_width.unwrap_or(0))
+        .max(info_width)
+        .max(time_width)
+        .max(ring_width)
+        .max(dots_width)
+        .max(agenda_width)
+        .max(calendar_width);
+    let block_size = (
+        block_width,
+        indicator_rows
+            + ring_rows
+            + height
+            + ampm_rows
+            + dots_rows
+            + time_rows
+            + zone_rows
+            + info_rows
+            + comparison_rows
+            + agenda_rows
+            + calendar_rows
+            + lap_rows
+            + notice_rows,
+    );
+    let block = match &app.bounce {
+        Some(bounce) => bounce.place(area, block_size),
+        None => layout::aligned_rect(area, block_size, app.align, 0),
+    };
+    // A column of space either side keeps the rain from running into the digits
+    clear_rain(
+        frame,
+        app,
+        block.outer(Margin::new(1, 0)).intersection(screen),
+    );
+    let [
+        indicator_area,
+        ring_area,
+        clock_area,
+        ampm_area,
+        dots_area,
+        time_area,
+        zone_area,
+        info_area,
+        comparisons_area,
+        agenda_area,
+        calendar_area,
+        laps_area,
+        notice_area,
+    ] = Layout::vertical([
+        Constraint::Length(indicator_rows),
+        Constraint::Length(ring_rows),
+        Constraint::Length(height),
+        Constraint::Length(ampm_rows),
+        Constraint::Length(dots_rows),
+        Constraint::Length(time_rows),
+        Constraint::Length(zone_rows),
+        Constraint::Length(info_rows),
+        Constraint::Length(comparison_rows),
+        Constraint::Length(agenda_rows),
+        Constraint::Length(calendar_rows),
+        Constraint::Fill(1),
+        Constraint::Length(notice_rows),
+    ])
+    .areas(block);
+    // Narrower elements line up with the digits the same way the whole block does
+    let row_align = Align {
+        vertical: VAlign::Top,
+        ..app.align
+    };
+
+    if let Some(indicator) = indicator {
+        frame.render_widget(
+            Paragraph::new(indicator).alignment(app.align.horizontal.into()),
+            indicator_area,
+        );
+    }
+
+    if let Some((ring, progress_ring)) = progress_ring {
+        frame.render_widget(
+            progress_ring,
+            layout::aligned_rect(ring_area, ring.size(), row_align, 0),
+        );
+    }
+
+    match face {
+        Some(face) => frame.render_widget(
+            face,
+            layout::aligned_rect(clock_area, (width, height), row_align, 0),
+        ),
+        None => frame.render_widget(clock.align(row_align), clock_area),
+    }
+
+    // In the digits' color, level with their top row or centered under them
+    if let Some(ampm) = ampm {
+        let line = Line::from(ampm).style(Style::new().fg(app.digit_color(local)));
+        let area = match app.ampm_position {
+            AmPmPosition::Beside => {
+                let clock = layout::aligned_rect(clock_area, (clock_width, height), row_align, 0);
+                Rect {
+                    x: clock.x + width + 1,
+                    width: ampm_columns - 1,
+                    height: clock.height.min(1),
+                    ..clock
+                }
+                .intersection(clock_area)
+            },
+            _ => layout::aligned_rect(ampm_area, (line.width() as u16, 1), row_align, 0),
+        };
+        frame.render_widget(line, area);
+    }
+
+    if let Some(line) = seconds_dots {
+        let [_, dots_row] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(dots_area);
+        let row = layout::aligned_rect(dots_row, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(line, row);
+    }
+
+    if let Some(time) = time_under {
+        let line = Line::from(time).style(Style::new().fg(app.theme.fg));
+        let row = layout::aligned_rect(time_area, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(line, row);
+    }
+
+    if let Some(label) = zone_label {
+        let line = Line::from(label).style(app.theme.dim_style());
+        let row = layout::aligned_rect(zone_area, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(line, row);
+    }
+
+    if let Some(info_line) = info_line {
+        let line = Line::from(info_line).style(app.theme.dim_style());
+        let row = layout::aligned_rect(info_area, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(line, row);
+    }
+
+    // Below a blank row, one line per zone
+    for (line, row) in comparisons.into_iter().zip(comparisons_area.rows().skip(1)) {
+        let line = Line::from(line).style(app.theme.dim_style());
+        let row = layout::aligned_rect(row, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(line, row);
+    }
+
+    // A file that can't be read is only a dim warning, not worth more attention than the event
+    if let Some((text, error)) = agenda {
+        let [_, agenda_row] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(agenda_area);
+        let style = if error {
+            app.theme.dim_style()
+        } else {
+            Style::new().fg(app.theme.fg)
+        };
+        let line = Line::from(text).style(style);
+        let row = layout::aligned_rect(agenda_row, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(line, row);
+    }
+
+    if app.calendar {
+        let [_, calendar_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(calendar_area);
+        let today = local.date_naive();
+        let style = Style::new().fg(app.theme.fg);
+        frame.render_widget(
+            MonthCalendar {
+                month: app.calendar_month(today),
+                today,
+                style,
+                header_style: app.theme.dim_style(),
+                today_style: style.reversed(),
+            },
+            layout::aligned_rect(calendar_area, calendar::SIZE, row_align, 0),
+        );
+    }
+
+    if !laps.is_empty() {
+        let table_area =
+            layout::aligned_rect(laps_area, (LAP_TABLE_WIDTH, laps_area.height), row_align, 0);
+        draw_laps(frame, laps, table_area, app.theme.dim_style());
+    }
+
+    if let Some((text, style)) = message {
+        let [_, notice_row] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(notice_area);
+        let line = Line::from(text).style(style);
+        let row = layout::aligned_rect(notice_row, (line.width() as u16, 1), row_align, 0);
+        frame.render_widget(Paragraph::new(line), row);
+    }
+
+    if let Mode::Timer(timer) = &app.mode {
+        draw_presets(frame, app, timer, area);
+    }
+
+    // The status bar shows the next alarm itself
+    if let Some((text, soon)) = app.next_alarm_text(local).filter(|_| !app.status_bar) {
+        let style = if soon {
+            Style::new().fg(app.theme.fg).bold()
+        } else {
+            app.theme.dim_style()
+        };
+        let line = Line::from(text).style(style);
+        let corner = Rect {
+            x: area.right().saturating_sub(line.width() as u16),
+            height: area.height.min(1),
+            ..area
+        };
+        frame.render_widget(line, corner);
+    }
+
+    // Across from the next alarm, so the two don't overlap
+    if let Some((text, synced)) = app.sync.as_ref().and_then(SyncWatch::tag) {
+        let style = if synced {
+            app.theme.dim_style()
+        } else {
+            Style::new().fg(Color::Yellow)
+        };
+        let line = Line::from(text).style(style);
+        let corner = Rect {
+            width: area.width.min(line.width() as u16),
+            height: area.height.min(1),
+            ..area
+        };
+        frame.render_widget(line, corner);
+    }
+
+    // Drawn after the timer presets, so it stays readable if they run into the corner
+    if let Some((text, low)) = app
+        .battery
+        .as_ref()
+        .and_then(|battery| battery.tag(app.ascii))
+    {
+        let style = if low {
+            Style::new().fg(Color::Red)
+        } else {
+            app.theme.dim_style()
+        };
+        let line = Line::from(text).style(style);
+        let width = area.width.min(line.width() as u16);
+        let corner = Rect {
+            x: area.right() - width,
+            y: area.bottom().saturating_sub(1),
+            width,
+            height: area.height.min(1),
+        };
+        frame.render_widget(line, corner);
+    }
+
+    // Across from the battery
+    if app.frozen.is_some() {
+        let line = Line::from(" paused ").style(app.theme.style().reversed());
+        let corner = Rect {
+            y: area.bottom().saturating_sub(1),
+            width: area.width.min(line.width() as u16),
+            height: area.height.min(1),
+            ..area
+        };
+        frame.render_widget(line, corner);
+    }
+
+    draw_overlays(frame, app, screen, now);
+}
+
+/// Blanks `area` back to the theme's background under `--matrix` rain, so what's drawn there
+/// stays readable.
+fn clear_rain(frame: &mut Frame, app: &App, area: Rect) {
+    if app.matrix.is_some() {
+        frame.render_widget(Clear, area);
+        frame.render_widget(Block::new().style(app.theme.style()), area);
+    }
+}
+
+/// What goes over everything else in `screen`: the help and quit prompt, the screen flash, and
+/// bringing the colors within the terminal's depth.
+fn draw_overlays(frame: &mut Frame, app: &App, screen: Rect, now: Instant) {
+    if app.show_help {
+        draw_help(frame, app, screen);
+    }
+    if let Some(picker) = &app.zone_picker {
+        draw_zone_picker(frame, picker, screen);
+    }
+    if let Some(prompt) = &app.quit_prompt {
+        draw_prompt(frame, prompt, screen);
+    }
+    if let Some(prompt) = app.resume_prompt(now) {
+        draw_prompt(frame, &prompt, screen);
+    }
+
+    if let Some(flash) = app.flash_style(now) {
+        // A solid color would vanish without colors, so it's inverted instead
+        let flash = match flash {
+            FlashStyle::Solid(_) if app.color_depth == ColorDepth::Mono => FlashStyle::Inverted,
+            flash => flash,
+        };
+        frame.buffer_mut().set_style(screen, flash.style());
+    }
+    app.color_depth.apply(frame.buffer_mut());
+}
+
+/// Both sides of a chess clock in halves of `area`: side by side, or stacked when that gives
+/// bigger digits. Both are drawn the size the longer reading fits at, so they match. The side to
+/// move is bright and the other dim, and a side out of time blinks until a key is pressed.
+fn draw_chess(frame: &mut Frame, app: &App, chess: &ChessClock, area: Rect, now: Instant) {
+    // Each side's name goes under its digits, below a blank row
+    const LABEL_ROWS: u16 = 2;
+    let readings = Side::BOTH.map(|side| chess.reading(side, now));
+    let longest = if readings[0].len() >= readings[1].len() {
+        &readings[0]
+    } else {
+        &readings[1]
+    };
+    let fit_halves = |direction| {
+        let halves: [Rect; 2] = Layout::new(direction, [Constraint::Fill(1); 2])
+            .spacing(1)
+            .areas(area);
+        // An odd row or column goes to one half, so fit in the other
+        let smaller = Rect {
+            width: halves[0].width.min(halves[1].width),
+            height: halves[0].height.min(halves[1].height),
+            ..halves[0]
+        };
+        let fit = layout::fit(
+            smaller,
+            &DisplayOptions {
+                text: longest,
+                both: None,
+                without_seconds: None,
+                short: None,
+                pixel_size: app.pixel_size,
+                line_sizes: &[],
+                font: app.font.as_ref(),
+                ascii: app.ascii,
+                header: false,
+                status_bar: false,
+                extra_rows: LABEL_ROWS,
+                extra_columns: 0,
+            },
+        );
+        (halves, fit.pixel_size)
+    };
+    let cells = |pixel_size| {
+        let (width, height) = layout::text_size(longest, pixel_size, app.font.as_ref());
+        u32::from(width) * u32::from(height)
+    };
+    let across = fit_halves(Direction::Horizontal);
+    let stacked = fit_halves(Direction::Vertical);
+    let (halves, pixel_size) = if cells(stacked.1) > cells(across.1) {
+        stacked
+    } else {
+        across
+    };
+
+    let centered = Align {
+        vertical: VAlign::Top,
+        ..Align::default()
+    };
+    for ((side, reading), half) in Side::BOTH.into_iter().zip(readings).zip(halves) {
+        let flagged = chess.flagged() == Some(side);
+        let mut style = if side == chess.turn() {
+            Style::new().fg(app.theme.fg)
+        } else {
+            app.theme.dim_style()
+        };
+        // With flashing off, the side out of time stays inverted instead
+        if flagged && (chess.blink_on(now) || !app.flash) {
+            style = style.reversed();
+        }
+        let status = match chess.state() {
+            _ if flagged => Some("out of time".to_owned()),
+            GameState::Ready if side == chess.turn() => app
+                .keymap
+                .key_for(&Action::Pause)
+                .map(|key| format!("{key} to start")),
+            GameState::Playing if side == chess.turn() && chess.is_paused() => {
+                Some("paused".to_owned())
+            },
+            _ => None,
+        };
+        let label = match status {
+            Some(status) => format!("{} · {status}", side.name()),
+            None => side.name().to_owned(),
+        };
+
+        let clock = ClockWidget::text(reading)
+            .style(style)
+            .font(app.font.as_ref())
+            .off_style(app.segment_off_style())
+            .ascii(app.ascii);
+        let clock = match pixel_size {
+            Some(pixel_size) => clock.pixel_size(pixel_size),
+            None => clock.plain(),
+        };
+        let (width, height) = clock.size();
+        let block = layout::aligned_rect(
+            half,
+            (width.max(label.width() as u16), height + LABEL_ROWS),
+            Align::default(),
+            0,
+        );
+        let [digits, _, label_row] = Layout::vertical([
+            Constraint::Length(height),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(block);
+        frame.render_widget(clock.align(centered), digits);
+        frame.render_widget(
+            Paragraph::new(label)
+                .alignment(Alignment::Center)
+                .style(app.theme.dim_style()),
+            label_row,
+        );
+    }
+}
+
+/// The `--grid` cells in `area`, filled a row at a time. Each is fitted to its own share of the
+/// screen, so a short reading can be drawn bigger than a long one beside it, and each drops to
+/// plain text before its digits would be cut off.
+fn draw_grid(
+    frame: &mut Frame,
+    app: &App,
+    grid: Grid,
+    area: Rect,
+    local: DateTime<Local>,
+    now: Instant,
+) {
+    // Each cell's label goes under its digits, below a blank row
+    const LABEL_ROWS: u16 = 2;
+    let slots = Layout::vertical(vec![Constraint::Fill(1); grid.rows.into()])
+        .spacing(1)
+        .split(area)
+        .iter()
+        .flat_map(|&row| {
+            Layout::horizontal(vec![Constraint::Fill(1); grid.columns.into()])
+                .spacing(2)
+                .split(row)
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+    let centered = Align {
+        vertical: VAlign::Top,
+        ..Align::default()
+    };
+    for (cell, slot) in app.grid_cells().iter().zip(slots) {
+        let reading = app.cell_text(cell, local, now);
+        let fit = layout::fit(
+            slot,
+            &DisplayOptions {
+                text: &reading,
+                both: None,
+                without_seconds: None,
+                short: None,
+                pixel_size: app.pixel_size,
+                line_sizes: &[],
+                font: app.font.as_ref(),
+                ascii: app.ascii,
+                header: false,
+                status_bar: false,
+                extra_rows: LABEL_ROWS,
+                extra_columns: 0,
+            },
+        );
+        let clock = ClockWidget::text(fit.text)
+            .style(Style::new().fg(app.digit_color(local)))
+            .font(app.font.as_ref())
+            .off_style(app.segment_off_style())
+            .ascii(app.ascii);
+        let clock = match fit.pixel_size {
+            Some(pixel_size) => clock.pixel_size(pixel_size),
+            None => clock.plain(),
+        };
+        let (width, height) = clock.size();
+        let block = layout::aligned_rect(
+            slot,
+            (width.max(cell.label.width() as u16), height + LABEL_ROWS),
+            Align::default(),
+            0,
+        );
+        let [digits, _, label_row] = Layout::vertical([
+            Constraint::Length(height),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(block);
+        frame.render_widget(clock.align(centered), digits);
+        frame.render_widget(
+            Paragraph::new(cell.label.as_str())
+                .alignment(Alignment::Center)
+                .style(app.theme.dim_style()),
+            label_row,
+        );
+    }
+}
+
+/// The `--agenda` notes in `area`, set off from the clock by a rule down their left side.
+fn draw_notes(frame: &mut Frame, app: &App, notes: &Notes, area: Rect) {
+    let block = Block::new()
+        .borders(Borders::LEFT)
+        .border_style(app.theme.dim_style())
+        .padding(Padding::horizontal(1));
+    let paragraph = match notes.lines() {
+        Ok(lines) => Paragraph::new(
+            lines
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .style(Style::new().fg(app.theme.fg)),
+        Err(err) => Paragraph::new(err)
+            .style(app.theme.dim_style())
+            .wrap(Wrap { trim: true }),
+    };
+    frame.render_widget(paragraph.block(block).scroll((notes.scroll(), 0)), area);
+}
+
+/// The timer presets as a footer along the bottom row of `area`, with the selected one
+/// highlighted.
+fn draw_presets(frame: &mut Frame, app: &App, timer: &Timer, area: Rect) {
+    let selected = timer.selected().map(|preset| &preset.name);
+    let mut spans = Vec::new();
+    for preset in timer.presets() {
+        if !spans.is_empty() {
+            spans.push(Span::raw(" · "));
+        }
+        let label = format!(
+            " {} {} ",
+            preset.name,
+            duration::format_compact(preset.duration)
+        );
+        spans.push(if Some(&preset.name) == selected {
+            Span::styled(label, app.theme.style().reversed())
+        } else {
+            Span::raw(label)
+        });
+    }
+
+    let footer = Rect {
+        y: area.bottom().saturating_sub(1),
+        height: area.height.min(1),
+        ..area
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(spans))
+            .alignment(app.align.horizontal.into())
+            .style(app.theme.dim_style()),
+        footer,
+    );
+}
+
+/// The most recent laps that fit in `area`, below a blank row and a header. Older laps scroll
+/// off the top.
+fn draw_laps(frame: &mut Frame, laps: &[Lap], area: Rect, style: Style) {
+    let visible = usize::from(area.height.saturating_sub(2));
+    let first = laps.len().saturating_sub(visible);
+    let rows = laps[first..].iter().enumerate().map(|(i, lap)| {
+        Row::new([
+            format!("{}", first + i + 1),
+            duration::format_hms_hundredths(lap.split),
+            duration::format_hms_hundredths(lap.total),
+        ])
+    });
+
+    let widths = [
+        Constraint::Length(4),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ];
+    let table = Table::new(rows, widths)
+        .style(style)
+        .header(Row::new(["Lap", "Split", "Total"]).style(Style::new().bold()));
+    let [_, table_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+    frame.render_widget(table, table_area);
+}
+
+/// A popup listing every key binding, drawn over the clock.
+fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
+    let lines: Vec<Line> = app
+        .keymap
+        .bindings()
+        .iter()
+        .map(|(key, action)| Line::from(format!("{key:>10}  {}", action.name())))
+        .collect();
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let popup = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width.min(area.width),
+        height.min(area.height),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Keys ")),
+        popup,
+    );
+}
+
+/// The zone picker in a box in the middle of the screen: what's been typed, with the cursor after
+/// it, over the zones matching it with the selected one highlighted.
+fn draw_zone_picker(frame: &mut Frame, picker: &ZonePicker, area: Rect) {
+    const WIDTH: u16 = 40;
+    const HEIGHT: u16 = 16;
+    let popup = Rect::new(
+        area.x + area.width.saturating_sub(WIDTH) / 2,
+        area.y + area.height.saturating_sub(HEIGHT) / 2,
+        WIDTH.min(area.width),
+        HEIGHT.min(area.height),
+    );
+    frame.render_widget(Clear, popup);
+    let block = Block::bordered().title(" Time zone ");
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let [query, list] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+    let prompt = Line::from(format!("> {}", picker.query()));
+    let cursor_x = query.x + prompt.width() as u16;
+    frame.render_widget(prompt, query);
+    frame.set_cursor_position((cursor_x.min(query.right().saturating_sub(1)), query.y));
+
+    let mut state = ListState::default()
+        .with_offset(picker.offset.get())
+        .with_selected(picker.selected_row());
+    frame.render_stateful_widget(
+        List::new(picker.matches()).highlight_style(Style::new().reversed()),
+        list,
+        &mut state,
+    );
+    picker.offset.set(state.offset());
+}
+
+/// A question, like whether to quit, in a box in the middle of the screen.
+fn draw_prompt(frame: &mut Frame, prompt: &str, area: Rect) {
+    let line = Line::from(format!(" {prompt} "));
+    let width = line.width() as u16 + 2;
+    let popup = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(3) / 2,
+        width.min(area.width),
+        area.height.min(3),
+    );
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(line).block(Block::bordered()), popup);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    use ratatui::{
+        Terminal,
+        backend::TestBackend,
+        buffer::Buffer,
+        crossterm::event::{KeyCode, KeyEvent},
+    };
+    use tui_big_text::PixelSize;
+
+    use ratatui::style::{Color, Modifier};
+
+    use super::*;
+    use crate::{
+        alternate::Alternation,
+        bounce::Bounce,
+        content::ContentMode,
+        grid::{CellConfig, CellMode},
+        holiday::Holidays,
+        layout::{Margins, Region},
+        matrix::{self, Matrix},
+        theme::Theme,
+        widget::tests::rows,
+    };
+
+    #[test]
+    fn header_sits_above_the_centered_clock() {
+        let app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            header: Some("%a %d %b".to_owned()),
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(44, 12)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, Instant::now()))
+            .unwrap();
+
+        assert_eq!(
+            rows(terminal.backend().buffer()),
+            [
+                "                 Fri 01 Mar                 ",
+                "                                            ",
+                "    ██     ████            █████   █████    ",
+                "   ███    ██  ██    ██    ██   ██ ██   ██   ",
+                "    ██        ██    ██    ██  ███ ██  ███   ",
+                "    ██      ███           ██ ████ ██ ████   ",
+                "    ██     ██             ████ ██ ████ ██   ",
+                "    ██    ██  ██    ██    ███  ██ ███  ██   ",
+                "  ██████  ██████    ██     █████   █████    ",
+                "                                            ",
+                "                                            ",
+                "                                            ",
+            ]
+        );
+    }
+
+    #[test]
+    fn header_names_the_holiday() {
+        let (holidays, _) = Holidays::parse(
+            r#"
+            last-friday-of-march = "Spring Day"
+            03-04 = "Founders' Day"
+            "#,
+        )
+        .unwrap();
+        let mut app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            header: Some("%a %d %b".to_owned()),
+            holidays,
+            ..App::default()
+        };
+        let header = |app: &App, day| {
+            let local = Local.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap();
+            let mut terminal = Terminal::new(TestBackend::new(44, 12)).unwrap();
+            terminal
+                .draw(|frame| draw(frame, app, local, Instant::now()))
+                .unwrap();
+            rows(terminal.backend().buffer())[0].trim().to_owned()
+        };
+        assert_eq!(header(&app, 29), "Fri 29 Mar · Spring Day");
+        assert_eq!(header(&app, 1), "Fri 01 Mar");
+        app.holidays_ahead = Some(3);
+        assert_eq!(header(&app, 1), "Fri 01 Mar · Founders' Day in 3d");
+
+        // Without --header the holiday still gets a line, only on the days there is one
+        app.header = None;
+        assert_eq!(header(&app, 1), "Founders' Day in 3d");
+        app.holidays_ahead = None;
+        assert_eq!(header(&app, 1), "");
+    }
+
+    #[test]
+    fn clock_is_centered_at_every_size() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        for pixel_size in [
+            PixelSize::Full,
+            PixelSize::HalfHeight,
+            PixelSize::Quadrant,
+            PixelSize::Sextant,
+        ] {
+            let app = App {
+                custom_format: Some("%H:%M".to_owned()),
+                pixel_size,
+                ..App::default()
+            };
+            let (_, text_height) = layout::big_text_size(&[Line::from("12:00")], pixel_size);
+            for (width, height) in [(44, 12), (80, 24), (120, 41), (200, 60)] {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal
+                    .draw(|frame| draw(frame, &app, local, Instant::now()))
+                    .unwrap();
+                let rows = rows(terminal.backend().buffer());
+                let inked: Vec<_> = (0..height)
+                    .filter(|&y| !rows[usize::from(y)].trim().is_empty())
+                    .collect();
+
+                // The digits' top row is inked, and the glyphs' blank bottom row counts as theirs
+                let top = inked[0];
+                let below = height - top - text_height;
+                assert!(
+                    top.abs_diff(below) <= 1,
+                    "{pixel_size:?} at {width}x{height}: {top} rows above, {below} below"
+                );
+                assert!(inked.last().is_some_and(|&y| y < top + text_height));
+            }
+        }
+    }
+
+    #[test]
+    fn notes_panel_sits_beside_the_clock() {
+        let path =
+            std::env::temp_dir().join(format!("tui-time-ui-notes-{}.txt", std::process::id()));
+        std::fs::write(&path, "Standup 9:30\nReview PRs\n").unwrap();
+        let app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            notes: Some(Notes::load(path.clone())),
+            notes_width: 20,
+            ..App::default()
+        };
+        std::fs::remove_file(&path).unwrap();
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(70, 10)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, Instant::now()))
+            .unwrap();
+
+        // The clock centers in the 50 columns left of the panel
+        assert_eq!(
+            rows(terminal.backend().buffer()),
+            [
+                "                                                  │ Standup 9:30      ",
+                "       ██     ████            █████   █████       │ Review PRs        ",
+                "      ███    ██  ██    ██    ██   ██ ██   ██      │                   ",
+                "       ██        ██    ██    ██  ███ ██  ███      │                   ",
+                "       ██      ███           ██ ████ ██ ████      │                   ",
+                "       ██     ██             ████ ██ ████ ██      │                   ",
+                "       ██    ██  ██    ██    ███  ██ ███  ██      │                   ",
+                "     ██████  ██████    ██     █████   █████       │                   ",
+                "                                                  │                   ",
+                "                                                  │                   ",
+            ]
+        );
+    }
+
+    #[test]
+    fn alternates_with_the_date_only_when_both_dont_fit() {
+        let render = |app: &App, (width, height), local| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|frame| draw(frame, app, local, Instant::now()))
+                .unwrap();
+            rows(terminal.backend().buffer())
+        };
+        let showing = |text: &str| App {
+            text: Some(text.to_owned()),
+            ..App::default()
+        };
+        let mut app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            alternation: Some(Alternation::new(Duration::from_secs(5))),
+            ..App::default()
+        };
+        let at = |secs| Local.with_ymd_and_hms(2024, 3, 1, 12, 0, secs).unwrap();
+
+        let small = (20, 6);
+        assert_eq!(
+            render(&app, small, at(0)),
+            render(&showing("12:00"), small, at(0))
+        );
+        assert_eq!(
+            render(&app, small, at(5)),
+            render(&showing("Mar 1"), small, at(5))
+        );
+        let _ = app.press(
+            &KeyEvent::from(KeyCode::Char('x')),
+            Action::Acknowledge,
+            at(7),
+            Instant::now(),
+        );
+        assert_eq!(
+            render(&app, small, at(7)),
+            render(&showing("12:00"), small, at(7))
+        );
+        assert_eq!(
+            render(&app, small, at(12)),
+            render(&showing("Mar 1"), small, at(12))
+        );
+
+        assert_ne!(render(&app, small, at(12)), render(&app, small, at(10)));
+
+        let big = (44, 18);
+        assert_eq!(
+            render(&app, big, at(12)),
+            render(&showing("12:00\nMar 1"), big, at(12))
+        );
+    }
+
+    #[test]
+    fn zone_picker_lists_the_matches_under_the_query() {
+        let zones = [
+            "Asia/Tokyo",
+            "Europe/Belgrade",
+            "Europe/Berlin",
+            "Europe/Paris",
+        ];
+        let mut picker = ZonePicker::new(zones.map(str::to_owned).to_vec(), &[]);
+        for c in "be".chars() {
+            picker.push(c);
+        }
+        picker.move_selection(1);
+        let app = App {
+            text: Some(String::new()),
+            zone_picker: Some(picker),
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(30, 6)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, Instant::now()))
+            .unwrap();
+
+        assert_eq!(
+            rows(terminal.backend().buffer()),
+            [
+                "┌ Time zone ─────────────────┐",
+                "│> be                        │",
+                "│Europe/Belgrade             │",
+                "│Europe/Berlin               │",
+                "│                            │",
+                "└────────────────────────────┘",
+            ]
+        );
+        let highlighted = terminal.backend().buffer()[(1, 3)].modifier;
+        assert!(highlighted.contains(Modifier::REVERSED));
+        assert_eq!(terminal.get_cursor_position().unwrap(), (5, 1).into());
+    }
+
+    #[test]
+    fn chess_clocks_sit_side_by_side_or_stacked() {
+        let start = Instant::now();
+        let mut app = App {
+            mode: Mode::Chess(ChessClock::new(
+                Duration::from_secs(300),
+                Duration::from_secs(3),
+            )),
+            ..App::default()
+        };
+        let draw_at = |app: &App, (width, height), now| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|frame| draw(frame, app, Local::now(), now))
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+        let row_of =
+            |buffer: &Buffer, label: &str| rows(buffer).iter().position(|row| row.contains(label));
+
+        let wide = draw_at(&app, (80, 12), start);
+        assert_eq!(
+            row_of(&wide, "White · space to start"),
+            row_of(&wide, "Black")
+        );
+        let tall = draw_at(&app, (30, 24), start);
+        assert!(row_of(&tall, "White") < row_of(&tall, "Black"));
+
+        let Mode::Chess(chess) = &mut app.mode else {
+            unreachable!()
+        };
+        chess.switch(start);
+        chess.update(start + Duration::from_secs(300));
+        let flagged = draw_at(&app, (80, 12), start + Duration::from_secs(300));
+        assert!(row_of(&flagged, "White · out of time").is_some());
+        // The side out of time blinks, starting lit
+        assert!(flagged[(10, 3)].modifier.contains(Modifier::REVERSED));
+        assert!(!flagged[(50, 3)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn grid_cells_each_fit_their_own_share() {
+        let now = Instant::now();
+        let cell = |mode, zone: Option<&str>, duration: Option<&str>, label: &str| {
+            CellConfig {
+                mode,
+                zone: zone.map(str::to_owned),
+                label: Some(label.to_owned()),
+                duration: duration.map(str::to_owned),
+            }
+            .cell(now)
+            .unwrap()
+        };
+        let app = App {
+            grid: Some(Grid {
+                columns: 2,
+                rows: 2,
+            }),
+            cells: vec![
+                cell(CellMode::Clock, Some("UTC"), None, "London-ish"),
+                cell(CellMode::Timer, None, Some("3m"), "Tea"),
+                cell(CellMode::Stopwatch, None, None, "Run"),
+            ],
+            hour24: true,
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 9, 41, 0).unwrap();
+        let draw_at = |(width, height)| {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|frame| draw(frame, &app, local, now))
+                .unwrap();
+            rows(terminal.backend().buffer())
+        };
+        let row_of = |rows: &[String], label: &str| rows.iter().position(|row| row.contains(label));
+
+        let big = draw_at((100, 30));
+        assert_eq!(row_of(&big, "London-ish"), row_of(&big, "Tea"));
+        assert!(row_of(&big, "London-ish") < row_of(&big, "Run"));
+        // Big digits, not the plain readings
+        assert_eq!(row_of(&big, "03:00"), None);
+
+        // Cramped, each falls back to plain text rather than being cut off
+        let small = draw_at((30, 8));
+        let utc = local.to_utc().format("%H:%M").to_string();
+        assert!(row_of(&small, &utc).is_some(), "{small:#?}");
+        assert_eq!(row_of(&small, "03:00"), row_of(&small, &utc));
+        assert!(row_of(&small, "00:00") > row_of(&small, &utc));
+    }
+
+    #[test]
+    fn region_keeps_the_clock_to_its_corner() {
+        let app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            pixel_size: PixelSize::Quadrant,
+            region: Some(Region {
+                x: 30,
+                y: 1,
+                width: Some(20),
+                height: Some(5),
+            }),
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, Instant::now()))
+            .unwrap();
+
+        assert_eq!(
+            rows(terminal.backend().buffer()),
+            [
+                "                                                            ",
+                "                              ▗█  ▟▀▙  ▄  ▟▀▜▖▟▀▜▖          ",
+                "                               █   ▄▛  ▀  █▗█▌█▗█▌          ",
+                "                               █  ▟▘▄  ▄  █▛▐▌█▛▐▌          ",
+                "                              ▀▀▀ ▀▀▀  ▀  ▝▀▀ ▝▀▀           ",
+                "                                                            ",
+                "                                                            ",
+                "                                                            ",
+                "                                                            ",
+                "                                                            ",
+            ]
+        );
+    }
+
+    #[test]
+    fn ampm_can_sit_small_beside_or_below_the_digits() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 21, 41, 0).unwrap();
+        let drawn = |ampm_position| {
+            let app = App {
+                ampm_position,
+                pixel_size: PixelSize::Quadrant,
+                no_leading_zero: true,
+                ..App::default()
+            };
+            let mut terminal = Terminal::new(TestBackend::new(30, 7)).unwrap();
+            terminal
+                .draw(|frame| draw(frame, &app, local, Instant::now()))
+                .unwrap();
+            rows(terminal.backend().buffer())
+        };
+
+        // Centered together, with PM level with the digits' top
+        assert_eq!(
+            drawn(AmPmPosition::Beside),
+            [
+                "                              ",
+                "      ▟▀▙  ▄   ▟█ ▗█  PM      ",
+                "      ▜▄█  ▀  ▟▘█  █          ",
+                "       ▗▛  ▄  ▀▀█▘ █          ",
+                "      ▝▀   ▀   ▝▀▘▀▀▀         ",
+                "                              ",
+                "                              ",
+            ]
+        );
+        // Or on a line of its own, the pair still centered
+        assert_eq!(
+            drawn(AmPmPosition::Below),
+            [
+                "                              ",
+                "       ▟▀▙  ▄   ▟█ ▗█         ",
+                "       ▜▄█  ▀  ▟▘█  █         ",
+                "        ▗▛  ▄  ▀▀█▘ █         ",
+                "       ▝▀   ▀   ▝▀▘▀▀▀        ",
+                "              PM              ",
+                "                              ",
+            ]
+        );
+    }
+
+    #[test]
+    fn frozen_display_is_badged() {
+        let mut app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            pixel_size: PixelSize::Quadrant,
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let _ = app.apply(Action::Hold, local, now);
+        let mut terminal = Terminal::new(TestBackend::new(24, 6)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, now))
+            .unwrap();
+
+        let rows = rows(terminal.backend().buffer());
+        assert_eq!(rows[5], " paused                 ");
+    }
+
+    #[test]
+    fn date_and_time_stack_centered() {
+        let app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            content: ContentMode::DateTime,
+            pixel_size: PixelSize::Quadrant,
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(30, 12)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, Instant::now()))
+            .unwrap();
+
+        // Two lines of big text, with as much room above them as below
+        let rows = rows(terminal.backend().buffer());
+        let drawn: Vec<_> = rows.iter().map(|row| !row.trim().is_empty()).collect();
+        assert_eq!(
+            drawn,
+            [vec![false; 2], vec![true; 8], vec![false; 2]].concat()
+        );
+        assert!(rows[2].contains("▗█  ▟▀▙"), "{rows:#?}");
+    }
+
+    #[test]
+    fn big_date_drops_its_weekday_when_short() {
+        let app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            content: ContentMode::Date,
+            date_weekday: true,
+            pixel_size: PixelSize::Quadrant,
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 8, 12, 0, 0).unwrap();
+        let render = |height| {
+            let mut terminal = Terminal::new(TestBackend::new(30, height)).unwrap();
+            terminal
+                .draw(|frame| draw(frame, &app, local, Instant::now()))
+                .unwrap();
+            let rows = rows(terminal.backend().buffer());
+            let drawn: Vec<_> = rows
+                .iter()
+                .filter(|row| !row.trim().is_empty())
+                .cloned()
+                .collect();
+            drawn
+        };
+
+        // The date and weekday in big text, then the time small under them
+        let tall = render(12);
+        assert_eq!(tall.len(), 9, "{tall:#?}");
+        assert_eq!(tall[8].trim(), "12:00");
+        // Without room for the weekday, just the date
+        let short = render(6);
+        assert_eq!(short.len(), 5, "{short:#?}");
+        assert_eq!(short[..4], tall[..4]);
+        assert_eq!(short[4].trim(), "12:00");
+    }
+
+    #[test]
+    fn bounces_until_a_key_is_pressed() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            pixel_size: PixelSize::Quadrant,
+            bounce: Some(Bounce::new(Duration::from_secs(1), now)),
+            ..App::default()
+        };
+        let render = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(30, 8)).unwrap();
+            terminal.draw(|frame| draw(frame, app, local, now)).unwrap();
+            rows(terminal.backend().buffer())
+        };
+        let centered = render(&app);
+
+        // A cell right and down with each step, but no sooner
+        app.update(local, now + Duration::from_millis(500));
+        assert_eq!(render(&app), centered);
+        app.update(local, now + Duration::from_secs(1));
+        let moved = render(&app);
+        for (row, moved) in centered.iter().zip(&moved[1..]) {
+            assert_eq!(&format!(" {}", &row[..row.len() - " ".len()]), moved);
+        }
+
+        let _ = app.press(
+            &KeyEvent::from(KeyCode::Char('x')),
+            Action::Acknowledge,
+            local,
+            now,
+        );
+        assert!(app.bounce.is_none());
+        assert_eq!(render(&app), centered);
+    }
+
+    #[test]
+    fn rain_falls_around_the_clock_until_unfocused() {
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let now = Instant::now();
+        let mut app = App {
+            custom_format: Some("%H:%M".to_owned()),
+            pixel_size: PixelSize::Quadrant,
+            status_bar: true,
+            frame_size: Size::new(30, 10),
+            ..App::default()
+        };
+        let render = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(30, 10)).unwrap();
+            terminal.draw(|frame| draw(frame, app, local, now)).unwrap();
+            rows(terminal.backend().buffer())
+        };
+        let plain = render(&app);
+        app.matrix = Some(Matrix::new(1, now));
+        assert_eq!(app.frame_period(), Some(matrix::STEP));
+        let mut step = now;
+        for _ in 0..30 {
+            step += matrix::STEP;
+            app.update(local, step);
+        }
+        let rain = render(&app);
+        assert_ne!(rain, plain);
+
+        // The status bar, and the digits with a column either side, are as they'd be without it
+        assert_eq!(rain.last(), plain.last());
+        for (rain, plain) in rain.iter().zip(&plain[..plain.len() - 1]) {
+            let (rain, plain): (Vec<_>, Vec<_>) = (rain.chars().collect(), plain.chars().collect());
+            let Some(start) = plain.iter().position(|&c| c != ' ') else {
+                continue;
+            };
+            let end = plain.iter().rposition(|&c| c != ' ').unwrap() + 1;
+            assert_eq!(rain[start - 1..=end], plain[start - 1..=end]);
+        }
+
+        // Out of focus, it stops with no frames to draw it in
+        app.unfocused = true;
+        assert_eq!(app.frame_period(), None);
+        app.update(local, step + matrix::STEP * 5);
+        assert_eq!(render(&app), rain);
+    }
+
+    #[test]
+    fn background_fills_the_margins_and_status_bar() {
+        let app = App {
+            theme: Theme {
+                bg: Color::Blue,
+                ..Theme::default()
+            },
+            margin: Margins::uniform(2),
+            status_bar: true,
+            ..App::default()
+        };
+        let local = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+        terminal
+            .draw(|frame| draw(frame, &app, local, Instant::now()))
+            .unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let status_row = buffer.area.bottom() - 3;
+        assert_eq!(buffer[(2, status_row)].symbol(), "c");
+        for (x, y) in [(0, 0), (59, 13), (2, status_row), (30, 7)] {
+            assert_eq!(buffer[(x, y)].bg, Color::Blue, "at ({x}, {y})");
+        }
+    }
+}
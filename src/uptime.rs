@@ -0,0 +1,47 @@
+use std::{fs, io, time::Duration};
+
+use crate::timer::clock_now;
+
+/// How long the machine has been up, suspended time included. Kernels too old for
+/// `CLOCK_BOOTTIME` have `/proc/uptime` instead, which counts the same.
+pub fn read() -> io::Result<Duration> {
+    clock_now(libc::CLOCK_BOOTTIME).or_else(|_| {
+        let text = fs::read_to_string("/proc/uptime")?;
+        parse_proc_uptime(&text).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected /proc/uptime {text:?}"),
+            )
+        })
+    })
+}
+
+/// How long the machine has spent suspended since boot: the gap between `CLOCK_BOOTTIME` and
+/// `CLOCK_MONOTONIC`, which stops while it sleeps. Zero where the clocks can't be read.
+pub fn suspended() -> Duration {
+    let monotonic = clock_now(libc::CLOCK_MONOTONIC).unwrap_or_default();
+    let boottime = clock_now(libc::CLOCK_BOOTTIME).unwrap_or_default();
+    boottime.saturating_sub(monotonic)
+}
+
+/// The first of `/proc/uptime`'s two figures, the seconds since boot. The second is the idle
+/// time summed over every CPU.
+fn parse_proc_uptime(text: &str) -> Option<Duration> {
+    let seconds: f64 = text.split_whitespace().next()?.parse().ok()?;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_first_figure_of_proc_uptime() {
+        assert_eq!(
+            parse_proc_uptime("350735.47 234388.90\n"),
+            Some(Duration::from_millis(350_735_470))
+        );
+        assert_eq!(parse_proc_uptime(""), None);
+        assert_eq!(parse_proc_uptime("soon 1.0"), None);
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    ffi::{CString, OsString},
+    io, mem,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::ffi::OsStrExt,
+    },
+    path::Path,
+};
+
+use tokio::io::unix::AsyncFd;
+
+/// Watches a config file (or the `--ics` calendar) for edits with inotify. The directory is
+/// watched rather than the file, since editors that save by writing a new file and renaming it
+/// over the old one would otherwise leave the watch on a deleted inode.
+pub struct ConfigWatcher {
+    inotify: AsyncFd<OwnedFd>,
+    name: OsString,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &Path) -> io::Result<Self> {
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path has no file name",
+            ));
+        };
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let dir = CString::new(dir.as_os_str().as_bytes())?;
+        let mask = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO;
+        if unsafe { libc::inotify_add_watch(fd.as_raw_fd(), dir.as_ptr(), mask) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            inotify: AsyncFd::new(fd)?,
+            name: name.to_owned(),
+        })
+    }
+
+    /// Waits until the config file has been written or renamed into place.
+    pub async fn changed(&self) -> io::Result<()> {
+        let mut buf = [0_u8; 4096];
+        loop {
+            let mut guard = self.inotify.readable().await?;
+            let read = guard.try_io(|inotify| {
+                match unsafe { libc::read(inotify.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) }
+                {
+                    ..0 => Err(io::Error::last_os_error()),
+                    n => Ok(n as usize),
+                }
+            });
+            let Ok(len) = read else {
+                continue;
+            };
+            if event_names(&buf[..len?]).any(|name| name == self.name.as_bytes()) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// The file names in a buffer of inotify events, each trimmed of its NUL padding.
+fn event_names(mut buf: &[u8]) -> impl Iterator<Item = &[u8]> {
+    const HEADER: usize = mem::size_of::<libc::inotify_event>();
+    std::iter::from_fn(move || {
+        if buf.len() < HEADER {
+            return None;
+        }
+        let event: libc::inotify_event =
+            unsafe { buf.as_ptr().cast::<libc::inotify_event>().read_unaligned() };
+        let end = (HEADER + event.len as usize).min(buf.len());
+        let name = &buf[HEADER..end];
+        buf = &buf[end..];
+        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        Some(&name[..len])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &[u8], padded_len: u32) -> Vec<u8> {
+        let header = libc::inotify_event {
+            wd: 1,
+            mask: libc::IN_CLOSE_WRITE,
+            cookie: 0,
+            len: padded_len,
+        };
+        let mut bytes = unsafe {
+            std::slice::from_raw_parts((&raw const header).cast::<u8>(), mem::size_of_val(&header))
+        }
+        .to_vec();
+        bytes.extend_from_slice(name);
+        bytes.resize(bytes.len() + padded_len as usize - name.len(), 0);
+        bytes
+    }
+
+    #[test]
+    fn splits_events_and_strips_padding() {
+        let mut buf = event(b"config.toml", 16);
+        buf.extend(event(b".config.toml.swp", 32));
+        let names: Vec<_> = event_names(&buf).collect();
+        assert_eq!(names, [&b"config.toml"[..], b".config.toml.swp"]);
+    }
+}
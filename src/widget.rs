@@ -0,0 +1,535 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, TimeZone};
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Text},
+    widgets::Widget,
+};
+use serde::Deserialize;
+use tui_big_text::{BigText, PixelSize};
+
+use crate::{
+    animate::{self, Animation},
+    layout::{self, Align, VAlign},
+    segment_font::{SegmentFont, SegmentText},
+};
+
+/// How many terminal cells each pixel of the digits takes, as `--pixel-size` spells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scale {
+    #[default]
+    Full,
+    HalfHeight,
+    HalfWidth,
+    Quadrant,
+    ThirdHeight,
+    Sextant,
+    QuarterHeight,
+    Octant,
+}
+
+impl From<Scale> for PixelSize {
+    fn from(scale: Scale) -> Self {
+        match scale {
+            Scale::Full => Self::Full,
+            Scale::HalfHeight => Self::HalfHeight,
+            Scale::HalfWidth => Self::HalfWidth,
+            Scale::Quadrant => Self::Quadrant,
+            Scale::ThirdHeight => Self::ThirdHeight,
+            Scale::Sextant => Self::Sextant,
+            Scale::QuarterHeight => Self::QuarterHeight,
+            Scale::Octant => Self::Octant,
+        }
+    }
+}
+
+/// Whether `term`, the `TERM` variable, names a terminal without Unicode's block characters: a
+/// dumb one, or a DEC VT model, which only has its own line-drawing set.
+pub fn term_lacks_blocks(term: &str) -> bool {
+    term == "dumb" || term.starts_with("vt")
+}
+
+/// Whether tui-big-text has a glyph for `c`. Its font only covers printable ASCII.
+pub fn has_big_glyph(c: char) -> bool {
+    c == ' ' || c.is_ascii_graphic()
+}
+
+/// A color and pixel size for one line of the clock's text, in place of the widget's own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineStyle {
+    pub color: Option<Color>,
+    pub pixel_size: Option<PixelSize>,
+}
+
+/// The clock's digits in big text, placed within the area it's rendered to per its alignment and
+/// clipped to it when the area is too small. Text with several lines, split at `\n`, is placed
+/// as one block, each line aligned within it the same way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockWidget<'a> {
+    text: String,
+    style: Style,
+    align: Align,
+    pixel_size: PixelSize,
+    /// Each line's own style, in order. Lines with a pixel size of their own are drawn one under
+    /// the other, each as big as it says.
+    lines: Vec<LineStyle>,
+    /// A bitmap font to draw in instead of tui-big-text's, ignoring the pixel size.
+    font: Option<&'a SegmentFont>,
+    /// The style of the font's unlit segments, if they're drawn at all.
+    off_style: Option<Style>,
+    /// Ordinary text rather than big digits, for when even the smallest digits don't fit.
+    plain: bool,
+    /// Draw the digits' pixels as `#`, for terminals without block characters. The smaller pixel
+    /// sizes need partial blocks, so the digits are always full size.
+    ascii: bool,
+    /// The text being changed from, how, and how far through the change it is.
+    transition: Option<(&'a str, Animation, f64)>,
+}
+
+impl<'a> ClockWidget<'a> {
+    /// `time` written with the strftime `format`, which should already be validated (see
+    /// [`crate::app::validate_format`]).
+    pub fn new<Tz: TimeZone>(time: &DateTime<Tz>, format: &str) -> Self
+    where
+        Tz::Offset: Display,
+    {
+        Self::text(time.format(format).to_string())
+    }
+
+    /// Arbitrary text in the clock's place, like a countdown's remainder.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: Style::new(),
+            align: Align::default(),
+            pixel_size: PixelSize::Full,
+            lines: Vec::new(),
+            font: None,
+            off_style: None,
+            plain: false,
+            ascii: false,
+            transition: None,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn pixel_size(mut self, pixel_size: PixelSize) -> Self {
+        self.pixel_size = pixel_size;
+        self
+    }
+
+    pub fn line_styles(mut self, lines: Vec<LineStyle>) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a SegmentFont>) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn off_style(mut self, off_style: Option<Style>) -> Self {
+        self.off_style = off_style;
+        self
+    }
+
+    pub fn plain(mut self) -> Self {
+        self.plain = true;
+        self
+    }
+
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Animates the change from `transition`'s text, when it's as long as this one. Only big
+    /// text without line styles animates; fonts' glyphs aren't all as wide, so they can't be moved one by one.
+    pub fn transition(mut self, transition: Option<(&'a str, Animation, f64)>) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// The size in cells the digits take up, before any clipping.
+    pub fn size(&self) -> (u16, u16) {
+        if self.stacked() {
+            let sizes: Vec<_> = self.lines.iter().map(|line| line.pixel_size).collect();
+            return layout::stacked_size(&self.text, self.pixel_size, &sizes);
+        }
+        let pixel_size = (!self.plain).then_some(self.effective_pixel_size());
+        layout::text_size(&self.text, pixel_size, self.font)
+    }
+
+    /// Whether each line is drawn as big text of its own, in its own style.
+    fn stacked(&self) -> bool {
+        !self.lines.is_empty() && !self.plain && self.font.is_none() && !self.ascii
+    }
+
+    /// The style of the `i`th line.
+    fn line_style(&self, i: usize) -> Style {
+        match self.lines.get(i).and_then(|line| line.color) {
+            Some(color) => self.style.fg(color),
+            None => self.style,
+        }
+    }
+
+    fn effective_pixel_size(&self) -> PixelSize {
+        if self.ascii {
+            PixelSize::Full
+        } else {
+            self.pixel_size
+        }
+    }
+}
+
+impl Widget for ClockWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rect = layout::aligned_rect(area, self.size(), self.align, 0);
+        let alignment = self.align.horizontal.into();
+        // Drawn with blocks off to the side, then copied over with each block swapped for a `#`
+        if self.ascii && !self.plain {
+            let mut blocks = Buffer::empty(rect);
+            let lines = self
+                .lines
+                .iter()
+                .map(|line| LineStyle {
+                    pixel_size: None,
+                    ..*line
+                })
+                .collect();
+            Self {
+                ascii: false,
+                pixel_size: PixelSize::Full,
+                lines,
+                ..self
+            }
+            .render(rect, &mut blocks);
+            for position in rect.positions() {
+                if blocks[position].symbol() == "█" {
+                    let style = blocks[position].style();
+                    buf[position].set_symbol("#").set_style(style);
+                }
+            }
+            return;
+        }
+        if self.plain {
+            let lines = self
+                .text
+                .split('\n')
+                .enumerate()
+                .map(|(i, line)| Line::from(line).style(self.line_style(i)));
+            return Text::from_iter(lines)
+                .style(self.style)
+                .alignment(alignment)
+                .render(rect, buf);
+        }
+        if let Some(font) = self.font {
+            let line_align = Align {
+                vertical: VAlign::Top,
+                ..self.align
+            };
+            let mut rows = rect;
+            for (i, line) in self.text.split('\n').enumerate() {
+                let size = font.size(line);
+                let text = SegmentText {
+                    text: line,
+                    font,
+                    style: self.line_style(i),
+                    off_style: self.off_style,
+                };
+                text.render(layout::aligned_rect(rows, size, line_align, 0), buf);
+                // The line and a blank row after it
+                let used = (size.1 + 1).min(rows.height);
+                rows.y += used;
+                rows.height -= used;
+            }
+            return;
+        }
+        if self.stacked() {
+            let mut rows = rect;
+            for (i, line) in self.text.split('\n').enumerate() {
+                let pixel_size = self
+                    .lines
+                    .get(i)
+                    .and_then(|line| line.pixel_size)
+                    .unwrap_or(self.pixel_size);
+                let line = Line::from(line.to_owned());
+                let (_, height) = layout::big_text_size(std::slice::from_ref(&line), pixel_size);
+                let height = height.min(rows.height);
+                BigText::builder()
+                    .pixel_size(pixel_size)
+                    .style(self.line_style(i))
+                    .alignment(alignment)
+                    .lines(vec![line])
+                    .build()
+                    .render(Rect { height, ..rows }, buf);
+                rows.y += height;
+                rows.height -= height;
+            }
+            return;
+        }
+        let big_text = |text: &str| {
+            let lines = text.split('\n').map(|line| Line::from(line.to_owned()));
+            BigText::builder()
+                .pixel_size(self.pixel_size)
+                .style(self.style)
+                .alignment(alignment)
+                .lines(lines.collect::<Vec<_>>())
+                .build()
+        };
+        match self.transition {
+            Some((from, animation, progress)) if animate::animatable(from, &self.text) => {
+                // Both texts are drawn over what's already there, then pieced together
+                let backdrop = || {
+                    let mut scratch = Buffer::empty(rect);
+                    for position in rect.positions() {
+                        scratch[position] = buf[position].clone();
+                    }
+                    scratch
+                };
+                let (mut old, mut new) = (backdrop(), backdrop());
+                big_text(from).render(rect, &mut old);
+                big_text(&self.text).render(rect, &mut new);
+                let (glyph_width, _) = layout::big_text_size(&[Line::from("0")], self.pixel_size);
+                animate::composite(
+                    (from, &old),
+                    (&self.text, &new),
+                    glyph_width,
+                    animation,
+                    progress,
+                    buf,
+                );
+            },
+            _ => big_text(&self.text).render(rect, buf),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::Utc;
+    use ratatui::{Terminal, backend::TestBackend, style::Color};
+
+    use super::*;
+
+    fn render(widget: ClockWidget<'_>, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(widget, frame.area()))
+            .unwrap();
+        rows(terminal.backend().buffer())
+    }
+
+    /// The buffer's text, one string per row.
+    pub(crate) fn rows(buffer: &Buffer) -> Vec<String> {
+        let width = usize::from(buffer.area.width);
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    fn noon() -> ClockWidget<'static> {
+        ClockWidget::new(
+            &Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+            "%H:%M",
+        )
+    }
+
+    #[test]
+    fn centers_in_a_larger_area() {
+        assert_eq!(noon().size(), (40, 8));
+        assert_eq!(
+            render(noon(), 44, 10),
+            [
+                "                                            ",
+                "    ██     ████            █████   █████    ",
+                "   ███    ██  ██    ██    ██   ██ ██   ██   ",
+                "    ██        ██    ██    ██  ███ ██  ███   ",
+                "    ██      ███           ██ ████ ██ ████   ",
+                "    ██     ██             ████ ██ ████ ██   ",
+                "    ██    ██  ██    ██    ███  ██ ███  ██   ",
+                "  ██████  ██████    ██     █████   █████    ",
+                "                                            ",
+                "                                            ",
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_the_alignment() {
+        let widget = noon().align("bottom-right".parse().unwrap());
+        assert_eq!(
+            render(widget, 42, 9),
+            [
+                "                                          ",
+                "    ██     ████            █████   █████  ",
+                "   ███    ██  ██    ██    ██   ██ ██   ██ ",
+                "    ██        ██    ██    ██  ███ ██  ███ ",
+                "    ██      ███           ██ ████ ██ ████ ",
+                "    ██     ██             ████ ██ ████ ██ ",
+                "    ██    ██  ██    ██    ███  ██ ███  ██ ",
+                "  ██████  ██████    ██     █████   █████  ",
+                "                                          ",
+            ]
+        );
+    }
+
+    #[test]
+    fn smaller_pixels_shrink_the_digits() {
+        let widget = noon().pixel_size(Scale::Quadrant.into());
+        assert_eq!(widget.size(), (20, 4));
+        assert_eq!(
+            render(widget, 20, 4),
+            [
+                "▗█  ▟▀▙  ▄  ▟▀▜▖▟▀▜▖",
+                " █   ▄▛  ▀  █▗█▌█▗█▌",
+                " █  ▟▘▄  ▄  █▛▐▌█▛▐▌",
+                "▀▀▀ ▀▀▀  ▀  ▝▀▀ ▝▀▀ ",
+            ]
+        );
+    }
+
+    #[test]
+    fn lines_can_each_have_their_own_color_and_size() {
+        let widget = ClockWidget::text("12\n00").line_styles(vec![
+            LineStyle {
+                color: Some(Color::Red),
+                pixel_size: Some(Scale::Quadrant.into()),
+            },
+            LineStyle::default(),
+        ]);
+        assert_eq!(widget.size(), (16, 12));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 12));
+        widget.render(buf.area, &mut buf);
+        assert_eq!(
+            rows(&buf),
+            [
+                "    ▗█  ▟▀▙     ",
+                "     █   ▄▛     ",
+                "     █  ▟▘▄     ",
+                "    ▀▀▀ ▀▀▀     ",
+                " █████   █████  ",
+                "██   ██ ██   ██ ",
+                "██  ███ ██  ███ ",
+                "██ ████ ██ ████ ",
+                "████ ██ ████ ██ ",
+                "███  ██ ███  ██ ",
+                " █████   █████  ",
+                "                ",
+            ]
+        );
+        assert_eq!(buf[(5, 1)].fg, Color::Red);
+        assert_eq!(buf[(1, 5)].fg, Color::Reset);
+    }
+
+    #[test]
+    fn clips_to_a_small_area() {
+        assert_eq!(
+            render(noon(), 20, 4),
+            [
+                "  ██     ████       ",
+                " ███    ██  ██    ██",
+                "  ██        ██    ██",
+                "  ██      ███       ",
+            ]
+        );
+    }
+
+    #[test]
+    fn ascii_draws_full_size_pixels_as_hashes() {
+        let widget = noon()
+            .pixel_size(Scale::Quadrant.into())
+            .style(Style::new().fg(Color::Red))
+            .ascii(true);
+        assert_eq!(widget.size(), (40, 8));
+        let mut terminal = Terminal::new(TestBackend::new(42, 8)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(widget, frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        assert_eq!(
+            rows(buffer),
+            [
+                "   ##     ####            #####   #####   ",
+                "  ###    ##  ##    ##    ##   ## ##   ##  ",
+                "   ##        ##    ##    ##  ### ##  ###  ",
+                "   ##      ###           ## #### ## ####  ",
+                "   ##     ##             #### ## #### ##  ",
+                "   ##    ##  ##    ##    ###  ## ###  ##  ",
+                " ######  ######    ##     #####   #####   ",
+                "                                          ",
+            ]
+        );
+        assert_eq!(buffer[(3, 0)].fg, Color::Red);
+
+        let font = SegmentFont::seven_segment(1);
+        let segments = ClockWidget::text("1").font(Some(&font)).ascii(true);
+        let mut terminal = Terminal::new(TestBackend::new(5, 7)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(segments, frame.area()))
+            .unwrap();
+        assert_eq!(rows(terminal.backend().buffer())[1], "    #");
+    }
+
+    #[test]
+    fn slides_only_the_changed_digits() {
+        let widget = ClockWidget::text("13")
+            .pixel_size(Scale::Quadrant.into())
+            .transition(Some(("12", Animation::Slide, 0.5)));
+        // Halfway, the 2's bottom half sits over the 3's top half, and the 1 hasn't moved
+        assert_eq!(
+            render(widget, 8, 4),
+            ["▗█  ▟▘▄ ", " █  ▀▀▀ ", " █  ▟▀▙ ", "▀▀▀  ▄▛ ",]
+        );
+    }
+
+    #[test]
+    fn dumb_and_vt_terminals_lack_blocks() {
+        assert!(term_lacks_blocks("dumb"));
+        assert!(term_lacks_blocks("vt100"));
+        assert!(term_lacks_blocks("vt220"));
+        assert!(!term_lacks_blocks("xterm-256color"));
+        assert!(!term_lacks_blocks("linux"));
+    }
+
+    #[test]
+    fn stacks_lines_as_one_centered_block() {
+        let widget = ClockWidget::text("1\n12:00").pixel_size(Scale::Quadrant.into());
+        assert_eq!(widget.size(), (20, 8));
+        assert_eq!(
+            render(widget, 22, 10),
+            [
+                "                      ",
+                "         ▗█           ",
+                "          █           ",
+                "          █           ",
+                "         ▀▀▀          ",
+                " ▗█  ▟▀▙  ▄  ▟▀▜▖▟▀▜▖ ",
+                "  █   ▄▛  ▀  █▗█▌█▗█▌ ",
+                "  █  ▟▘▄  ▄  █▛▐▌█▛▐▌ ",
+                " ▀▀▀ ▀▀▀  ▀  ▝▀▀ ▝▀▀  ",
+                "                      ",
+            ]
+        );
+
+        let plain = ClockWidget::text("Focus\n12:00").plain();
+        assert_eq!(plain.size(), (5, 2));
+        assert_eq!(render(plain, 7, 2), [" Focus ", " 12:00 "]);
+    }
+}
@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{TZ_VARIANTS, Tz};
+
+/// Every zone name in the tz database, like `America/New_York`, sorted.
+pub fn zone_names() -> Vec<String> {
+    let mut names: Vec<_> = TZ_VARIANTS
+        .iter()
+        .map(|zone| zone.name().to_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// The `--list-timezones` lines: the zones whose names contain `filter`, ignoring case, sorted,
+/// each followed by its offset at `now` with `with_offset`.
+pub fn listing(filter: &str, with_offset: bool, now: DateTime<Utc>) -> Vec<String> {
+    let filter = filter.to_lowercase();
+    zone_names()
+        .into_iter()
+        .filter(|name| name.to_lowercase().contains(&filter))
+        .map(|name| match with_offset {
+            true => {
+                let offset = Zone::load(&name).map(|zone| zone.offset_at(now));
+                format!("{name}\t{}", offset.expect("listed zones load"))
+            },
+            false => name,
+        })
+        .collect()
+}
+
+/// A time zone from the tz database, for showing the time somewhere other than the local zone,
+/// which the process's `TZ` decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Zone(Tz);
+
+impl Zone {
+    /// The zone `name` names in the tz database.
+    pub fn load(name: &str) -> anyhow::Result<Self> {
+        match name.parse() {
+            Ok(zone) => Ok(Self(zone)),
+            Err(_) => bail!("unknown time zone {name:?} (expected a name like Europe/Paris)"),
+        }
+    }
+
+    /// The zone the C library would use for local time: the one `TZ` names, by name or by its
+    /// path in the database, or the system's when it's unset. Rules spelled out in `TZ`, like
+    /// `EST5EDT,M3.2.0,M11.1.0`, aren't zones the database knows.
+    pub fn local() -> anyhow::Result<Self> {
+        let tz = std::env::var("TZ").unwrap_or_default();
+        let name = tz.strip_prefix(':').unwrap_or(&tz);
+        if name.is_empty() {
+            let name = iana_time_zone::get_timezone().context("finding the local time zone")?;
+            return Self::load(&name);
+        }
+        Self::load(name.rsplit_once("zoneinfo/").map_or(name, |(_, name)| name))
+    }
+
+    /// The name it has in the database, like `Europe/Paris`.
+    pub fn name(self) -> &'static str {
+        self.0.name()
+    }
+
+    pub fn offset_at(self, time: DateTime<Utc>) -> FixedOffset {
+        self.0.offset_from_utc_datetime(&time.naive_utc()).fix()
+    }
+
+    /// The instant the zone's clocks read `time`. Times skipped by a transition are read on the
+    /// offset before it, so land that much later, and repeated ones take the first reading.
+    pub fn to_utc(self, time: NaiveDateTime) -> DateTime<Utc> {
+        let wall = time.and_utc();
+        let offset = self.offset_at(wall - self.offset_at(wall));
+        let candidate = wall - offset;
+        match self.offset_at(candidate) {
+            found if found == offset => candidate,
+            before => wall - before,
+        }
+    }
+
+    /// What the zone's time is called at `time`, like `EST` or `EDT`. Some zones have no
+    /// abbreviation and use their offset, like `+0545`.
+    pub fn abbreviation_at(self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.0).format("%Z").to_string()
+    }
+}
+
+/// A zone to show alongside the local time, from `--compare ZONE[=LABEL]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub label: String,
+    pub zone: Zone,
+}
+
+impl FromStr for Comparison {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (name, label) = match s.split_once('=') {
+            Some((name, label)) => (name, Some(label.to_owned())),
+            None => (s, None),
+        };
+        // The city, e.g. `New York` for America/New_York
+        let label =
+            label.unwrap_or_else(|| name.rsplit('/').next().unwrap_or(name).replace('_', " "));
+        Ok(Self {
+            label,
+            zone: Zone::load(name)?,
+        })
+    }
+}
+
+/// Formats the difference between two UTC offsets, e.g. `+4h30m` or `-5h`.
+pub fn format_offset_difference(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.unsigned_abs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (hours, 0) => format!("{sign}{hours}h"),
+        (0, minutes) => format!("{sign}{minutes}m"),
+        (hours, minutes) => format!("{sign}{hours}h{minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone};
+
+    use super::*;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn hours(zone: Zone, time: DateTime<Utc>) -> f64 {
+        f64::from(zone.offset_at(time).local_minus_utc()) / 3600.0
+    }
+
+    #[test]
+    fn lists_zones_matching_the_filter() {
+        let now = utc(2024, 1, 15, 12, 0);
+        let all = listing("", false, now);
+        assert!(all.is_sorted());
+        assert!(all.iter().any(|name| name == "America/New_York"));
+        assert_eq!(listing("PARIS", true, now), ["Europe/Paris\t+01:00"]);
+        assert!(listing("atlantis", false, now).is_empty());
+    }
+
+    #[test]
+    fn follows_daylight_saving_both_sides_of_the_equator() {
+        let new_york = Zone::load("America/New_York").unwrap();
+        assert_eq!(new_york.name(), "America/New_York");
+        assert_eq!(hours(new_york, utc(2024, 3, 10, 6, 59)), -5.0);
+        assert_eq!(hours(new_york, utc(2024, 3, 10, 7, 0)), -4.0);
+        assert_eq!(hours(new_york, utc(2024, 11, 3, 5, 59)), -4.0);
+        assert_eq!(hours(new_york, utc(2024, 11, 3, 6, 0)), -5.0);
+        assert_eq!(new_york.abbreviation_at(utc(2024, 1, 1, 0, 0)), "EST");
+        assert_eq!(new_york.abbreviation_at(utc(2024, 7, 1, 0, 0)), "EDT");
+
+        let sydney = Zone::load("Australia/Sydney").unwrap();
+        assert_eq!(hours(sydney, utc(2025, 1, 15, 0, 0)), 11.0);
+        assert_eq!(hours(sydney, utc(2025, 7, 1, 0, 0)), 10.0);
+
+        let kathmandu = Zone::load("Asia/Kathmandu").unwrap();
+        assert_eq!(kathmandu.abbreviation_at(utc(2024, 1, 1, 0, 0)), "+0545");
+
+        for bad in ["", "Europe/Atlantis", "../etc/passwd"] {
+            assert!(Zone::load(bad).is_err(), "{bad:?} should not load");
+        }
+    }
+
+    #[test]
+    fn converts_wall_clock_times_to_utc() {
+        let zone = Zone::load("America/New_York").unwrap();
+        let wall = |d: u32, h: u32, m: u32| {
+            NaiveDate::from_ymd_opt(2024, 3, d)
+                .unwrap()
+                .and_hms_opt(h, m, 0)
+                .unwrap()
+        };
+        assert_eq!(zone.to_utc(wall(9, 9, 30)), utc(2024, 3, 9, 14, 30));
+        assert_eq!(zone.to_utc(wall(11, 9, 30)), utc(2024, 3, 11, 13, 30));
+        // 02:30 never happens that night
+        assert_eq!(zone.to_utc(wall(10, 2, 30)), utc(2024, 3, 10, 7, 30));
+    }
+
+    #[test]
+    fn formats_signed_differences() {
+        assert_eq!(format_offset_difference(4 * 3600 + 30 * 60), "+4h30m");
+        assert_eq!(format_offset_difference(-5 * 3600), "-5h");
+        assert_eq!(format_offset_difference(-45 * 60), "-45m");
+        assert_eq!(format_offset_difference(0), "+0h");
+    }
+}
@@ -0,0 +1,112 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn tui_time() -> Command {
+    Command::cargo_bin("tui-time").unwrap()
+}
+
+#[test]
+fn help_lists_the_subcommands() {
+    tui_time()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Usage: tui-time"))
+        .stdout(predicate::str::contains("clock").and(predicate::str::contains("stopwatch")));
+}
+
+#[test]
+fn version_prints_the_package_version() {
+    tui_time()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("tui-time {}\n", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn unknown_flags_exit_with_usage() {
+    tui_time()
+        .arg("--no-such-flag")
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "unexpected argument '--no-such-flag'",
+        ))
+        .stderr(predicate::str::contains("Usage: tui-time"));
+}
+
+#[test]
+fn invalid_values_are_rejected_before_starting() {
+    for args in [
+        &["--format", "%Q"][..],
+        &["--timezone", "Mars/Olympus_Mons"],
+        &["--compare", "Mars/Olympus_Mons=Base"],
+        &["--color", "not-a-color"],
+        &["--pixel-size", "huge"],
+        &["--ampm", "mixed"],
+        &["--margin", "1,2,3"],
+        &["--margin-x", "-1"],
+        &["--optical-center", "60"],
+        &["--agenda-width", "40"],
+        &["--alternate", "0"],
+        &["--color-depth", "88"],
+        &["--separator", "::"],
+        &["--since", "2020-13-01"],
+        &["--since", "9999-01-01"],
+        &["--since", "2020-01-01", "--uptime"],
+        &["--holidays-ahead", "3"],
+        &["chess", "--time", "0s"],
+        &["--width", "0"],
+        &["--decimal", "--analog"],
+        &["--fuzzy", "7"],
+        &["--grid", "2by2"],
+        &["--inline", "0"],
+        &["--notify", "loud"],
+        &["--ampm-position", "above"],
+        &["--segment-thickness", "4"],
+        &["--bounce=0s"],
+        &["--12-hour", "--24-hour"],
+        &["--log-level", "debug"],
+        &["--log-file", "/dev/null", "--log-level", "chatty"],
+        &["chess", "--increment", "3x"],
+        &["--holidays", "holidays.toml", "--holidays-ahead", "0"],
+        &["--agenda", "notes.txt", "--agenda-width", "2"],
+        &["--ring", "day"],
+        &["--ring", "minute", "--ring-radius", "0"],
+        &["--lat", "91", "--lon", "0"],
+        &["--lat", "52.5"],
+        &["--battery", "--battery-low", "101"],
+        &["--with-offset"],
+        &["timer", "ten minutes"],
+        &["stopwatch", "--sweep"],
+        &["stopwatch", "--precision", "us"],
+        &["stopwatch", "--fps", "0"],
+    ] {
+        tui_time()
+            .args(args)
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains(
+                "For more information, try '--help'",
+            ));
+    }
+}
+
+#[test]
+fn unknown_profiles_are_rejected_before_starting() {
+    let path = std::env::temp_dir().join(format!("tui-time-profiles-{}.toml", std::process::id()));
+    std::fs::write(&path, "[profile.desk]\n[profile.work]\n").unwrap();
+    tui_time()
+        .arg("--config")
+        .arg(&path)
+        .env("TUI_TIME_PROFILE", "home")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "there's no profile \"home\"; the profiles are desk, work",
+        ));
+    std::fs::remove_file(path).unwrap();
+}
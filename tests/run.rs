@@ -0,0 +1,300 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+};
+use tui_time::{
+    app::{Action, AmPm, App, Mode},
+    countdown::Timer,
+    persist::Fingerprint,
+    run::{Host, LoopEvent, LoopOptions, run_loop},
+    tick,
+    timer::{Arming, Tick, Ticks},
+};
+
+/// What's fed to the loop, in order: a tick from the ticker, a key, focus change, or error
+/// (given by its errno) from the host, or nothing until a moment has passed.
+enum Step {
+    Tick,
+    Key(KeyEvent, Action),
+    Focus(bool),
+    Fail(i32),
+    Until(Instant),
+}
+
+type Script = Rc<RefCell<VecDeque<Step>>>;
+
+/// Takes the next step of the script once it's one `take` accepts, leaving the others for
+/// whichever source they belong to. Once the script runs out, never returns.
+async fn next_step<T>(script: &Script, take: impl Fn(&Step) -> Option<T>) -> T {
+    loop {
+        let passed =
+            matches!(script.borrow().front(), Some(Step::Until(at)) if *at <= Instant::now());
+        if passed {
+            script.borrow_mut().pop_front();
+        }
+        let taken = script.borrow().front().and_then(&take);
+        if let Some(taken) = taken {
+            script.borrow_mut().pop_front();
+            return taken;
+        }
+        tokio::task::yield_now().await;
+    }
+}
+
+struct ScriptedTicks {
+    script: Script,
+    arming: Arming,
+    clock: libc::clockid_t,
+}
+
+impl Ticks for ScriptedTicks {
+    fn arming(&self) -> Arming {
+        self.arming
+    }
+
+    fn clock(&self) -> libc::clockid_t {
+        self.clock
+    }
+
+    fn set_arming(&mut self, arming: Arming, _interval: Duration) -> anyhow::Result<Option<Tick>> {
+        self.arming = arming;
+        Ok(None)
+    }
+
+    fn set_clock(&mut self, clock: libc::clockid_t, _interval: Duration) -> anyhow::Result<()> {
+        self.clock = clock;
+        Ok(())
+    }
+
+    async fn tick(&mut self, _interval: Duration) -> anyhow::Result<Tick> {
+        next_step(&self.script, |step| {
+            matches!(step, Step::Tick).then_some(())
+        })
+        .await;
+        Ok(Tick::Interval)
+    }
+}
+
+struct ScriptedHost {
+    script: Script,
+    /// The clock's text after each frame, `(suspended)` where the process was stopped, and
+    /// `(saved)` where something being timed was persisted.
+    drawn: Vec<String>,
+}
+
+impl Host for ScriptedHost {
+    async fn next_event(&mut self) -> anyhow::Result<LoopEvent> {
+        let event = next_step(&self.script, |step| match step {
+            Step::Key(key, action) => Some(Ok(LoopEvent::Key(*key, action.clone()))),
+            Step::Focus(focused) => Some(Ok(LoopEvent::Focus(*focused))),
+            Step::Fail(errno) => Some(Err(io::Error::from_raw_os_error(*errno).into())),
+            Step::Tick | Step::Until(_) => None,
+        });
+        event.await
+    }
+
+    fn suspend(&mut self) -> io::Result<()> {
+        self.drawn.push("(suspended)".to_owned());
+        Ok(())
+    }
+
+    fn drew(&mut self, _app: &mut App, line: &str) {
+        self.drawn.push(line.to_owned());
+    }
+
+    fn persist(&mut self, app: &mut App) {
+        if Fingerprint::of(&app.mode).is_some() {
+            self.drawn.push("(saved)".to_owned());
+        }
+    }
+}
+
+/// Runs the loop on `app` through `steps`, which have to end it, returning how it ended, the
+/// terminal it drew on, and the clock's text after each frame.
+async fn try_run(
+    app: &mut App,
+    steps: Vec<Step>,
+) -> (anyhow::Result<()>, Terminal<TestBackend>, Vec<String>) {
+    let script = Rc::new(RefCell::new(VecDeque::from(steps)));
+    let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+    let mut ticker = ScriptedTicks {
+        script: Rc::clone(&script),
+        arming: Arming::ALIGNED,
+        clock: libc::CLOCK_REALTIME,
+    };
+    let mut host = ScriptedHost {
+        script: Rc::clone(&script),
+        drawn: Vec::new(),
+    };
+    let options = LoopOptions {
+        tick_interval: tick::MINUTE,
+        max_fps: 60,
+        inline: None,
+        exit: None,
+    };
+    let running = run_loop(&mut terminal, app, &mut ticker, &mut host, options);
+    let ended = tokio::time::timeout(Duration::from_secs(5), running)
+        .await
+        .expect("the loop should end on quit");
+    assert!(script.borrow().is_empty());
+    (ended, terminal, host.drawn)
+}
+
+/// [`try_run`], for steps that end the loop by quitting.
+async fn run(app: &mut App, steps: Vec<Step>) -> (Terminal<TestBackend>, Vec<String>) {
+    let (ended, terminal, drawn) = try_run(app, steps).await;
+    ended.unwrap();
+    (terminal, drawn)
+}
+
+fn key(c: char, action: Action) -> Step {
+    Step::Key(KeyCode::Char(c).into(), action)
+}
+
+#[tokio::test]
+async fn ticks_and_keys_drive_the_loop_until_it_quits() {
+    let mut app = App {
+        ampm: AmPm::Upper,
+        status_bar: true,
+        ..App::default()
+    };
+    let steps = vec![
+        Step::Tick,
+        key('f', Action::ToggleFormat),
+        key('q', Action::Quit),
+    ];
+    let (terminal, drawn) = run(&mut app, steps).await;
+
+    // A 12-hour clock at the start, and the 24-hour one last. The tick's frame may have been put
+    // off by the frame rate cap and folded into the key's; quitting draws nothing
+    let (Some(start), Some(toggled)) = (drawn.first(), drawn.last()) else {
+        panic!("nothing was drawn");
+    };
+    assert!(start.ends_with(" AM") || start.ends_with(" PM"), "{start}");
+    assert_eq!(toggled.len(), "15:04".len(), "{toggled}");
+    assert!(app.hour24);
+
+    let buffer = terminal.backend().buffer();
+    let bottom: String = (0..buffer.area.width)
+        .map(|x| buffer[(x, buffer.area.height - 1)].symbol())
+        .collect();
+    assert!(bottom.starts_with("clock · UTC"), "{bottom}");
+}
+
+#[tokio::test]
+async fn suspending_draws_everything_again_once_continued() {
+    let mut app = App {
+        hour24: true,
+        ..App::default()
+    };
+    let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+    let steps = vec![Step::Key(ctrl_z, Action::Suspend), key('q', Action::Quit)];
+    let (terminal, drawn) = run(&mut app, steps).await;
+
+    assert_eq!(drawn.len(), 3, "{drawn:?}");
+    assert_eq!(drawn[1], "(suspended)");
+    assert!(!app.suspending);
+    // Cleared, then the whole clock drawn again
+    let buffer = terminal.backend().buffer();
+    assert!(buffer.content().iter().any(|cell| cell.symbol() != " "));
+}
+
+#[tokio::test]
+async fn a_timer_running_out_is_saved_without_a_key() {
+    let start = Instant::now();
+    let mut timer = Timer::new(Vec::new());
+    timer.start(Duration::from_millis(50), start);
+    let mut app = App {
+        mode: Mode::Timer(timer),
+        ..App::default()
+    };
+    let steps = vec![
+        Step::Until(start + Duration::from_millis(300)),
+        key('q', Action::Quit),
+    ];
+    let (_, drawn) = run(&mut app, steps).await;
+
+    let Mode::Timer(timer) = &app.mode else {
+        panic!("not a timer");
+    };
+    assert!(timer.overtime(Instant::now()).is_some());
+    // When the loop started, when the timer ran out, and on the way out
+    let saved = drawn.iter().filter(|line| *line == "(saved)").count();
+    assert_eq!(saved, 3, "{drawn:?}");
+}
+
+#[tokio::test]
+async fn ticks_go_undrawn_while_frozen() {
+    let mut app = App::default();
+    let steps = vec![
+        key('p', Action::Hold),
+        Step::Tick,
+        Step::Tick,
+        key('q', Action::Quit),
+    ];
+    let (_, drawn) = run(&mut app, steps).await;
+    // At the start and on freezing, and not for the ticks after
+    assert_eq!(drawn.len(), 2, "{drawn:?}");
+    assert!(app.frozen.is_some());
+}
+
+#[tokio::test]
+async fn ticks_are_drawn_while_unfocused() {
+    let mut app = App::default();
+    // Long enough apart for the frame rate cap to let every tick be drawn
+    let start = Instant::now();
+    let steps = vec![
+        Step::Focus(false),
+        Step::Tick,
+        Step::Until(start + Duration::from_millis(50)),
+        Step::Tick,
+        Step::Until(start + Duration::from_millis(100)),
+        Step::Focus(true),
+        key('q', Action::Quit),
+    ];
+    let (_, drawn) = run(&mut app, steps).await;
+    // At the start, on losing focus, for each tick, and on getting it back
+    assert_eq!(drawn.len(), 5, "{drawn:?}");
+    assert!(!app.unfocused);
+}
+
+#[tokio::test]
+async fn transient_errors_are_shown_until_dismissed() {
+    let mut app = App::default();
+    let eio = || Step::Fail(libc::EIO);
+    let (terminal, _) = run(&mut app, vec![eio(), eio(), key('q', Action::Quit)]).await;
+    let buffer = terminal.backend().buffer();
+    let shown = (0..buffer.area.height).any(|y| {
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect();
+        row.contains("error: Input/output error (os error 5) (2 times)")
+    });
+    assert!(shown, "{buffer:?}");
+
+    let steps = vec![key('x', Action::Acknowledge), key('q', Action::Quit)];
+    run(&mut app, steps).await;
+    assert_eq!(app.error, None);
+}
+
+#[tokio::test]
+async fn fatal_errors_end_the_loop() {
+    let mut app = App::default();
+    let (ended, _, drawn) = try_run(&mut app, vec![Step::Fail(libc::EBADF)]).await;
+    let err = ended.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<io::Error>()
+            .and_then(io::Error::raw_os_error),
+        Some(libc::EBADF)
+    );
+    assert_eq!(drawn.len(), 1);
+}